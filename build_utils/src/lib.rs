@@ -11,7 +11,9 @@
 //! This module provides common functionality for Python environment discovery
 //! and CUDA installation detection used by various build scripts.
 
+use std::collections::HashMap;
 use std::env;
+use std::ffi::OsStr;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -48,6 +50,12 @@ for library_path in cpp_extension.library_paths():
 print('LIBTORCH_CXX11:', torch._C._GLIBCXX_USE_CXX11_ABI)
 ";
 
+/// Python script to extract the C++11 ABI flag PyTorch was built with
+pub const PYTHON_PRINT_CXX11_ABI: &str = r"
+import torch
+print('LIBTORCH_CXX11:', torch._C._GLIBCXX_USE_CXX11_ABI)
+";
+
 /// Python script to extract Python include paths
 pub const PYTHON_PRINT_INCLUDE_PATH: &str = r"
 import sysconfig
@@ -71,6 +79,18 @@ pub struct PythonConfig {
     pub lib_dir: Option<String>,
 }
 
+/// Per-project build settings read from [`BUILD_CONFIG_FILE`].
+///
+/// Each setting is consulted after its environment variable and before any
+/// probing of the `PATH` or default install locations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildConfig {
+    /// CUDA toolkit to use when `CUDA_HOME`/`CUDA_PATH` are unset.
+    pub cuda_home: Option<String>,
+    /// Python interpreter to use when `PYO3_PYTHON` is unset.
+    pub python: Option<String>,
+}
+
 /// Error type for build utilities
 #[derive(Debug)]
 pub enum BuildError {
@@ -78,6 +98,8 @@ pub enum BuildError {
     PythonNotFound,
     CommandFailed(String),
     PathNotFound(String),
+    InvalidArgs(String),
+    InvalidBindings(String),
 }
 
 impl std::fmt::Display for BuildError {
@@ -87,6 +109,8 @@ impl std::fmt::Display for BuildError {
             BuildError::PythonNotFound => write!(f, "Python interpreter not found"),
             BuildError::CommandFailed(cmd) => write!(f, "Command failed: {}", cmd),
             BuildError::PathNotFound(path) => write!(f, "Path not found: {}", path),
+            BuildError::InvalidArgs(msg) => write!(f, "Invalid arguments: {}", msg),
+            BuildError::InvalidBindings(msg) => write!(f, "Invalid bindings: {}", msg),
         }
     }
 }
@@ -99,18 +123,96 @@ pub fn get_env_var_with_rerun(name: &str) -> Result<String, std::env::VarError>
     env::var(name)
 }
 
+/// Name of the optional per-project build configuration file.
+pub const BUILD_CONFIG_FILE: &str = "monarch-build.toml";
+
+/// Parse the contents of a [`BUILD_CONFIG_FILE`].
+///
+/// The file holds flat `key = "value"` lines; blank lines and `#` comments are
+/// allowed. Unknown keys are ignored so one file can serve several checkouts.
+pub fn parse_build_config(contents: &str) -> Result<BuildConfig, BuildError> {
+    let mut config = BuildConfig::default();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let invalid = || {
+            BuildError::InvalidArgs(format!(
+                "{} line {}: expected `key = \"value\"`, got `{}`",
+                BUILD_CONFIG_FILE,
+                index + 1,
+                line
+            ))
+        };
+        let (key, rest) = line.split_once('=').ok_or_else(invalid)?;
+        let (value, trailing) = rest
+            .trim()
+            .strip_prefix('"')
+            .and_then(|rest| rest.split_once('"'))
+            .ok_or_else(invalid)?;
+        let trailing = trailing.trim();
+        if !trailing.is_empty() && !trailing.starts_with('#') {
+            return Err(invalid());
+        }
+        match key.trim() {
+            "cuda_home" => config.cuda_home = Some(value.to_string()),
+            "python" => config.python = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    Ok(config)
+}
+
+/// Find the nearest [`BUILD_CONFIG_FILE`] in `start` or one of its ancestors.
+pub fn find_build_config_file(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(BUILD_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Load the [`BUILD_CONFIG_FILE`] closest to the crate being built.
+///
+/// The search starts at `CARGO_MANIFEST_DIR` (or the current directory) and
+/// walks up, so a file at the workspace root applies to every crate. Cargo is
+/// asked to rerun the build script if the file changes. A missing file yields
+/// the default (empty) configuration; an unreadable or malformed one is
+/// reported as a cargo warning and ignored.
+pub fn build_config() -> BuildConfig {
+    let start = env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .or_else(|| env::current_dir().ok());
+    let Some(path) = start.as_deref().and_then(find_build_config_file) else {
+        return BuildConfig::default();
+    };
+    println!("cargo::rerun-if-changed={}", path.display());
+    let config = std::fs::read_to_string(&path)
+        .map_err(|err| BuildError::PathNotFound(format!("{}: {}", path.display(), err)))
+        .and_then(|contents| parse_build_config(&contents));
+    match config {
+        Ok(config) => config,
+        Err(err) => {
+            println!("cargo::warning=ignoring {}: {}", path.display(), err);
+            BuildConfig::default()
+        }
+    }
+}
+
 /// Find CUDA home directory using various heuristics
 ///
 /// This function attempts to locate CUDA installation through:
 /// 1. CUDA_HOME environment variable
 /// 2. CUDA_PATH environment variable
-/// 3. Finding nvcc in PATH and deriving cuda home
-/// 4. Platform-specific default locations
+/// 3. `cuda_home` in [`BUILD_CONFIG_FILE`]
+/// 4. Finding nvcc in PATH and deriving cuda home
+/// 5. Platform-specific default locations
 pub fn find_cuda_home() -> Option<String> {
-    // Guess #1: Environment variables
+    // Guess #1: Environment variables, then the project's build config
     let mut cuda_home = get_env_var_with_rerun("CUDA_HOME")
         .ok()
-        .or_else(|| get_env_var_with_rerun("CUDA_PATH").ok());
+        .or_else(|| get_env_var_with_rerun("CUDA_PATH").ok())
+        .or_else(|| build_config().cuda_home);
 
     if cuda_home.is_none() {
         // Guess #2: Find nvcc in PATH
@@ -140,10 +242,52 @@ pub fn find_cuda_home() -> Option<String> {
     cuda_home
 }
 
+/// Find the CUDA toolkit to take headers and libraries from for the target.
+///
+/// When cross-compiling (e.g. an x86_64 host building for an aarch64 Jetson),
+/// `MONARCH_TARGET_CUDA_HOME` points at the target's toolkit. Otherwise the
+/// host toolkit from [`find_cuda_home`] is used. Compilers such as nvcc always
+/// come from the host toolkit.
+pub fn find_target_cuda_home() -> Option<String> {
+    get_env_var_with_rerun("MONARCH_TARGET_CUDA_HOME")
+        .ok()
+        .or_else(find_cuda_home)
+}
+
+/// Path to the host's nvcc, which drives codegen even when cross-compiling.
+pub fn find_host_nvcc() -> Option<String> {
+    find_cuda_home().map(|home| format!("{}/bin/nvcc", home))
+}
+
+/// CUDA `targets/<dir>` subdirectories holding headers and libraries for the
+/// given Rust `target_arch` and `target_os`.
+///
+/// aarch64 toolkits ship either `aarch64-linux` (Jetson) or `sbsa-linux`
+/// (server) layouts, so both are candidates.
+pub fn cuda_target_dirs(target_arch: &str, target_os: &str) -> Vec<String> {
+    match (target_arch, target_os) {
+        ("aarch64", "linux") => vec!["aarch64-linux".to_string(), "sbsa-linux".to_string()],
+        (arch, os) => vec![format!("{}-{}", arch, os)],
+    }
+}
+
+/// The `targets/<dir>` subdirectories for the target being compiled.
+///
+/// Build scripts see the target through `CARGO_CFG_TARGET_ARCH` and
+/// `CARGO_CFG_TARGET_OS`; outside of one, the host is assumed.
+fn target_cuda_dirs() -> Vec<String> {
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_else(|_| env::consts::ARCH.to_string());
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_else(|_| env::consts::OS.to_string());
+    cuda_target_dirs(&arch, &os)
+}
+
 /// Discover CUDA configuration including home, include dirs, and lib dirs
+///
+/// The directories come from the target toolkit (see [`find_target_cuda_home`]).
 pub fn discover_cuda_config() -> Result<CudaConfig, BuildError> {
-    let cuda_home = find_cuda_home().ok_or(BuildError::CudaNotFound)?;
+    let cuda_home = find_target_cuda_home().ok_or(BuildError::CudaNotFound)?;
     let cuda_home_path = PathBuf::from(&cuda_home);
+    let target_dirs = target_cuda_dirs();
 
     let mut config = CudaConfig {
         cuda_home: Some(cuda_home_path.clone()),
@@ -152,8 +296,13 @@ pub fn discover_cuda_config() -> Result<CudaConfig, BuildError> {
     };
 
     // Add standard include directories
-    // Check both old-style (include) and new-style (targets/x86_64-linux/include) CUDA installations
-    for include_subdir in &["include", "targets/x86_64-linux/include"] {
+    // Check both old-style (include) and new-style (targets/<arch>-<os>/include) CUDA installations
+    let include_subdirs = std::iter::once("include".to_string()).chain(
+        target_dirs
+            .iter()
+            .map(|target| format!("targets/{}/include", target)),
+    );
+    for include_subdir in include_subdirs {
         let include_dir = cuda_home_path.join(include_subdir);
         if include_dir.exists() {
             config.include_dirs.push(include_dir);
@@ -161,8 +310,16 @@ pub fn discover_cuda_config() -> Result<CudaConfig, BuildError> {
     }
 
     // Add standard library directories
-    // Check both old-style (lib64, lib) and new-style (targets/x86_64-linux/lib) CUDA installations
-    for lib_subdir in &["lib64", "lib", "lib/x64", "targets/x86_64-linux/lib"] {
+    // Check both old-style (lib64, lib) and new-style (targets/<arch>-<os>/lib) CUDA installations
+    let lib_subdirs = ["lib64", "lib", "lib/x64"]
+        .into_iter()
+        .map(str::to_string)
+        .chain(
+            target_dirs
+                .iter()
+                .map(|target| format!("targets/{}/lib", target)),
+        );
+    for lib_subdir in lib_subdirs {
         let lib_dir = cuda_home_path.join(lib_subdir);
         if lib_dir.exists() {
             config.lib_dirs.push(lib_dir);
@@ -198,11 +355,16 @@ pub fn get_cuda_lib_dir() -> Result<String, BuildError> {
         return Ok(cuda_lib_dir);
     }
 
-    // Try to deduce from CUDA configuration
+    // Try to deduce from the target's CUDA configuration
     let cuda_config = discover_cuda_config()?;
     if let Some(cuda_home) = cuda_config.cuda_home {
         // Check both old-style and new-style CUDA library paths
-        for lib_subdir in &["lib64", "lib", "targets/x86_64-linux/lib"] {
+        let lib_subdirs = ["lib64", "lib"].into_iter().map(str::to_string).chain(
+            target_cuda_dirs()
+                .into_iter()
+                .map(|target| format!("targets/{}/lib", target)),
+        );
+        for lib_subdir in lib_subdirs {
             let lib_path = cuda_home.join(lib_subdir);
             if lib_path.exists() {
                 return Ok(lib_path.to_string_lossy().to_string());
@@ -215,46 +377,629 @@ pub fn get_cuda_lib_dir() -> Result<String, BuildError> {
     ))
 }
 
-/// Discover Python environment directories using sysconfig
+/// Whether `CUDA_LINK_STATIC=1` requests linking the CUDA runtime statically.
+pub fn cuda_link_static() -> bool {
+    get_env_var_with_rerun("CUDA_LINK_STATIC").is_ok_and(|v| v == "1")
+}
+
+/// Cargo link directives for the CUDA runtime.
 ///
-/// Returns tuple of (include_dir, lib_dir) as optional strings
-pub fn python_env_dirs() -> Result<PythonConfig, BuildError> {
-    python_env_dirs_with_interpreter("python")
+/// Dynamic linking (the default) links `libcudart.so`, which must then be
+/// present on the deploy host. Static linking links `libcudart_static.a` plus
+/// the libraries it depends on, so binaries don't need the runtime installed,
+/// at the cost of larger binaries and a runtime version fixed at build time.
+/// Either way the driver (`libcuda.so`) is still needed on the host.
+pub fn cudart_link_directives(static_link: bool) -> Vec<String> {
+    if static_link {
+        vec![
+            "cargo:rustc-link-lib=static=cudart_static".to_string(),
+            "cargo:rustc-link-lib=static=culibos".to_string(),
+            "cargo:rustc-link-lib=rt".to_string(),
+            "cargo:rustc-link-lib=pthread".to_string(),
+            "cargo:rustc-link-lib=dl".to_string(),
+        ]
+    } else {
+        vec!["cargo:rustc-link-lib=cudart".to_string()]
+    }
 }
 
-/// Discover Python environment directories with specific interpreter
-pub fn python_env_dirs_with_interpreter(interpreter: &str) -> Result<PythonConfig, BuildError> {
+/// Emit the link directives for the CUDA runtime found in `cuda_lib_dir`,
+/// honoring `CUDA_LINK_STATIC`.
+///
+/// Fails if static linking is requested but `libcudart_static.a` isn't in
+/// `cuda_lib_dir`.
+pub fn link_cudart(cuda_lib_dir: &str) -> Result<(), BuildError> {
+    let static_link = cuda_link_static();
+    if static_link {
+        let static_lib = Path::new(cuda_lib_dir).join("libcudart_static.a");
+        if !static_lib.exists() {
+            return Err(BuildError::PathNotFound(format!(
+                "{} (required by CUDA_LINK_STATIC=1)",
+                static_lib.display()
+            )));
+        }
+    }
+    for directive in cudart_link_directives(static_link) {
+        println!("{}", directive);
+    }
+    Ok(())
+}
+
+/// The compute backend a `-sys` crate was built against, published to the build
+/// scripts of its dependents as `links` metadata.
+///
+/// Dependents read it with [`BackendMetadata::from_dep_env`] instead of probing
+/// for CUDA again, so every crate in the graph agrees on the toolkit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendMetadata {
+    /// Compute backend, currently always `cuda`.
+    pub backend: String,
+    /// CUDA toolkit the crate was built against.
+    pub cuda_home: String,
+    /// CUDA library directory the crate linked from, if it linked at all.
+    pub cuda_lib_dir: Option<String>,
+}
+
+impl BackendMetadata {
+    /// Metadata for a build against the CUDA toolkit at `cuda_home`.
+    pub fn cuda(cuda_home: &str, cuda_lib_dir: Option<&str>) -> Self {
+        Self {
+            backend: "cuda".to_string(),
+            cuda_home: cuda_home.to_string(),
+            cuda_lib_dir: cuda_lib_dir.map(str::to_string),
+        }
+    }
+
+    /// Cargo directives that publish this metadata. Dependents see each key as
+    /// `DEP_<LINKS>_<KEY>`, e.g. `DEP_RDMAXCEL_SYS_BACKEND`.
+    pub fn directives(&self) -> Vec<String> {
+        let mut directives = vec![
+            format!("cargo:backend={}", self.backend),
+            format!("cargo:cuda_home={}", self.cuda_home),
+        ];
+        if let Some(cuda_lib_dir) = &self.cuda_lib_dir {
+            directives.push(format!("cargo:cuda_lib_dir={}", cuda_lib_dir));
+        }
+        directives
+    }
+
+    /// Reads the metadata published by the dependency whose `links` key is
+    /// `links` (e.g. `rdmaxcel_sys`). Returns `None` if it published none.
+    pub fn from_dep_env(links: &str) -> Option<Self> {
+        let prefix = format!("DEP_{}", links.to_uppercase());
+        let var = |key: &str| env::var(format!("{}_{}", prefix, key)).ok();
+        Some(Self {
+            backend: var("BACKEND")?,
+            cuda_home: var("CUDA_HOME")?,
+            cuda_lib_dir: var("CUDA_LIB_DIR"),
+        })
+    }
+}
+
+/// A prebuilt rdmaxcel installed under a prefix, used by `rdmaxcel-sys` in place
+/// of compiling its sources when `RDMAXCEL_SYS_USE_SYSTEM_LIB` is set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemRdmaxcel {
+    /// `rdmaxcel.h`, from which bindings are generated.
+    pub header: PathBuf,
+    /// Directory holding the libraries.
+    pub lib_dir: PathBuf,
+    /// Libraries to link, in link order, with whether each is a static archive.
+    pub libs: Vec<(String, bool)>,
+}
+
+/// Libraries built from the rdmaxcel sources, in link order. Only the first is
+/// required: an install may ship everything combined in `librdmaxcel`.
+const RDMAXCEL_LIBS: &[&str] = &["rdmaxcel", "rdmaxcel_cpp", "rdmaxcel_cuda"];
+
+/// The prefix named by `RDMAXCEL_SYS_USE_SYSTEM_LIB`, if set and non-empty.
+pub fn rdmaxcel_system_prefix() -> Option<PathBuf> {
+    get_env_var_with_rerun("RDMAXCEL_SYS_USE_SYSTEM_LIB")
+        .ok()
+        .filter(|prefix| !prefix.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Locate a prebuilt rdmaxcel under `prefix`: the header in `include/` and the
+/// libraries in `lib64/` or `lib/`, whichever holds `librdmaxcel`.
+pub fn find_system_rdmaxcel(prefix: &Path) -> Result<SystemRdmaxcel, BuildError> {
+    let header = prefix.join("include").join("rdmaxcel.h");
+    if !header.is_file() {
+        return Err(BuildError::PathNotFound(header.display().to_string()));
+    }
+    let lib_dirs = [prefix.join("lib64"), prefix.join("lib")];
+    let lib_dir = lib_dirs
+        .iter()
+        .find(|dir| find_library(std::slice::from_ref(*dir), RDMAXCEL_LIBS[0]).is_some())
+        .ok_or_else(|| {
+            BuildError::PathNotFound(format!("librdmaxcel in {}/lib{{64,}}", prefix.display()))
+        })?
+        .clone();
+    let libs = RDMAXCEL_LIBS
+        .iter()
+        .filter_map(|name| {
+            let path = find_library(std::slice::from_ref(&lib_dir), name)?;
+            let is_static = path.extension().is_some_and(|ext| ext == "a");
+            Some((name.to_string(), is_static))
+        })
+        .collect();
+    Ok(SystemRdmaxcel {
+        header,
+        lib_dir,
+        libs,
+    })
+}
+
+impl SystemRdmaxcel {
+    /// Cargo link directives for the prebuilt libraries.
+    pub fn link_directives(&self) -> Vec<String> {
+        let mut directives = vec![format!(
+            "cargo:rustc-link-search=native={}",
+            self.lib_dir.display()
+        )];
+        for (name, is_static) in &self.libs {
+            let kind = if *is_static { "static=" } else { "" };
+            directives.push(format!("cargo:rustc-link-lib={}{}", kind, name));
+        }
+        directives
+    }
+}
+
+/// Whether `TORCH_SYS_USE_PYTORCH_APIS` (default `1`) allows running Python to
+/// locate PyTorch and linking against it. Set it to `0` to build without PyTorch.
+pub fn use_pytorch_apis() -> bool {
+    get_env_var_with_rerun("TORCH_SYS_USE_PYTORCH_APIS").map_or(true, |v| v == "1")
+}
+
+/// Cargo link directives for the PyTorch libraries that provide the c10 symbols.
+///
+/// Nothing is emitted when `use_pytorch_apis` is false, so a crate can be built
+/// and linked without PyTorch installed. `libtorch_lib` is added to the link
+/// search path when known.
+pub fn torch_link_directives(use_pytorch_apis: bool, libtorch_lib: Option<&str>) -> Vec<String> {
+    if !use_pytorch_apis {
+        return Vec::new();
+    }
+    let mut directives = Vec::new();
+    if let Some(path) = libtorch_lib {
+        directives.push(format!("cargo:rustc-link-search=native={}", path));
+    }
+    for lib in ["torch_cpu", "torch", "c10"] {
+        directives.push(format!("cargo:rustc-link-lib={}", lib));
+    }
+    directives
+}
+
+/// Environment variable holding extra clang arguments for bindgen, e.g. to add
+/// include paths or defines for a non-standard install.
+pub const BINDGEN_EXTRA_CLANG_ARGS_ENV: &str = "MONARCH_BINDGEN_EXTRA_CLANG_ARGS";
+
+/// Split `args` into words the way a POSIX shell would, without expansion.
+///
+/// Words are separated by whitespace. Single quotes preserve their contents
+/// literally; inside double quotes a backslash escapes `"`, `\`, `$` and
+/// `` ` ``; elsewhere a backslash escapes the next character. Fails on an
+/// unterminated quote or a trailing backslash.
+pub fn split_shell_args(args: &str) -> Result<Vec<String>, BuildError> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    // Whether a word has been started, so that `''` yields an empty word.
+    let mut in_word = false;
+    let mut chars = args.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => {
+                            return Err(BuildError::InvalidArgs(format!(
+                                "unterminated single quote in {:?}",
+                                args
+                            )));
+                        }
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => {
+                                return Err(BuildError::InvalidArgs(format!(
+                                    "unterminated double quote in {:?}",
+                                    args
+                                )));
+                            }
+                        },
+                        Some(c) => word.push(c),
+                        None => {
+                            return Err(BuildError::InvalidArgs(format!(
+                                "unterminated double quote in {:?}",
+                                args
+                            )));
+                        }
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some(c) => word.push(c),
+                    None => {
+                        return Err(BuildError::InvalidArgs(format!(
+                            "trailing backslash in {:?}",
+                            args
+                        )));
+                    }
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// Extra clang arguments for bindgen from [`BINDGEN_EXTRA_CLANG_ARGS_ENV`],
+/// split with [`split_shell_args`]. Empty if the variable is unset.
+pub fn bindgen_extra_clang_args() -> Result<Vec<String>, BuildError> {
+    match get_env_var_with_rerun(BINDGEN_EXTRA_CLANG_ARGS_ENV) {
+        Ok(args) => split_shell_args(&args),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+/// Environment variable holding extra bindgen allowlist regexes for
+/// rdmaxcel-sys, so more of the ibverbs/mlx5 API can be bound without
+/// patching its build script.
+pub const RDMA_EXTRA_ALLOWLIST_ENV: &str = "MONARCH_RDMA_EXTRA_ALLOWLIST";
+
+/// Split a comma-separated list, trimming whitespace and dropping empty
+/// entries.
+pub fn split_comma_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Extra allowlist regexes from [`RDMA_EXTRA_ALLOWLIST_ENV`]. Each one is
+/// meant to be applied as both a function and a type allowlist entry. Empty
+/// if the variable is unset.
+pub fn rdma_extra_allowlist() -> Vec<String> {
+    get_env_var_with_rerun(RDMA_EXTRA_ALLOWLIST_ENV)
+        .map(|list| split_comma_list(&list))
+        .unwrap_or_default()
+}
+
+/// nvcc `-gencode` arguments for a CMake-style `CUDA_ARCHITECTURES` list.
+///
+/// `archs` is semicolon-separated (e.g. `"80;90"`). An entry produces
+/// `-gencode arch=compute_X,code=sm_X`, or `code=compute_X` (PTX only) with
+/// CMake's `-virtual` suffix. The `-real` suffix is accepted and ignored.
+pub fn gencode_flags(archs: &str) -> Vec<String> {
+    archs
+        .split(';')
+        .map(|arch| arch.trim().trim_end_matches("-real"))
+        .filter(|arch| !arch.is_empty())
+        .flat_map(|arch| {
+            let code = match arch.strip_suffix("-virtual") {
+                Some(arch) => format!("arch=compute_{},code=compute_{}", arch, arch),
+                None => format!("arch=compute_{},code=sm_{}", arch, arch),
+            };
+            ["-gencode".to_string(), code]
+        })
+        .collect()
+}
+
+/// The CUDA architectures to compile device code for.
+///
+/// Honors `CUDA_ARCHITECTURES` if set, otherwise uses the compute capability
+/// of the first installed device as reported by `nvidia-smi`. Returns `None`
+/// if neither is available, leaving nvcc on its default target.
+pub fn cuda_architectures() -> Option<String> {
+    if let Ok(archs) = get_env_var_with_rerun("CUDA_ARCHITECTURES") {
+        return Some(archs);
+    }
+    let output = std::process::Command::new("nvidia-smi")
+        .args(["--query-gpu=compute_cap", "--format=csv,noheader"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let arch = stdout.lines().next()?.trim().replace('.', "");
+    (!arch.is_empty()).then_some(arch)
+}
+
+/// Parse `KEY: value` lines into values grouped by key.
+///
+/// Values for a repeated key accumulate in the order they appear. Lines
+/// without a `": "` separator are ignored.
+pub fn parse_kv(output: &str) -> HashMap<String, Vec<String>> {
+    let mut values: HashMap<String, Vec<String>> = HashMap::new();
+    for line in output.lines() {
+        if let Some((key, value)) = line.split_once(": ") {
+            values
+                .entry(key.to_string())
+                .or_default()
+                .push(value.to_string());
+        }
+    }
+    values
+}
+
+/// Run a Python `script` with `interpreter` and parse its `KEY: value` output
+/// lines with [`parse_kv`].
+///
+/// Fails if the interpreter can't be run or the script exits with an error.
+pub fn run_python_kv(
+    interpreter: impl AsRef<OsStr>,
+    script: &str,
+) -> Result<HashMap<String, Vec<String>>, BuildError> {
+    let interpreter = interpreter.as_ref();
     let output = std::process::Command::new(interpreter)
         .arg("-c")
-        .arg(PYTHON_PRINT_DIRS)
+        .arg(script)
         .output()
-        .map_err(|_| BuildError::CommandFailed(format!("running {}", interpreter)))?;
+        .map_err(|_| BuildError::CommandFailed(format!("running {:?}", interpreter)))?;
 
     if !output.status.success() {
         return Err(BuildError::CommandFailed(format!(
-            "{} exited with error",
+            "{:?} exited with error",
             interpreter
         )));
     }
 
-    let mut include_dir = None;
-    let mut lib_dir = None;
+    Ok(parse_kv(&String::from_utf8_lossy(&output.stdout)))
+}
 
-    for line in String::from_utf8_lossy(&output.stdout).lines() {
-        if let Some(path) = line.strip_prefix("PYTHON_INCLUDE_DIR: ") {
-            include_dir = Some(path.to_string());
-        }
-        if let Some(path) = line.strip_prefix("PYTHON_LIB_DIR: ") {
-            lib_dir = Some(path.to_string());
-        }
+/// Extract PyTorch's `_GLIBCXX_USE_CXX11_ABI` setting from the
+/// `LIBTORCH_CXX11` line of [`parse_kv`] output.
+///
+/// Returns `None` if the line is missing or isn't `True`/`False`.
+pub fn parse_torch_cxx11_abi(values: &HashMap<String, Vec<String>>) -> Option<bool> {
+    match values.get("LIBTORCH_CXX11")?.last()?.as_str() {
+        "True" => Some(true),
+        "False" => Some(false),
+        _ => None,
     }
+}
+
+/// Query the `_GLIBCXX_USE_CXX11_ABI` setting of the PyTorch installed for
+/// `interpreter`.
+///
+/// C++ code that uses c10 must be compiled with the same setting, or linking
+/// against torch fails on `std::string`-taking symbols. Returns `None` if
+/// torch can't be imported.
+pub fn torch_cxx11_abi(interpreter: impl AsRef<OsStr>) -> Option<bool> {
+    run_python_kv(interpreter, PYTHON_PRINT_CXX11_ABI)
+        .ok()
+        .and_then(|values| parse_torch_cxx11_abi(&values))
+}
+
+/// Interpreter names tried, in order, when `PYO3_PYTHON` isn't usable.
+pub const PYTHON_INTERPRETER_CANDIDATES: [&str; 2] = ["python3", "python"];
+
+/// Check whether `--version` output names a Python 3 interpreter.
+///
+/// Python 2 prints its version to stderr, so callers should pass both streams.
+pub fn is_python3_version(output: &str) -> bool {
+    output
+        .lines()
+        .any(|line| line.trim().starts_with("Python 3."))
+}
+
+/// Check whether `interpreter` can be run and is a Python 3 interpreter.
+pub fn is_python3(interpreter: impl AsRef<OsStr>) -> bool {
+    std::process::Command::new(interpreter)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| {
+            output.status.success()
+                && (is_python3_version(&String::from_utf8_lossy(&output.stdout))
+                    || is_python3_version(&String::from_utf8_lossy(&output.stderr)))
+        })
+}
+
+/// Pick the Python interpreter to use for build-time discovery.
+///
+/// Tries `pyo3_python` (the value of `PYO3_PYTHON`, if set) and then each of
+/// [`PYTHON_INTERPRETER_CANDIDATES`], returning the first one for which
+/// `is_python3` holds.
+pub fn select_python_interpreter(
+    pyo3_python: Option<&str>,
+    is_python3: impl Fn(&str) -> bool,
+) -> Option<String> {
+    pyo3_python
+        .into_iter()
+        .chain(PYTHON_INTERPRETER_CANDIDATES)
+        .find(|&candidate| is_python3(candidate))
+        .map(str::to_string)
+}
+
+/// Find the Python 3 interpreter build scripts should run.
+///
+/// This is the one place interpreter discovery happens; build scripts must
+/// call it rather than hardcoding a name. `PYO3_PYTHON` takes precedence, or
+/// else `python` from [`BUILD_CONFIG_FILE`], then `python3` and `python` on the
+/// `PATH`. If none of them is a working Python 3, returns `python3` so that the
+/// caller's error names a sensible command.
+pub fn find_python_interpreter() -> PathBuf {
+    let pyo3_python = get_env_var_with_rerun("PYO3_PYTHON")
+        .ok()
+        .or_else(|| build_config().python);
+    select_python_interpreter(pyo3_python.as_deref(), |candidate| is_python3(candidate))
+        .unwrap_or_else(|| PYTHON_INTERPRETER_CANDIDATES[0].to_string())
+        .into()
+}
+
+/// Discover Python environment directories using sysconfig
+///
+/// Returns tuple of (include_dir, lib_dir) as optional strings
+pub fn python_env_dirs() -> Result<PythonConfig, BuildError> {
+    python_env_dirs_with_interpreter(find_python_interpreter())
+}
+
+/// Discover Python environment directories with specific interpreter
+pub fn python_env_dirs_with_interpreter(
+    interpreter: impl AsRef<OsStr>,
+) -> Result<PythonConfig, BuildError> {
+    let mut values = run_python_kv(interpreter, PYTHON_PRINT_DIRS)?;
+    let mut last = |key: &str| values.remove(key).and_then(|mut v| v.pop());
 
     Ok(PythonConfig {
-        include_dir,
-        lib_dir,
+        include_dir: last("PYTHON_INCLUDE_DIR"),
+        lib_dir: last("PYTHON_LIB_DIR"),
+    })
+}
+
+/// Generated bindings shorter than this can't contain a useful allowlist.
+pub const MIN_BINDINGS_LEN: usize = 1024;
+
+/// Check that bindgen output looks usable before it is written out.
+///
+/// When clang can't find a header it may still produce a nearly empty
+/// `bindings.rs`; the crate then compiles but has no symbols, and dependents
+/// fail with confusing errors. This fails instead if `bindings` is shorter
+/// than [`MIN_BINDINGS_LEN`] or never mentions the identifier `sentinel`.
+pub fn check_bindings(bindings: &str, sentinel: &str) -> Result<(), BuildError> {
+    let hint = "check that clang can find the headers (see MONARCH_BINDGEN_EXTRA_CLANG_ARGS)";
+    if bindings.len() < MIN_BINDINGS_LEN {
+        return Err(BuildError::InvalidBindings(format!(
+            "generated bindings are only {} bytes; {}",
+            bindings.len(),
+            hint
+        )));
+    }
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let found = bindings.match_indices(sentinel).any(|(start, _)| {
+        let before = bindings[..start].chars().next_back();
+        let after = bindings[start + sentinel.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    });
+    if !found {
+        return Err(BuildError::InvalidBindings(format!(
+            "generated bindings do not define `{}`; {}",
+            sentinel, hint
+        )));
+    }
+    Ok(())
+}
+
+/// Check whether `output` needs to be rebuilt from `inputs`.
+///
+/// Returns true if the output does not exist, or if any input is missing or
+/// has a modification time newer than the output. This mirrors the
+/// incremental behavior of the `cc` crate for tools (like nvcc) that we
+/// invoke by hand.
+pub fn needs_rebuild<P: AsRef<Path>>(output: impl AsRef<Path>, inputs: &[P]) -> bool {
+    let output_mtime = match std::fs::metadata(output.as_ref()).and_then(|m| m.modified()) {
+        Ok(mtime) => mtime,
+        Err(_) => return true,
+    };
+    inputs.iter().any(
+        |input| match std::fs::metadata(input.as_ref()).and_then(|m| m.modified()) {
+            Ok(mtime) => mtime > output_mtime,
+            Err(_) => true,
+        },
+    )
+}
+
+/// Record a hash of the command line `program args...` in the stamp file `path`.
+///
+/// The stamp is only rewritten when the hash changes, so passing it to
+/// [`needs_rebuild`] as an input rebuilds the output exactly when the flags
+/// used to produce it change.
+pub fn write_command_stamp<S: AsRef<OsStr>>(
+    path: impl AsRef<Path>,
+    program: impl AsRef<OsStr>,
+    args: &[S],
+) -> std::io::Result<()> {
+    use std::hash::Hash;
+    use std::hash::Hasher;
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    program.as_ref().hash(&mut hasher);
+    for arg in args {
+        arg.as_ref().hash(&mut hasher);
+    }
+    let stamp = format!("{:016x}\n", hasher.finish());
+    if std::fs::read_to_string(path.as_ref()).ok().as_deref() != Some(stamp.as_str()) {
+        std::fs::write(path.as_ref(), stamp)?;
+    }
+    Ok(())
+}
+
+/// Find the library `name` in `dirs`, returning the path of the first match.
+///
+/// Directories are searched in order. Within a directory, `lib{name}.so` is
+/// preferred, then `lib{name}.a`, then a versioned `lib{name}.so.*` (the
+/// lexicographically first, for determinism).
+pub fn find_library(dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+    let shared = format!("lib{}.so", name);
+    let archive = format!("lib{}.a", name);
+    dirs.iter().find_map(|dir| {
+        [&shared, &archive]
+            .into_iter()
+            .map(|file| dir.join(file))
+            .find(|path| path.is_file())
+            .or_else(|| {
+                let mut versioned: Vec<PathBuf> = std::fs::read_dir(dir)
+                    .ok()?
+                    .filter_map(Result::ok)
+                    .filter(|entry| {
+                        entry
+                            .file_name()
+                            .to_str()
+                            .and_then(|file| file.strip_prefix(&shared))
+                            .is_some_and(|rest| rest.starts_with('.'))
+                    })
+                    .map(|entry| entry.path())
+                    .collect();
+                versioned.sort();
+                versioned.into_iter().next()
+            })
     })
 }
 
+/// Return the subset of `names` for which [`find_library`] finds a library in
+/// `lib_dirs`.
+///
+/// Use this before emitting `rustc-link-lib` for libraries that not every
+/// PyTorch build ships (e.g. `c10_cuda`), so a missing one is skipped rather
+/// than failing the link.
+pub fn find_libs<'a>(lib_dirs: &[PathBuf], names: &[&'a str]) -> Vec<&'a str> {
+    names
+        .iter()
+        .copied()
+        .filter(|name| find_library(lib_dirs, name).is_some())
+        .collect()
+}
+
 /// Print helpful error message for CUDA not found
 pub fn print_cuda_error_help() {
     eprintln!("Error: CUDA installation not found!");
@@ -278,20 +1023,594 @@ pub fn print_cuda_lib_error_help() {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Mutex;
+
     use super::*;
 
+    // Serializes tests that modify the process environment.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
     #[test]
     fn test_find_cuda_home_env_var() {
+        let _env = ENV_LOCK.lock().unwrap();
         env::set_var("CUDA_HOME", "/test/cuda");
         let result = find_cuda_home();
         env::remove_var("CUDA_HOME");
         assert_eq!(result, Some("/test/cuda".to_string()));
     }
 
+    #[test]
+    fn test_target_cuda_home_overrides_host_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _env = ENV_LOCK.lock().unwrap();
+        let dir = env::temp_dir().join(format!("build_utils_target_cuda_{}", std::process::id()));
+        let host = dir.join("host");
+        let target = dir.join("target");
+        std::fs::create_dir_all(host.join("bin")).unwrap();
+        std::fs::create_dir_all(target.join("targets/aarch64-linux/lib")).unwrap();
+        std::fs::create_dir_all(target.join("include")).unwrap();
+        let nvcc = host.join("bin/nvcc");
+        std::fs::write(&nvcc, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&nvcc, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let saved: Vec<_> = ["PATH", "CUDA_HOME", "CUDA_PATH"]
+            .into_iter()
+            .map(|name| (name, env::var_os(name)))
+            .collect();
+        env::remove_var("CUDA_HOME");
+        env::remove_var("CUDA_PATH");
+        env::set_var("PATH", host.join("bin"));
+        env::set_var("CARGO_CFG_TARGET_ARCH", "aarch64");
+        env::set_var("CARGO_CFG_TARGET_OS", "linux");
+
+        // Without an override, the toolkit is found through nvcc on the host PATH.
+        let host_home = host.to_string_lossy().into_owned();
+        assert_eq!(find_target_cuda_home(), Some(host_home.clone()));
+
+        env::set_var("MONARCH_TARGET_CUDA_HOME", &target);
+        let target_home = find_target_cuda_home();
+        let config = discover_cuda_config();
+        let host_nvcc = find_host_nvcc();
+
+        env::remove_var("MONARCH_TARGET_CUDA_HOME");
+        env::remove_var("CARGO_CFG_TARGET_ARCH");
+        env::remove_var("CARGO_CFG_TARGET_OS");
+        for (name, value) in saved {
+            match value {
+                Some(value) => env::set_var(name, value),
+                None => env::remove_var(name),
+            }
+        }
+
+        assert_eq!(target_home, Some(target.to_string_lossy().into_owned()));
+        let config = config.unwrap();
+        assert_eq!(config.cuda_home, Some(target.clone()));
+        assert_eq!(config.include_dirs, vec![target.join("include")]);
+        assert_eq!(
+            config.lib_dirs,
+            vec![target.join("targets/aarch64-linux/lib")]
+        );
+        // Codegen still uses the host compiler.
+        assert_eq!(host_nvcc, Some(format!("{}/bin/nvcc", host_home)));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_cuda_target_dirs() {
+        assert_eq!(cuda_target_dirs("x86_64", "linux"), vec!["x86_64-linux"]);
+        assert_eq!(
+            cuda_target_dirs("aarch64", "linux"),
+            vec!["aarch64-linux", "sbsa-linux"]
+        );
+    }
+
     #[test]
     fn test_python_scripts_constants() {
         assert!(PYTHON_PRINT_DIRS.contains("sysconfig"));
         assert!(PYTHON_PRINT_PYTORCH_DETAILS.contains("torch"));
         assert!(PYTHON_PRINT_CUDA_DETAILS.contains("CUDA_HOME"));
     }
+
+    #[test]
+    fn test_cudart_link_directives() {
+        assert_eq!(
+            cudart_link_directives(false),
+            vec!["cargo:rustc-link-lib=cudart"]
+        );
+        let static_directives = cudart_link_directives(true);
+        assert_eq!(
+            static_directives[0],
+            "cargo:rustc-link-lib=static=cudart_static"
+        );
+        for lib in ["culibos", "rt", "pthread", "dl"] {
+            assert!(
+                static_directives
+                    .iter()
+                    .any(|d| d.ends_with(&format!("={}", lib))),
+                "missing {} in {:?}",
+                lib,
+                static_directives
+            );
+        }
+        assert!(!static_directives.iter().any(|d| d.ends_with("=cudart")));
+    }
+
+    #[test]
+    fn test_torch_link_directives() {
+        assert!(torch_link_directives(false, None).is_empty());
+        assert!(torch_link_directives(false, Some("/opt/torch/lib")).is_empty());
+
+        let directives = torch_link_directives(true, None);
+        assert_eq!(
+            directives,
+            vec![
+                "cargo:rustc-link-lib=torch_cpu",
+                "cargo:rustc-link-lib=torch",
+                "cargo:rustc-link-lib=c10",
+            ]
+        );
+
+        let directives = torch_link_directives(true, Some("/opt/torch/lib"));
+        assert_eq!(
+            directives[0],
+            "cargo:rustc-link-search=native=/opt/torch/lib"
+        );
+        assert_eq!(directives.len(), 4);
+    }
+
+    #[test]
+    fn test_parse_kv() {
+        let output = "\
+LIBTORCH_CXX11: True
+LIBTORCH_INCLUDE: /torch/include
+LIBTORCH_INCLUDE: /torch/include/torch/csrc/api/include
+not a key value line
+LIBTORCH_LIB: /torch/lib
+CUDA_HOME: /usr/local/cuda
+";
+        let values = parse_kv(output);
+        assert_eq!(values.len(), 4);
+        assert_eq!(values["LIBTORCH_CXX11"], vec!["True"]);
+        assert_eq!(
+            values["LIBTORCH_INCLUDE"],
+            vec!["/torch/include", "/torch/include/torch/csrc/api/include"]
+        );
+        assert_eq!(values["LIBTORCH_LIB"], vec!["/torch/lib"]);
+        assert_eq!(values["CUDA_HOME"], vec!["/usr/local/cuda"]);
+        assert!(parse_kv("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_torch_cxx11_abi() {
+        let abi = |output: &str| parse_torch_cxx11_abi(&parse_kv(output));
+        assert_eq!(
+            abi("LIBTORCH_CXX11: True\nLIBTORCH_LIB: /torch/lib\n"),
+            Some(true)
+        );
+        assert_eq!(abi("LIBTORCH_CXX11: False\n"), Some(false));
+        assert_eq!(abi("LIBTORCH_CXX11: 1\n"), None);
+        assert_eq!(abi("LIBTORCH_LIB: /torch/lib\n"), None);
+    }
+
+    #[test]
+    fn test_gencode_flags() {
+        assert_eq!(
+            gencode_flags("80;90"),
+            vec![
+                "-gencode",
+                "arch=compute_80,code=sm_80",
+                "-gencode",
+                "arch=compute_90,code=sm_90",
+            ]
+        );
+        assert_eq!(
+            gencode_flags(" 86-real ;;89-virtual;"),
+            vec![
+                "-gencode",
+                "arch=compute_86,code=sm_86",
+                "-gencode",
+                "arch=compute_89,code=compute_89",
+            ]
+        );
+        assert!(gencode_flags("").is_empty());
+    }
+
+    #[test]
+    fn test_find_library_naming_conventions() {
+        use std::fs::File;
+
+        let dir = env::temp_dir().join(format!("build_utils_find_library_{}", std::process::id()));
+        let first = dir.join("first");
+        let second = dir.join("second");
+        std::fs::create_dir_all(&first).unwrap();
+        std::fs::create_dir_all(&second).unwrap();
+        File::create(first.join("libshared.so")).unwrap();
+        File::create(first.join("libboth.a")).unwrap();
+        File::create(first.join("libboth.so")).unwrap();
+        File::create(first.join("libversioned.so.2.5")).unwrap();
+        File::create(first.join("libversioned.so.1")).unwrap();
+        File::create(second.join("libarchive.a")).unwrap();
+        File::create(second.join("libshared.so")).unwrap();
+        // Different library with a shared prefix.
+        File::create(first.join("libshared_extra.so")).unwrap();
+        let dirs = vec![first.clone(), second.clone()];
+
+        assert_eq!(
+            find_library(&dirs, "shared"),
+            Some(first.join("libshared.so"))
+        );
+        assert_eq!(find_library(&dirs, "both"), Some(first.join("libboth.so")));
+        assert_eq!(
+            find_library(&dirs, "versioned"),
+            Some(first.join("libversioned.so.1"))
+        );
+        assert_eq!(
+            find_library(&dirs, "archive"),
+            Some(second.join("libarchive.a"))
+        );
+        assert_eq!(find_library(&dirs, "shared_ext"), None);
+        assert_eq!(find_library(&dirs, "missing"), None);
+        assert_eq!(find_library(&[dir.join("nonexistent")], "shared"), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_find_libs_synthetic_dir() {
+        use std::fs::File;
+
+        let dir = env::temp_dir().join(format!("build_utils_find_libs_{}", std::process::id()));
+        let other = dir.join("other");
+        std::fs::create_dir_all(&other).unwrap();
+        File::create(dir.join("libc10.so")).unwrap();
+        File::create(dir.join("libc10_hip.so.2.5")).unwrap();
+        File::create(other.join("libtorch_cpu.a")).unwrap();
+        // Not a match for `c10_cuda`: different library with a shared prefix.
+        File::create(dir.join("libc10_cudart.so")).unwrap();
+
+        assert_eq!(
+            find_libs(std::slice::from_ref(&dir), &["c10", "c10_cuda", "c10_hip"]),
+            vec!["c10", "c10_hip"]
+        );
+        assert_eq!(
+            find_libs(&[dir.clone(), other], &["torch_cpu", "c10_cuda"]),
+            vec!["torch_cpu"]
+        );
+        assert!(find_libs(&[dir.join("missing")], &["c10"]).is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_needs_rebuild_mtimes() {
+        use std::fs::File;
+        use std::time::Duration;
+        use std::time::SystemTime;
+
+        let dir = env::temp_dir().join(format!("build_utils_rebuild_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("kernel.cu");
+        let header = dir.join("kernel.h");
+        let object = dir.join("kernel.o");
+
+        let now = SystemTime::now();
+        let set_mtime = |path: &Path, mtime: SystemTime| {
+            File::create(path).unwrap().set_modified(mtime).unwrap();
+        };
+        set_mtime(&source, now - Duration::from_secs(20));
+        set_mtime(&header, now - Duration::from_secs(20));
+
+        // Missing output always needs a rebuild.
+        assert!(needs_rebuild(&object, &[&source, &header]));
+
+        // Output newer than all inputs is up to date.
+        set_mtime(&object, now - Duration::from_secs(10));
+        assert!(!needs_rebuild(&object, &[&source, &header]));
+
+        // Touching a header invalidates the output.
+        set_mtime(&header, now);
+        assert!(needs_rebuild(&object, &[&source, &header]));
+
+        // A missing input is treated as changed.
+        assert!(needs_rebuild(&object, &[dir.join("missing.h")]));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_write_command_stamp() {
+        let dir = env::temp_dir().join(format!("build_utils_stamp_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let stamp = dir.join("kernel.cmd");
+
+        write_command_stamp(&stamp, "nvcc", &["-O2", "-c"]).unwrap();
+        let first = std::fs::read_to_string(&stamp).unwrap();
+        let mtime = std::fs::metadata(&stamp).unwrap().modified().unwrap();
+
+        // The same command line leaves the stamp untouched.
+        write_command_stamp(&stamp, "nvcc", &["-O2", "-c"]).unwrap();
+        assert_eq!(std::fs::read_to_string(&stamp).unwrap(), first);
+        assert_eq!(
+            std::fs::metadata(&stamp).unwrap().modified().unwrap(),
+            mtime
+        );
+
+        // Changing a flag changes the stamp.
+        write_command_stamp(&stamp, "nvcc", &["-O3", "-c"]).unwrap();
+        assert_ne!(std::fs::read_to_string(&stamp).unwrap(), first);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_split_shell_args() {
+        assert_eq!(split_shell_args("").unwrap(), Vec::<String>::new());
+        assert_eq!(
+            split_shell_args("  -I/opt/rdma/include   -DFOO=1 ").unwrap(),
+            vec!["-I/opt/rdma/include", "-DFOO=1"]
+        );
+        assert_eq!(
+            split_shell_args(r#"-I"/opt/my includes" '-DNAME="x y"' -DA=\ b"#).unwrap(),
+            vec!["-I/opt/my includes", r#"-DNAME="x y""#, "-DA= b"]
+        );
+        assert_eq!(
+            split_shell_args(r#""a\"b\\c\d" '' x"#).unwrap(),
+            vec![r#"a"b\c\d"#, "", "x"]
+        );
+        assert!(matches!(
+            split_shell_args("-DFOO='bar"),
+            Err(BuildError::InvalidArgs(_))
+        ));
+        assert!(matches!(
+            split_shell_args(r#"-DFOO="bar"#),
+            Err(BuildError::InvalidArgs(_))
+        ));
+        assert!(matches!(
+            split_shell_args("-DFOO\\"),
+            Err(BuildError::InvalidArgs(_))
+        ));
+    }
+
+    #[test]
+    fn test_rdma_extra_allowlist() {
+        assert_eq!(split_comma_list(""), Vec::<String>::new());
+        assert_eq!(
+            split_comma_list(" ibv_query_device_ex , mlx5dv_.*,,ibv_device_attr_ex,"),
+            vec!["ibv_query_device_ex", "mlx5dv_.*", "ibv_device_attr_ex"]
+        );
+
+        let _env = ENV_LOCK.lock().unwrap();
+        env::remove_var(RDMA_EXTRA_ALLOWLIST_ENV);
+        assert!(rdma_extra_allowlist().is_empty());
+        env::set_var(RDMA_EXTRA_ALLOWLIST_ENV, "ibv_query_device_ex,ibv_.*_ex");
+        let allowlist = rdma_extra_allowlist();
+        env::remove_var(RDMA_EXTRA_ALLOWLIST_ENV);
+        assert_eq!(allowlist, vec!["ibv_query_device_ex", "ibv_.*_ex"]);
+    }
+
+    #[test]
+    fn test_select_python_interpreter() {
+        let available = |names: &'static [&'static str]| move |c: &str| names.contains(&c);
+
+        // PYO3_PYTHON wins when it works.
+        assert_eq!(
+            select_python_interpreter(
+                Some("/opt/py/bin/python"),
+                available(&["/opt/py/bin/python", "python3", "python"])
+            ),
+            Some("/opt/py/bin/python".to_string())
+        );
+        // A broken PYO3_PYTHON falls through to python3, then python.
+        assert_eq!(
+            select_python_interpreter(Some("/missing"), available(&["python3", "python"])),
+            Some("python3".to_string())
+        );
+        assert_eq!(
+            select_python_interpreter(None, available(&["python"])),
+            Some("python".to_string())
+        );
+        assert_eq!(select_python_interpreter(None, available(&[])), None);
+
+        assert!(is_python3_version("Python 3.10.12\n"));
+        assert!(!is_python3_version("Python 2.7.18\n"));
+        assert!(!is_python3_version(""));
+    }
+
+    #[test]
+    fn test_find_python_interpreter_prefers_pyo3_python() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _env = ENV_LOCK.lock().unwrap();
+        let dir = env::temp_dir().join(format!("build_utils_find_python_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        let fake_python = |path: &Path, version: &str| {
+            std::fs::write(path, format!("#!/bin/sh\necho 'Python {}'\n", version)).unwrap();
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        };
+        let pinned = dir.join("pinned-python");
+        fake_python(&pinned, "3.12.1");
+        fake_python(&dir.join("bin/python3"), "3.10.12");
+        fake_python(&dir.join("bin/python"), "2.7.18");
+
+        let saved: Vec<_> = ["PATH", "PYO3_PYTHON"]
+            .into_iter()
+            .map(|name| (name, env::var_os(name)))
+            .collect();
+        env::set_var("PATH", dir.join("bin"));
+        env::set_var("PYO3_PYTHON", &pinned);
+        let with_pyo3_python = find_python_interpreter();
+        env::remove_var("PYO3_PYTHON");
+        let without_pyo3_python = find_python_interpreter();
+        for (name, value) in saved {
+            match value {
+                Some(value) => env::set_var(name, value),
+                None => env::remove_var(name),
+            }
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(with_pyo3_python, pinned);
+        assert_eq!(without_pyo3_python, PathBuf::from("python3"));
+    }
+
+    #[test]
+    fn test_check_bindings() {
+        let padding = "// padding\n".repeat(MIN_BINDINGS_LEN);
+        let bindings = format!("{}pub struct ibv_qp {{ _unused: [u8; 0] }}\n", padding);
+        assert!(check_bindings(&bindings, "ibv_qp").is_ok());
+
+        // Only whole identifiers count.
+        let prefixed = format!("{}pub struct ibv_qp_ex {{ _unused: [u8; 0] }}\n", padding);
+        assert!(matches!(
+            check_bindings(&prefixed, "ibv_qp"),
+            Err(BuildError::InvalidBindings(_))
+        ));
+
+        // Tiny output fails even if it happens to mention the sentinel.
+        assert!(matches!(
+            check_bindings("pub struct ibv_qp;", "ibv_qp"),
+            Err(BuildError::InvalidBindings(_))
+        ));
+        assert!(matches!(
+            check_bindings("", "ibv_qp"),
+            Err(BuildError::InvalidBindings(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_build_config() {
+        let config = parse_build_config(
+            r#"
+            # Per-project toolchain
+            cuda_home = "/opt/cuda-12.4"
+            python = "/opt/py311/bin/python"  # pinned
+            rocm_home = "/opt/rocm"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            BuildConfig {
+                cuda_home: Some("/opt/cuda-12.4".to_string()),
+                python: Some("/opt/py311/bin/python".to_string()),
+            }
+        );
+        assert_eq!(parse_build_config("").unwrap(), BuildConfig::default());
+        assert!(parse_build_config("cuda_home = /opt/cuda").is_err());
+        assert!(parse_build_config("cuda_home").is_err());
+        assert!(parse_build_config("cuda_home = \"/opt/cuda\" extra").is_err());
+    }
+
+    #[test]
+    fn test_build_config_used_when_env_unset() {
+        let _env = ENV_LOCK.lock().unwrap();
+        let dir = env::temp_dir().join(format!("build_utils_config_{}", std::process::id()));
+        let crate_dir = dir.join("some-sys");
+        std::fs::create_dir_all(&crate_dir).unwrap();
+        std::fs::write(
+            dir.join(BUILD_CONFIG_FILE),
+            "cuda_home = \"/from/config/cuda\"\n",
+        )
+        .unwrap();
+
+        let saved: Vec<_> = ["CARGO_MANIFEST_DIR", "CUDA_HOME", "CUDA_PATH"]
+            .into_iter()
+            .map(|name| (name, env::var_os(name)))
+            .collect();
+        env::set_var("CARGO_MANIFEST_DIR", &crate_dir);
+        env::remove_var("CUDA_PATH");
+        env::remove_var("CUDA_HOME");
+        let from_config = find_cuda_home();
+        env::set_var("CUDA_HOME", "/from/env/cuda");
+        let from_env = find_cuda_home();
+        for (name, value) in saved {
+            match value {
+                Some(value) => env::set_var(name, value),
+                None => env::remove_var(name),
+            }
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(from_config, Some("/from/config/cuda".to_string()));
+        assert_eq!(from_env, Some("/from/env/cuda".to_string()));
+    }
+
+    #[test]
+    fn test_backend_metadata_round_trip() {
+        let _env = ENV_LOCK.lock().unwrap();
+        let metadata = BackendMetadata::cuda("/opt/cuda", Some("/opt/cuda/lib64"));
+        assert_eq!(
+            metadata.directives(),
+            vec![
+                "cargo:backend=cuda",
+                "cargo:cuda_home=/opt/cuda",
+                "cargo:cuda_lib_dir=/opt/cuda/lib64",
+            ]
+        );
+
+        // Cargo hands `cargo:<key>=<value>` to dependents as DEP_<LINKS>_<KEY>.
+        assert_eq!(BackendMetadata::from_dep_env("test_sys"), None);
+        for directive in metadata.directives() {
+            let (key, value) = directive
+                .strip_prefix("cargo:")
+                .unwrap()
+                .split_once('=')
+                .unwrap();
+            env::set_var(format!("DEP_TEST_SYS_{}", key.to_uppercase()), value);
+        }
+        let read = BackendMetadata::from_dep_env("test_sys");
+        for key in ["BACKEND", "CUDA_HOME", "CUDA_LIB_DIR"] {
+            env::remove_var(format!("DEP_TEST_SYS_{}", key));
+        }
+        assert_eq!(read, Some(metadata));
+
+        assert_eq!(
+            BackendMetadata::cuda("/opt/cuda", None).directives(),
+            vec!["cargo:backend=cuda", "cargo:cuda_home=/opt/cuda"]
+        );
+    }
+
+    #[test]
+    fn test_find_system_rdmaxcel() {
+        use std::fs::File;
+
+        let prefix = env::temp_dir().join(format!("build_utils_rdmaxcel_{}", std::process::id()));
+        std::fs::create_dir_all(prefix.join("include")).unwrap();
+        std::fs::create_dir_all(prefix.join("lib")).unwrap();
+        std::fs::create_dir_all(prefix.join("lib64")).unwrap();
+        assert!(matches!(
+            find_system_rdmaxcel(&prefix),
+            Err(BuildError::PathNotFound(_))
+        ));
+
+        File::create(prefix.join("include/rdmaxcel.h")).unwrap();
+        // Headers alone aren't enough.
+        assert!(matches!(
+            find_system_rdmaxcel(&prefix),
+            Err(BuildError::PathNotFound(_))
+        ));
+
+        // lib64 holds no rdmaxcel, so lib is used.
+        File::create(prefix.join("lib64/libother.so")).unwrap();
+        File::create(prefix.join("lib/librdmaxcel.so")).unwrap();
+        File::create(prefix.join("lib/librdmaxcel_cuda.a")).unwrap();
+        let system = find_system_rdmaxcel(&prefix).unwrap();
+        assert_eq!(system.header, prefix.join("include/rdmaxcel.h"));
+        assert_eq!(system.lib_dir, prefix.join("lib"));
+        assert_eq!(
+            system.link_directives(),
+            vec![
+                format!(
+                    "cargo:rustc-link-search=native={}",
+                    prefix.join("lib").display()
+                ),
+                "cargo:rustc-link-lib=rdmaxcel".to_string(),
+                "cargo:rustc-link-lib=static=rdmaxcel_cuda".to_string(),
+            ]
+        );
+
+        std::fs::remove_dir_all(&prefix).unwrap();
+    }
 }
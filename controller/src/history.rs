@@ -17,6 +17,8 @@ use monarch_messages::client::Exception;
 use monarch_messages::controller::Seq;
 use monarch_messages::controller::WorkerError;
 use monarch_messages::worker::Ref;
+use serde::Deserialize;
+use serde::Serialize;
 
 /// An invocation tracks a discrete node in the graph of operations executed by
 /// the worker based on instructions from the client.
@@ -25,7 +27,7 @@ use monarch_messages::worker::Ref;
 /// to support better failure handling.
 // Allowing dead code until we do something smarter with defs, uses etc.
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Invocation {
     /// The sequence number of the invocation. This should be unique and increasing across all
     /// invocations.
@@ -69,10 +71,24 @@ impl Invocation {
         }
     }
 
-    fn set_exception(&mut self, exception: Exception) {
+    /// Record an error for this invocation. If it already has one, the error that
+    /// arrived first is kept, unless `keep_first_error` is set, in which case the error
+    /// with the earliest `caused_by` seq wins regardless of arrival order.
+    fn set_exception(&mut self, exception: Exception, keep_first_error: bool) {
         match exception {
             Exception::Error(_, caused_by, error) => {
-                self.set_result(Err(Exception::Error(self.seq, caused_by, error)));
+                let replace = match self.exception() {
+                    Some(Exception::Error(_, existing_caused_by, _)) => {
+                        keep_first_error && caused_by < *existing_caused_by
+                    }
+                    _ => false,
+                };
+                let exception = Exception::Error(self.seq, caused_by, error);
+                if replace {
+                    self.result = Some(Err(exception));
+                } else {
+                    self.set_result(Err(exception));
+                }
             }
             Exception::Failure(_) => {
                 tracing::error!(
@@ -99,7 +115,7 @@ impl Invocation {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 enum RefStatus {
     // The invocation for this ref is still in progress.
     Invoked(Seq),
@@ -107,6 +123,17 @@ enum RefStatus {
     Errored(Exception),
 }
 
+/// Configuration for a [`History`].
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// When an invocation is hit by more than one error, keep the one with the
+    /// earliest `caused_by` seq. The earliest error is usually the root cause, which
+    /// makes this the better mode for debugging. By default the error that reached
+    /// the invocation first is kept, which reflects the order in which failures were
+    /// observed but may be a downstream cascade of the original failure.
+    pub keep_first_error: bool,
+}
+
 /// The history of invocations sent by the client to be executed on the workers.
 /// This is used to track dependencies between invocations and to propagate exceptions.
 /// It purges history for completed invocations to avoid memory bloat.
@@ -143,6 +170,21 @@ pub struct History {
     // - the deadline
     // - if it has already been reported to the client
     deadline: Option<(Seq, tokio::time::Instant, bool)>,
+    config: HistoryConfig,
+}
+
+/// The persistent state of a [`History`], produced by [`History::snapshot`].
+/// Memoized minimums are recomputed on restore, and the progress deadline is
+/// not kept since it is relative to this process's clock.
+#[derive(Serialize, Deserialize)]
+struct HistorySnapshot {
+    first_incomplete_seqs: Vec<Seq>,
+    first_incomplete_seqs_controller: Vec<Seq>,
+    invocations: Vec<Invocation>,
+    invocation_for_ref: Vec<(Ref, RefStatus)>,
+    marked_for_deletion: Vec<Ref>,
+    max_seq: Option<Seq>,
+    config: HistoryConfig,
 }
 
 /// A vector that keeps track of the minimum value.
@@ -199,6 +241,10 @@ where
 
 impl History {
     pub fn new(world_size: usize) -> Self {
+        Self::with_config(world_size, HistoryConfig::default())
+    }
+
+    pub fn with_config(world_size: usize, config: HistoryConfig) -> Self {
         Self {
             first_incomplete_seqs: MinVector::new(vec![Seq::default(); world_size]),
             min_incomplete_seq: Seq::default(),
@@ -209,9 +255,67 @@ impl History {
             first_incomplete_seqs_controller: MinVector::new(vec![Seq::default(); world_size]),
             min_incompleted_seq_controller: Seq::default(),
             deadline: None,
+            config,
         }
     }
 
+    /// Serialize the history so that a restarted controller can rebuild it with
+    /// [`History::restore`]. This includes in-flight invocations with their
+    /// dependency edges and results, the ref map, and per-rank progress. Results
+    /// are kept as the `Serialized` bytes the workers sent, without decoding them.
+    /// The progress deadline is not included; it is re-armed on the next call to
+    /// [`History::deadline`].
+    pub fn snapshot(&self) -> Vec<u8> {
+        let snapshot = HistorySnapshot {
+            first_incomplete_seqs: self.first_incomplete_seqs.vec().clone(),
+            first_incomplete_seqs_controller: self.first_incomplete_seqs_controller.vec().clone(),
+            invocations: self.invocations.values().cloned().collect(),
+            invocation_for_ref: self
+                .invocation_for_ref
+                .iter()
+                .map(|(ref_, status)| (*ref_, status.clone()))
+                .collect(),
+            marked_for_deletion: self.marked_for_deletion.iter().copied().collect(),
+            max_seq: *self.max_seq.inner(),
+            config: self.config,
+        };
+        bincode::serialize(&snapshot).expect("history snapshot should be serializable")
+    }
+
+    /// Rebuild a history from bytes produced by [`History::snapshot`].
+    pub fn restore(bytes: &[u8]) -> anyhow::Result<Self> {
+        let snapshot: HistorySnapshot = bincode::deserialize(bytes)?;
+        anyhow::ensure!(
+            snapshot.first_incomplete_seqs.len() == snapshot.first_incomplete_seqs_controller.len(),
+            "history snapshot has {} ranks but {} controller ranks",
+            snapshot.first_incomplete_seqs.len(),
+            snapshot.first_incomplete_seqs_controller.len()
+        );
+        anyhow::ensure!(
+            !snapshot.first_incomplete_seqs.is_empty(),
+            "history snapshot has no ranks"
+        );
+        let first_incomplete_seqs = MinVector::new(snapshot.first_incomplete_seqs);
+        let first_incomplete_seqs_controller =
+            MinVector::new(snapshot.first_incomplete_seqs_controller);
+        Ok(Self {
+            min_incomplete_seq: first_incomplete_seqs.min(),
+            first_incomplete_seqs,
+            invocations: snapshot
+                .invocations
+                .into_iter()
+                .map(|invocation| (invocation.seq, invocation))
+                .collect(),
+            invocation_for_ref: snapshot.invocation_for_ref.into_iter().collect(),
+            marked_for_deletion: snapshot.marked_for_deletion.into_iter().collect(),
+            max_seq: OptionSeq::from(snapshot.max_seq),
+            min_incompleted_seq_controller: first_incomplete_seqs_controller.min(),
+            first_incomplete_seqs_controller,
+            deadline: None,
+            config: snapshot.config,
+        })
+    }
+
     #[cfg(test)]
     pub fn first_incomplete_seqs(&self) -> &[Seq] {
         self.first_incomplete_seqs.vec()
@@ -275,7 +379,7 @@ impl History {
                     // We know that this invocation hasn't been completed yet, so we can
                     // directly call set_exception on it.
                     if !invocation.reported {
-                        invocation.set_exception(exception.clone());
+                        invocation.set_exception(exception.clone(), self.config.keep_first_error);
                         results.push((seq, Some(Err(exception.clone()))));
                         invocation.reported = true;
                     }
@@ -321,8 +425,9 @@ impl History {
                 continue;
             };
 
-            // Overwrite the error, so we are using the last error for this invocation to send
-            // to the client.
+            // An invocation that already has an error keeps the one that reached it
+            // first, unless `HistoryConfig::keep_first_error` selects the error with the
+            // earliest `caused_by` seq instead.
             for def in invocation.defs.iter() {
                 match self.invocation_for_ref.get(def) {
                     Some(RefStatus::Invoked(invoked_seq)) if *invoked_seq == seq => self
@@ -331,7 +436,7 @@ impl History {
                     _ => None,
                 };
             }
-            invocation.set_exception(exception.clone());
+            invocation.set_exception(exception.clone(), self.config.keep_first_error);
             queue.extend(invocation.users.iter());
         }
     }
@@ -672,6 +777,53 @@ mod tests {
         assert_eq!(res, vec![3.into(), 4.into()]);
     }
 
+    #[test]
+    fn snapshot_round_trip() {
+        let mut history = History::new(2);
+        history.add_invocation(0.into(), vec![], vec![Ref { id: 1 }]);
+        history.add_invocation(1.into(), vec![Ref { id: 1 }], vec![Ref { id: 2 }]);
+        history.add_invocation(2.into(), vec![Ref { id: 2 }], vec![Ref { id: 3 }]);
+        history.set_result(
+            1.into(),
+            Ok(Serialized::serialize(&"1".to_string()).unwrap()),
+        );
+        history.rank_completed(0, 1.into());
+
+        let mut restored = History::restore(&history.snapshot()).unwrap();
+        assert_eq!(restored.world_size(), 2);
+        assert_eq!(
+            restored.first_incomplete_seqs(),
+            history.first_incomplete_seqs()
+        );
+        assert_eq!(restored.max_seq, history.max_seq);
+        assert_eq!(restored.invocation_for_ref, history.invocation_for_ref);
+
+        let mut res = restored
+            .iter_users_transitive(0.into())
+            .collect::<Vec<Seq>>();
+        res.sort();
+        assert_eq!(res, vec![0.into(), 1.into(), 2.into()]);
+        assert_eq!(
+            restored
+                .get_invocation(1.into())
+                .unwrap()
+                .value()
+                .unwrap()
+                .deserialized::<String>()
+                .unwrap(),
+            "1"
+        );
+
+        // The restored history keeps tracking completions.
+        restored.rank_completed(0, 3.into());
+        let results = restored.rank_completed(1, 3.into());
+        let seqs = results.iter().map(|(seq, _)| *seq).collect::<Vec<Seq>>();
+        assert_eq!(seqs, vec![0.into(), 1.into(), 2.into()]);
+        assert!(restored.invocations.is_empty());
+
+        assert!(History::restore(b"not a snapshot").is_err());
+    }
+
     #[test]
     fn min_vector() {
         // Test initialization
@@ -827,6 +979,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn keep_first_error() {
+        let error = |seq: u64| {
+            Exception::Error(
+                seq.into(),
+                seq.into(),
+                WorkerError {
+                    backtrace: format!("error in {}", seq),
+                    worker_actor_id: id!(test[234].testactor[6]),
+                },
+            )
+        };
+        let caused_by =
+            |history: &History| match history.get_invocation(2.into()).unwrap().exception() {
+                Some(Exception::Error(_, caused_by, _)) => *caused_by,
+                other => panic!("unexpected exception: {:?}", other),
+            };
+
+        for (config, expected) in [
+            (HistoryConfig::default(), 1),
+            (
+                HistoryConfig {
+                    keep_first_error: true,
+                },
+                0,
+            ),
+        ] {
+            let mut history = History::with_config(1, config);
+            history.add_invocation(0.into(), vec![], vec![Ref { id: 1 }]);
+            history.add_invocation(1.into(), vec![], vec![Ref { id: 2 }]);
+            history.add_invocation(2.into(), vec![Ref { id: 1 }, Ref { id: 2 }], vec![]);
+
+            // A downstream error reaches invocation 2 before the root-cause error does.
+            history.propagate_exception(1.into(), error(1));
+            history.propagate_exception(0.into(), error(0));
+            assert_eq!(caused_by(&history), Seq::from(expected));
+        }
+    }
+
     #[test]
     fn test_option_seq_comparision() {
         assert_eq!(OptionSeq::from(None), OptionSeq::from(None));
@@ -48,7 +48,7 @@ fn main() {
     }
 
     // Include headers and libs from the active environment.
-    let python_config = match build_utils::python_env_dirs_with_interpreter("python3") {
+    let python_config = match build_utils::python_env_dirs() {
         Ok(config) => config,
         Err(_) => {
             eprintln!("Warning: Failed to get Python environment directories");
@@ -62,6 +62,10 @@ fn main() {
     if let Some(include_dir) = &python_config.include_dir {
         builder = builder.clang_arg(format!("-I{}", include_dir));
     }
+    builder = builder.clang_args(
+        build_utils::bindgen_extra_clang_args()
+            .expect("failed to parse MONARCH_BINDGEN_EXTRA_CLANG_ARGS"),
+    );
     if let Some(lib_dir) = &python_config.lib_dir {
         println!("cargo::rustc-link-search=native={}", lib_dir);
         // Set cargo metadata to inform dependent binaries about how to set their
@@ -82,6 +86,9 @@ fn main() {
 
     // Generate bindings - fail fast if this doesn't work
     let bindings = builder.generate().expect("Unable to generate bindings");
+    if let Err(err) = build_utils::check_bindings(&bindings.to_string(), "cudaError_t") {
+        panic!("{}", err);
+    }
 
     // Write the bindings to the $OUT_DIR/bindings.rs file
     match env::var("OUT_DIR") {
@@ -26,6 +26,7 @@ use std::sync::Mutex;
 use std::sync::OnceLock;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use enum_as_inner::EnumAsInner;
@@ -40,6 +41,8 @@ use hyperactor::channel::ChannelAddr;
 use hyperactor::channel::ChannelRx;
 use hyperactor::channel::ChannelTransport;
 use hyperactor::channel::MetaTlsAddr;
+use hyperactor::clock::Clock;
+use hyperactor::clock::RealClock;
 use hyperactor::config;
 use hyperactor::config::CONFIG;
 use hyperactor::config::ConfigAttr;
@@ -120,6 +123,18 @@ pub enum AllocatorError {
     #[error("not enough resources; requested: {requested}, available: {available}")]
     NotEnoughResources { requested: Extent, available: usize },
 
+    /// The alloc reported a failure before the expected procs were running.
+    #[error("allocation failed: {0}")]
+    Failed(String),
+
+    /// The expected procs were not running before the deadline.
+    #[error("timed out after {timeout:?}; {running} of {expected} procs running")]
+    Timeout {
+        timeout: Duration,
+        running: usize,
+        expected: usize,
+    },
+
     /// An uncategorized error from an underlying system.
     #[error(transparent)]
     Other(#[from] anyhow::Error),
@@ -281,6 +296,50 @@ impl fmt::Display for ProcState {
     }
 }
 
+/// Version of the JSON schema written by [`JsonEventWriter`]. Bump this
+/// whenever [`ProcStateEvent`] or [`ProcState`] changes incompatibly.
+pub const PROC_STATE_EVENT_VERSION: u32 = 1;
+
+/// A [`ProcState`] tagged with the schema version, as written (one per line)
+/// by [`JsonEventWriter`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcStateEvent {
+    /// The schema version; [`PROC_STATE_EVENT_VERSION`] for events written
+    /// by this build.
+    pub version: u32,
+    /// The emitted state.
+    pub state: ProcState,
+}
+
+/// Writes [`ProcState`] events as JSON lines, flushing after each one so that
+/// a reader on the other end of a pipe sees them immediately.
+pub struct JsonEventWriter<W> {
+    writer: W,
+}
+
+impl<W: std::io::Write> JsonEventWriter<W> {
+    /// Create a writer emitting events to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Serialize `state` as a single [`ProcStateEvent`] line and flush it.
+    pub fn write(&mut self, state: &ProcState) -> std::io::Result<()> {
+        let event = ProcStateEvent {
+            version: PROC_STATE_EVENT_VERSION,
+            state: state.clone(),
+        };
+        serde_json::to_writer(&mut self.writer, &event)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+
+    /// Return the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
 /// The reason a proc stopped.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, EnumAsInner)]
 pub enum ProcStopReason {
@@ -538,6 +597,152 @@ impl<A: ?Sized + Send + Alloc> AllocExt for A {
     }
 }
 
+/// Readiness helpers available on every [`Alloc`].
+#[async_trait]
+pub trait AllocWaitExt {
+    /// Drain events until `count` procs are running.
+    ///
+    /// Returns [`AllocatorError::Failed`] if the alloc reports a failure or a
+    /// proc stops first, [`AllocatorError::Timeout`] if `timeout` elapses, and
+    /// [`AllocatorError::Incomplete`] if the event stream ends.
+    async fn wait_until_running(
+        &mut self,
+        count: usize,
+        timeout: Duration,
+    ) -> Result<(), AllocatorError> {
+        self.wait_until_running_with(count, timeout, |_| {}).await
+    }
+
+    /// Like [`AllocWaitExt::wait_until_running`], but passes every drained
+    /// event to `observe` before acting on it.
+    async fn wait_until_running_with<F>(
+        &mut self,
+        count: usize,
+        timeout: Duration,
+        observe: F,
+    ) -> Result<(), AllocatorError>
+    where
+        F: FnMut(&ProcState) + Send;
+}
+
+#[async_trait]
+impl<A: ?Sized + Send + Alloc> AllocWaitExt for A {
+    async fn wait_until_running_with<F>(
+        &mut self,
+        count: usize,
+        timeout: Duration,
+        mut observe: F,
+    ) -> Result<(), AllocatorError>
+    where
+        F: FnMut(&ProcState) + Send,
+    {
+        let mut running = 0;
+        let wait = async {
+            while running < count {
+                let Some(state) = self.next().await else {
+                    return Err(AllocatorError::Incomplete(self.extent().clone()));
+                };
+                observe(&state);
+                match state {
+                    ProcState::Running { .. } => running += 1,
+                    ProcState::Stopped { create_key, reason } => {
+                        return Err(AllocatorError::Failed(format!(
+                            "proc with create key {} stopped: {}",
+                            create_key, reason
+                        )));
+                    }
+                    ProcState::Failed { description, .. } => {
+                        return Err(AllocatorError::Failed(description));
+                    }
+                    ProcState::Created { .. } => {}
+                }
+            }
+            Ok(())
+        };
+        match RealClock.timeout(timeout, wait).await {
+            Ok(result) => result,
+            Err(_) => Err(AllocatorError::Timeout {
+                timeout,
+                running,
+                expected: count,
+            }),
+        }
+    }
+}
+
+/// Adapters over the [`ProcState`] event stream of an [`Alloc`].
+pub trait AllocStreamExt: Alloc + Sized {
+    /// Wrap this alloc so that [`Alloc::next`] only yields the states for
+    /// which `pred` returns true. The result is itself an [`Alloc`], so it
+    /// composes with [`AllocWaitExt`] and other adapters.
+    fn filter_states<F>(self, pred: F) -> FilteredAlloc<Self, F>
+    where
+        F: FnMut(&ProcState) -> bool + Send,
+    {
+        FilteredAlloc { alloc: self, pred }
+    }
+}
+
+impl<A: Alloc> AllocStreamExt for A {}
+
+/// An [`Alloc`] that drops the events not matching a predicate. Created by
+/// [`AllocStreamExt::filter_states`].
+pub struct FilteredAlloc<A, F> {
+    alloc: A,
+    pred: F,
+}
+
+impl<A, F> FilteredAlloc<A, F> {
+    /// Return the wrapped alloc.
+    pub fn into_inner(self) -> A {
+        self.alloc
+    }
+}
+
+#[async_trait]
+impl<A, F> Alloc for FilteredAlloc<A, F>
+where
+    A: Alloc + Send,
+    F: FnMut(&ProcState) -> bool + Send,
+{
+    async fn next(&mut self) -> Option<ProcState> {
+        loop {
+            let state = self.alloc.next().await?;
+            if (self.pred)(&state) {
+                return Some(state);
+            }
+        }
+    }
+
+    fn spec(&self) -> &AllocSpec {
+        self.alloc.spec()
+    }
+
+    fn extent(&self) -> &Extent {
+        self.alloc.extent()
+    }
+
+    fn world_id(&self) -> &WorldId {
+        self.alloc.world_id()
+    }
+
+    fn transport(&self) -> ChannelTransport {
+        self.alloc.transport()
+    }
+
+    async fn stop(&mut self) -> Result<(), AllocatorError> {
+        self.alloc.stop().await
+    }
+
+    fn is_local(&self) -> bool {
+        self.alloc.is_local()
+    }
+
+    fn client_router_addr(&self) -> ChannelAddr {
+        self.alloc.client_router_addr()
+    }
+}
+
 /// If addr is Tcp or Metatls, use its IP address or hostname to create
 /// a new addr with port unspecified.
 ///
@@ -1033,3 +1238,168 @@ pub(crate) mod testing {
         assert_eq!(stopped, running);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ndslice::extent;
+
+    use super::*;
+
+    fn running(i: usize) -> ProcState {
+        ProcState::Running {
+            create_key: ShortUuid::generate(),
+            proc_id: format!("test[{i}]").parse().unwrap(),
+            mesh_agent: ActorRef::<ProcMeshAgent>::attest(
+                format!("test[{i}].mesh_agent[{i}]").parse().unwrap(),
+            ),
+            addr: ChannelAddr::Unix("/proc0".parse().unwrap()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_running() {
+        let mut alloc = MockAlloc::new();
+        for i in 0..2 {
+            alloc
+                .expect_next()
+                .times(1)
+                .return_once(move || Some(running(i)));
+        }
+        alloc
+            .wait_until_running(2, Duration::from_secs(5))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_running_failed() {
+        let mut alloc = MockAlloc::new();
+        alloc
+            .expect_next()
+            .times(1)
+            .return_once(|| Some(running(0)));
+        alloc.expect_next().times(1).return_once(|| {
+            Some(ProcState::Failed {
+                world_id: WorldId("test".to_string()),
+                description: "boom".to_string(),
+            })
+        });
+        let err = alloc
+            .wait_until_running(2, Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AllocatorError::Failed(description) if description == "boom"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_running_incomplete() {
+        let mut alloc = MockAlloc::new();
+        alloc
+            .expect_next()
+            .times(1)
+            .return_once(|| Some(running(0)));
+        alloc.expect_next().return_const(None);
+        alloc.expect_extent().return_const(extent!(replica = 2));
+        let err = alloc
+            .wait_until_running(2, Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AllocatorError::Incomplete(_)));
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_running_timeout() {
+        let mut alloc = MockAllocWrapper::new_block_next(MockAlloc::new(), 1);
+        alloc
+            .alloc
+            .expect_next()
+            .times(1)
+            .return_once(|| Some(running(0)));
+        let mut observed = 0;
+        let err = alloc
+            .wait_until_running_with(2, Duration::from_millis(100), |_| observed += 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AllocatorError::Timeout {
+                running: 1,
+                expected: 2,
+                ..
+            }
+        ));
+        assert_eq!(observed, 1);
+    }
+
+    #[test]
+    fn test_json_event_writer() {
+        let mut writer = JsonEventWriter::new(Vec::new());
+        writer.write(&running(0)).unwrap();
+        writer.write(&running(1)).unwrap();
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let value: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(value["version"], PROC_STATE_EVENT_VERSION);
+        assert!(value["state"].get("Running").is_some());
+
+        let event: ProcStateEvent = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(event.version, PROC_STATE_EVENT_VERSION);
+        assert!(event.state.is_running());
+    }
+
+    fn created(i: usize) -> ProcState {
+        ProcState::Created {
+            create_key: ShortUuid::generate(),
+            point: extent!(replica = 2).point_of_rank(i).unwrap(),
+            pid: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_states() {
+        let mut alloc = MockAlloc::new();
+        for i in 0..2 {
+            alloc
+                .expect_next()
+                .times(1)
+                .return_once(move || Some(created(i)));
+            alloc
+                .expect_next()
+                .times(1)
+                .return_once(move || Some(running(i)));
+        }
+        alloc.expect_next().return_const(None);
+
+        let mut alloc = alloc.filter_states(|state| state.is_running());
+        let mut states = Vec::new();
+        while let Some(state) = alloc.next().await {
+            states.push(state);
+        }
+        assert_eq!(states.len(), 2);
+        assert!(states.iter().all(ProcState::is_running));
+    }
+
+    #[tokio::test]
+    async fn test_filter_states_composes_with_wait() {
+        let mut alloc = MockAlloc::new();
+        alloc
+            .expect_next()
+            .times(1)
+            .return_once(|| Some(created(0)));
+        alloc
+            .expect_next()
+            .times(1)
+            .return_once(|| Some(running(0)));
+
+        let mut seen = Vec::new();
+        alloc
+            .filter_states(|state| !state.is_created())
+            .wait_until_running_with(1, Duration::from_secs(5), |state| seen.push(state.clone()))
+            .await
+            .unwrap();
+        assert_eq!(seen.len(), 1);
+        assert!(seen[0].is_running());
+    }
+}
@@ -9,12 +9,14 @@
 #![allow(dead_code)] // some things currently used only in tests
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::future::Future;
 use std::os::unix::process::ExitStatusExt;
 use std::process::ExitStatus;
 use std::process::Stdio;
 use std::sync::Arc;
 use std::sync::OnceLock;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use enum_as_inner::EnumAsInner;
@@ -28,6 +30,8 @@ use hyperactor::channel::ChannelTx;
 use hyperactor::channel::Rx;
 use hyperactor::channel::Tx;
 use hyperactor::channel::TxStatus;
+use hyperactor::clock::Clock;
+use hyperactor::clock::RealClock;
 use hyperactor::sync::flag;
 use hyperactor::sync::monitor;
 use ndslice::view::Extent;
@@ -68,6 +72,7 @@ pub const CLIENT_TRACE_ID_LABEL: &str = "CLIENT_TRACE_ID";
 /// The process allocator tees the stdout and stderr of each proc to the parent process.
 pub struct ProcessAllocator {
     cmd: Arc<Mutex<Command>>,
+    children: LiveChildren,
 }
 
 impl ProcessAllocator {
@@ -78,8 +83,133 @@ impl ProcessAllocator {
     pub fn new(cmd: Command) -> Self {
         Self {
             cmd: Arc::new(Mutex::new(cmd)),
+            children: LiveChildren::default(),
         }
     }
+
+    /// Terminate every child process spawned by this allocator's allocs.
+    ///
+    /// Each child is sent SIGTERM, and this waits up to `timeout` for all of
+    /// them to exit. Children still running after that are sent SIGKILL and
+    /// an error is returned.
+    pub async fn shutdown(&self, timeout: Duration) -> Result<(), AllocatorError> {
+        self.children.terminate(timeout).await
+    }
+
+    /// Install SIGTERM and SIGINT handlers that [`shutdown`](Self::shutdown)
+    /// all children before exiting the process with the conventional
+    /// `128 + signal` status.
+    ///
+    /// This does not rely on the OS tearing down the process group, so
+    /// children are cleaned up even when they were started in their own
+    /// session. Must be called from within a tokio runtime.
+    pub fn shutdown_on_signal(&self, timeout: Duration) -> io::Result<tokio::task::JoinHandle<()>> {
+        use tokio::signal::unix::SignalKind;
+
+        let mut sigterm = tokio::signal::unix::signal(SignalKind::terminate())?;
+        let mut sigint = tokio::signal::unix::signal(SignalKind::interrupt())?;
+        let children = self.children.clone();
+        Ok(tokio::spawn(async move {
+            let signo = tokio::select! {
+                _ = sigterm.recv() => signal::SIGTERM,
+                _ = sigint.recv() => signal::SIGINT,
+            };
+            tracing::info!("received {}, shutting down child processes", signo);
+            if let Err(e) = children.terminate(timeout).await {
+                tracing::error!("child shutdown incomplete: {}", e);
+            }
+            std::process::exit(128 + signo as i32);
+        }))
+    }
+}
+
+/// Pids of the child processes spawned by a [`ProcessAllocator`] that may
+/// still be running.
+#[derive(Clone, Default)]
+struct LiveChildren(Arc<std::sync::Mutex<HashSet<u32>>>);
+
+impl LiveChildren {
+    fn insert(&self, pid: u32) {
+        // A pid of 0 means the child already exited; signalling it would
+        // signal our own process group instead.
+        if pid != 0 {
+            self.0.lock().unwrap().insert(pid);
+        }
+    }
+
+    fn remove(&self, pid: u32) {
+        self.0.lock().unwrap().remove(&pid);
+    }
+
+    fn pids(&self) -> Vec<u32> {
+        self.0.lock().unwrap().iter().copied().collect()
+    }
+
+    fn kill_all(&self, sig: signal::Signal) {
+        for pid in self.pids() {
+            if let Err(e) = signal::kill(Pid::from_raw(pid as i32), sig)
+                && e != nix::errno::Errno::ESRCH
+            {
+                tracing::error!("failed to send {} to {}: {}", sig, pid, e);
+            }
+        }
+    }
+
+    /// Forget children that have exited, whether or not their monitor has
+    /// reaped them yet. Monitors are owned by the alloc, so this keeps
+    /// `terminate` working after the alloc has been dropped.
+    fn remove_exited(&self) {
+        self.0.lock().unwrap().retain(|&pid| !has_exited(pid));
+    }
+
+    async fn terminate(&self, timeout: Duration) -> Result<(), AllocatorError> {
+        self.kill_all(signal::SIGTERM);
+        let deadline = RealClock.now() + timeout;
+        loop {
+            self.remove_exited();
+            if self.pids().is_empty() {
+                break;
+            }
+            if RealClock.now() >= deadline {
+                let remaining = self.pids();
+                self.kill_all(signal::SIGKILL);
+                return Err(AllocatorError::Other(anyhow::anyhow!(
+                    "children {:?} did not exit within {:?}; sent SIGKILL",
+                    remaining,
+                    timeout
+                )));
+            }
+            RealClock.sleep(Duration::from_millis(10)).await;
+        }
+        Ok(())
+    }
+}
+
+/// Whether the child `pid` has exited. The child is left waitable, so its
+/// monitor still reaps it and observes its exit status.
+fn has_exited(pid: u32) -> bool {
+    // SAFETY: An all-zero `siginfo_t` is valid; `waitid` only writes to it.
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+    // SAFETY: `info` is valid for writes for the duration of the call.
+    let rc = unsafe {
+        libc::waitid(
+            libc::P_PID,
+            pid as libc::id_t,
+            &mut info,
+            libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+        )
+    };
+    if rc != 0 {
+        // ECHILD: already reaped, so there is nothing left to wait for.
+        return io::Error::last_os_error().raw_os_error() == Some(libc::ECHILD);
+    }
+    // With WNOHANG, `si_pid` stays zero while the child is still running.
+    #[cfg(target_os = "linux")]
+    // SAFETY: `waitid` filled in `info` for a child state change.
+    let exited_pid = unsafe { info.si_pid() };
+    #[cfg(not(target_os = "linux"))]
+    let exited_pid = info.si_pid;
+    exited_pid != 0
 }
 
 #[async_trait]
@@ -116,6 +246,7 @@ impl Allocator for ProcessAllocator {
             created: Vec::new(),
             cmd: Arc::clone(&self.cmd),
             children: JoinSet::new(),
+            live_children: self.children.clone(),
             running: true,
             failed: false,
             client_context: ClientContext {
@@ -152,6 +283,7 @@ pub struct ProcessAlloc {
     created: Vec<ShortUuid>,
     cmd: Arc<Mutex<Command>>,
     children: JoinSet<(usize, ProcStopReason)>,
+    live_children: LiveChildren,
     running: bool,
     failed: bool,
     client_context: ClientContext,
@@ -524,7 +656,13 @@ impl ProcessAlloc {
                         self.active.insert(index, handle);
 
                         // Now spawn the monitor task
-                        self.children.spawn(async move { (index, monitor.await) });
+                        self.live_children.insert(pid);
+                        let live_children = self.live_children.clone();
+                        self.children.spawn(async move {
+                            let reason = monitor.await;
+                            live_children.remove(pid);
+                            (index, reason)
+                        });
 
                         // Adjust for shape slice offset for non-zero shapes (sub-shapes).
                         let point = self.spec.extent.point_of_rank(rank).unwrap();
@@ -756,4 +894,58 @@ mod tests {
             })
         ));
     }
+
+    #[cfg(fbcode_build)]
+    #[tokio::test]
+    async fn test_shutdown_terminates_children() {
+        use crate::alloc::AllocWaitExt;
+
+        let bootstrap_binary = crate::testresource::get("monarch/hyperactor_mesh/bootstrap");
+        let mut allocator = ProcessAllocator::new(Command::new(bootstrap_binary));
+
+        let mut alloc = allocator
+            .allocate(AllocSpec {
+                extent: ndslice::extent!(replica = 2),
+                constraints: Default::default(),
+                proc_name: None,
+                transport: ChannelTransport::Unix,
+                proc_allocation_mode: Default::default(),
+            })
+            .await
+            .unwrap();
+
+        let mut pids = Vec::new();
+        alloc
+            .wait_until_running_with(2, Duration::from_secs(30), |state| {
+                if let ProcState::Created { pid, .. } = state {
+                    pids.push(*pid);
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(pids.len(), 2);
+
+        allocator.shutdown(Duration::from_secs(10)).await.unwrap();
+        for pid in pids {
+            assert!(has_exited(pid));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_terminate_without_monitor() {
+        // No alloc is monitoring this child, so `terminate` must notice the
+        // exit on its own.
+        let mut child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id().unwrap();
+        let children = LiveChildren::default();
+        children.insert(pid);
+        assert!(!has_exited(pid));
+
+        children.terminate(Duration::from_secs(10)).await.unwrap();
+        assert!(children.pids().is_empty());
+        assert_eq!(child.wait().await.unwrap().signal(), Some(15));
+    }
 }
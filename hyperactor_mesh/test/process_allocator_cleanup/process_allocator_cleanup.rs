@@ -16,6 +16,7 @@ use std::time::Duration;
 use std::time::Instant;
 
 use hyperactor_mesh::alloc::ProcState;
+use hyperactor_mesh::alloc::ProcStateEvent;
 use nix::sys::signal::Signal;
 use nix::sys::signal::{self};
 use nix::unistd::Pid;
@@ -55,7 +56,10 @@ async fn test_process_allocator_child_cleanup() {
         #[allow(clippy::disallowed_methods)]
         match timeout(Duration::from_secs(30), reader.next_line()).await {
             Ok(Ok(Some(line))) => {
-                if let Ok(proc_state) = serde_json::from_str::<ProcState>(&line) {
+                if let Ok(ProcStateEvent {
+                    state: proc_state, ..
+                }) = serde_json::from_str::<ProcStateEvent>(&line)
+                {
                     eprintln!("Received ProcState: {:?}", proc_state);
 
                     match proc_state {
@@ -6,6 +6,8 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::time::Duration;
+
 use hyperactor::channel::ChannelTransport;
 /// Test binary for ProcessAllocator child process cleanup behavior.
 /// This binary creates a ProcessAllocator and spawns several child processes,
@@ -14,22 +16,13 @@ use hyperactor::channel::ChannelTransport;
 use hyperactor_mesh::alloc::Alloc;
 use hyperactor_mesh::alloc::AllocConstraints;
 use hyperactor_mesh::alloc::AllocSpec;
+use hyperactor_mesh::alloc::AllocWaitExt;
 use hyperactor_mesh::alloc::Allocator;
-use hyperactor_mesh::alloc::ProcState;
+use hyperactor_mesh::alloc::JsonEventWriter;
 use hyperactor_mesh::alloc::ProcessAllocator;
 use ndslice::extent;
 use tokio::process::Command;
 
-fn emit_proc_state(state: &ProcState) {
-    if let Ok(json) = serde_json::to_string(state) {
-        println!("{}", json);
-        // Flush immediately to ensure parent can read events in real-time
-        use std::io::Write;
-        use std::io::{self};
-        io::stdout().flush().unwrap();
-    }
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize tracing to stderr to avoid interfering with JSON output
@@ -53,8 +46,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         })
         .await?;
 
+    // Events go to stdout for the parent to parse, so a write failure means
+    // the parent is gone.
+    let mut events = JsonEventWriter::new(std::io::stdout());
+    alloc
+        .wait_until_running_with(4, Duration::from_secs(60), |state| {
+            events.write(state).unwrap()
+        })
+        .await?;
+    // Keep forwarding events until the parent kills us.
     while let Some(state) = alloc.next().await {
-        emit_proc_state(&state);
+        events.write(&state)?;
     }
     Ok(())
 }
@@ -34,6 +34,10 @@ pub const ENABLE_SQLITE_TRACING: &str = "ENABLE_SQLITE_TRACING";
 // Log level (debug, info, warn, error, critical) to capture for Monarch traces on dedicated log file (changes based on environment, see `log_file_path`).
 const MONARCH_FILE_LOG_ENV: &str = "MONARCH_FILE_LOG";
 
+/// Environment variable holding `tracing` filter directives for logs mirrored to stderr,
+/// e.g. `MONARCH_LOG=monarch_rdma=debug,info`. Nothing is written to stderr when unset.
+pub const MONARCH_LOG_ENV: &str = "MONARCH_LOG";
+
 pub const MAST_HPC_JOB_NAME_ENV: &str = "MAST_HPC_JOB_NAME";
 
 // Log level constants
@@ -83,6 +87,7 @@ use tracing_appender::rolling::RollingFileAppender;
 use tracing_glog::Glog;
 use tracing_glog::GlogFields;
 use tracing_glog::LocalTime;
+use tracing_subscriber::EnvFilter;
 use tracing_subscriber::Layer;
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::filter::Targets;
@@ -562,7 +567,7 @@ pub fn initialize_logging_for_test() {
 /// test -> stderr
 /// local -> /tmp/monarch_log.log
 /// mast -> /logs/dedicated_monarch_logs.log
-/// Additionally, is MONARCH_STDERR_LOG sets logs level, then logs will be routed to stderr as well.
+/// Additionally, if MONARCH_LOG holds filter directives, matching logs are routed to stderr as well.
 pub fn initialize_logging_with_log_prefix(
     clock: impl TelemetryClock + Send + 'static,
     prefix_env_var: Option<String>,
@@ -596,6 +601,12 @@ pub fn initialize_logging_with_log_prefix(
                 .with_target("opentelemetry", LevelFilter::OFF), // otel has some log span under debug that we don't care about
         );
 
+    let stderr_layer = std::env::var(MONARCH_LOG_ENV).ok().map(|directives| {
+        fmt::Layer::default()
+            .with_writer(std::io::stderr)
+            .with_filter(EnvFilter::new(directives))
+    });
+
     use tracing_subscriber::Registry;
     use tracing_subscriber::layer::SubscriberExt;
     use tracing_subscriber::util::SubscriberInitExt;
@@ -623,6 +634,7 @@ pub fn initialize_logging_with_log_prefix(
                 None
             })
             .with(file_layer)
+            .with(stderr_layer)
             .with(if !is_layer_disabled(DISABLE_RECORDER_TRACING) {
                 Some(recorder().layer())
             } else {
@@ -657,6 +669,7 @@ pub fn initialize_logging_with_log_prefix(
     {
         if let Err(err) = Registry::default()
             .with(file_layer)
+            .with(stderr_layer)
             .with(
                 if std::env::var(DISABLE_RECORDER_TRACING).unwrap_or_default() != "1" {
                     Some(recorder().layer())
@@ -568,6 +568,25 @@ impl ClientActor {
     }
 }
 
+fn client_message_into_py(py: Python<'_>, message: ClientMessage) -> PyResult<PyObject> {
+    match message {
+        ClientMessage::Result { seq, result } => WorkerResponse { seq, result }.into_py_any(py),
+        ClientMessage::Log { level, message } => LogMessage {
+            level: PyLogLevel::from(level),
+            message,
+        }
+        .into_py_any(py),
+        ClientMessage::DebuggerMessage {
+            debugger_actor_id,
+            action,
+        } => DebuggerMessage {
+            debugger_actor_id: debugger_actor_id.into(),
+            action,
+        }
+        .into_py_any(py),
+    }
+}
+
 #[pymethods]
 impl ClientActor {
     #[new]
@@ -728,28 +747,48 @@ impl ClientActor {
             .drain_and_stop()
             .map_err(|err| PyRuntimeError::new_err(err.to_string()))?
             .into_iter()
-            .map(|message| match message {
-                ClientMessage::Result { seq, result } => {
-                    WorkerResponse { seq, result }.into_py_any(py)
-                }
-                ClientMessage::Log { level, message } => LogMessage {
-                    level: PyLogLevel::from(level),
-                    message,
-                }
-                .into_py_any(py),
-                ClientMessage::DebuggerMessage {
-                    debugger_actor_id,
-                    action,
-                } => DebuggerMessage {
-                    debugger_actor_id: debugger_actor_id.into(),
-                    action,
-                }
-                .into_py_any(py),
-            })
+            .map(|message| client_message_into_py(py, message))
             .collect::<PyResult<Vec<_>>>()?;
         PyList::new(py, messages)
     }
 
+    /// Return up to `max` messages that have already been received, without
+    /// blocking. Returns an empty list if no messages are ready.
+    /// If the actor has been stopped, this returns an error.
+    fn drain_completed<'py>(
+        &mut self,
+        py: Python<'py>,
+        max: usize,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let result = self.instance.blocking_lock().drain_ready(max);
+        let messages = match result {
+            Ok(messages) => messages
+                .into_iter()
+                .map(|message| client_message_into_py(py, message))
+                .collect::<PyResult<Vec<_>>>()?,
+            Err(err) => {
+                let Some(ControllerError::Failed(controller_id, err_msg)) =
+                    err.downcast_ref::<ControllerError>()
+                else {
+                    return Err(PyRuntimeError::new_err(err.to_string()));
+                };
+                let failure = DeviceFailure {
+                    actor_id: controller_id.clone(),
+                    address: "".to_string(), // Controller is always task 0 for now.
+                    backtrace: err_msg.clone(),
+                };
+                vec![
+                    WorkerResponse {
+                        seq: Seq::default(),
+                        result: Some(Err(Exception::Failure(failure))),
+                    }
+                    .into_py_any(py)?,
+                ]
+            }
+        };
+        PyList::new(py, messages)
+    }
+
     /// Get the status of all the worlds from the system.
     #[pyo3(signature = (filter = None))]
     fn world_status<'py>(
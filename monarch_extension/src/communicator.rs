@@ -0,0 +1,176 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ * All rights reserved.
+ *
+ * This source code is licensed under the BSD-style license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use torch_sys::CudaDevice;
+use torch_sys::DeviceIndex;
+use torch_sys::Tensor;
+use torch_sys::TensorCell;
+use torch_sys_cuda::cuda::Stream;
+use torch_sys_cuda::nccl;
+use torch_sys_cuda::nccl::NcclError;
+use torch_sys_cuda::nccl::ReduceOp;
+use torch_sys_cuda::nccl::UniqueId;
+
+pub(crate) fn register_python_bindings(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Communicator>()?;
+    Ok(())
+}
+
+/// A NCCL communicator usable directly from Python, for running collectives on
+/// torch tensors without going through `torch.distributed`.
+///
+/// Every rank creates one with the same `unique_id` (from `Communicator.unique_id()`
+/// on one rank) and must then issue the same collectives in the same order.
+/// The GIL is released while NCCL calls run, so ranks may live on threads of one
+/// process.
+#[pyclass(
+    frozen,
+    name = "Communicator",
+    module = "monarch._rust_bindings.monarch_extension.communicator"
+)]
+struct Communicator {
+    inner: std::sync::Mutex<nccl::Communicator>,
+    device: CudaDevice,
+}
+
+fn to_py_error(e: NcclError) -> PyErr {
+    PyRuntimeError::new_err(e.to_string())
+}
+
+fn parse_reduce_op(op: &str) -> PyResult<ReduceOp> {
+    match op {
+        "sum" => Ok(ReduceOp::Sum),
+        "prod" => Ok(ReduceOp::Prod),
+        "avg" => Ok(ReduceOp::Avg),
+        "min" => Ok(ReduceOp::Min),
+        "max" => Ok(ReduceOp::Max),
+        _ => Err(PyValueError::new_err(format!(
+            "Unsupported reduction {}",
+            op
+        ))),
+    }
+}
+
+impl Communicator {
+    /// Resolves `stream` (a `torch.cuda.Stream`) to a `Stream`, defaulting to the
+    /// current stream of this communicator's device.
+    fn resolve_stream(
+        &self,
+        py: Python<'_>,
+        stream: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<Stream> {
+        let Some(stream) = stream else {
+            return Ok(Stream::get_current_stream_on_device(self.device));
+        };
+        // `torch.cuda.stream` makes `stream` current, which is the only way to
+        // reach the underlying c10 stream from Python.
+        let guard = py.import("torch.cuda")?.call_method1("stream", (stream,))?;
+        guard.call_method0("__enter__")?;
+        let resolved = Stream::get_current_stream_on_device(self.device);
+        guard.call_method1("__exit__", (py.None(), py.None(), py.None()))?;
+        Ok(resolved)
+    }
+
+    /// Runs `f` on the inner communicator with the GIL released.
+    fn with_comm<T: Send>(
+        &self,
+        py: Python<'_>,
+        f: impl FnOnce(&mut nccl::Communicator) -> Result<T, NcclError> + Send,
+    ) -> PyResult<T> {
+        py.allow_threads(|| f(&mut self.inner.lock().unwrap()))
+            .map_err(to_py_error)
+    }
+}
+
+#[pymethods]
+impl Communicator {
+    /// Creates the communicator for `rank` on CUDA device `device`. Blocks until
+    /// all `world_size` ranks have joined.
+    #[new]
+    fn new(
+        py: Python<'_>,
+        device: i8,
+        world_size: i32,
+        unique_id: &[u8],
+        rank: i32,
+    ) -> PyResult<Self> {
+        let device = CudaDevice::new(DeviceIndex(device));
+        let unique_id = UniqueId::from_bytes(unique_id).map_err(to_py_error)?;
+        let inner = py
+            .allow_threads(|| nccl::Communicator::new(device, world_size, unique_id, rank))
+            .map_err(to_py_error)?;
+        Ok(Self {
+            inner: std::sync::Mutex::new(inner),
+            device,
+        })
+    }
+
+    /// Generates a new unique id to share with every rank of a communicator.
+    #[staticmethod]
+    fn unique_id(py: Python<'_>) -> PyResult<Bound<'_, PyBytes>> {
+        let unique_id = UniqueId::new().map_err(|e| to_py_error(e.into()))?;
+        Ok(PyBytes::new(py, unique_id.as_bytes()))
+    }
+
+    /// Reduces `tensor` across all ranks in place. The collective is enqueued on
+    /// `stream`, or the current stream if none is given.
+    #[pyo3(signature = (tensor, op = "sum", stream = None))]
+    fn all_reduce(
+        &self,
+        py: Python<'_>,
+        tensor: Tensor,
+        op: &str,
+        stream: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let op = parse_reduce_op(op)?;
+        let stream = self.resolve_stream(py, stream)?;
+        let tensor = TensorCell::new(tensor);
+        self.with_comm(py, |comm| comm.all_reduce(&tensor, op, &stream))?;
+        Ok(())
+    }
+
+    /// Overwrites `tensor` on every rank with its contents on `root`.
+    #[pyo3(signature = (tensor, root = 0, stream = None))]
+    fn broadcast(
+        &self,
+        py: Python<'_>,
+        tensor: Tensor,
+        root: i32,
+        stream: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        let stream = self.resolve_stream(py, stream)?;
+        let tensor = TensorCell::new(tensor);
+        self.with_comm(py, |comm| comm.broadcast(&tensor, root, &stream))?;
+        Ok(())
+    }
+
+    /// Gathers `tensor` from every rank into `outputs`, one tensor per rank.
+    #[pyo3(signature = (outputs, tensor, stream = None))]
+    fn all_gather(
+        &self,
+        py: Python<'_>,
+        outputs: Vec<Tensor>,
+        tensor: Tensor,
+        stream: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<()> {
+        if outputs.is_empty() {
+            return Err(PyValueError::new_err(
+                "all_gather needs one output per rank",
+            ));
+        }
+        let stream = self.resolve_stream(py, stream)?;
+        let outputs: Vec<_> = outputs.into_iter().map(TensorCell::new).collect();
+        let tensor = TensorCell::new(tensor);
+        self.with_comm(py, |comm| comm.all_gather(&outputs, &tensor, &stream))?;
+        Ok(())
+    }
+}
@@ -12,6 +12,8 @@
 mod client;
 pub mod code_sync;
 #[cfg(feature = "tensor_engine")]
+mod communicator;
+#[cfg(feature = "tensor_engine")]
 mod controller;
 #[cfg(feature = "tensor_engine")]
 pub mod convert;
@@ -141,6 +143,10 @@ pub fn mod_init(module: &Bound<'_, PyModule>) -> PyResult<()> {
             module,
             "monarch_extension.mesh_controller",
         )?)?;
+        crate::communicator::register_python_bindings(&get_or_add_new_module(
+            module,
+            "monarch_extension.communicator",
+        )?)?;
         monarch_rdma_extension::register_python_bindings(&get_or_add_new_module(module, "rdma")?)?;
     }
     simulation_tools::register_python_bindings(&get_or_add_new_module(
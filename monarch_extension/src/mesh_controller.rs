@@ -13,11 +13,13 @@ use std::collections::VecDeque;
 use std::error::Error;
 use std::fmt::Debug;
 use std::fmt::Formatter;
+use std::fmt::Write;
 use std::ops::DerefMut;
 use std::sync;
 use std::sync::Arc;
 use std::sync::atomic;
 use std::sync::atomic::AtomicUsize;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use hyperactor::Actor;
@@ -70,8 +72,12 @@ use ndslice::Slice;
 use ndslice::ViewExt;
 use ndslice::selection::ReifySlice;
 use pyo3::exceptions::PyRuntimeError;
+use pyo3::exceptions::PyTimeoutError;
+use pyo3::exceptions::PyTypeError;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use pyo3::types::PyTuple;
 use tokio::sync::Mutex;
 
 use crate::convert::convert;
@@ -90,10 +96,22 @@ pub(crate) fn register_python_bindings(module: &Bound<'_, PyModule>) -> PyResult
 struct _Controller {
     controller_handle: Arc<Mutex<ActorHandle<MeshControllerActor>>>,
     broker_id: (String, usize),
+    /// Python types that fetched results are expected to have, by seq.
+    expected_types: HashMap<u64, Py<PyAny>>,
 }
 
 static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
 
+/// Extract the destination of a send, either a single slice or a list of them.
+fn extract_slices(ranks: &Bound<'_, PyAny>) -> PyResult<Vec<Slice>> {
+    if let Ok(slice) = ranks.extract::<PySlice>() {
+        Ok(vec![slice.into()])
+    } else {
+        let slices = ranks.extract::<Vec<PySlice>>()?;
+        Ok(slices.iter().map(|x| x.into()).collect())
+    }
+}
+
 fn to_py_error<T>(e: T) -> PyErr
 where
     T: Error,
@@ -162,6 +180,7 @@ impl _Controller {
             // top-level spawned actors.
             // todo: plumb these through as proper actor mesh refs
             broker_id: (format!("tensor_engine_brokers_{}", id), 0),
+            expected_types: HashMap::new(),
         })
     }
 
@@ -170,7 +189,7 @@ impl _Controller {
         self.broker_id.clone()
     }
 
-    #[pyo3(signature = (seq, defs, uses, response_port, tracebacks))]
+    #[pyo3(signature = (seq, defs, uses, response_port, tracebacks, expected_type = None))]
     fn node<'py>(
         &mut self,
         seq: u64,
@@ -178,7 +197,12 @@ impl _Controller {
         uses: Bound<'py, PyAny>,
         response_port: Option<(PyPortId, PySlice)>,
         tracebacks: Py<PyAny>,
+        expected_type: Option<Py<PyAny>>,
     ) -> PyResult<()> {
+        let retain_result = expected_type.is_some();
+        if let Some(expected_type) = expected_type {
+            self.expected_types.insert(seq, expected_type);
+        }
         let response_port: Option<PortInfo> = response_port.map(|(port, ranks)| PortInfo {
             port: PortRef::attest(port.into()),
             ranks: ranks.into(),
@@ -195,6 +219,7 @@ impl _Controller {
                 .collect::<PyResult<Vec<Ref>>>()?,
             tracebacks,
             response_port,
+            retain_result,
         };
         self.controller_handle
             .blocking_lock()
@@ -209,6 +234,16 @@ impl _Controller {
             .map_err(to_py_error)
     }
 
+    /// Re-assign ranks to the current set of workers and resize completion
+    /// tracking to match, e.g. after the worker mesh was resized or a worker
+    /// restarted.
+    fn reassign_ranks(&mut self) -> PyResult<()> {
+        self.controller_handle
+            .blocking_lock()
+            .send(ClientToControllerMessage::ReassignRanks)
+            .map_err(to_py_error)
+    }
+
     fn sync_at_exit(&mut self, port: PyPortId) -> PyResult<()> {
         self.controller_handle
             .blocking_lock()
@@ -219,12 +254,7 @@ impl _Controller {
     }
 
     fn send<'py>(&mut self, ranks: Bound<'py, PyAny>, message: Bound<'py, PyAny>) -> PyResult<()> {
-        let slices = if let Ok(slice) = ranks.extract::<PySlice>() {
-            vec![slice.into()]
-        } else {
-            let slices = ranks.extract::<Vec<PySlice>>()?;
-            slices.iter().map(|x| x.into()).collect::<Vec<Slice>>()
-        };
+        let slices = extract_slices(&ranks)?;
         let message: WorkerMessage = convert(message)?;
         self.controller_handle
             .blocking_lock()
@@ -232,6 +262,170 @@ impl _Controller {
             .map_err(to_py_error)
     }
 
+    /// Like `send`, but for an iterable of messages that all go to `ranks`. The
+    /// messages are converted in one call and cast in order. If any message fails
+    /// to convert, none are sent.
+    fn send_batch<'py>(
+        &mut self,
+        ranks: Bound<'py, PyAny>,
+        messages: Bound<'py, PyAny>,
+    ) -> PyResult<()> {
+        let slices = extract_slices(&ranks)?;
+        let messages = messages
+            .try_iter()?
+            .map(|message| convert(message?))
+            .collect::<PyResult<Vec<WorkerMessage>>>()?;
+        if messages.is_empty() {
+            return Ok(());
+        }
+        self.controller_handle
+            .blocking_lock()
+            .send(ClientToControllerMessage::SendBatch { slices, messages })
+            .map_err(to_py_error)
+    }
+
+    /// Block until every rank has processed all messages sent before this call.
+    /// Raises a `TimeoutError` if not all ranks report within `timeout_msec`.
+    fn barrier(
+        &mut self,
+        py: Python<'_>,
+        instance: &PyInstance,
+        timeout_msec: u64,
+    ) -> PyResult<()> {
+        let (barrier_port, barrier_receiver) =
+            instance_dispatch!(instance, |cx_instance| { cx_instance.open_once_port() });
+
+        self.controller_handle
+            .blocking_lock()
+            .send(ClientToControllerMessage::Barrier {
+                response_port: barrier_port,
+            })
+            .map_err(to_py_error)?;
+        let timeout = Duration::from_millis(timeout_msec);
+        signal_safe_block_on(py, async move {
+            tokio::time::timeout(timeout, barrier_receiver.recv()).await
+        })?
+        .map_err(|_| {
+            PyTimeoutError::new_err(format!(
+                "barrier timed out after {} ms waiting for all ranks",
+                timeout_msec
+            ))
+        })?
+        .map_err(to_py_error)
+    }
+
+    /// Block until every rank has completed all invocations up to and including
+    /// `seq`, e.g. before taking a checkpoint. Raises a `TimeoutError` if that
+    /// does not happen within `timeout_msec`.
+    fn wait_for_seq(
+        &mut self,
+        py: Python<'_>,
+        instance: &PyInstance,
+        seq: u64,
+        timeout_msec: u64,
+    ) -> PyResult<()> {
+        let (wait_port, wait_receiver) =
+            instance_dispatch!(instance, |cx_instance| { cx_instance.open_once_port() });
+
+        self.controller_handle
+            .blocking_lock()
+            .send(ClientToControllerMessage::WaitForSeq {
+                seq: seq.into(),
+                response_port: wait_port,
+            })
+            .map_err(to_py_error)?;
+        let timeout = Duration::from_millis(timeout_msec);
+        signal_safe_block_on(py, async move {
+            tokio::time::timeout(timeout, wait_receiver.recv()).await
+        })?
+        .map_err(|_| {
+            PyTimeoutError::new_err(format!(
+                "timed out after {} ms waiting for all ranks to complete seq {}",
+                timeout_msec, seq
+            ))
+        })?
+        .map_err(to_py_error)
+    }
+
+    /// Return the per-rank results of `seq`, which must have been added with an
+    /// `expected_type` and completed. Each result is deserialized and checked to
+    /// be an instance of that type, raising a `TypeError` otherwise. If `seq`
+    /// failed, the remote exception is raised instead.
+    fn _get_typed_result(
+        &mut self,
+        py: Python<'_>,
+        instance: &PyInstance,
+        seq: u64,
+    ) -> PyResult<Vec<PyObject>> {
+        let expected_type = self
+            .expected_types
+            .get(&seq)
+            .map(|expected_type| expected_type.clone_ref(py))
+            .ok_or_else(|| {
+                PyValueError::new_err(format!(
+                    "seq {} was not added with an expected result type",
+                    seq
+                ))
+            })?;
+        let (result_port, result_receiver) =
+            instance_dispatch!(instance, |cx_instance| { cx_instance.open_once_port() });
+
+        self.controller_handle
+            .blocking_lock()
+            .send(ClientToControllerMessage::GetResult {
+                seq: seq.into(),
+                response_port: result_port,
+            })
+            .map_err(to_py_error)?;
+        let results = signal_safe_block_on(py, async move { result_receiver.recv().await })?
+            .map_err(to_py_error)?
+            .ok_or_else(|| {
+                PyValueError::new_err(format!("no result for seq {}; it has not completed", seq))
+            })?;
+        // The controller hands results out once, so only forget the type now.
+        self.expected_types.remove(&seq);
+
+        let unflatten = py
+            .import("monarch._src.actor.pickle")?
+            .getattr("unflatten")?;
+        let expected_type = expected_type.bind(py);
+        results
+            .into_iter()
+            .map(|result| {
+                let value = unflatten.call1((
+                    PyBytes::new(py, result.message.as_ref()),
+                    PyTuple::empty(py),
+                ))?;
+                if let PythonMessageKind::Exception { .. } = result.kind {
+                    return Err(PyErr::from_value(value));
+                }
+                if !value.is_instance(expected_type)? {
+                    return Err(PyTypeError::new_err(format!(
+                        "result for seq {} is of type {}, expected {}",
+                        seq,
+                        value.get_type().name()?,
+                        expected_type.repr()?
+                    )));
+                }
+                Ok(value.unbind())
+            })
+            .collect()
+    }
+
+    /// Return the graph of in-flight invocations as a Graphviz DOT string.
+    fn _export_graph(&mut self, py: Python<'_>, instance: &PyInstance) -> PyResult<String> {
+        let (graph_port, graph_receiver) =
+            instance_dispatch!(instance, |cx_instance| { cx_instance.open_once_port() });
+
+        self.controller_handle
+            .blocking_lock()
+            .send(ClientToControllerMessage::ExportGraph {
+                response_port: graph_port,
+            })
+            .map_err(to_py_error)?;
+        signal_safe_block_on(py, async move { graph_receiver.recv().await })?.map_err(to_py_error)
+    }
+
     fn _drain_and_stop(&mut self, py: Python<'_>, instance: &PyInstance) -> PyResult<()> {
         let (stop_worker_port, stop_worker_receiver) =
             instance_dispatch!(instance, |cx_instance| { cx_instance.open_once_port() });
@@ -292,15 +486,23 @@ struct Invocation {
     /// both result and error == None
     response_port: Option<PortInfo>,
     tracebacks: Py<PyAny>,
+    /// Whether the results are kept after completion for a typed fetch.
+    retain_result: bool,
 }
 
 impl Invocation {
-    fn new(seq: Seq, tracebacks: Py<PyAny>, response_port: Option<PortInfo>) -> Self {
+    fn new(
+        seq: Seq,
+        tracebacks: Py<PyAny>,
+        response_port: Option<PortInfo>,
+        retain_result: bool,
+    ) -> Self {
         Self {
             seq,
             status: Status::incomplete(),
             response_port,
             tracebacks,
+            retain_result,
         }
     }
 
@@ -341,23 +543,38 @@ impl Invocation {
         }
     }
 
-    fn complete(&mut self, sender: &impl context::Actor) -> Result<(), MailboxSenderError> {
+    /// Completes the invocation, sending its results to the response port.
+    /// Returns the results (or the exception) to keep if `retain_result` is set.
+    fn complete(
+        &mut self,
+        sender: &impl context::Actor,
+    ) -> Result<Option<Vec<PythonMessage>>, MailboxSenderError> {
         let old_status = std::mem::replace(&mut self.status, Status::Complete {});
+        let mut retained = None;
         match old_status {
-            Status::Incomplete { results, .. } => match &self.response_port {
-                Some(PortInfo { port, ranks }) => {
-                    assert!(ranks.len() == results.iter().len());
-                    for result in results.into_iter() {
-                        port.send(sender, result)?;
+            Status::Incomplete { results, .. } => {
+                if self.retain_result {
+                    retained = Some(results.clone());
+                }
+                match &self.response_port {
+                    Some(PortInfo { port, ranks }) => {
+                        assert!(ranks.len() == results.iter().len());
+                        for result in results.into_iter() {
+                            port.send(sender, result)?;
+                        }
                     }
+                    None => {}
+                }
+            }
+            Status::Errored { exception } => {
+                if self.retain_result {
+                    retained = Some(vec![exception.as_ref().clone()]);
                 }
-                None => {}
-            },
-            _ => {
-                self.status = old_status;
+                self.status = Status::Errored { exception };
             }
+            Status::Complete {} => {}
         }
-        Ok(())
+        Ok(retained)
     }
 
     /// Changes the status of this invocation to an Errored. If this invocation was
@@ -378,6 +595,11 @@ impl Invocation {
                 let old_status = std::mem::replace(&mut invocation.status, err);
                 match old_status {
                     Status::Incomplete { users, .. } => {
+                        tracing::info!(
+                            seq = %invocation.seq,
+                            users = users.len(),
+                            "invocation errored"
+                        );
                         match &invocation.response_port {
                             Some(PortInfo { port, ranks }) => {
                                 *unreported_exception = None;
@@ -434,6 +656,12 @@ struct History {
     seq_lower_bound: Seq,
     unreported_exception: Option<Arc<PythonMessage>>,
     exit_port: Option<PortRef<PythonMessage>>,
+    /// Barriers waiting for all ranks to complete up to (and including) the
+    /// given Seq, ordered by Seq.
+    pending_barriers: VecDeque<(Seq, OncePortHandle<()>)>,
+    /// Results of completed invocations that requested a typed fetch, kept
+    /// until the client takes them or drops the refs the invocation defined.
+    retained_results: HashMap<Seq, Vec<PythonMessage>>,
 }
 
 /// A vector that keeps track of the minimum value.
@@ -474,6 +702,10 @@ where
     fn min(&self) -> T {
         *self.value_counts.keys().next().unwrap()
     }
+
+    fn vec(&self) -> &[T] {
+        &self.data
+    }
 }
 
 impl History {
@@ -486,6 +718,8 @@ impl History {
             seq_lower_bound: 0.into(),
             unreported_exception: None,
             exit_port: None,
+            pending_barriers: VecDeque::new(),
+            retained_results: HashMap::new(),
         }
     }
 
@@ -494,9 +728,21 @@ impl History {
         self.first_incomplete_seqs.vec()
     }
 
+    /// Whether every rank has completed all invocations up to and including `seq`.
+    pub fn all_ranks_completed_through(&self, seq: Seq) -> bool {
+        self.min_incomplete_seq > seq
+    }
+
     pub fn drop_refs(&mut self, refs: Vec<Ref>) {
         for r in refs {
-            self.invocation_for_ref.remove(&r);
+            if let Some(invocation) = self.invocation_for_ref.remove(&r) {
+                // Once the client drops what an invocation defined, nobody can ask
+                // for its typed result any more.
+                let invocation = invocation.lock().unwrap();
+                if invocation.retain_result {
+                    self.retained_results.remove(&invocation.seq);
+                }
+            }
         }
     }
 
@@ -509,7 +755,15 @@ impl History {
         defs: Vec<Ref>,
         tracebacks: Py<PyAny>,
         response_port: Option<PortInfo>,
+        retain_result: bool,
     ) -> Result<(), MailboxSenderError> {
+        let _span = tracing::debug_span!(
+            "add_invocation",
+            seq = %seq,
+            uses = uses.len(),
+            defs = defs.len()
+        )
+        .entered();
         assert!(
             seq >= self.seq_lower_bound,
             "nonmonotonic seq: {:?}; current lower bound: {:?}",
@@ -521,6 +775,7 @@ impl History {
             seq,
             tracebacks,
             response_port,
+            retain_result,
         )));
         self.inflight_invocations.insert(seq, invocation.clone());
         for ref use_ in uses {
@@ -547,6 +802,7 @@ impl History {
         exception: WorkerError,
         rank: usize,
     ) -> Result<(), MailboxSenderError> {
+        let _span = tracing::debug_span!("propagate_exception", seq = %seq, rank).entered();
         // TODO: supplement PythonMessage with the stack trace we have in invocation
         let invocation = self.inflight_invocations.get(&seq).unwrap().clone();
 
@@ -601,16 +857,45 @@ impl History {
         rank: usize,
         seq: Seq,
     ) -> Result<(), MailboxSenderError> {
+        let _span = tracing::debug_span!("rank_completed", seq = %seq, rank).entered();
         self.first_incomplete_seqs.set(rank, seq);
+        self.advance_min_incomplete_seq(sender)
+    }
+
+    /// Change the number of ranks tracked, e.g. after workers were added or removed.
+    /// Surviving ranks keep their progress. New ranks start at the current minimum
+    /// incomplete Seq, so nothing is purged on their behalf until they report.
+    /// Dropping ranks may advance the minimum and purge invocations only they held.
+    pub fn resize(
+        &mut self,
+        sender: &impl context::Actor,
+        world_size: usize,
+    ) -> Result<(), MailboxSenderError> {
+        let mut seqs = self.first_incomplete_seqs.vec().to_vec();
+        seqs.resize(world_size, self.min_incomplete_seq);
+        self.first_incomplete_seqs = MinVector::new(seqs);
+        self.advance_min_incomplete_seq(sender)
+    }
+
+    /// Recompute the minimum incomplete Seq, completing and purging every invocation
+    /// below it and releasing any barriers it satisfies.
+    fn advance_min_incomplete_seq(
+        &mut self,
+        sender: &impl context::Actor,
+    ) -> Result<(), MailboxSenderError> {
         let prev = self.min_incomplete_seq;
         self.min_incomplete_seq = self.first_incomplete_seqs.min();
 
         for i in Seq::iter_between(prev, self.min_incomplete_seq) {
             if let Some(invocation) = self.inflight_invocations.remove(&i) {
+                tracing::debug!(seq = %i, "purging completed invocation");
                 let mut invocation = invocation.lock().unwrap();
-                invocation.complete(sender)?;
+                if let Some(results) = invocation.complete(sender)? {
+                    self.retained_results.insert(i, results);
+                }
             }
         }
+        self.release_barriers();
         if let Some(port) = &self.exit_port {
             if self.min_incomplete_seq >= self.seq_lower_bound {
                 let result = match &self.unreported_exception {
@@ -635,9 +920,83 @@ impl History {
         invocation.lock().unwrap().set_result(result);
     }
 
+    /// Render the in-flight invocations as a Graphviz DOT digraph. Nodes are
+    /// labeled with their seq and status, and each edge points from an invocation
+    /// to a user of the refs it defines. Errored invocations have already
+    /// propagated to their users, so they have no outgoing edges.
+    pub fn export_dot(&self) -> String {
+        let mut seqs: Vec<Seq> = self.inflight_invocations.keys().copied().collect();
+        seqs.sort();
+        let mut nodes = String::new();
+        let mut edges = String::new();
+        for seq in seqs {
+            let invocation = self.inflight_invocations[&seq].lock().unwrap();
+            let (status, color) = match &invocation.status {
+                Status::Errored { .. } => ("errored", "red"),
+                Status::Complete {} => ("complete", "black"),
+                Status::Incomplete { .. } => ("incomplete", "black"),
+            };
+            writeln!(
+                nodes,
+                "  \"{seq}\" [label=\"{seq}\\n{status}\", color={color}];"
+            )
+            .unwrap();
+            if let Status::Incomplete { users, .. } = &invocation.status {
+                let mut users: Vec<Seq> = users.keys().copied().collect();
+                users.sort();
+                for user in users {
+                    writeln!(edges, "  \"{seq}\" -> \"{user}\";").unwrap();
+                }
+            }
+        }
+        format!("digraph invocations {{\n{nodes}{edges}}}\n")
+    }
+
+    /// Take the results kept for a completed invocation that requested a typed fetch.
+    fn take_result(&mut self, seq: Seq) -> Option<Vec<PythonMessage>> {
+        self.retained_results.remove(&seq)
+    }
+
     fn report_exit(&mut self, port: PortRef<PythonMessage>) {
         self.exit_port = Some(port);
     }
+
+    /// Register a barrier on the latest Seq seen so far. The port is notified
+    /// once every rank has completed that Seq. Returns the Seq that the workers
+    /// should be asked to report status for.
+    fn add_barrier(&mut self, port: OncePortHandle<()>) -> Seq {
+        let seq = self.seq_lower_bound;
+        self.insert_barrier(seq, port);
+        seq
+    }
+
+    /// Register a wait for every rank to complete `seq`. Unlike `add_barrier`,
+    /// `seq` need not be the latest Seq sent. The port is notified
+    /// once `all_ranks_completed_through(seq)` holds.
+    fn add_seq_waiter(&mut self, seq: Seq, port: OncePortHandle<()>) {
+        self.insert_barrier(seq, port);
+    }
+
+    /// Keep `pending_barriers` sorted by Seq so that `release_barriers` can stop
+    /// at the first entry that is not yet satisfied.
+    fn insert_barrier(&mut self, seq: Seq, port: OncePortHandle<()>) {
+        let index = self.pending_barriers.partition_point(|(s, _)| *s <= seq);
+        self.pending_barriers.insert(index, (seq, port));
+        self.release_barriers();
+    }
+
+    fn release_barriers(&mut self) {
+        while let Some((seq, _)) = self.pending_barriers.front() {
+            if !self.all_ranks_completed_through(*seq) {
+                break;
+            }
+            let (seq, port) = self.pending_barriers.pop_front().unwrap();
+            // The caller may have already timed out and dropped the receiver.
+            if let Err(e) = port.send(()) {
+                tracing::debug!("barrier at {:?} was abandoned: {}", seq, e);
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -654,22 +1013,42 @@ enum ClientToControllerMessage {
         slices: Vec<Slice>,
         message: WorkerMessage,
     },
+    SendBatch {
+        slices: Vec<Slice>,
+        messages: Vec<WorkerMessage>,
+    },
     Node {
         seq: Seq,
         defs: Vec<Ref>,
         uses: Vec<Ref>,
         tracebacks: Py<PyAny>,
         response_port: Option<PortInfo>,
+        retain_result: bool,
     },
     DropRefs {
         refs: Vec<Ref>,
     },
+    ReassignRanks,
     SyncAtExit {
         port: PortRef<PythonMessage>,
     },
     StopWorkers {
         response_port: OncePortHandle<Result<(), String>>,
     },
+    Barrier {
+        response_port: OncePortHandle<()>,
+    },
+    ExportGraph {
+        response_port: OncePortHandle<String>,
+    },
+    GetResult {
+        seq: Seq,
+        response_port: OncePortHandle<Option<Vec<PythonMessage>>>,
+    },
+    WaitForSeq {
+        seq: Seq,
+        response_port: OncePortHandle<()>,
+    },
 }
 
 struct MeshControllerActor {
@@ -897,19 +1276,41 @@ impl Handler<ClientToControllerMessage> for MeshControllerActor {
                 let sel = workers.shape().slice().reify_slices(slices)?;
                 workers.cast(this, sel, message)?;
             }
+            ClientToControllerMessage::SendBatch { slices, messages } => {
+                let workers = self.workers();
+                let sel = workers.shape().slice().reify_slices(slices)?;
+                for message in messages {
+                    workers.cast(this, sel.clone(), message)?;
+                }
+            }
             ClientToControllerMessage::Node {
                 seq,
                 defs,
                 uses,
                 tracebacks,
                 response_port,
+                retain_result,
             } => {
-                self.history
-                    .add_invocation(this, seq, uses, defs, tracebacks, response_port)?;
+                self.history.add_invocation(
+                    this,
+                    seq,
+                    uses,
+                    defs,
+                    tracebacks,
+                    response_port,
+                    retain_result,
+                )?;
             }
             ClientToControllerMessage::DropRefs { refs } => {
                 self.history.drop_refs(refs);
             }
+            ClientToControllerMessage::ReassignRanks => {
+                let workers = self.workers();
+                let world_size = workers.shape().slice().len();
+                anyhow::ensure!(world_size > 0, "cannot reassign ranks of an empty mesh");
+                workers.cast(this, sel!(*), AssignRankMessage::AssignRank())?;
+                self.history.resize(this, world_size)?;
+            }
             ClientToControllerMessage::SyncAtExit { port } => {
                 self.workers().cast(
                     this,
@@ -930,7 +1331,350 @@ impl Handler<ClientToControllerMessage> for MeshControllerActor {
                     response_port.send(Err(format!("stopping mesh workers failed: tensor worker result: {:?}, broker result: {:?}", worker_stop_result, broker_stop_result)))?;
                 }
             }
+            ClientToControllerMessage::Barrier { response_port } => {
+                let seq = self.history.add_barrier(response_port);
+                self.workers()
+                    .cast(this, sel!(*), WorkerMessage::Barrier { seq })?;
+            }
+            ClientToControllerMessage::ExportGraph { response_port } => {
+                response_port.send(self.history.export_dot())?;
+            }
+            ClientToControllerMessage::GetResult { seq, response_port } => {
+                response_port.send(self.history.take_result(seq))?;
+            }
+            ClientToControllerMessage::WaitForSeq { seq, response_port } => {
+                self.history.add_seq_waiter(seq, response_port);
+                // Workers only report progress when asked, so ask for status on
+                // everything sent so far. A `seq` that has not been sent yet stays
+                // pending until a later status covers it.
+                self.workers().cast(
+                    this,
+                    sel!(*),
+                    WorkerMessage::RequestStatus {
+                        seq: self.history.seq_lower_bound,
+                        controller: false,
+                    },
+                )?;
+            }
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hyperactor::proc::Proc;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_barrier_waits_for_all_ranks() {
+        let proc = Proc::local();
+        let (client, _handle) = proc.instance("client").unwrap();
+        let mut history = History::new(2);
+
+        let (port, receiver) = client.open_once_port::<()>();
+        let seq = history.add_barrier(port);
+        assert_eq!(seq, Seq::default());
+
+        // Workers report status for `seq + 1` once they reach the barrier.
+        history.rank_completed(&client, 0, seq.next()).unwrap();
+        assert_eq!(history.pending_barriers.len(), 1);
+
+        history.rank_completed(&client, 1, seq.next()).unwrap();
+        assert!(history.pending_barriers.is_empty());
+        receiver.recv().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_seq_waits_for_lagging_rank() {
+        let proc = Proc::local();
+        let (client, _handle) = proc.instance("client").unwrap();
+        let mut history = History::new(2);
+
+        history.rank_completed(&client, 0, 6.into()).unwrap();
+        history.rank_completed(&client, 1, 3.into()).unwrap();
+        assert!(history.all_ranks_completed_through(2.into()));
+        assert!(!history.all_ranks_completed_through(3.into()));
+        assert!(!history.all_ranks_completed_through(5.into()));
+
+        let (later_port, later_receiver) = client.open_once_port::<()>();
+        history.add_seq_waiter(5.into(), later_port);
+        let (port, receiver) = client.open_once_port::<()>();
+        history.add_seq_waiter(3.into(), port);
+        assert_eq!(history.pending_barriers.len(), 2);
+
+        // Rank 1 catches up through seq 3 but not 5.
+        history.rank_completed(&client, 1, 4.into()).unwrap();
+        assert!(history.all_ranks_completed_through(3.into()));
+        receiver.recv().await.unwrap();
+        assert_eq!(history.pending_barriers.len(), 1);
+
+        history.rank_completed(&client, 1, 6.into()).unwrap();
+        assert!(history.all_ranks_completed_through(5.into()));
+        assert!(history.pending_barriers.is_empty());
+        later_receiver.recv().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_barrier_after_later_seq_waiter() {
+        let proc = Proc::local();
+        let (client, _handle) = proc.instance("client").unwrap();
+        let mut history = History::new(2);
+
+        let (waiter_port, waiter_receiver) = client.open_once_port::<()>();
+        history.add_seq_waiter(5.into(), waiter_port);
+        let (barrier_port, barrier_receiver) = client.open_once_port::<()>();
+        let seq = history.add_barrier(barrier_port);
+        assert!(seq < Seq::from(5));
+        assert_eq!(history.pending_barriers.len(), 2);
+
+        // The barrier is satisfied even though the earlier-registered waiter is not.
+        history.rank_completed(&client, 0, seq.next()).unwrap();
+        history.rank_completed(&client, 1, seq.next()).unwrap();
+        barrier_receiver.recv().await.unwrap();
+        assert_eq!(history.pending_barriers.len(), 1);
+
+        history.rank_completed(&client, 0, 6.into()).unwrap();
+        history.rank_completed(&client, 1, 6.into()).unwrap();
+        assert!(history.pending_barriers.is_empty());
+        waiter_receiver.recv().await.unwrap();
+    }
+
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_exception_event_has_seq() {
+        pyo3::prepare_freethreaded_python();
+        let proc = Proc::local();
+        let (client, _handle) = proc.instance("client").unwrap();
+        let mut history = History::new(1);
+        let tracebacks = || Python::with_gil(|py| py.None());
+
+        history
+            .add_invocation(
+                &client,
+                7.into(),
+                vec![],
+                vec![Ref { id: 1 }],
+                tracebacks(),
+                None,
+                false,
+            )
+            .unwrap();
+        history
+            .add_invocation(
+                &client,
+                8.into(),
+                vec![Ref { id: 1 }],
+                vec![],
+                tracebacks(),
+                None,
+                false,
+            )
+            .unwrap();
+
+        let exception = Arc::new(PythonMessage::new_from_buf(
+            PythonMessageKind::Exception { rank: Some(0) },
+            vec![],
+        ));
+        let invocation = history
+            .inflight_invocations
+            .get(&Seq::from(7))
+            .unwrap()
+            .clone();
+        invocation
+            .lock()
+            .unwrap()
+            .set_exception(&client, &mut history.unreported_exception, exception)
+            .unwrap();
+
+        assert!(logs_contain("invocation errored"));
+        assert!(logs_contain("seq=s7"));
+        // The error is propagated to the user of the failing invocation.
+        assert!(logs_contain("seq=s8"));
+
+        history.rank_completed(&client, 0, 9.into()).unwrap();
+        assert!(logs_contain("purging completed invocation"));
+    }
+
+    #[tokio::test]
+    async fn test_retained_result_round_trips() {
+        pyo3::prepare_freethreaded_python();
+        let proc = Proc::local();
+        let (client, _handle) = proc.instance("client").unwrap();
+        let mut history = History::new(1);
+        let tracebacks = || Python::with_gil(|py| py.None());
+
+        history
+            .add_invocation(&client, 3.into(), vec![], vec![], tracebacks(), None, true)
+            .unwrap();
+        history
+            .add_invocation(&client, 4.into(), vec![], vec![], tracebacks(), None, false)
+            .unwrap();
+        // The pickle of the Python int 42.
+        let pickled = b"\x80\x04K*.".to_vec();
+        history.set_result(
+            3.into(),
+            PythonMessage::new_from_buf(
+                PythonMessageKind::Result { rank: Some(0) },
+                pickled.clone(),
+            ),
+        );
+        assert!(history.take_result(3.into()).is_none());
+
+        history.rank_completed(&client, 0, 5.into()).unwrap();
+        assert!(history.take_result(4.into()).is_none());
+        let results = history.take_result(3.into()).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].message.as_ref(), pickled.as_slice());
+        Python::with_gil(|py| {
+            let value = py
+                .import("pickle")
+                .unwrap()
+                .call_method1("loads", (PyBytes::new(py, results[0].message.as_ref()),))
+                .unwrap();
+            assert_eq!(value.extract::<i64>().unwrap(), 42);
+        });
+        // Results are handed out once.
+        assert!(history.take_result(3.into()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_retained_result_purged_with_refs() {
+        pyo3::prepare_freethreaded_python();
+        let proc = Proc::local();
+        let (client, _handle) = proc.instance("client").unwrap();
+        let mut history = History::new(1);
+        let tracebacks = || Python::with_gil(|py| py.None());
+
+        history
+            .add_invocation(
+                &client,
+                0.into(),
+                vec![],
+                vec![Ref { id: 1 }],
+                tracebacks(),
+                None,
+                true,
+            )
+            .unwrap();
+        history.set_result(
+            0.into(),
+            PythonMessage::new_from_buf(
+                PythonMessageKind::Result { rank: Some(0) },
+                b"\x80\x04K*.".to_vec(),
+            ),
+        );
+        history.rank_completed(&client, 0, 1.into()).unwrap();
+        assert!(history.retained_results.contains_key(&Seq::from(0)));
+
+        history.drop_refs(vec![Ref { id: 1 }]);
+        assert!(history.retained_results.is_empty());
+        assert!(history.take_result(0.into()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_export_dot() {
+        pyo3::prepare_freethreaded_python();
+        let proc = Proc::local();
+        let (client, _handle) = proc.instance("client").unwrap();
+        let mut history = History::new(1);
+        let tracebacks = || Python::with_gil(|py| py.None());
+
+        // 1 and 2 both use the ref defined by 0; 3 is independent.
+        history
+            .add_invocation(
+                &client,
+                0.into(),
+                vec![],
+                vec![Ref { id: 1 }],
+                tracebacks(),
+                None,
+                false,
+            )
+            .unwrap();
+        for seq in [1, 2] {
+            history
+                .add_invocation(
+                    &client,
+                    seq.into(),
+                    vec![Ref { id: 1 }],
+                    vec![],
+                    tracebacks(),
+                    None,
+                    false,
+                )
+                .unwrap();
+        }
+        history
+            .add_invocation(&client, 3.into(), vec![], vec![], tracebacks(), None, false)
+            .unwrap();
+        let exception = Arc::new(PythonMessage::new_from_buf(
+            PythonMessageKind::Exception { rank: Some(0) },
+            vec![],
+        ));
+        let invocation = history.inflight_invocations[&Seq::from(3)].clone();
+        invocation
+            .lock()
+            .unwrap()
+            .set_exception(&client, &mut history.unreported_exception, exception)
+            .unwrap();
+
+        let dot = history.export_dot();
+        assert!(dot.starts_with("digraph invocations {\n"), "{}", dot);
+        assert!(dot.ends_with("}\n"), "{}", dot);
+        assert!(dot.contains("\"s0\" [label=\"s0\\nincomplete\", color=black];"));
+        assert!(dot.contains("\"s3\" [label=\"s3\\nerrored\", color=red];"));
+        assert!(dot.contains("\"s0\" -> \"s1\";"));
+        assert!(dot.contains("\"s0\" -> \"s2\";"));
+        assert_eq!(dot.matches("->").count(), 2, "{}", dot);
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_barrier_is_released() {
+        let proc = Proc::local();
+        let (client, _handle) = proc.instance("client").unwrap();
+        let mut history = History::new(2);
+
+        // Simulate a caller that timed out before all ranks reported.
+        let (port, receiver) = client.open_once_port::<()>();
+        let seq = history.add_barrier(port);
+        drop(receiver);
+
+        history.rank_completed(&client, 0, seq.next()).unwrap();
+        history.rank_completed(&client, 1, seq.next()).unwrap();
+        assert!(history.pending_barriers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resize_updates_completion_bookkeeping() {
+        pyo3::prepare_freethreaded_python();
+        let proc = Proc::local();
+        let (client, _handle) = proc.instance("client").unwrap();
+        let mut history = History::new(2);
+        let tracebacks = || Python::with_gil(|py| py.None());
+
+        history
+            .add_invocation(&client, 0.into(), vec![], vec![], tracebacks(), None, false)
+            .unwrap();
+        history.resize(&client, 3).unwrap();
+        assert_eq!(history.first_incomplete_seqs().len(), 3);
+
+        // The new rank still holds seq 0 back.
+        history.rank_completed(&client, 0, 1.into()).unwrap();
+        history.rank_completed(&client, 1, 1.into()).unwrap();
+        assert!(history.inflight_invocations.contains_key(&Seq::from(0)));
+        history.rank_completed(&client, 2, 1.into()).unwrap();
+        assert!(history.inflight_invocations.is_empty());
+
+        // Shrinking drops lagging ranks, so the remaining rank alone decides.
+        history
+            .add_invocation(&client, 1.into(), vec![], vec![], tracebacks(), None, false)
+            .unwrap();
+        history.rank_completed(&client, 0, 2.into()).unwrap();
+        assert!(history.inflight_invocations.contains_key(&Seq::from(1)));
+        history.resize(&client, 1).unwrap();
+        assert_eq!(history.first_incomplete_seqs(), &[Seq::from(2)]);
+        assert!(history.inflight_invocations.is_empty());
+    }
+}
@@ -570,6 +570,28 @@ impl RequestStatus {
     }
 }
 
+#[pyclass(frozen, extends=PyWorkerMessage, module = "monarch._rust_bindings.monarch_extension.tensor_worker")]
+struct Barrier;
+
+#[pymethods]
+impl Barrier {
+    #[new]
+    #[pyo3(signature = (*, seq))]
+    fn new(seq: u64) -> (Self, PyWorkerMessage) {
+        (
+            Self,
+            PyWorkerMessage {
+                message: WorkerMessage::Barrier { seq: seq.into() },
+            },
+        )
+    }
+
+    #[getter]
+    fn seq(self_: PyRef<Self>) -> u64 {
+        (*self_.as_ref().message.as_barrier().unwrap()).into()
+    }
+}
+
 #[pyclass(
     name = "ReductionType",
     module = "monarch._rust_bindings.monarch_extension.tensor_worker",
@@ -584,6 +606,9 @@ enum PyReduction {
     Max,
     Min,
     Avg,
+    BitOr,
+    BitAnd,
+    BitXor,
 }
 
 impl From<PyReduction> for Reduction {
@@ -595,6 +620,9 @@ impl From<PyReduction> for Reduction {
             PyReduction::Max => Reduction::ReduceOp(ReduceOp::Max),
             PyReduction::Min => Reduction::ReduceOp(ReduceOp::Min),
             PyReduction::Avg => Reduction::ReduceOp(ReduceOp::Avg),
+            PyReduction::BitOr => Reduction::ReduceOp(ReduceOp::BitOr),
+            PyReduction::BitAnd => Reduction::ReduceOp(ReduceOp::BitAnd),
+            PyReduction::BitXor => Reduction::ReduceOp(ReduceOp::BitXor),
         }
     }
 }
@@ -676,6 +704,9 @@ impl Reduce {
             Reduction::ReduceOp(ReduceOp::Max) => PyReduction::Max,
             Reduction::ReduceOp(ReduceOp::Min) => PyReduction::Min,
             Reduction::ReduceOp(ReduceOp::Avg) => PyReduction::Avg,
+            Reduction::ReduceOp(ReduceOp::BitOr) => PyReduction::BitOr,
+            Reduction::ReduceOp(ReduceOp::BitAnd) => PyReduction::BitAnd,
+            Reduction::ReduceOp(ReduceOp::BitXor) => PyReduction::BitXor,
         }
     }
 
@@ -1331,6 +1362,9 @@ pub(crate) fn worker_message_to_py(py: Python<'_>, message: &WorkerMessage) -> P
         WorkerMessage::RequestStatus { .. } => {
             Py::new(py, initializer.add_subclass(RequestStatus {}))?.into_py_any(py)
         }
+        WorkerMessage::Barrier { .. } => {
+            Py::new(py, initializer.add_subclass(Barrier {}))?.into_py_any(py)
+        }
         WorkerMessage::Reduce { .. } => {
             Py::new(py, initializer.add_subclass(Reduce {}))?.into_py_any(py)
         }
@@ -1419,6 +1453,7 @@ pub(crate) fn register_python_bindings(worker_mod: &Bound<'_, PyModule>) -> PyRe
     worker_mod.add_class::<BorrowDrop>()?;
     worker_mod.add_class::<DeleteRefs>()?;
     worker_mod.add_class::<RequestStatus>()?;
+    worker_mod.add_class::<Barrier>()?;
     worker_mod.add_class::<Reduce>()?;
     worker_mod.add_class::<SendTensor>()?;
     worker_mod.add_class::<CreatePipe>()?;
@@ -608,6 +608,27 @@ impl<M: RemoteMessage> InstanceWrapper<M> {
         })
     }
 
+    /// Return up to `max` messages that have already been received, without waiting
+    /// for more. Returns an empty list if no messages are ready.
+    #[hyperactor::instrument(level = "trace", fields(actor_id = hyperactor::tracing::field::display(self.actor_id())))]
+    pub fn drain_ready(&mut self, max: usize) -> Result<Vec<M>> {
+        self.ensure_detached_and_alive()?;
+        let mut messages = Vec::new();
+        while messages.len() < max {
+            match self.message_receiver.try_recv()? {
+                Some(message) => messages.push(message),
+                None => break,
+            }
+        }
+        if !messages.is_empty() {
+            hyperactor::metrics::ACTOR_MESSAGES_RECEIVED.add(
+                messages.len() as u64,
+                hyperactor::kv_pairs!("actor_id" => self.actor_id().to_string()),
+            );
+        }
+        Ok(messages)
+    }
+
     /// Put the actor in stopped mode and return any messages that were received.
     #[hyperactor::instrument(fields(actor_id=hyperactor::tracing::field::display(self.actor_id())))]
     pub fn drain_and_stop(&mut self) -> Result<Vec<M>> {
@@ -699,3 +720,39 @@ pub fn register_python_bindings(hyperactor_mod: &Bound<'_, PyModule>) -> PyResul
     hyperactor_mod.add_class::<PySerialized>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use hyperactor::PortRef;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_drain_ready_returns_only_ready_messages() -> Result<()> {
+        let proc = Proc::local();
+        let (instance, _handle) = proc.instance("wrapper")?;
+        let mut wrapper =
+            InstanceWrapper::<u64>::new_with_instance_and_clock(instance, proc.clock().clone())?;
+        let (client, _client_handle) = proc.instance("client")?;
+
+        // Nothing has been sent yet, so this must return immediately.
+        assert!(wrapper.drain_ready(10)?.is_empty());
+
+        let port = PortRef::<u64>::attest_message_port(wrapper.actor_id());
+        for i in 0..5 {
+            port.send(&client, i)?;
+        }
+
+        // Batches are capped at `max` and preserve arrival order.
+        let mut drained = Vec::new();
+        while drained.len() < 5 {
+            let batch = wrapper.drain_ready(3)?;
+            assert!(batch.len() <= 3);
+            drained.extend(batch);
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(drained, vec![0, 1, 2, 3, 4]);
+        assert!(wrapper.drain_ready(3)?.is_empty());
+        Ok(())
+    }
+}
@@ -725,6 +725,15 @@ pub enum WorkerMessage {
         controller: bool,
     },
 
+    /// Synchronize all workers that receive this message. Like
+    /// [`WorkerMessage::RequestStatus`], a [`ControllerMessage::Status`] for
+    /// `seq + 1` is sent to the controller once all streams have processed all
+    /// the messages sent before this one; the controller releases the barrier
+    /// when every rank has reported.
+    Barrier {
+        seq: Seq,
+    },
+
     /// Perform a reduction operation, using an efficient communication backend.
     /// Only NCCL is supported for now.
     Reduce {
@@ -0,0 +1,233 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ * All rights reserved.
+ *
+ * This source code is licensed under the BSD-style license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! RDMA write latency and bandwidth benchmarks.
+//!
+//! Each benchmark sets up two `RdmaManagerActor`s on host memory, connects them
+//! with a queue pair of the given type and times `put`s until their send
+//! completion. Benchmarks are skipped when no RDMA device is present.
+
+use std::time::Duration;
+use std::time::Instant;
+
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use criterion::Throughput;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use hyperactor::ActorRef;
+use hyperactor::Instance;
+use hyperactor::Proc;
+use hyperactor::channel::ChannelTransport;
+use hyperactor_mesh::Mesh;
+use hyperactor_mesh::ProcMesh;
+use hyperactor_mesh::RootActorMesh;
+use hyperactor_mesh::alloc::AllocSpec;
+use hyperactor_mesh::alloc::Allocator;
+use hyperactor_mesh::alloc::LocalAllocator;
+use monarch_rdma::IbverbsConfig;
+use monarch_rdma::PollTarget;
+use monarch_rdma::RdmaBuffer;
+use monarch_rdma::RdmaManagerActor;
+use monarch_rdma::RdmaManagerMessageClient;
+use monarch_rdma::RdmaQpType;
+use monarch_rdma::RdmaQueuePair;
+use monarch_rdma::get_all_devices;
+use ndslice::extent;
+use tokio::runtime::Runtime;
+
+const LATENCY_SIZE: usize = 4 * 1024;
+const BANDWIDTH_SIZES: [usize; 4] = [4 * 1024, 1024 * 1024, 16 * 1024 * 1024, 256 * 1024 * 1024];
+const QP_TYPES: [(&str, RdmaQpType); 2] = [
+    ("standard", RdmaQpType::Standard),
+    ("mlx5dv", RdmaQpType::Mlx5dv),
+];
+const COMPLETION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Two RDMA managers with a registered host buffer each and a queue pair from
+/// the first to the second.
+struct BenchEnv {
+    client: &'static Instance<()>,
+    actor_1: ActorRef<RdmaManagerActor>,
+    actor_2: ActorRef<RdmaManagerActor>,
+    handle_1: RdmaBuffer,
+    handle_2: RdmaBuffer,
+    qp: RdmaQueuePair,
+    _buffers: [Box<[u8]>; 2],
+}
+
+impl BenchEnv {
+    async fn setup(size: usize, qp_type: RdmaQpType) -> Result<Self, anyhow::Error> {
+        let (instance, _) = Proc::local().instance("bench")?;
+        let mut actors = Vec::new();
+        let mut clients = Vec::new();
+        for _ in 0..2 {
+            let alloc = LocalAllocator
+                .allocate(AllocSpec {
+                    extent: extent! { proc = 1 },
+                    constraints: Default::default(),
+                    proc_name: None,
+                    transport: ChannelTransport::Local,
+                    proc_allocation_mode: Default::default(),
+                })
+                .await?;
+            let proc_mesh: &'static ProcMesh =
+                Box::leak(Box::new(ProcMesh::allocate(alloc).await?));
+            let config = IbverbsConfig {
+                use_gpu_direct: false,
+                qp_type,
+                ..IbverbsConfig::targeting("cpu:0")
+            };
+            let actor_mesh: RootActorMesh<'_, RdmaManagerActor> = proc_mesh
+                .spawn(&instance, "rdma_manager", &Some(config))
+                .await?;
+            actors.push(actor_mesh.get(0).unwrap());
+            clients.push(proc_mesh.client());
+        }
+
+        let mut buffers = [
+            vec![1u8; size].into_boxed_slice(),
+            vec![0u8; size].into_boxed_slice(),
+        ];
+        let handle_1 = actors[0]
+            .request_buffer(clients[0], buffers[0].as_mut_ptr() as usize, size)
+            .await?;
+        let handle_2 = actors[1]
+            .request_buffer(clients[1], buffers[1].as_mut_ptr() as usize, size)
+            .await?;
+        let qp = actors[0]
+            .request_queue_pair(
+                clients[0],
+                actors[1].clone(),
+                handle_1.device_name.clone(),
+                handle_2.device_name.clone(),
+            )
+            .await?;
+
+        Ok(Self {
+            client: clients[0],
+            actor_1: actors[0].clone(),
+            actor_2: actors[1].clone(),
+            handle_1,
+            handle_2,
+            qp,
+            _buffers: buffers,
+        })
+    }
+
+    /// Writes the first buffer into the second and busy-polls until the write completes.
+    fn write(&mut self) -> Result<(), anyhow::Error> {
+        self.qp.put(self.handle_1.clone(), self.handle_2.clone())?;
+        let start = Instant::now();
+        while self.qp.poll_completion_target(PollTarget::Send)?.is_none() {
+            if start.elapsed() > COMPLETION_TIMEOUT {
+                anyhow::bail!("RDMA write timed out after {:?}", COMPLETION_TIMEOUT);
+            }
+        }
+        Ok(())
+    }
+
+    /// Times `iters` writes.
+    fn time_writes(&mut self, iters: u64) -> Duration {
+        let start = Instant::now();
+        for _ in 0..iters {
+            self.write().unwrap();
+        }
+        start.elapsed()
+    }
+
+    async fn teardown(self) -> Result<(), anyhow::Error> {
+        self.actor_1
+            .release_queue_pair(
+                self.client,
+                self.actor_2.clone(),
+                self.handle_1.device_name.clone(),
+                self.handle_2.device_name.clone(),
+                self.qp,
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+fn rdma_available() -> bool {
+    if get_all_devices().is_empty() {
+        println!("Skipping RDMA benchmarks: RDMA devices not available");
+        return false;
+    }
+    true
+}
+
+fn format_size(size: usize) -> String {
+    if size >= 1024 * 1024 {
+        format!("{}MB", size / (1024 * 1024))
+    } else {
+        format!("{}KB", size / 1024)
+    }
+}
+
+// Time for a 4KB write to complete, in µs.
+fn bench_write_latency(c: &mut Criterion) {
+    if !rdma_available() {
+        return;
+    }
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("rdma_write_latency");
+    for (name, qp_type) in QP_TYPES {
+        let mut env = runtime
+            .block_on(BenchEnv::setup(LATENCY_SIZE, qp_type))
+            .unwrap();
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter_custom(|iters| env.time_writes(iters))
+        });
+        runtime.block_on(env.teardown()).unwrap();
+    }
+    group.finish();
+}
+
+// Write bandwidth across buffer sizes, reported in GB/s.
+fn bench_write_bandwidth(c: &mut Criterion) {
+    if !rdma_available() {
+        return;
+    }
+    let runtime = Runtime::new().unwrap();
+    for (name, qp_type) in QP_TYPES {
+        let mut group = c.benchmark_group(format!("rdma_write_bandwidth/{}", name));
+        group.sample_size(10);
+        for size in BANDWIDTH_SIZES {
+            let mut env = runtime.block_on(BenchEnv::setup(size, qp_type)).unwrap();
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_function(BenchmarkId::from_parameter(format_size(size)), |b| {
+                b.iter_custom(|iters| env.time_writes(iters))
+            });
+            runtime.block_on(env.teardown()).unwrap();
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_write_latency, bench_write_bandwidth);
+criterion_main!(benches);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_harness_runs_one_iteration() {
+        if !rdma_available() {
+            return;
+        }
+        let runtime = Runtime::new().unwrap();
+        let mut env = runtime
+            .block_on(BenchEnv::setup(LATENCY_SIZE, RdmaQpType::Auto))
+            .unwrap();
+        assert!(env.time_writes(1) > Duration::ZERO);
+        runtime.block_on(env.teardown()).unwrap();
+    }
+}
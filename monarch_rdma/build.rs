@@ -11,17 +11,24 @@ fn main() {}
 
 #[cfg(not(target_os = "macos"))]
 fn main() {
-    // Validate CUDA installation and get CUDA home path
-    let _cuda_home = match build_utils::validate_cuda_installation() {
-        Ok(home) => home,
-        Err(_) => {
-            build_utils::print_cuda_error_help();
+    // Use the CUDA toolkit rdmaxcel-sys was built against, so both crates agree.
+    // Probe for one only if rdmaxcel-sys didn't report it (e.g. bindings-only builds).
+    let backend = build_utils::BackendMetadata::from_dep_env("rdmaxcel_sys");
+    if let Some(backend) = &backend {
+        if backend.backend != "cuda" {
+            eprintln!(
+                "Error: rdmaxcel-sys was built for unsupported backend {}",
+                backend.backend
+            );
             std::process::exit(1);
         }
-    };
+    } else if build_utils::validate_cuda_installation().is_err() {
+        build_utils::print_cuda_error_help();
+        std::process::exit(1);
+    }
 
     // Include headers and libs from the active environment.
-    let python_config = match build_utils::python_env_dirs_with_interpreter("python3") {
+    let python_config = match build_utils::python_env_dirs() {
         Ok(config) => config,
         Err(_) => {
             eprintln!("Warning: Failed to get Python environment directories");
@@ -40,7 +47,10 @@ fn main() {
     }
 
     // Get CUDA library directory and emit link directives
-    let cuda_lib_dir = match build_utils::get_cuda_lib_dir() {
+    let cuda_lib_dir = match backend
+        .and_then(|backend| backend.cuda_lib_dir)
+        .map_or_else(build_utils::get_cuda_lib_dir, Ok)
+    {
         Ok(dir) => dir,
         Err(_) => {
             build_utils::print_cuda_lib_error_help();
@@ -49,40 +59,53 @@ fn main() {
     };
     println!("cargo:rustc-link-search=native={}", cuda_lib_dir);
     println!("cargo:rustc-link-lib=cuda");
-    println!("cargo:rustc-link-lib=cudart");
+    if let Err(err) = build_utils::link_cudart(&cuda_lib_dir) {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
 
     // Link against the ibverbs and mlx5 libraries (used by rdmaxcel-sys)
     println!("cargo:rustc-link-lib=ibverbs");
     println!("cargo:rustc-link-lib=mlx5");
 
     // Link PyTorch libraries needed for C10 symbols used by rdmaxcel-sys
-    let use_pytorch_apis = build_utils::get_env_var_with_rerun("TORCH_SYS_USE_PYTORCH_APIS")
-        .unwrap_or_else(|_| "1".to_owned());
-    if use_pytorch_apis == "1" {
+    if build_utils::use_pytorch_apis() {
         // Get PyTorch library directory using build_utils
-        let python_interpreter = std::path::PathBuf::from("python");
-        if let Ok(output) = std::process::Command::new(&python_interpreter)
-            .arg("-c")
-            .arg(build_utils::PYTHON_PRINT_PYTORCH_DETAILS)
-            .output()
-        {
-            if output.status.success() {
-                for line in String::from_utf8_lossy(&output.stdout).lines() {
-                    if let Some(path) = line.strip_prefix("LIBTORCH_LIB: ") {
-                        // Add library search path
-                        println!("cargo:rustc-link-search=native={}", path);
-                        // Set rpath so runtime linker can find the libraries
-                        println!("cargo::rustc-link-arg=-Wl,-rpath,{}", path);
-                    }
-                }
+        let mut torch_lib_dirs = Vec::new();
+        if let Ok(mut details) = build_utils::run_python_kv(
+            build_utils::find_python_interpreter(),
+            build_utils::PYTHON_PRINT_PYTORCH_DETAILS,
+        ) {
+            for path in details.remove("LIBTORCH_LIB").unwrap_or_default() {
+                // Add library search path
+                println!("cargo:rustc-link-search=native={}", path);
+                // Set rpath so runtime linker can find the libraries
+                println!("cargo::rustc-link-arg=-Wl,-rpath,{}", path);
+                torch_lib_dirs.push(std::path::PathBuf::from(path));
             }
         }
 
         // Link core PyTorch libraries needed for C10 symbols
         println!("cargo:rustc-link-lib=torch_cpu");
         println!("cargo:rustc-link-lib=torch");
-        println!("cargo:rustc-link-lib=c10");
-        println!("cargo:rustc-link-lib=c10_cuda");
+        // Only link the c10 libraries this PyTorch build actually ships; if the
+        // lib dir is unknown, fall back to linking them all.
+        let c10_libs = ["c10", "c10_cuda"];
+        let present = if torch_lib_dirs.is_empty() {
+            c10_libs.to_vec()
+        } else {
+            build_utils::find_libs(&torch_lib_dirs, &c10_libs)
+        };
+        for lib in c10_libs {
+            if present.contains(&lib) {
+                println!("cargo:rustc-link-lib={}", lib);
+            } else {
+                println!(
+                    "cargo::warning=lib{} not found in {:?}; not linking it",
+                    lib, torch_lib_dirs
+                );
+            }
+        }
     } else {
         // Fallback to torch-sys links metadata if available
         if let Ok(torch_lib_path) = std::env::var("DEP_TORCH_LIB_PATH") {
@@ -100,7 +123,11 @@ fn main() {
 
     // Link the static libraries from rdmaxcel-sys
     // Try the Cargo dependency mechanism first, then fall back to fixed paths
-    if let Ok(rdmaxcel_out_dir) = std::env::var("DEP_RDMAXCEL_SYS_OUT_DIR") {
+    if let Ok(system_lib_dir) = std::env::var("DEP_RDMAXCEL_SYS_SYSTEM_LIB_DIR") {
+        // rdmaxcel-sys linked a prebuilt rdmaxcel (RDMAXCEL_SYS_USE_SYSTEM_LIB) itself;
+        // only make sure a shared one is found at runtime.
+        println!("cargo::rustc-link-arg=-Wl,-rpath,{}", system_lib_dir);
+    } else if let Ok(rdmaxcel_out_dir) = std::env::var("DEP_RDMAXCEL_SYS_OUT_DIR") {
         println!("cargo:rustc-link-search=native={}", rdmaxcel_out_dir);
         println!("cargo:rustc-link-lib=static=rdmaxcel");
         println!("cargo:rustc-link-lib=static=rdmaxcel_cpp");
@@ -110,6 +137,9 @@ fn main() {
 
         // Use relative paths to the known locations
         let cuda_build_dir = "../rdmaxcel-sys/target/cuda_build";
+        if build_utils::find_library(&[cuda_build_dir.into()], "rdmaxcel_cuda").is_none() {
+            eprintln!("Warning: librdmaxcel_cuda not found in {}", cuda_build_dir);
+        }
         println!("cargo:rustc-link-search=native={}", cuda_build_dir);
         println!("cargo:rustc-link-lib=static=rdmaxcel_cuda");
 
@@ -130,13 +160,18 @@ fn main() {
             rdmaxcel_dirs
                 .sort_by_key(|entry| entry.metadata().ok().and_then(|m| m.modified().ok()));
 
-            if let Some(most_recent) = rdmaxcel_dirs.last() {
-                let out_dir = most_recent.path().join("out");
-                if out_dir.exists() {
-                    println!("cargo:rustc-link-search=native={}", out_dir.display());
-                    println!("cargo:rustc-link-lib=static=rdmaxcel");
-                    println!("cargo:rustc-link-lib=static=rdmaxcel_cpp");
-                }
+            // Use the most recent build that actually produced the libraries
+            let out_dir = rdmaxcel_dirs
+                .iter()
+                .rev()
+                .map(|entry| entry.path().join("out"))
+                .find(|out_dir| {
+                    build_utils::find_library(&[out_dir.clone()], "rdmaxcel").is_some()
+                });
+            if let Some(out_dir) = out_dir {
+                println!("cargo:rustc-link-search=native={}", out_dir.display());
+                println!("cargo:rustc-link-lib=static=rdmaxcel");
+                println!("cargo:rustc-link-lib=static=rdmaxcel_cpp");
             } else {
                 eprintln!("Warning: No rdmaxcel-sys build directories found");
             }
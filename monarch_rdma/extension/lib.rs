@@ -7,31 +7,95 @@
  */
 
 #![allow(unsafe_op_in_unsafe_fn)]
+use std::future::Future;
+use std::time::Duration;
+
 use hyperactor::ActorId;
 use hyperactor::ActorRef;
 use hyperactor::Named;
 use hyperactor::ProcId;
+use hyperactor::clock::Clock;
+use hyperactor::clock::RealClock;
 use hyperactor_mesh::RootActorMesh;
 use hyperactor_mesh::shared_cell::SharedCell;
 use monarch_hyperactor::context::PyInstance;
 use monarch_hyperactor::instance_dispatch;
 use monarch_hyperactor::proc_mesh::PyProcMesh;
 use monarch_hyperactor::pytokio::PyPythonTask;
+use monarch_hyperactor::runtime::get_tokio_runtime;
 use monarch_hyperactor::runtime::signal_safe_block_on;
 use monarch_hyperactor::v1::proc_mesh::PyProcMesh as PyProcMeshV1;
 use monarch_rdma::RdmaBuffer;
+use monarch_rdma::RdmaError;
 use monarch_rdma::RdmaManagerActor;
 use monarch_rdma::RdmaManagerMessageClient;
+use monarch_rdma::rdma_device_info as query_rdma_device_info;
 use monarch_rdma::rdma_supported;
 use pyo3::IntoPyObjectExt;
 use pyo3::exceptions::PyException;
 use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
 use pyo3::types::PyTuple;
 use pyo3::types::PyType;
 use serde::Deserialize;
 use serde::Serialize;
 
+/// Python exception types for [`RdmaError`]s, so callers can `except` specific
+/// failures. All of them derive from `RdmaError`.
+mod exceptions {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyException;
+
+    create_exception!(monarch._rust_bindings.rdma, RdmaError, PyException);
+    create_exception!(monarch._rust_bindings.rdma, RdmaTimeoutError, RdmaError);
+    create_exception!(monarch._rust_bindings.rdma, RdmaCompletionError, RdmaError);
+    create_exception!(monarch._rust_bindings.rdma, RdmaDeviceError, RdmaError);
+    create_exception!(
+        monarch._rust_bindings.rdma,
+        RdmaRegistrationError,
+        RdmaError
+    );
+    create_exception!(monarch._rust_bindings.rdma, RdmaOverflowError, RdmaError);
+}
+
+/// Converts `err` into the matching Python exception, prefixing its message
+/// with `context`.
+fn rdma_error_to_py(context: &str, err: RdmaError) -> PyErr {
+    let message = format!("{}: {}", context, err);
+    match err {
+        RdmaError::Timeout(_) => exceptions::RdmaTimeoutError::new_err(message),
+        RdmaError::CompletionStatus { .. } => exceptions::RdmaCompletionError::new_err(message),
+        RdmaError::Device(_) | RdmaError::StateTransition { .. } => {
+            exceptions::RdmaDeviceError::new_err(message)
+        }
+        RdmaError::Registration(_) => exceptions::RdmaRegistrationError::new_err(message),
+        RdmaError::Overflow { .. } => exceptions::RdmaOverflowError::new_err(message),
+        RdmaError::SizeMismatch { .. } | RdmaError::InvalidConfig(_) | RdmaError::Other(_) => {
+            exceptions::RdmaError::new_err(message)
+        }
+    }
+}
+
+/// Runs `transfer` in the background and waits at most `timeout` seconds for
+/// it, queue pair setup included. After a timeout the transfer keeps running,
+/// so its queue pairs are still released to the RdmaManagerActor.
+async fn transfer_with_timeout<F>(context: &str, timeout: u64, transfer: F) -> PyResult<()>
+where
+    F: Future<Output = Result<bool, RdmaError>> + Send + 'static,
+{
+    let timeout = Duration::from_secs(timeout);
+    let handle = get_tokio_runtime().spawn(transfer);
+    match RealClock.timeout(timeout, handle).await {
+        Ok(Ok(result)) => result.map(|_| ()).map_err(|e| rdma_error_to_py(context, e)),
+        Ok(Err(join_err)) => Err(PyException::new_err(format!(
+            "{}: transfer task failed: {}",
+            context, join_err
+        ))),
+        Err(_) => Err(rdma_error_to_py(context, RdmaError::Timeout(timeout))),
+    }
+}
+
 fn setup_rdma_context(
     rdma_buffer: &PyRdmaBuffer,
     local_proc_id: String,
@@ -51,12 +115,13 @@ struct PyRdmaBuffer {
     owner_ref: ActorRef<RdmaManagerActor>,
 }
 
-async fn create_rdma_buffer(
+/// Registers `size` bytes at `addr` with the RdmaManagerActor on `proc_id`.
+async fn request_rdma_buffer(
     addr: usize,
     size: usize,
     proc_id: ProcId,
     client: PyInstance,
-) -> PyResult<PyRdmaBuffer> {
+) -> PyResult<(RdmaBuffer, ActorRef<RdmaManagerActor>)> {
     // Get the owning RdmaManagerActor's ActorRef
     // TODO: find some better way to look this up, or else formally define "service names"
     let owner_id = ActorId(proc_id, "rdma_manager".to_string(), 0);
@@ -68,6 +133,16 @@ async fn create_rdma_buffer(
             .request_buffer_deprecated(&cx_instance, addr, size)
             .await?
     });
+    Ok((buffer, owner_ref))
+}
+
+async fn create_rdma_buffer(
+    addr: usize,
+    size: usize,
+    proc_id: ProcId,
+    client: PyInstance,
+) -> PyResult<PyRdmaBuffer> {
+    let (buffer, owner_ref) = request_rdma_buffer(addr, size, proc_id, client).await?;
     Ok(PyRdmaBuffer { buffer, owner_ref })
 }
 
@@ -159,9 +234,7 @@ impl PyRdmaBuffer {
                 local_buffer
                     .write_from(cx_instance, buffer, timeout)
                     .await
-                    .map_err(|e| {
-                        PyException::new_err(format!("failed to read into buffer: {}", e))
-                    })?
+                    .map_err(|e| rdma_error_to_py("failed to read into buffer", e))?
             });
             instance_dispatch!(client, |cx_instance| {
                 local_owner_ref
@@ -205,9 +278,7 @@ impl PyRdmaBuffer {
                 local_buffer
                     .read_into(cx_instance, buffer, timeout)
                     .await
-                    .map_err(|e| {
-                        PyException::new_err(format!("failed to write from buffer: {}", e))
-                    })?
+                    .map_err(|e| rdma_error_to_py("failed to write from buffer", e))?
             });
             instance_dispatch!(client, |cx_instance| {
                 local_owner_ref
@@ -254,7 +325,7 @@ impl PyRdmaBuffer {
                 buffer
                     .drop_buffer(cx_instance)
                     .await
-                    .map_err(|e| PyException::new_err(format!("Failed to drop buffer: {}", e)))?
+                    .map_err(|e| rdma_error_to_py("Failed to drop buffer", e))?
             });
             Ok(())
         })
@@ -265,6 +336,135 @@ impl PyRdmaBuffer {
     }
 }
 
+/// A registered memory region that can be transferred to or from another handle
+/// over RDMA.
+///
+/// Unlike `_RdmaBuffer`, both ends of a transfer are handles: each side registers
+/// its memory once (e.g. a GPU tensor) and exchanges handles, which are picklable,
+/// with its peers.
+#[pyclass(name = "_RdmaHandle", module = "monarch._rust_bindings.rdma")]
+#[derive(Clone, Serialize, Deserialize, Named)]
+struct PyRdmaHandle {
+    buffer: RdmaBuffer,
+}
+
+#[pymethods]
+impl PyRdmaHandle {
+    /// Registers `size` bytes at `addr` with the RdmaManagerActor on `proc_id`.
+    #[classmethod]
+    fn create_rdma_handle_nonblocking(
+        _cls: &Bound<'_, PyType>,
+        addr: usize,
+        size: usize,
+        proc_id: String,
+        client: PyInstance,
+    ) -> PyResult<PyPythonTask> {
+        if !rdma_supported() {
+            return Err(PyException::new_err("RDMA is not supported on this system"));
+        }
+        let proc_id: ProcId = proc_id
+            .parse()
+            .map_err(|e| PyValueError::new_err(format!("invalid proc id: {}", e)))?;
+        PyPythonTask::new(async move {
+            let (buffer, _) = request_rdma_buffer(addr, size, proc_id, client).await?;
+            Ok(PyRdmaHandle { buffer })
+        })
+    }
+
+    /// Copies the contents of this handle into `peer`.
+    ///
+    /// # Arguments
+    /// * `peer` - The handle to write into; must be at least as large as this one
+    /// * `client` - The actor issuing the transfer
+    /// * `timeout` - Maximum time in seconds to wait for the transfer, including queue pair setup
+    fn read_into(
+        &self,
+        peer: &PyRdmaHandle,
+        client: PyInstance,
+        timeout: u64,
+    ) -> PyResult<PyPythonTask> {
+        let buffer = self.buffer.clone();
+        let peer = peer.buffer.clone();
+        PyPythonTask::new(async move {
+            transfer_with_timeout("RDMA read failed", timeout, async move {
+                instance_dispatch!(client, |cx_instance| {
+                    buffer.read_into(cx_instance, peer, timeout).await
+                })
+            })
+            .await
+        })
+    }
+
+    /// Copies the contents of `peer` into this handle.
+    ///
+    /// # Arguments
+    /// * `peer` - The handle to read from; must be at least as large as this one
+    /// * `client` - The actor issuing the transfer
+    /// * `timeout` - Maximum time in seconds to wait for the transfer, including queue pair setup
+    fn write_from(
+        &self,
+        peer: &PyRdmaHandle,
+        client: PyInstance,
+        timeout: u64,
+    ) -> PyResult<PyPythonTask> {
+        let buffer = self.buffer.clone();
+        let peer = peer.buffer.clone();
+        PyPythonTask::new(async move {
+            transfer_with_timeout("RDMA write failed", timeout, async move {
+                instance_dispatch!(client, |cx_instance| {
+                    buffer.write_from(cx_instance, peer, timeout).await
+                })
+            })
+            .await
+        })
+    }
+
+    /// Deregisters the memory region with its owning RdmaManagerActor.
+    fn drop(&self, client: PyInstance) -> PyResult<PyPythonTask> {
+        let buffer = self.buffer.clone();
+        PyPythonTask::new(async move {
+            instance_dispatch!(client, |cx_instance| {
+                buffer
+                    .drop_buffer(cx_instance)
+                    .await
+                    .map_err(|e| rdma_error_to_py("Failed to drop handle", e))?
+            });
+            Ok(())
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.buffer.size
+    }
+
+    fn owner_actor_id(&self) -> String {
+        self.buffer.owner.actor_id().to_string()
+    }
+
+    #[pyo3(name = "__repr__")]
+    fn repr(&self) -> String {
+        format!("<RdmaHandle'{:?}'>", self.buffer)
+    }
+
+    fn __reduce__(&self) -> PyResult<(PyObject, PyObject)> {
+        Python::with_gil(|py| {
+            let ctor = py.get_type::<PyRdmaHandle>().into_py_any(py)?;
+            let json = serde_json::to_string(self).map_err(|e| {
+                PyErr::new::<PyValueError, _>(format!("Serialization failed: {}", e))
+            })?;
+
+            let args = PyTuple::new(py, [json])?.into_py_any(py)?;
+            Ok((ctor, args))
+        })
+    }
+
+    #[new]
+    fn new_from_json(json: &str) -> PyResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| PyErr::new::<PyValueError, _>(format!("Deserialization failed: {}", e)))
+    }
+}
+
 #[pyclass(name = "_RdmaManager", module = "monarch._rust_bindings.rdma")]
 pub struct PyRdmaManager {
     #[allow(dead_code)] // field never read
@@ -337,8 +537,49 @@ impl PyRdmaManager {
     }
 }
 
+/// Returns the name, port state, link layer and active MTU of the RDMA device
+/// used by default on this host, as a dict. Useful to debug slow or failing
+/// transfers from Python.
+#[pyfunction]
+fn rdma_device_info(py: Python<'_>) -> PyResult<Bound<'_, PyDict>> {
+    let info = query_rdma_device_info().map_err(|err| PyException::new_err(err.to_string()))?;
+    let dict = PyDict::new(py);
+    dict.set_item("name", info.name)?;
+    dict.set_item("port_num", info.port_num)?;
+    dict.set_item("port_state", info.port_state)?;
+    dict.set_item("link_layer", info.link_layer)?;
+    dict.set_item("active_mtu", info.active_mtu)?;
+    Ok(dict)
+}
+
 pub fn register_python_bindings(module: &Bound<'_, PyModule>) -> PyResult<()> {
     module.add_class::<PyRdmaBuffer>()?;
+    module.add_class::<PyRdmaHandle>()?;
     module.add_class::<PyRdmaManager>()?;
+    let py = module.py();
+    module.add("RdmaError", py.get_type::<exceptions::RdmaError>())?;
+    module.add(
+        "RdmaTimeoutError",
+        py.get_type::<exceptions::RdmaTimeoutError>(),
+    )?;
+    module.add(
+        "RdmaCompletionError",
+        py.get_type::<exceptions::RdmaCompletionError>(),
+    )?;
+    module.add(
+        "RdmaDeviceError",
+        py.get_type::<exceptions::RdmaDeviceError>(),
+    )?;
+    module.add(
+        "RdmaRegistrationError",
+        py.get_type::<exceptions::RdmaRegistrationError>(),
+    )?;
+    module.add(
+        "RdmaOverflowError",
+        py.get_type::<exceptions::RdmaOverflowError>(),
+    )?;
+    let f = wrap_pyfunction!(rdma_device_info, module)?;
+    f.setattr("__module__", "monarch._rust_bindings.rdma")?;
+    module.add_function(f)?;
     Ok(())
 }
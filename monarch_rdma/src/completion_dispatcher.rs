@@ -0,0 +1,690 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ * All rights reserved.
+ *
+ * This source code is licensed under the BSD-style license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! # Completion Dispatcher
+//!
+//! A single background task that drains completion queues and routes each work
+//! completion to the future waiting on it, keyed by `(cq, wr_id)`.
+//!
+//! Without the dispatcher, every in-flight transfer spins on its own CQ. With it,
+//! waiters register interest via [`CompletionDispatcher::wait_for`] and the
+//! dispatcher polls each CQ that has outstanding waiters once per iteration,
+//! draining up to the batch size its waiters registered with completions per
+//! `ibv_poll_cq` call.
+//!
+//! Futures that can't hold a oneshot waiter across polls, such as
+//! [`crate::RdmaTransfer`], register a task waker instead with
+//! [`CompletionDispatcher::poll_completion`]; the dispatcher holds their
+//! completion and wakes the task to claim it.
+//!
+//! The polling task runs on the runtime that called
+//! [`CompletionDispatcher::start`] for as long as a [`DispatcherHandle`] is held
+//! and that runtime is up. Without a live task, callers poll their CQs themselves.
+//!
+//! Only CQs with registered waiters are polled, so a CQ is never touched after
+//! its owner stops waiting on it. Completions that arrive before their waiter
+//! registers are held until claimed; since queue pairs complete in order, a
+//! claimed completion also discards any held completions with a smaller `wr_id`
+//! on the same CQ.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::Weak;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
+
+use hyperactor::Named;
+use hyperactor::clock::Clock;
+use hyperactor::clock::RealClock;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+use crate::ibverbs_primitives::IbvWc;
+use crate::rdma_error::RdmaError;
+
+/// Default maximum number of completions drained from a CQ in a single poll.
+pub const POLL_BATCH_SIZE: usize = 32;
+
+/// Maximum number of unclaimed completions held per CQ before the oldest are dropped.
+const MAX_UNCLAIMED_PER_CQ: usize = 1024;
+
+/// How long the dispatcher sleeps after a poll that drained nothing.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+type CompletionResult = Result<IbvWc, RdmaError>;
+
+/// Counters describing the dispatcher's polling activity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Named, Serialize, Deserialize)]
+pub struct DispatcherStats {
+    /// Number of dispatcher iterations that polled at least one CQ.
+    pub polls: u64,
+    /// Total number of completions drained across all polls.
+    pub completions: u64,
+    /// Number of completions drained by the most recent poll.
+    pub last_drained: usize,
+    /// Largest number of completions drained by a single poll.
+    pub max_drained: usize,
+}
+
+#[derive(Debug, Default)]
+struct DispatcherState {
+    waiters: HashMap<(usize, u64), oneshot::Sender<CompletionResult>>,
+    // Tasks to wake once the completion for their key is held in `unclaimed`.
+    wakers: HashMap<(usize, u64), Waker>,
+    // Completions drained before their waiter registered, per CQ, ordered by wr_id.
+    unclaimed: HashMap<usize, BTreeMap<u64, CompletionResult>>,
+    // Batch size of each CQ with waiters, as given by its most recent registration.
+    batch_sizes: HashMap<usize, usize>,
+    stats: DispatcherStats,
+}
+
+/// Polls completion queues on behalf of all waiters in the process.
+#[derive(Debug, Default)]
+pub struct CompletionDispatcher {
+    state: Mutex<DispatcherState>,
+    // Id of the live polling task, 0 if there is none.
+    task_id: AtomicU64,
+    // The task handed out by `start`, kept alive by its `DispatcherHandle`s.
+    task: Mutex<Weak<PollTask>>,
+}
+
+static DISPATCHER: LazyLock<Arc<CompletionDispatcher>> =
+    LazyLock::new(|| Arc::new(CompletionDispatcher::default()));
+
+static NEXT_TASK_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Returns the process-wide completion dispatcher.
+///
+/// Each `RdmaManagerActor` holds a [`DispatcherHandle`], so the dispatcher polls
+/// while a manager is alive in the process.
+pub fn completion_dispatcher() -> Arc<CompletionDispatcher> {
+    DISPATCHER.clone()
+}
+
+/// Keeps the dispatcher's polling task running. The task is aborted once every
+/// handle returned by [`CompletionDispatcher::start`] for it has been dropped.
+#[derive(Debug, Clone)]
+pub struct DispatcherHandle {
+    _task: Arc<PollTask>,
+}
+
+#[derive(Debug)]
+struct PollTask {
+    abort: tokio::task::AbortHandle,
+}
+
+impl Drop for PollTask {
+    fn drop(&mut self) {
+        self.abort.abort();
+    }
+}
+
+/// Owned by the polling task's future, so it is dropped whenever the task ends:
+/// when it exits, is aborted, or its runtime shuts down.
+struct TaskGuard {
+    dispatcher: Weak<CompletionDispatcher>,
+    id: u64,
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        if let Some(dispatcher) = self.dispatcher.upgrade() {
+            dispatcher.stopped(self.id);
+        }
+    }
+}
+
+impl CompletionDispatcher {
+    /// Returns a handle to the polling task, spawning the task on the current
+    /// runtime if none is running.
+    ///
+    /// Must be called from within a tokio runtime. The task stops once all of its
+    /// handles are dropped or its runtime shuts down, whichever comes first; a later
+    /// call spawns a new one.
+    pub fn start(self: &Arc<Self>) -> DispatcherHandle {
+        let mut task = self.task.lock().unwrap();
+        if let Some(live) = task.upgrade() {
+            if self.is_running() {
+                return DispatcherHandle { _task: live };
+            }
+        }
+        let id = NEXT_TASK_ID.fetch_add(1, Ordering::Relaxed);
+        self.task_id.store(id, Ordering::SeqCst);
+        let guard = TaskGuard {
+            dispatcher: Arc::downgrade(self),
+            id,
+        };
+        let dispatcher = Arc::downgrade(self);
+        let join_handle = tokio::spawn(async move {
+            let _guard = guard;
+            Self::run(dispatcher).await
+        });
+        let live = Arc::new(PollTask {
+            abort: join_handle.abort_handle(),
+        });
+        *task = Arc::downgrade(&live);
+        tracing::debug!("completion dispatcher started");
+        DispatcherHandle { _task: live }
+    }
+
+    /// Returns whether a polling task is live.
+    pub fn is_running(&self) -> bool {
+        self.task_id.load(Ordering::SeqCst) != 0
+    }
+
+    /// Called when the polling task `id` ends. Registered tasks are woken so they
+    /// poll their CQs themselves, and waiters fail instead of waiting out their
+    /// timeouts.
+    fn stopped(&self, id: u64) {
+        if self
+            .task_id
+            .compare_exchange(id, 0, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.waiters.clear();
+        for (_, waker) in state.wakers.drain() {
+            waker.wake();
+        }
+        tracing::debug!("completion dispatcher stopped");
+    }
+
+    /// Returns a snapshot of the polling counters.
+    pub fn stats(&self) -> DispatcherStats {
+        self.state.lock().unwrap().stats
+    }
+
+    /// Waits until the work request `wr_id` posted to `cq` completes.
+    ///
+    /// # Arguments
+    ///
+    /// * `cq` - The completion queue (`*mut ibv_cq`) the work request reports to
+    /// * `wr_id` - The work request id to wait for
+    /// * `batch_size` - Most completions to drain from `cq` per poll, usually the owning
+    ///   queue pair's `IbverbsConfig::poll_batch_size`
+    /// * `timeout` - How long to wait before giving up
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(IbvWc)` - The work completion for `wr_id`
+    /// * `Err(RdmaError::CompletionStatus)` - The completion reported an error
+    /// * `Err(RdmaError::Device)` - Polling the CQ failed
+    /// * `Err(RdmaError::Timeout)` - The timeout elapsed
+    pub async fn wait_for(
+        &self,
+        cq: usize,
+        wr_id: u64,
+        batch_size: usize,
+        timeout: Duration,
+    ) -> Result<IbvWc, RdmaError> {
+        let rx = {
+            let mut state = self.state.lock().unwrap();
+            if let Some(result) = Self::claim(&mut state, cq, wr_id) {
+                return result;
+            }
+            let (tx, rx) = oneshot::channel();
+            state.waiters.insert((cq, wr_id), tx);
+            state.batch_sizes.insert(cq, batch_size);
+            rx
+        };
+
+        match RealClock.timeout(timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(anyhow::anyhow!(
+                "completion dispatcher stopped while waiting for wr_id {} on cq 0x{:x}",
+                wr_id,
+                cq
+            )
+            .into()),
+            Err(_) => {
+                self.state.lock().unwrap().waiters.remove(&(cq, wr_id));
+                tracing::debug!("timed out waiting for wr_id {} on cq 0x{:x}", wr_id, cq);
+                Err(RdmaError::Timeout(timeout))
+            }
+        }
+    }
+
+    /// Checks, without blocking, whether the work request `wr_id` posted to `cq`
+    /// has completed.
+    ///
+    /// Polls `cq` once, draining up to `batch_size` completions, if the completion
+    /// isn't already held. If it still hasn't
+    /// completed, `waker` is registered and woken once the dispatcher drains the
+    /// completion. If no polling task is live, `waker` is woken right away so the
+    /// caller polls the CQ again itself.
+    ///
+    /// # Returns
+    ///
+    /// * `Poll::Ready(Ok(IbvWc))` - The work completion for `wr_id`
+    /// * `Poll::Ready(Err(RdmaError::CompletionStatus))` - The completion reported an error
+    /// * `Poll::Ready(Err(RdmaError::Device))` - Polling the CQ failed
+    /// * `Poll::Pending` - `wr_id` hasn't completed yet
+    pub fn poll_completion(
+        &self,
+        cq: usize,
+        wr_id: u64,
+        batch_size: usize,
+        waker: &Waker,
+    ) -> Poll<Result<IbvWc, RdmaError>> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(result) = Self::claim(&mut state, cq, wr_id) {
+            state.wakers.remove(&(cq, wr_id));
+            return Poll::Ready(result);
+        }
+        match poll_cq_batch(cq, batch_size) {
+            Ok(wcs) => {
+                for wc in wcs {
+                    let id = wc.wr_id();
+                    let result = match RdmaError::from_wc(&wc) {
+                        Some(err) => Err(err),
+                        None => Ok(IbvWc::from(wc)),
+                    };
+                    Self::route(&mut state, cq, id, result);
+                }
+            }
+            Err(e) => {
+                state.wakers.remove(&(cq, wr_id));
+                return Poll::Ready(Err(RdmaError::Device(e)));
+            }
+        }
+        if let Some(result) = Self::claim(&mut state, cq, wr_id) {
+            state.wakers.remove(&(cq, wr_id));
+            return Poll::Ready(result);
+        }
+        if self.is_running() {
+            state.wakers.insert((cq, wr_id), waker.clone());
+            state.batch_sizes.insert(cq, batch_size);
+        } else {
+            waker.wake_by_ref();
+        }
+        Poll::Pending
+    }
+
+    /// Forgets the waker registered by [`Self::poll_completion`] for `(cq, wr_id)`.
+    pub fn cancel_wake(&self, cq: usize, wr_id: u64) {
+        self.state.lock().unwrap().wakers.remove(&(cq, wr_id));
+    }
+
+    /// Hands a completion drained outside the dispatcher to the waiter on
+    /// `(cq, wr_id)`, or holds it until one claims it.
+    pub(crate) fn hold(&self, cq: usize, wr_id: u64, result: Result<IbvWc, RdmaError>) {
+        Self::route(&mut self.state.lock().unwrap(), cq, wr_id, result);
+    }
+
+    /// Forgets everything held for `cq`: unclaimed completions, registered wakers and
+    /// waiters, which fail with an error. Used when the queue pair owning `cq` is reset
+    /// and its work request ids start over.
+    pub fn purge_cq(&self, cq: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.unclaimed.remove(&cq);
+        state.batch_sizes.remove(&cq);
+        state.wakers.retain(|&(waker_cq, _), _| waker_cq != cq);
+        let keys: Vec<_> = state
+            .waiters
+            .keys()
+            .filter(|&&(waiter_cq, _)| waiter_cq == cq)
+            .copied()
+            .collect();
+        for key in keys {
+            if let Some(tx) = state.waiters.remove(&key) {
+                let _ = tx.send(Err(anyhow::anyhow!(
+                    "cq 0x{:x} was purged while waiting for wr_id {}",
+                    cq,
+                    key.1
+                )
+                .into()));
+            }
+        }
+    }
+
+    /// Takes the held completion for `(cq, wr_id)`, discarding older ones on `cq`.
+    fn claim(state: &mut DispatcherState, cq: usize, wr_id: u64) -> Option<CompletionResult> {
+        let held = state.unclaimed.get_mut(&cq)?;
+        let result = held.remove(&wr_id)?;
+        held.retain(|&id, _| id > wr_id);
+        if held.is_empty() {
+            state.unclaimed.remove(&cq);
+        }
+        Some(result)
+    }
+
+    async fn run(dispatcher: Weak<Self>) {
+        loop {
+            let drained = match dispatcher.upgrade() {
+                Some(dispatcher) => dispatcher.poll_once(),
+                None => break,
+            };
+            if drained == 0 {
+                RealClock.sleep(IDLE_POLL_INTERVAL).await;
+            } else {
+                tokio::task::yield_now().await;
+            }
+        }
+    }
+
+    /// Drains every CQ that has waiters once and routes the completions.
+    ///
+    /// Returns the number of completions drained.
+    fn poll_once(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let cqs: HashSet<usize> = state
+            .waiters
+            .keys()
+            .chain(state.wakers.keys())
+            .map(|&(cq, _)| cq)
+            .collect();
+        state.batch_sizes.retain(|cq, _| cqs.contains(cq));
+        if cqs.is_empty() {
+            return 0;
+        }
+
+        let mut drained = 0;
+        for cq in cqs {
+            let batch_size = state
+                .batch_sizes
+                .get(&cq)
+                .copied()
+                .unwrap_or(POLL_BATCH_SIZE);
+            match poll_cq_batch(cq, batch_size) {
+                Ok(wcs) => {
+                    drained += wcs.len();
+                    for wc in wcs {
+                        let wr_id = wc.wr_id();
+                        let result = match RdmaError::from_wc(&wc) {
+                            Some(err) => Err(err),
+                            None => Ok(IbvWc::from(wc)),
+                        };
+                        Self::route(&mut state, cq, wr_id, result);
+                    }
+                }
+                Err(e) => {
+                    // Fail every waiter on this CQ rather than spinning on a broken queue.
+                    let keys: Vec<_> = state
+                        .waiters
+                        .keys()
+                        .filter(|&&(waiter_cq, _)| waiter_cq == cq)
+                        .copied()
+                        .collect();
+                    for key in keys {
+                        if let Some(tx) = state.waiters.remove(&key) {
+                            let _ = tx.send(Err(RdmaError::Device(e.clone())));
+                        }
+                    }
+                    // Woken tasks poll the CQ themselves and see the error.
+                    let keys: Vec<_> = state
+                        .wakers
+                        .keys()
+                        .filter(|&&(waker_cq, _)| waker_cq == cq)
+                        .copied()
+                        .collect();
+                    for key in keys {
+                        if let Some(waker) = state.wakers.remove(&key) {
+                            waker.wake();
+                        }
+                    }
+                }
+            }
+        }
+
+        state.stats.polls += 1;
+        state.stats.completions += drained as u64;
+        state.stats.last_drained = drained;
+        state.stats.max_drained = state.stats.max_drained.max(drained);
+        drained
+    }
+
+    fn route(state: &mut DispatcherState, cq: usize, wr_id: u64, result: CompletionResult) {
+        match state.waiters.remove(&(cq, wr_id)) {
+            Some(tx) => {
+                if let Some(held) = state.unclaimed.get_mut(&cq) {
+                    held.retain(|&id, _| id > wr_id);
+                }
+                // The waiter may have timed out concurrently; nothing to do then.
+                let _ = tx.send(result);
+            }
+            None => {
+                let held = state.unclaimed.entry(cq).or_default();
+                held.insert(wr_id, result);
+                while held.len() > MAX_UNCLAIMED_PER_CQ {
+                    held.pop_first();
+                }
+                if let Some(waker) = state.wakers.remove(&(cq, wr_id)) {
+                    waker.wake();
+                }
+            }
+        }
+    }
+}
+
+/// Drains up to `max` completions from `cq` in a single `ibv_poll_cq` call.
+///
+/// On failure, returns a description of the error for every waiter on `cq`.
+pub(crate) fn poll_cq_batch(cq: usize, max: usize) -> Result<Vec<rdmaxcel_sys::ibv_wc>, String> {
+    // SAFETY: Callers keep the queue pair owning `cq` alive for the call. The
+    // dispatcher only polls a CQ while a waiter is registered on it, and the
+    // waiter's owner keeps the queue pair (and its CQ) alive until it returns.
+    unsafe {
+        let cq = cq as *mut rdmaxcel_sys::ibv_cq;
+        let context = (*cq).context;
+        let ops = &mut (*context).ops;
+        let mut wcs =
+            vec![std::mem::MaybeUninit::<rdmaxcel_sys::ibv_wc>::zeroed().assume_init(); max];
+        let ret = ops.poll_cq.as_mut().unwrap()(cq, max as i32, wcs.as_mut_ptr());
+        if ret < 0 {
+            return Err(format!(
+                "Failed to poll CQ: {}",
+                std::io::Error::last_os_error()
+            ));
+        }
+        wcs.truncate(ret as usize);
+        Ok(wcs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use super::*;
+
+    #[timed_test::async_timed_test(timeout_secs = 10)]
+    async fn test_early_completion_is_claimed_and_prunes_older() {
+        let dispatcher = CompletionDispatcher::default();
+        {
+            let mut state = dispatcher.state.lock().unwrap();
+            for wr_id in 0..3 {
+                CompletionDispatcher::route(
+                    &mut state,
+                    0x1000,
+                    wr_id,
+                    Err(anyhow::anyhow!("wr {}", wr_id).into()),
+                );
+            }
+        }
+
+        let result = dispatcher
+            .wait_for(0x1000, 1, POLL_BATCH_SIZE, Duration::from_millis(10))
+            .await;
+        assert_eq!(result.unwrap_err().to_string(), "wr 1");
+
+        let state = dispatcher.state.lock().unwrap();
+        let held: Vec<u64> = state.unclaimed[&0x1000].keys().copied().collect();
+        assert_eq!(held, vec![2]);
+        assert!(state.waiters.is_empty());
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 10)]
+    async fn test_wait_for_times_out_and_deregisters() {
+        let dispatcher = CompletionDispatcher::default();
+        let result = dispatcher
+            .wait_for(0x2000, 7, POLL_BATCH_SIZE, Duration::from_millis(10))
+            .await;
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        assert!(matches!(err, RdmaError::Timeout(_)));
+        assert!(dispatcher.state.lock().unwrap().waiters.is_empty());
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 10)]
+    async fn test_routed_completion_wakes_registered_task() {
+        let dispatcher = CompletionDispatcher::default();
+        let woken = Arc::new(AtomicBool::new(false));
+        struct Flag(Arc<AtomicBool>);
+        impl std::task::Wake for Flag {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+        let waker = Waker::from(Arc::new(Flag(woken.clone())));
+        dispatcher
+            .state
+            .lock()
+            .unwrap()
+            .wakers
+            .insert((0x3000, 4), waker);
+
+        CompletionDispatcher::route(
+            &mut dispatcher.state.lock().unwrap(),
+            0x3000,
+            4,
+            Err(anyhow::anyhow!("wr 4").into()),
+        );
+        assert!(woken.load(Ordering::SeqCst));
+
+        let mut state = dispatcher.state.lock().unwrap();
+        assert!(state.wakers.is_empty());
+        let result = CompletionDispatcher::claim(&mut state, 0x3000, 4).unwrap();
+        assert_eq!(result.unwrap_err().to_string(), "wr 4");
+    }
+
+    #[test]
+    fn test_poll_task_stops_with_its_runtime() {
+        let dispatcher = Arc::new(CompletionDispatcher::default());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let handle = runtime.block_on(async { dispatcher.start() });
+        assert!(dispatcher.is_running());
+
+        let woken = Arc::new(AtomicBool::new(false));
+        struct Flag(Arc<AtomicBool>);
+        impl std::task::Wake for Flag {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+        dispatcher
+            .state
+            .lock()
+            .unwrap()
+            .wakers
+            .insert((0x6000, 1), Waker::from(Arc::new(Flag(woken.clone()))));
+
+        // The handle outlives the runtime, but the task doesn't.
+        drop(runtime);
+        assert!(!dispatcher.is_running());
+        assert!(woken.load(Ordering::SeqCst));
+        drop(handle);
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 10)]
+    async fn test_poll_task_stops_when_handles_drop() {
+        let dispatcher = Arc::new(CompletionDispatcher::default());
+        let first = dispatcher.start();
+        let second = dispatcher.start();
+        drop(first);
+        tokio::task::yield_now().await;
+        assert!(dispatcher.is_running());
+
+        drop(second);
+        while dispatcher.is_running() {
+            tokio::task::yield_now().await;
+        }
+
+        // A later start spawns a new task.
+        let _handle = dispatcher.start();
+        assert!(dispatcher.is_running());
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 10)]
+    async fn test_batch_size_is_kept_per_cq() {
+        let dispatcher = Arc::new(CompletionDispatcher::default());
+        let waits = [(0x7000, 8), (0x8000, 16)].map(|(cq, batch_size)| {
+            let dispatcher = dispatcher.clone();
+            tokio::spawn(async move {
+                dispatcher
+                    .wait_for(cq, 1, batch_size, Duration::from_secs(5))
+                    .await
+            })
+        });
+        while dispatcher.state.lock().unwrap().waiters.len() < 2 {
+            tokio::task::yield_now().await;
+        }
+        {
+            let state = dispatcher.state.lock().unwrap();
+            assert_eq!(state.batch_sizes[&0x7000], 8);
+            assert_eq!(state.batch_sizes[&0x8000], 16);
+        }
+
+        for cq in [0x7000, 0x8000] {
+            dispatcher.hold(cq, 1, Err(anyhow::anyhow!("wr 1").into()));
+        }
+        for wait in waits {
+            assert!(wait.await.unwrap().is_err());
+        }
+        dispatcher.purge_cq(0x7000);
+        assert!(
+            !dispatcher
+                .state
+                .lock()
+                .unwrap()
+                .batch_sizes
+                .contains_key(&0x7000)
+        );
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 10)]
+    async fn test_purge_cq_forgets_held_state() {
+        let dispatcher = CompletionDispatcher::default();
+        {
+            let mut state = dispatcher.state.lock().unwrap();
+            for cq in [0x4000, 0x5000] {
+                CompletionDispatcher::route(&mut state, cq, 1, Err(anyhow::anyhow!("wr 1").into()));
+                state.wakers.insert((cq, 2), Waker::noop().clone());
+            }
+        }
+        let (tx, rx) = oneshot::channel();
+        dispatcher
+            .state
+            .lock()
+            .unwrap()
+            .waiters
+            .insert((0x4000, 3), tx);
+
+        dispatcher.purge_cq(0x4000);
+        assert!(rx.await.unwrap().is_err());
+
+        let state = dispatcher.state.lock().unwrap();
+        assert!(!state.unclaimed.contains_key(&0x4000));
+        assert!(state.unclaimed.contains_key(&0x5000));
+        let wakers: Vec<_> = state.wakers.keys().copied().collect();
+        assert_eq!(wakers, vec![(0x5000, 2)]);
+        assert!(state.waiters.is_empty());
+    }
+}
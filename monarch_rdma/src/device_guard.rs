@@ -0,0 +1,282 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ * All rights reserved.
+ *
+ * This source code is licensed under the BSD-style license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! # Device Guard
+//!
+//! [`DeviceGuard`] binds a CUDA context to the calling thread for a scope and
+//! restores whatever context was current before once it is dropped, so device
+//! memory operations on one GPU can't leak their context into later operations
+//! meant for another.
+//!
+//! [`with_device`] runs a closure with a device's primary context current. The
+//! primary context is the authoritative one: it is what the CUDA runtime's
+//! `cudaSetDevice` binds, so memory allocated under [`with_device`] is visible
+//! to torch and NCCL on the same device. Code in this crate should switch
+//! devices through [`with_device`] rather than calling `cuCtxSetCurrent` or
+//! `cudaSetDevice` directly.
+//!
+//! [`device_synchronize`] and [`device_reset`] are blunter tools for getting a
+//! device back to a known state, e.g. between tests.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+
+use crate::rdma_error::RdmaError;
+
+/// Makes a CUDA context current for the lifetime of the guard.
+///
+/// The previously current context (possibly none) is restored on drop. The guard
+/// is tied to the thread it was created on, so it must not be held across an
+/// `.await`.
+#[derive(Debug)]
+pub struct DeviceGuard {
+    previous: rdmaxcel_sys::CUcontext,
+}
+
+impl DeviceGuard {
+    /// Makes `context` current on this thread, remembering the context it replaces.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DeviceGuard)` - `context` is current until the guard is dropped
+    /// * `Err(RdmaError::Device)` - The current context could not be queried or set
+    pub fn set(context: rdmaxcel_sys::CUcontext) -> Result<Self, RdmaError> {
+        let mut previous: rdmaxcel_sys::CUcontext = std::ptr::null_mut();
+        // SAFETY: `previous` is a valid out-pointer for the duration of the call.
+        let result = unsafe { rdmaxcel_sys::rdmaxcel_cuCtxGetCurrent(&mut previous) };
+        if result != rdmaxcel_sys::CUDA_SUCCESS {
+            return Err(RdmaError::Device(format!(
+                "cuCtxGetCurrent failed: {:?}",
+                result
+            )));
+        }
+        // SAFETY: The caller provides a context created by the CUDA driver.
+        let result = unsafe { rdmaxcel_sys::rdmaxcel_cuCtxSetCurrent(context) };
+        if result != rdmaxcel_sys::CUDA_SUCCESS {
+            return Err(RdmaError::Device(format!(
+                "cuCtxSetCurrent failed: {:?}",
+                result
+            )));
+        }
+        Ok(Self { previous })
+    }
+}
+
+impl Drop for DeviceGuard {
+    fn drop(&mut self) {
+        // SAFETY: `previous` was current on this thread when the guard was created.
+        let result = unsafe { rdmaxcel_sys::rdmaxcel_cuCtxSetCurrent(self.previous) };
+        if result != rdmaxcel_sys::CUDA_SUCCESS {
+            tracing::error!("failed to restore previous CUDA context: {:?}", result);
+        }
+    }
+}
+
+fn check(result: rdmaxcel_sys::CUresult, what: &str) -> Result<(), RdmaError> {
+    if result != rdmaxcel_sys::CUDA_SUCCESS {
+        return Err(RdmaError::Device(format!("{} failed: {:?}", what, result)));
+    }
+    Ok(())
+}
+
+fn get_device(device: i32) -> Result<rdmaxcel_sys::CUdevice, RdmaError> {
+    let mut handle: rdmaxcel_sys::CUdevice = 0;
+    // SAFETY: `handle` is a valid out-pointer for the duration of the call.
+    check(
+        unsafe { rdmaxcel_sys::rdmaxcel_cuDeviceGet(&mut handle, device) },
+        "cuDeviceGet",
+    )?;
+    Ok(handle)
+}
+
+/// Returns `device`'s primary context, retaining it on first use.
+///
+/// Like the CUDA runtime, the retained primary context stays alive for the rest
+/// of the process, so memory allocated in it is never freed behind the caller's
+/// back.
+pub fn primary_context(device: i32) -> Result<rdmaxcel_sys::CUcontext, RdmaError> {
+    static PRIMARY_CONTEXTS: OnceLock<Mutex<HashMap<i32, usize>>> = OnceLock::new();
+    let mut contexts = PRIMARY_CONTEXTS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap();
+    if let Some(&context) = contexts.get(&device) {
+        return Ok(context as rdmaxcel_sys::CUcontext);
+    }
+    let handle = get_device(device)?;
+    let mut primary: rdmaxcel_sys::CUcontext = std::ptr::null_mut();
+    // SAFETY: `primary` is a valid out-pointer and `handle` a valid device.
+    check(
+        unsafe { rdmaxcel_sys::rdmaxcel_cuDevicePrimaryCtxRetain(&mut primary, handle) },
+        "cuDevicePrimaryCtxRetain",
+    )?;
+    contexts.insert(device, primary as usize);
+    Ok(primary)
+}
+
+/// Runs `f` with `device`'s primary context current on this thread, then
+/// restores the context that was current before, even if `f` panics.
+///
+/// Calls nest: each one restores exactly the context its caller had.
+///
+/// # Returns
+///
+/// * `Ok(T)` - The value returned by `f`
+/// * `Err(RdmaError::Device)` - The primary context could not be retained or
+///   made current; `f` was not called
+pub fn with_device<T>(device: i32, f: impl FnOnce() -> T) -> Result<T, RdmaError> {
+    let _guard = DeviceGuard::set(primary_context(device)?)?;
+    Ok(f())
+}
+
+/// Blocks until all work queued on `device` has completed.
+///
+/// If the context current on this thread belongs to `device`, that context is
+/// synchronized. Otherwise the device's primary context is retained and
+/// synchronized for the duration of the call.
+///
+/// # Returns
+///
+/// * `Ok(())` - All previously queued work on the device has finished
+/// * `Err(RdmaError::Device)` - A driver call failed, including errors raised by
+///   the queued work itself
+pub fn device_synchronize(device: i32) -> Result<(), RdmaError> {
+    let handle = get_device(device)?;
+
+    let mut current: rdmaxcel_sys::CUcontext = std::ptr::null_mut();
+    // SAFETY: `current` is a valid out-pointer for the duration of the call.
+    check(
+        unsafe { rdmaxcel_sys::rdmaxcel_cuCtxGetCurrent(&mut current) },
+        "cuCtxGetCurrent",
+    )?;
+    if !current.is_null() {
+        let mut current_device: rdmaxcel_sys::CUdevice = 0;
+        // SAFETY: A context is current, and `current_device` is a valid out-pointer.
+        check(
+            unsafe { rdmaxcel_sys::rdmaxcel_cuCtxGetDevice(&mut current_device) },
+            "cuCtxGetDevice",
+        )?;
+        if current_device == handle {
+            // SAFETY: A context is current on this thread.
+            return check(
+                unsafe { rdmaxcel_sys::rdmaxcel_cuCtxSynchronize() },
+                "cuCtxSynchronize",
+            );
+        }
+    }
+
+    let mut primary: rdmaxcel_sys::CUcontext = std::ptr::null_mut();
+    // SAFETY: `primary` is a valid out-pointer and `handle` a valid device.
+    check(
+        unsafe { rdmaxcel_sys::rdmaxcel_cuDevicePrimaryCtxRetain(&mut primary, handle) },
+        "cuDevicePrimaryCtxRetain",
+    )?;
+    let result = DeviceGuard::set(primary).and_then(|_guard| {
+        // SAFETY: The guard keeps the primary context current on this thread.
+        check(
+            unsafe { rdmaxcel_sys::rdmaxcel_cuCtxSynchronize() },
+            "cuCtxSynchronize",
+        )
+    });
+    // SAFETY: Balances the retain above; the guard has already restored the
+    // previous context.
+    check(
+        unsafe { rdmaxcel_sys::rdmaxcel_cuDevicePrimaryCtxRelease_v2(handle) },
+        "cuDevicePrimaryCtxRelease",
+    )?;
+    result
+}
+
+/// Destroys all allocations and resets all state in `device`'s primary context.
+///
+/// This is destructive: every pointer, stream and module created in the primary
+/// context becomes invalid. Contexts created explicitly with `cuCtxCreate` are
+/// not affected. Intended for cleaning up between tests, not for production use.
+///
+/// # Safety
+///
+/// No memory or other resources of the primary context may be used after this
+/// call, including memory registered with an RDMA protection domain.
+pub unsafe fn device_reset(device: i32) -> Result<(), RdmaError> {
+    let handle = get_device(device)?;
+    // SAFETY: The caller guarantees nothing in the primary context is still in use.
+    check(
+        unsafe { rdmaxcel_sys::rdmaxcel_cuDevicePrimaryCtxReset_v2(handle) },
+        "cuDevicePrimaryCtxReset",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cu_check;
+
+    fn current_context() -> rdmaxcel_sys::CUcontext {
+        let mut context: rdmaxcel_sys::CUcontext = std::ptr::null_mut();
+        unsafe {
+            cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxGetCurrent(&mut context));
+        }
+        context
+    }
+
+    #[test]
+    fn test_previous_context_restored_on_drop() {
+        if !crate::is_cuda_available() {
+            println!("Skipping test: CUDA not available");
+            return;
+        }
+        let context = unsafe {
+            let mut device: rdmaxcel_sys::CUdevice = std::mem::zeroed();
+            cu_check!(rdmaxcel_sys::rdmaxcel_cuDeviceGet(&mut device, 0));
+            let mut context: rdmaxcel_sys::CUcontext = std::mem::zeroed();
+            // Creating a context also makes it current, so clear it afterwards.
+            cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxCreate_v2(
+                &mut context,
+                0,
+                device
+            ));
+            cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxSetCurrent(std::ptr::null_mut()));
+            context
+        };
+        assert!(current_context().is_null());
+
+        {
+            let _guard = DeviceGuard::set(context).unwrap();
+            assert_eq!(current_context(), context);
+        }
+        assert!(current_context().is_null());
+    }
+
+    #[test]
+    fn test_nested_with_device_restores() {
+        if !crate::is_cuda_available() {
+            println!("Skipping test: CUDA not available");
+            return;
+        }
+        unsafe {
+            cu_check!(rdmaxcel_sys::rdmaxcel_cuInit(0));
+            cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxSetCurrent(std::ptr::null_mut()));
+        }
+        let primary = primary_context(0).unwrap();
+
+        with_device(0, || {
+            assert_eq!(current_context(), primary);
+            // A device's primary context is the same on every call.
+            assert_eq!(primary_context(0).unwrap(), primary);
+            unsafe {
+                cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxSetCurrent(std::ptr::null_mut()));
+            }
+            with_device(0, || assert_eq!(current_context(), primary)).unwrap();
+            // The inner call restored the (empty) context it found.
+            assert!(current_context().is_null());
+        })
+        .unwrap();
+        assert!(current_context().is_null());
+    }
+}
@@ -15,7 +15,15 @@ use std::path::Path;
 
 use regex::Regex;
 
+use crate::ibverbs_primitives::LinkLayer;
+use crate::ibverbs_primitives::PortAttr;
 use crate::ibverbs_primitives::RdmaDevice;
+use crate::rdma_error::RdmaError;
+
+/// Environment variable naming the RDMA device to use, e.g. `mlx5_2`. When set,
+/// [`crate::IbverbsConfig::targeting`] uses that device instead of picking one
+/// from the PCI topology.
+pub const RDMA_DEVICE_ENV: &str = "MONARCH_RDMA_DEVICE";
 
 // ==== PCI TOPOLOGY DISTANCE CONSTANTS ====
 //
@@ -277,7 +285,7 @@ pub fn get_cuda_pci_address(device_idx: &str) -> Option<String> {
     let gpu_proc_dir = "/proc/driver/nvidia/gpus";
 
     if !Path::new(gpu_proc_dir).exists() {
-        return None;
+        return get_cuda_pci_address_from_driver(idx);
     }
 
     for entry in fs::read_dir(gpu_proc_dir).ok()? {
@@ -296,7 +304,37 @@ pub fn get_cuda_pci_address(device_idx: &str) -> Option<String> {
             }
         }
     }
-    None
+    get_cuda_pci_address_from_driver(idx)
+}
+
+/// Queries the CUDA driver for the PCI address of device `idx`.
+///
+/// Used when the NVIDIA procfs entries are unavailable (e.g. inside some
+/// containers). Returns the address in `dddd:bb:dd.f` form.
+fn get_cuda_pci_address_from_driver(idx: i32) -> Option<String> {
+    // SAFETY: Plain CUDA driver queries writing into local out-parameters.
+    unsafe {
+        if rdmaxcel_sys::rdmaxcel_cuInit(0) != rdmaxcel_sys::CUDA_SUCCESS {
+            return None;
+        }
+        let mut device: rdmaxcel_sys::CUdevice = std::mem::zeroed();
+        if rdmaxcel_sys::rdmaxcel_cuDeviceGet(&mut device, idx) != rdmaxcel_sys::CUDA_SUCCESS {
+            return None;
+        }
+        let attr = |attrib: rdmaxcel_sys::CUdevice_attribute| -> Option<i32> {
+            let mut value: i32 = 0;
+            if rdmaxcel_sys::rdmaxcel_cuDeviceGetAttribute(&mut value, attrib, device)
+                != rdmaxcel_sys::CUDA_SUCCESS
+            {
+                return None;
+            }
+            Some(value)
+        };
+        let domain = attr(rdmaxcel_sys::CU_DEVICE_ATTRIBUTE_PCI_DOMAIN_ID)?;
+        let bus = attr(rdmaxcel_sys::CU_DEVICE_ATTRIBUTE_PCI_BUS_ID)?;
+        let dev = attr(rdmaxcel_sys::CU_DEVICE_ATTRIBUTE_PCI_DEVICE_ID)?;
+        Some(format!("{:04x}:{:02x}:{:02x}.0", domain, bus, dev))
+    }
 }
 
 pub fn get_numa_pci_address(numa_node: &str) -> Option<String> {
@@ -379,10 +417,122 @@ pub fn get_nic_pci_address(nic_name: &str) -> Option<String> {
     None
 }
 
+/// Returns the name of the RDMA NIC closest to `source` in the PCI topology.
+///
+/// `rdma_devices` pairs each NIC name with its PCI address, as returned by
+/// [`get_all_rdma_devices`]. NICs whose address is missing from `pci_devices`
+/// are ignored. Ties are broken in favor of the earlier NIC.
+pub fn closest_rdma_device_name(
+    source: &PCIDevice,
+    pci_devices: &HashMap<String, PCIDevice>,
+    rdma_devices: &[(String, String)],
+) -> Option<String> {
+    let (names, candidates): (Vec<&String>, Vec<PCIDevice>) = rdma_devices
+        .iter()
+        .filter_map(|(name, addr)| pci_devices.get(addr).map(|dev| (name, dev.clone())))
+        .unzip();
+    let closest_idx = source.find_closest(&candidates)?;
+    Some(names[closest_idx].clone())
+}
+
+/// Returns the first RDMA device with an active port, or the first device
+/// if none report an active port.
+pub fn first_active_rdma_device() -> Option<RdmaDevice> {
+    let devices = crate::ibverbs_primitives::get_all_devices();
+    devices
+        .iter()
+        .find(|dev| dev.ports().iter().any(|port| port.state() == "PORT_ACTIVE"))
+        .or(devices.first())
+        .cloned()
+}
+
+/// Returns the number of the first active port in `ports` with the `preferred`
+/// link layer, or of the first active port if none has it.
+///
+/// Returns `None` if no port is active.
+pub fn select_port(ports: &[PortAttr], preferred: LinkLayer) -> Option<u8> {
+    let active = || ports.iter().filter(|port| port.state == "PORT_ACTIVE");
+    active()
+        .find(|port| port.link_layer == preferred.as_str())
+        .or_else(|| active().next())
+        .map(|port| port.port_num)
+}
+
+/// Returns the index of the device named `name` in `devices`, which pairs each
+/// device name with its ports.
+///
+/// # Errors
+///
+/// * `Err(RdmaError::InvalidConfig)` - No device is named `name`, or it has no
+///   active port; the message lists the available devices
+pub fn find_named_device(
+    name: &str,
+    devices: &[(&str, Vec<PortAttr>)],
+) -> Result<usize, RdmaError> {
+    let is_active = |ports: &[PortAttr]| ports.iter().any(|port| port.state == "PORT_ACTIVE");
+    let available = || {
+        devices
+            .iter()
+            .map(|(name, ports)| {
+                let state = if is_active(ports) {
+                    "active"
+                } else {
+                    "inactive"
+                };
+                format!("{} ({})", name, state)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match devices.iter().position(|(device, _)| *device == name) {
+        Some(index) if is_active(&devices[index].1) => Ok(index),
+        Some(_) => Err(RdmaError::InvalidConfig(format!(
+            "{}={} has no active port; available devices: {}",
+            RDMA_DEVICE_ENV,
+            name,
+            available()
+        ))),
+        None => Err(RdmaError::InvalidConfig(format!(
+            "{}={} does not name an RDMA device; available devices: {}",
+            RDMA_DEVICE_ENV,
+            name,
+            available()
+        ))),
+    }
+}
+
+/// Returns the device named by [`RDMA_DEVICE_ENV`], or `None` if it is unset.
+///
+/// # Errors
+///
+/// * `Some(Err(RdmaError::InvalidConfig))` - The device doesn't exist or has no
+///   active port
+pub fn rdma_device_from_env() -> Option<Result<RdmaDevice, RdmaError>> {
+    let name = std::env::var(RDMA_DEVICE_ENV).ok()?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let devices = crate::ibverbs_primitives::get_all_devices();
+    let described: Vec<(&str, Vec<PortAttr>)> = devices
+        .iter()
+        .map(|dev| {
+            (
+                dev.name().as_str(),
+                dev.ports().iter().map(PortAttr::from).collect(),
+            )
+        })
+        .collect();
+    Some(find_named_device(name, &described).map(|index| devices[index].clone()))
+}
+
 /// Step 1: Parse device string into prefix and postfix
 /// Step 2: Get PCI address from compute device
 /// Step 3: Get PCI address for all RDMA NIC devices
 /// Step 4: Calculate PCI distances and return closest RDMA NIC device
+///
+/// For `cuda` and `cpu` hints, falls back to the first active RDMA device when
+/// the topology can't be resolved.
 pub fn select_optimal_rdma_device(device_hint: Option<&str>) -> Option<RdmaDevice> {
     let device_hint = device_hint?;
 
@@ -396,38 +546,30 @@ pub fn select_optimal_rdma_device(device_hint: Option<&str>) -> Option<RdmaDevic
                 .find(|dev| dev.name() == &postfix)
         }
         "cuda" | "cpu" => {
-            let source_pci_addr = match prefix.as_str() {
-                "cuda" => get_cuda_pci_address(&postfix)?,
-                "cpu" => get_numa_pci_address(&postfix)?,
-                _ => unreachable!(),
-            };
-            let rdma_devices = get_all_rdma_devices();
-            if rdma_devices.is_empty() {
-                return RdmaDevice::first_available();
-            }
-            let pci_devices = parse_pci_topology().ok()?;
-            let source_device = pci_devices.get(&source_pci_addr)?;
-
-            let rdma_names: Vec<String> =
-                rdma_devices.iter().map(|(name, _)| name.clone()).collect();
-            let rdma_pci_devices: Vec<PCIDevice> = rdma_devices
-                .iter()
-                .filter_map(|(_, addr)| pci_devices.get(addr).cloned())
-                .collect();
-
-            if let Some(closest_idx) = source_device.find_closest(&rdma_pci_devices) {
-                if let Some(optimal_name) = rdma_names.get(closest_idx) {
-                    let all_rdma_devices = crate::ibverbs_primitives::get_all_devices();
-                    for device in all_rdma_devices {
-                        if *device.name() == *optimal_name {
-                            return Some(device);
-                        }
-                    }
+            let optimal_name = (|| {
+                let source_pci_addr = match prefix.as_str() {
+                    "cuda" => get_cuda_pci_address(&postfix)?,
+                    "cpu" => get_numa_pci_address(&postfix)?,
+                    _ => unreachable!(),
+                };
+                let rdma_devices = get_all_rdma_devices();
+                let pci_devices = parse_pci_topology().ok()?;
+                let source_device = pci_devices.get(&source_pci_addr)?;
+                closest_rdma_device_name(source_device, &pci_devices, &rdma_devices)
+            })();
+
+            if let Some(optimal_name) = optimal_name {
+                let all_rdma_devices = crate::ibverbs_primitives::get_all_devices();
+                if let Some(device) = all_rdma_devices
+                    .into_iter()
+                    .find(|dev| *dev.name() == optimal_name)
+                {
+                    return Some(device);
                 }
             }
 
             // Fallback
-            RdmaDevice::first_available()
+            first_active_rdma_device()
         }
         _ => {
             // Direct device name lookup for backward compatibility
@@ -519,6 +661,122 @@ mod tests {
         );
     }
 
+    /// Builds a `PCIDevice` whose ancestors are `path` (nearest first).
+    fn mock_pci_device(address: &str, path: &[&str]) -> PCIDevice {
+        let mut parent: Option<Box<PCIDevice>> = None;
+        for addr in path.iter().rev() {
+            parent = Some(Box::new(PCIDevice {
+                address: addr.to_string(),
+                parent,
+            }));
+        }
+        PCIDevice {
+            address: address.to_string(),
+            parent,
+        }
+    }
+
+    #[test]
+    fn test_closest_rdma_device_name_mocked_topology() {
+        // Root complex 0000:00:00.0 with two PCIe switches; the GPU shares
+        // switch 0000:10:00.0 with mlx5_1 only.
+        let root = "0000:00:00.0";
+        let switch_a = "0000:10:00.0";
+        let switch_b = "0000:20:00.0";
+        let gpu = mock_pci_device("0000:11:00.0", &[switch_a, root]);
+        let nic_near = mock_pci_device("0000:12:00.0", &[switch_a, root]);
+        let nic_far = mock_pci_device("0000:21:00.0", &[switch_b, root]);
+        let nic_other_domain = mock_pci_device("0001:11:00.0", &["0001:00:00.0"]);
+
+        let pci_devices: HashMap<String, PCIDevice> =
+            [&gpu, &nic_near, &nic_far, &nic_other_domain]
+                .into_iter()
+                .map(|dev| (dev.address.clone(), dev.clone()))
+                .collect();
+        let rdma_devices = vec![
+            ("mlx5_0".to_string(), nic_far.address.clone()),
+            // Not present in the topology; must not shift the selection.
+            ("mlx5_9".to_string(), "0000:99:00.0".to_string()),
+            ("mlx5_1".to_string(), nic_near.address.clone()),
+            ("mlx5_2".to_string(), nic_other_domain.address.clone()),
+        ];
+
+        assert_eq!(
+            closest_rdma_device_name(&gpu, &pci_devices, &rdma_devices),
+            Some("mlx5_1".to_string())
+        );
+        // Without the switch-local NIC, the same-root NIC wins over the other domain.
+        assert_eq!(
+            closest_rdma_device_name(&gpu, &pci_devices, &rdma_devices[..2]),
+            Some("mlx5_0".to_string())
+        );
+        assert_eq!(closest_rdma_device_name(&gpu, &pci_devices, &[]), None);
+    }
+
+    fn mock_port(port_num: u8, state: &str, link_layer: LinkLayer) -> PortAttr {
+        PortAttr {
+            port_num,
+            state: state.to_string(),
+            link_layer: link_layer.as_str().to_string(),
+            active_mtu: 4096,
+        }
+    }
+
+    #[test]
+    fn test_select_port_prefers_link_layer() {
+        let ports = vec![
+            mock_port(1, "PORT_ACTIVE", LinkLayer::InfiniBand),
+            mock_port(2, "PORT_DOWN", LinkLayer::Ethernet),
+            mock_port(3, "PORT_ACTIVE", LinkLayer::Ethernet),
+        ];
+        assert_eq!(select_port(&ports, LinkLayer::Ethernet), Some(3));
+        assert_eq!(select_port(&ports, LinkLayer::InfiniBand), Some(1));
+
+        // Without an active port of the preferred link layer, any active port is used.
+        assert_eq!(select_port(&ports[..2], LinkLayer::Ethernet), Some(1));
+        assert_eq!(select_port(&ports[1..], LinkLayer::InfiniBand), Some(3));
+
+        assert_eq!(select_port(&ports[1..2], LinkLayer::Ethernet), None);
+        assert_eq!(select_port(&[], LinkLayer::Ethernet), None);
+    }
+
+    #[test]
+    fn test_find_named_device() {
+        let devices = vec![
+            (
+                "mlx5_0",
+                vec![mock_port(1, "PORT_ACTIVE", LinkLayer::InfiniBand)],
+            ),
+            (
+                "mlx5_1",
+                vec![mock_port(1, "PORT_DOWN", LinkLayer::InfiniBand)],
+            ),
+            (
+                "mlx5_2",
+                vec![
+                    mock_port(1, "PORT_DOWN", LinkLayer::Ethernet),
+                    mock_port(2, "PORT_ACTIVE", LinkLayer::Ethernet),
+                ],
+            ),
+        ];
+        assert_eq!(find_named_device("mlx5_2", &devices).unwrap(), 2);
+        assert_eq!(find_named_device("mlx5_0", &devices).unwrap(), 0);
+
+        let err = find_named_device("mlx5_1", &devices).unwrap_err();
+        assert!(matches!(err, RdmaError::InvalidConfig(_)));
+        assert!(err.to_string().contains("has no active port"));
+
+        let err = find_named_device("mlx5_9", &devices).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("MONARCH_RDMA_DEVICE=mlx5_9"));
+        assert!(
+            message
+                .contains("available devices: mlx5_0 (active), mlx5_1 (inactive), mlx5_2 (active)")
+        );
+
+        assert!(find_named_device("mlx5_0", &[]).is_err());
+    }
+
     /// Detect if we're running on GT20 hardware by checking for expected RDMA device configuration
     fn is_gt20_hardware() -> bool {
         let rdma_devices = get_all_rdma_devices();
@@ -0,0 +1,147 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ * All rights reserved.
+ *
+ * This source code is licensed under the BSD-style license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Host-side conversions between `f32` and the raw bits of IEEE 754 half
+//! precision (`fp16`) and bfloat16 (`bf16`) values.
+//!
+//! These let tests and tools interpret half-precision data copied back from a
+//! device, e.g. to check values after an RDMA transfer. Conversions to the
+//! narrow formats round to nearest, ties to even, and keep NaNs quiet.
+
+/// Converts the bits of an IEEE 754 half precision value to `f32`. The
+/// conversion is exact.
+pub fn half_to_f32(bits: u16) -> f32 {
+    let sign = u32::from(bits & 0x8000) << 16;
+    let exp = (bits >> 10) & 0x1f;
+    let mant = u32::from(bits & 0x3ff);
+    match exp {
+        0 => {
+            // Zero or subnormal: `mant` counts units of 2^-24.
+            let magnitude = mant as f32 * f32::powi(2.0, -24);
+            f32::from_bits(sign | magnitude.to_bits())
+        }
+        0x1f => f32::from_bits(sign | 0x7f80_0000 | (mant << 13)),
+        _ => f32::from_bits(sign | ((u32::from(exp) + 112) << 23) | (mant << 13)),
+    }
+}
+
+/// Converts `value` to the bits of the nearest IEEE 754 half precision value.
+/// Values beyond the half precision range become infinities.
+pub fn f32_to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mant = bits & 0x7f_ffff;
+
+    if exp == 0xff {
+        return if mant == 0 {
+            sign | 0x7c00
+        } else {
+            sign | 0x7e00 | (mant >> 13) as u16
+        };
+    }
+    let half_exp = exp - 127 + 15;
+    if half_exp >= 0x1f {
+        return sign | 0x7c00;
+    }
+    if half_exp <= 0 {
+        // Too small for a normal half: produce a subnormal, or zero if even
+        // rounding can't reach the smallest subnormal.
+        if half_exp < -10 {
+            return sign;
+        }
+        let shift = (14 - half_exp) as u32;
+        return sign | round_shift(mant | 0x80_0000, shift) as u16;
+    }
+    // A carry out of the mantissa correctly bumps the exponent, up to infinity.
+    sign | (((half_exp as u32) << 10) + round_shift(mant, 13)) as u16
+}
+
+/// Converts the bits of a bfloat16 value to `f32`. The conversion is exact.
+pub fn bf16_to_f32(bits: u16) -> f32 {
+    f32::from_bits(u32::from(bits) << 16)
+}
+
+/// Converts `value` to the bits of the nearest bfloat16 value.
+pub fn f32_to_bf16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    if value.is_nan() {
+        return ((bits >> 16) | 0x40) as u16;
+    }
+    let rounding_bias = 0x7fff + ((bits >> 16) & 1);
+    ((bits + rounding_bias) >> 16) as u16
+}
+
+/// Shifts `value` right by `shift` bits, rounding to nearest, ties to even.
+fn round_shift(value: u32, shift: u32) -> u32 {
+    let truncated = value >> shift;
+    let remainder = value & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+    if remainder > halfway || (remainder == halfway && truncated & 1 == 1) {
+        truncated + 1
+    } else {
+        truncated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_known_values() {
+        assert_eq!(half_to_f32(0x3c00), 1.0);
+        assert_eq!(half_to_f32(0xc000), -2.0);
+        assert_eq!(half_to_f32(0x7bff), 65504.0);
+        assert_eq!(half_to_f32(0x0001), f32::powi(2.0, -24));
+        assert_eq!(half_to_f32(0x7c00), f32::INFINITY);
+        assert!(half_to_f32(0x7e00).is_nan());
+        assert_eq!(half_to_f32(0x8000).to_bits(), (-0.0f32).to_bits());
+
+        assert_eq!(f32_to_half(1.0), 0x3c00);
+        assert_eq!(f32_to_half(-2.0), 0xc000);
+        assert_eq!(f32_to_half(65504.0), 0x7bff);
+        assert_eq!(f32_to_half(65520.0), 0x7c00);
+        assert_eq!(f32_to_half(f32::powi(2.0, -24)), 0x0001);
+        assert_eq!(f32_to_half(f32::powi(2.0, -26)), 0x0000);
+        assert_eq!(f32_to_half(-0.0), 0x8000);
+        assert_eq!(f32_to_half(f32::NEG_INFINITY), 0xfc00);
+        assert_eq!(f32_to_half(f32::NAN) & 0x7e00, 0x7e00);
+        // 1 + 2^-11 lies halfway between 1.0 and the next half; ties go to even.
+        assert_eq!(f32_to_half(1.0 + f32::powi(2.0, -11)), 0x3c00);
+    }
+
+    #[test]
+    fn test_half_round_trip() {
+        for bits in 0..=u16::MAX {
+            let value = half_to_f32(bits);
+            if value.is_nan() {
+                assert!(half_to_f32(f32_to_half(value)).is_nan(), "{:#06x}", bits);
+            } else {
+                assert_eq!(f32_to_half(value), bits, "{:#06x}", bits);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bf16_known_values() {
+        assert_eq!(bf16_to_f32(0x3f80), 1.0);
+        assert_eq!(bf16_to_f32(0xc000), -2.0);
+        assert_eq!(bf16_to_f32(0x7f80), f32::INFINITY);
+        assert!(bf16_to_f32(0x7fc0).is_nan());
+
+        assert_eq!(f32_to_bf16(1.0), 0x3f80);
+        assert_eq!(f32_to_bf16(-2.0), 0xc000);
+        assert_eq!(f32_to_bf16(f32::INFINITY), 0x7f80);
+        assert_eq!(f32_to_bf16(f32::NAN) & 0x7fc0, 0x7fc0);
+        assert_eq!(f32_to_bf16(f32::MAX), 0x7f80);
+        // 1 + 2^-8 lies halfway between 1.0 and the next bf16; ties go to even.
+        assert_eq!(f32_to_bf16(1.0 + f32::powi(2.0, -8)), 0x3f80);
+        assert_eq!(f32_to_bf16(1.0 + 3.0 * f32::powi(2.0, -8)), 0x3f82);
+    }
+}
@@ -21,6 +21,8 @@
 //!   its name, vendor ID, vendor part ID, hardware version, firmware version, node GUID, and capabilities.
 //! - `RdmaPort`: Represents information about the port of an RDMA device, including state, physical state,
 //!   LID (Local Identifier), and GID (Global Identifier) information.
+//! - `DeviceCaps` / `PortAttr`: Device limits and port attributes returned by `query_device` and
+//!   `query_port` for an open device context.
 //! - `RdmaMemoryRegionView`: Represents a memory region that can be registered with an RDMA device for direct
 //!   memory access operations.
 //! - `RdmaOperation`: Represents the type of RDMA operation to perform (Read or Write).
@@ -29,11 +31,15 @@
 use std::ffi::CStr;
 use std::fmt;
 use std::sync::OnceLock;
+use std::time::Duration;
 
 use hyperactor::Named;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::RdmaError;
+use crate::completion_dispatcher::POLL_BATCH_SIZE;
+
 #[derive(
     Default,
     Copy,
@@ -61,6 +67,13 @@ impl Gid {
         u64::from_be_bytes(self.raw[8..].try_into().unwrap())
     }
 }
+
+impl From<[u8; 16]> for Gid {
+    fn from(raw: [u8; 16]) -> Self {
+        Self { raw }
+    }
+}
+
 impl From<rdmaxcel_sys::ibv_gid> for Gid {
     fn from(gid: rdmaxcel_sys::ibv_gid) -> Self {
         Self {
@@ -99,6 +112,141 @@ pub enum RdmaQpType {
     Standard,
     /// Force mlx5dv extended queue pair
     Mlx5dv,
+    /// Unreliable datagram queue pair for multicast, used by `MulticastQueuePair`.
+    /// Connected queue pairs (`RdmaQueuePair`) reject it.
+    UnreliableDatagram,
+}
+
+/// RDMA provider used to drive the NIC.
+///
+/// `Mlx5` uses the Mellanox device-specific (mlx5dv) extensions for direct WQE
+/// construction and doorbell ringing. `Verbs` restricts the stack to the generic
+/// `ibv_*` path so that non-Mellanox NICs (e.g. AWS EFA) can be used.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RdmaProvider {
+    /// Mellanox mlx5 provider with mlx5dv extensions
+    Mlx5,
+    /// Generic ibverbs provider, no mlx5dv calls are made
+    Verbs,
+}
+
+/// Link layer of an RDMA port.
+///
+/// RoCE ports report `Ethernet`; native InfiniBand ports report `InfiniBand`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum LinkLayer {
+    /// Native InfiniBand
+    InfiniBand,
+    /// RDMA over Converged Ethernet (RoCE)
+    Ethernet,
+}
+
+impl LinkLayer {
+    /// The name reported for this link layer by [`get_link_layer_str`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkLayer::InfiniBand => "InfiniBand",
+            LinkLayer::Ethernet => "Ethernet",
+        }
+    }
+}
+
+/// How RDMA completion loops wait for work completions.
+///
+/// `BusyPoll` polls the completion queue in a loop, sleeping briefly between empty
+/// polls, which gives the lowest latency at the cost of CPU. `EventDriven` attaches
+/// the completion queues to a completion channel and sleeps on its file descriptor
+/// until the NIC signals a completion, which scales better to many concurrent transfers.
+/// `Adaptive` spins on the completion queue first, so short operations complete with
+/// busy-poll latency, and only sleeps on the completion channel for longer ones.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PollStrategy {
+    /// Poll the completion queue until the work completes
+    #[default]
+    BusyPoll,
+    /// Wait on a completion channel (`ibv_req_notify_cq`) between polls
+    EventDriven,
+    /// Poll the completion queue without sleeping for `spin_for`, then wait on a
+    /// completion channel like `EventDriven`
+    Adaptive { spin_for: Duration },
+}
+
+impl PollStrategy {
+    /// Whether queue pairs using this strategy need a completion channel.
+    pub fn uses_completion_channel(&self) -> bool {
+        match self {
+            PollStrategy::BusyPoll => false,
+            PollStrategy::EventDriven | PollStrategy::Adaptive { .. } => true,
+        }
+    }
+}
+
+/// Environment variable overriding GPUDirect RDMA auto-detection: `0`, `1` or `auto`.
+pub const FORCE_GPUDIRECT_ENV: &str = "MONARCH_FORCE_GPUDIRECT";
+
+/// Whether GPUDirect RDMA is forced on, forced off, or auto-detected.
+///
+/// Read from [`FORCE_GPUDIRECT_ENV`]. Forcing it on skips the execution context
+/// check, so on a system without GPUDirect support registering device memory
+/// fails instead of falling back to standard ibverbs.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GpuDirectMode {
+    /// Use GPUDirect whenever the execution context supports it
+    #[default]
+    Auto,
+    /// Always use GPUDirect (`1`)
+    On,
+    /// Never use GPUDirect (`0`)
+    Off,
+}
+
+impl GpuDirectMode {
+    /// Parses a [`FORCE_GPUDIRECT_ENV`] value.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(RdmaError::InvalidConfig)` - `value` isn't `0`, `1` or `auto`
+    pub fn parse(value: &str) -> Result<Self, RdmaError> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "0" => Ok(Self::Off),
+            "1" => Ok(Self::On),
+            "auto" | "" => Ok(Self::Auto),
+            other => Err(RdmaError::InvalidConfig(format!(
+                "{} must be 0, 1 or auto, got {:?}",
+                FORCE_GPUDIRECT_ENV, other
+            ))),
+        }
+    }
+
+    /// Reads the mode from [`FORCE_GPUDIRECT_ENV`]. An unset or invalid value means
+    /// `Auto`; an invalid value is logged.
+    pub fn from_env() -> Self {
+        match std::env::var(FORCE_GPUDIRECT_ENV) {
+            Ok(value) => Self::parse(&value).unwrap_or_else(|e| {
+                tracing::warn!("{}; auto-detecting GPUDirect RDMA", e);
+                Self::Auto
+            }),
+            Err(_) => Self::Auto,
+        }
+    }
+
+    /// The forced setting, or `None` if GPUDirect should be auto-detected.
+    pub fn forced(self) -> Option<bool> {
+        match self {
+            Self::Auto => None,
+            Self::On => Some(true),
+            Self::Off => Some(false),
+        }
+    }
+
+    /// Whether to use GPUDirect: the forced setting, or else the result of `detect`,
+    /// which is only awaited in `Auto` mode.
+    pub async fn resolve(self, detect: impl Future<Output = bool>) -> bool {
+        match self.forced() {
+            Some(forced) => forced,
+            None => detect.await,
+        }
+    }
 }
 
 /// Converts `RdmaQpType` to the corresponding integer enum value in rdmaxcel_sys.
@@ -113,6 +261,7 @@ pub fn resolve_qp_type(qp_type: RdmaQpType) -> u32 {
         }
         RdmaQpType::Standard => rdmaxcel_sys::RDMA_QP_TYPE_STANDARD,
         RdmaQpType::Mlx5dv => rdmaxcel_sys::RDMA_QP_TYPE_MLX5DV,
+        RdmaQpType::UnreliableDatagram => rdmaxcel_sys::RDMA_QP_TYPE_UD,
     }
 }
 
@@ -125,15 +274,23 @@ pub fn resolve_qp_type(qp_type: RdmaQpType) -> u32 {
 pub struct IbverbsConfig {
     /// `device` - The RDMA device to use for the connection.
     pub device: RdmaDevice,
-    /// `cq_entries` - The number of completion queue entries.
+    /// `cq_entries` - The completion queue depth, clamped to the device's `max_cqe`. Used for
+    /// the send and receive completion queues unless overridden below.
     pub cq_entries: i32,
+    /// `send_cq_depth` - Depth of the send completion queue, clamped to the device's `max_cqe`.
+    /// `None` uses `cq_entries`.
+    pub send_cq_depth: Option<i32>,
+    /// `recv_cq_depth` - Depth of the receive completion queue, clamped to the device's
+    /// `max_cqe`. `None` uses `cq_entries`. Workloads that only issue RDMA reads and writes
+    /// post no receives, so a shallow receive queue saves device memory.
+    pub recv_cq_depth: Option<i32>,
     /// `port_num` - The physical port number on the device.
     pub port_num: u8,
     /// `gid_index` - The GID index for the RDMA device.
     pub gid_index: u8,
-    /// `max_send_wr` - The maximum number of outstanding send work requests.
+    /// `max_send_wr` - The maximum number of outstanding send work requests, clamped to the device's `max_qp_wr`.
     pub max_send_wr: u32,
-    /// `max_recv_wr` - The maximum number of outstanding receive work requests.
+    /// `max_recv_wr` - The maximum number of outstanding receive work requests, clamped to the device's `max_qp_wr`.
     pub max_recv_wr: u32,
     /// `max_send_sge` - Te maximum number of scatter/gather elements in a send work request.
     pub max_send_sge: u32,
@@ -141,13 +298,19 @@ pub struct IbverbsConfig {
     pub max_recv_sge: u32,
     /// `path_mtu` - The path MTU (Maximum Transmission Unit) for the connection.
     pub path_mtu: u32,
-    /// `retry_cnt` - The number of retry attempts for a connection request.
+    /// `retry_cnt` - How many times (0-7) an unacknowledged packet is retransmitted before the
+    /// work request fails. Higher values ride out more packet loss on lossy fabrics, at the cost
+    /// of reporting a dead peer later.
     pub retry_cnt: u8,
-    /// `rnr_retry` - The number of retry attempts for a receiver not ready (RNR) condition.
+    /// `rnr_retry` - How many times (0-7) a send is retried when the receiver is not ready
+    /// (RNR). 7 retries forever, which never surfaces a stuck receiver as an error.
     pub rnr_retry: u8,
-    /// `qp_timeout` - The timeout for a queue pair operation.
+    /// `qp_timeout` - The ACK timeout exponent (0-31): a retransmission happens after
+    /// 4.096 μs * 2^`qp_timeout` without an ACK, and 0 waits forever. Lower values recover
+    /// from loss faster but cause spurious retransmissions on congested fabrics.
     pub qp_timeout: u8,
-    /// `min_rnr_timer` - The minimum RNR timer value.
+    /// `min_rnr_timer` - The RNR NAK timer code (0-31) this QP asks senders to wait before
+    /// retrying an RNR.
     pub min_rnr_timer: u8,
     /// `max_dest_rd_atomic` - The maximum number of outstanding RDMA read operations at the destination.
     pub max_dest_rd_atomic: u8,
@@ -155,15 +318,47 @@ pub struct IbverbsConfig {
     pub max_rd_atomic: u8,
     /// `pkey_index` - The partition key index.
     pub pkey_index: u16,
-    /// `psn` - The packet sequence number.
+    /// `psn` - The initial 24-bit packet sequence number of the send queue.
     pub psn: u32,
-    /// `use_gpu_direct` - Whether to enable GPU Direct RDMA support on init.
+    /// `use_gpu_direct` - Whether to enable GPU Direct RDMA support on init. `targeting`
+    /// sets it when [`FORCE_GPUDIRECT_ENV`] forces GPUDirect on or off.
     pub use_gpu_direct: bool,
     /// `hw_init_delay_ms` - The delay in milliseconds before initializing the hardware.
     /// This is used to allow the hardware to settle before starting the first transmission.
     pub hw_init_delay_ms: u64,
-    /// `qp_type` - The type of queue pair to create (Auto, Standard, or Mlx5dv).
+    /// `qp_type` - The type of queue pair to create (Auto, Standard, Mlx5dv, or
+    /// UnreliableDatagram for a `MulticastQueuePair`).
     pub qp_type: RdmaQpType,
+    /// `provider` - The RDMA provider (Mlx5 or Verbs). `Verbs` disables all mlx5dv usage.
+    pub provider: RdmaProvider,
+    /// `cuda_device` - The CUDA device ordinal that registered buffers must live on. When set,
+    /// host pointers and pointers to other devices are rejected at registration. `None` accepts
+    /// both host and device memory. `targeting` sets it for `cuda:N` targets.
+    pub cuda_device: Option<i32>,
+    /// `poll_strategy` - How to wait for work completions (busy polling or a completion channel).
+    pub poll_strategy: PollStrategy,
+    /// `signal_every_n` - Only every Nth send work request (and the last of each batch) generates
+    /// a completion. `1` signals every work request.
+    pub signal_every_n: u32,
+    /// `max_transfer_chunk` - Largest number of bytes sent in one work request. Larger `put`s
+    /// and `get`s are split into chunks of this size, all posted before any completes.
+    pub max_transfer_chunk: usize,
+    /// `num_qps` - Number of queue pairs connecting each pair of devices. `read_into` and
+    /// `write_from` stripe their chunks across them round-robin, so that a single transfer
+    /// can use a multi-rail NIC's full bandwidth.
+    pub num_qps: usize,
+    /// `link_layer_preference` - Link layer `targeting` prefers when picking `port_num` on
+    /// the selected device. Any active port is used if none has the preferred link layer.
+    pub link_layer_preference: Option<LinkLayer>,
+    /// `local_copy` - Whether buffers registered by this actor may be copied directly, bypassing
+    /// the NIC, when `read_into`/`write_from` pairs them with another buffer registered in the
+    /// same process with `local_copy` enabled.
+    pub local_copy: bool,
+    /// `poll_batch_size` - Most completions drained from one of this queue pair's completion
+    /// queues by one poll of the completion dispatcher. Larger batches amortize polling
+    /// overhead at high completion rates. Must be between 1 and the depth of the smaller
+    /// completion queue.
+    pub poll_batch_size: usize,
 }
 
 /// Default RDMA parameters below are based on common values from rdma-core examples
@@ -174,6 +369,8 @@ impl Default for IbverbsConfig {
         Self {
             device: RdmaDevice::default(),
             cq_entries: 1024,
+            send_cq_depth: None,
+            recv_cq_depth: None,
             port_num: 1,
             gid_index: 3,
             max_send_wr: 512,
@@ -192,11 +389,127 @@ impl Default for IbverbsConfig {
             use_gpu_direct: false, // nv_peermem enabled for cuda
             hw_init_delay_ms: 2,
             qp_type: RdmaQpType::Auto,
+            provider: RdmaProvider::Mlx5,
+            cuda_device: None,
+            poll_strategy: PollStrategy::BusyPoll,
+            signal_every_n: 1,
+            max_transfer_chunk: 1024 * 1024 * 1024,
+            num_qps: 1,
+            link_layer_preference: None,
+            local_copy: true,
+            poll_batch_size: POLL_BATCH_SIZE,
         }
     }
 }
 
 impl IbverbsConfig {
+    /// Resolves the rdmaxcel_sys QP type to create for this configuration.
+    ///
+    /// The `Verbs` provider uses a standard ibverbs queue pair for every connected `qp_type`.
+    pub fn resolved_qp_type(&self) -> u32 {
+        // Datagram queue pairs only use the generic verbs, so any provider can create one.
+        if self.qp_type == RdmaQpType::UnreliableDatagram {
+            return rdmaxcel_sys::RDMA_QP_TYPE_UD;
+        }
+        match self.provider {
+            RdmaProvider::Mlx5 => resolve_qp_type(self.qp_type),
+            RdmaProvider::Verbs => rdmaxcel_sys::RDMA_QP_TYPE_STANDARD,
+        }
+    }
+
+    /// Clamps the queue depths to the limits reported by `self.device`.
+    ///
+    /// `cq_entries`, `send_cq_depth` and `recv_cq_depth` are limited to the device's
+    /// `max_cqe`, and `max_send_wr`/`max_recv_wr` to its `max_qp_wr`. Any value that had to
+    /// be reduced is logged as a warning.
+    pub fn clamp_to_device_limits(&mut self) {
+        self.cq_entries = clamp_to_device_limit("cq_entries", self.cq_entries, self.device.max_cqe);
+        self.send_cq_depth = self
+            .send_cq_depth
+            .map(|depth| clamp_to_device_limit("send_cq_depth", depth, self.device.max_cqe));
+        self.recv_cq_depth = self
+            .recv_cq_depth
+            .map(|depth| clamp_to_device_limit("recv_cq_depth", depth, self.device.max_cqe));
+        self.max_send_wr =
+            clamp_to_device_limit("max_send_wr", self.max_send_wr, self.device.max_qp_wr);
+        self.max_recv_wr =
+            clamp_to_device_limit("max_recv_wr", self.max_recv_wr, self.device.max_qp_wr);
+    }
+
+    /// The depth of the send completion queue: `send_cq_depth`, or `cq_entries` if unset.
+    pub fn send_cq_entries(&self) -> i32 {
+        self.send_cq_depth.unwrap_or(self.cq_entries)
+    }
+
+    /// The depth of the receive completion queue: `recv_cq_depth`, or `cq_entries` if unset.
+    pub fn recv_cq_entries(&self) -> i32 {
+        self.recv_cq_depth.unwrap_or(self.cq_entries)
+    }
+
+    /// Checks that `num_qps` is at least one and, if the device reports a limit, no more
+    /// than its `max_qp`.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(RdmaError::InvalidConfig)` - `num_qps` is out of range
+    pub fn validate_num_qps(&self) -> Result<(), RdmaError> {
+        if self.num_qps == 0 {
+            return Err(RdmaError::InvalidConfig(
+                "num_qps must be at least 1".to_string(),
+            ));
+        }
+        if self.device.max_qp > 0 && self.num_qps > self.device.max_qp as usize {
+            return Err(RdmaError::InvalidConfig(format!(
+                "num_qps ({}) exceeds the maximum of {} queue pairs on {}",
+                self.num_qps, self.device.max_qp, self.device.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that `poll_batch_size` is at least one and no more than the depth of either
+    /// completion queue.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(RdmaError::InvalidConfig)` - `poll_batch_size` is out of range
+    pub fn validate_poll_batch_size(&self) -> Result<(), RdmaError> {
+        let depth = self.send_cq_entries().min(self.recv_cq_entries()).max(0) as usize;
+        if self.poll_batch_size == 0 || self.poll_batch_size > depth {
+            return Err(RdmaError::InvalidConfig(format!(
+                "poll_batch_size ({}) must be between 1 and the completion queue depth ({})",
+                self.poll_batch_size, depth
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that the queue pair reliability parameters are within the ranges ibverbs
+    /// accepts: a 24-bit `psn`, 3-bit `retry_cnt` and `rnr_retry`, and 5-bit `qp_timeout`
+    /// and `min_rnr_timer`.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(RdmaError::InvalidConfig)` - A parameter is out of range
+    pub fn validate_reliability(&self) -> Result<(), RdmaError> {
+        let checks: [(&str, u32, u32); 5] = [
+            ("psn", self.psn, 0xffffff),
+            ("retry_cnt", self.retry_cnt.into(), 7),
+            ("rnr_retry", self.rnr_retry.into(), 7),
+            ("qp_timeout", self.qp_timeout.into(), 31),
+            ("min_rnr_timer", self.min_rnr_timer.into(), 31),
+        ];
+        for (name, value, max) in checks {
+            if value > max {
+                return Err(RdmaError::InvalidConfig(format!(
+                    "{} ({}) exceeds the maximum of {}",
+                    name, value, max
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Create a new IbverbsConfig targeting a specific device
     ///
     /// Device targets use a unified "type:id" format:
@@ -214,8 +527,23 @@ impl IbverbsConfig {
     ///
     /// # Returns
     ///
-    /// * `IbverbsConfig` with resolved device, or default device if resolution fails
+    /// * `IbverbsConfig` with resolved device, or default device if resolution fails.
+    ///   If [`crate::device_selection::RDMA_DEVICE_ENV`] names an active device, that
+    ///   device is used regardless of `target`. `use_gpu_direct` follows
+    ///   [`FORCE_GPUDIRECT_ENV`] if it is set to `0` or `1`. For a `cuda:N` target,
+    ///   `cuda_device` is set to `N` so that only buffers on that device are registered.
     pub fn targeting(target: &str) -> Self {
+        Self::targeting_with_link_layer(target, None)
+    }
+
+    /// Like [`IbverbsConfig::targeting`], but when `link_layer_preference` is set, picks
+    /// `port_num` as the device's first active port with that link layer, falling back to
+    /// its first active port of any link layer. `port_num` keeps its default if the device
+    /// has no active port.
+    pub fn targeting_with_link_layer(
+        target: &str,
+        link_layer_preference: Option<LinkLayer>,
+    ) -> Self {
         // Normalize shortcuts
         let normalized_target = match target {
             "cpu" => "cpu:0",
@@ -223,21 +551,70 @@ impl IbverbsConfig {
             _ => target,
         };
 
-        let device = crate::device_selection::select_optimal_rdma_device(Some(normalized_target))
-            .unwrap_or_else(RdmaDevice::default);
+        let select = || {
+            crate::device_selection::select_optimal_rdma_device(Some(normalized_target))
+                .unwrap_or_else(RdmaDevice::default)
+        };
+        let device = match crate::device_selection::rdma_device_from_env() {
+            Some(Ok(device)) => device,
+            Some(Err(e)) => {
+                tracing::error!("{}; selecting a device for {} instead", e, target);
+                select()
+            }
+            None => select(),
+        };
+
+        let defaults = Self::default();
+        let port_num = match link_layer_preference {
+            Some(preference) => {
+                let ports: Vec<PortAttr> = device.ports().iter().map(PortAttr::from).collect();
+                crate::device_selection::select_port(&ports, preference)
+                    .unwrap_or(defaults.port_num)
+            }
+            None => defaults.port_num,
+        };
 
         Self {
             device,
-            ..Default::default()
+            port_num,
+            link_layer_preference,
+            cuda_device: normalized_target
+                .strip_prefix("cuda:")
+                .and_then(|ordinal| ordinal.parse().ok()),
+            use_gpu_direct: GpuDirectMode::from_env()
+                .forced()
+                .unwrap_or(defaults.use_gpu_direct),
+            ..defaults
         }
     }
 }
 
+/// Returns `requested` limited to the device maximum `max`, warning if it was reduced.
+///
+/// A non-positive `max` means the device did not report a limit, so `requested` is kept.
+fn clamp_to_device_limit<T>(name: &str, requested: T, max: i32) -> T
+where
+    T: TryFrom<i32> + PartialOrd + Copy + fmt::Display,
+{
+    match T::try_from(max) {
+        Ok(max) if max > 0 && requested > max => {
+            tracing::warn!(
+                "{} ({}) exceeds the device limit, clamping to {}",
+                name,
+                requested,
+                max
+            );
+            max
+        }
+        _ => requested,
+    }
+}
+
 impl std::fmt::Display for IbverbsConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "IbverbsConfig {{ device: {}, port_num: {}, gid_index: {}, max_send_wr: {}, max_recv_wr: {}, max_send_sge: {}, max_recv_sge: {}, path_mtu: {:?}, retry_cnt: {}, rnr_retry: {}, qp_timeout: {}, min_rnr_timer: {}, max_dest_rd_atomic: {}, max_rd_atomic: {}, pkey_index: {}, psn: 0x{:x} }}",
+            "IbverbsConfig {{ device: {}, port_num: {}, gid_index: {}, max_send_wr: {}, max_recv_wr: {}, max_send_sge: {}, max_recv_sge: {}, path_mtu: {:?}, retry_cnt: {}, rnr_retry: {}, qp_timeout: {}, min_rnr_timer: {}, max_dest_rd_atomic: {}, max_rd_atomic: {}, pkey_index: {}, psn: 0x{:x}, provider: {:?}, cuda_device: {:?}, poll_strategy: {:?}, signal_every_n: {}, max_transfer_chunk: {}, num_qps: {}, link_layer_preference: {:?}, send_cq_entries: {}, recv_cq_entries: {}, local_copy: {}, poll_batch_size: {} }}",
             self.device.name(),
             self.port_num,
             self.gid_index,
@@ -254,6 +631,17 @@ impl std::fmt::Display for IbverbsConfig {
             self.max_rd_atomic,
             self.pkey_index,
             self.psn,
+            self.provider,
+            self.cuda_device,
+            self.poll_strategy,
+            self.signal_every_n,
+            self.max_transfer_chunk,
+            self.num_qps,
+            self.link_layer_preference,
+            self.send_cq_entries(),
+            self.recv_cq_entries(),
+            self.local_copy,
+            self.poll_batch_size,
         )
     }
 }
@@ -296,6 +684,8 @@ pub struct RdmaDevice {
     max_qp: i32,
     /// `max_cq` - Maximum number of completion queues supported.
     max_cq: i32,
+    /// `max_cqe` - Maximum number of entries per completion queue.
+    max_cqe: i32,
     /// `max_mr` - Maximum number of memory regions supported.
     max_mr: i32,
     /// `max_pd` - Maximum number of protection domains supported.
@@ -372,6 +762,11 @@ impl RdmaDevice {
         self.max_pd
     }
 
+    /// Returns the maximum number of entries per completion queue supported by the RDMA device.
+    pub fn max_cqe(&self) -> i32 {
+        self.max_cqe
+    }
+
     /// Returns the maximum number of work requests per queue pair supported by the RDMA device.
     pub fn max_qp_wr(&self) -> i32 {
         self.max_qp_wr
@@ -416,6 +811,8 @@ pub struct RdmaPort {
     capability_mask: u32,
     /// `link_layer` - The link layer type (e.g., InfiniBand, Ethernet).
     link_layer: String,
+    /// `active_mtu` - The active MTU of the port in bytes (0 if unknown).
+    active_mtu: u32,
     /// `gid` - Global Identifier for the port.
     gid: String,
     /// `gid_tbl_len` - Length of the GID table.
@@ -433,6 +830,7 @@ impl fmt::Display for RdmaDevice {
         writeln!(f, "\tVendor part ID: {}", self.vendor_part_id)?;
         writeln!(f, "\tMax QPs: {}", self.max_qp)?;
         writeln!(f, "\tMax CQs: {}", self.max_cq)?;
+        writeln!(f, "\tMax CQEs: {}", self.max_cqe)?;
         writeln!(f, "\tMax MRs: {}", self.max_mr)?;
         writeln!(f, "\tMax PDs: {}", self.max_pd)?;
         writeln!(f, "\tMax QP WRs: {}", self.max_qp_wr)?;
@@ -446,6 +844,28 @@ impl fmt::Display for RdmaDevice {
     }
 }
 
+impl RdmaPort {
+    /// Returns the physical port number on the device.
+    pub fn port_num(&self) -> u8 {
+        self.port_num
+    }
+
+    /// Returns the current state of the port.
+    pub fn state(&self) -> &String {
+        &self.state
+    }
+
+    /// Returns the link layer type of the port (e.g., InfiniBand, Ethernet).
+    pub fn link_layer(&self) -> &String {
+        &self.link_layer
+    }
+
+    /// Returns the active MTU of the port in bytes, or 0 if unknown.
+    pub fn active_mtu(&self) -> u32 {
+        self.active_mtu
+    }
+}
+
 impl fmt::Display for RdmaPort {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "\tPort {}:", self.port_num)?;
@@ -456,6 +876,7 @@ impl fmt::Display for RdmaPort {
         writeln!(f, "\t\tSM lid: {}", self.sm_lid)?;
         writeln!(f, "\t\tCapability mask: 0x{:08x}", self.capability_mask)?;
         writeln!(f, "\t\tLink layer: {}", self.link_layer)?;
+        writeln!(f, "\t\tActive MTU: {}", self.active_mtu)?;
         writeln!(f, "\t\tGID: {}", self.gid)?;
         writeln!(f, "\t\tGID table length: {}", self.gid_tbl_len)?;
         Ok(())
@@ -521,6 +942,26 @@ pub fn get_link_layer_str(link_layer: u8) -> String {
     }
 }
 
+/// Converts the given MTU enum value to its size in bytes.
+///
+/// # Arguments
+///
+/// * `mtu` - The MTU as defined by `ffi::ibv_mtu`.
+///
+/// # Returns
+///
+/// The MTU in bytes, or 0 if the value is not a known MTU.
+pub fn mtu_to_bytes(mtu: rdmaxcel_sys::ibv_mtu) -> u32 {
+    match mtu {
+        rdmaxcel_sys::IBV_MTU_256 => 256,
+        rdmaxcel_sys::IBV_MTU_512 => 512,
+        rdmaxcel_sys::IBV_MTU_1024 => 1024,
+        rdmaxcel_sys::IBV_MTU_2048 => 2048,
+        rdmaxcel_sys::IBV_MTU_4096 => 4096,
+        _ => 0,
+    }
+}
+
 /// Formats a GID (Global Identifier) into a human-readable string.
 ///
 /// # Arguments
@@ -609,6 +1050,7 @@ pub fn get_all_devices() -> Vec<RdmaDevice> {
                 ports: Vec::new(),
                 max_qp: device_attr.max_qp,
                 max_cq: device_attr.max_cq,
+                max_cqe: device_attr.max_cqe,
                 max_mr: device_attr.max_mr,
                 max_pd: device_attr.max_pd,
                 max_qp_wr: device_attr.max_qp_wr,
@@ -646,6 +1088,7 @@ pub fn get_all_devices() -> Vec<RdmaDevice> {
                     sm_lid: port_attr.sm_lid,
                     capability_mask: port_attr.port_cap_flags,
                     link_layer,
+                    active_mtu: mtu_to_bytes(port_attr.active_mtu),
                     gid: gid_str,
                     gid_tbl_len: port_attr.gid_tbl_len,
                 };
@@ -663,6 +1106,174 @@ pub fn get_all_devices() -> Vec<RdmaDevice> {
     devices
 }
 
+/// Queue and work request limits of an RDMA device, as reported by
+/// `ibv_query_device`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceCaps {
+    /// `max_qp_wr` - Maximum number of outstanding work requests per queue.
+    pub max_qp_wr: i32,
+    /// `max_cqe` - Maximum number of entries per completion queue.
+    pub max_cqe: i32,
+    /// `max_sge` - Maximum number of scatter/gather elements per work request.
+    pub max_sge: i32,
+    /// `max_inline_data` - Maximum inline data size in bytes, if known. Verbs only
+    /// reports this per queue pair (in `ibv_qp_cap`), so `ibv_query_device` leaves it `None`.
+    pub max_inline_data: Option<u32>,
+}
+
+impl From<&rdmaxcel_sys::ibv_device_attr> for DeviceCaps {
+    fn from(attr: &rdmaxcel_sys::ibv_device_attr) -> Self {
+        Self {
+            max_qp_wr: attr.max_qp_wr,
+            max_cqe: attr.max_cqe,
+            max_sge: attr.max_sge,
+            max_inline_data: None,
+        }
+    }
+}
+
+/// State, link layer and MTU of a port, as reported by `ibv_query_port`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortAttr {
+    /// `port_num` - The physical port number on the device.
+    pub port_num: u8,
+    /// `state` - The current state of the port (e.g., "PORT_ACTIVE").
+    pub state: String,
+    /// `link_layer` - The link layer type (e.g., InfiniBand, Ethernet).
+    pub link_layer: String,
+    /// `active_mtu` - The active MTU of the port in bytes (0 if unknown).
+    pub active_mtu: u32,
+}
+
+impl From<&RdmaPort> for PortAttr {
+    fn from(port: &RdmaPort) -> Self {
+        Self {
+            port_num: port.port_num,
+            state: port.state.clone(),
+            link_layer: port.link_layer.clone(),
+            active_mtu: port.active_mtu,
+        }
+    }
+}
+
+impl PortAttr {
+    /// Converts the raw attributes of port `port_num`.
+    pub fn from_raw(port_num: u8, attr: &rdmaxcel_sys::ibv_port_attr) -> Self {
+        Self {
+            port_num,
+            state: get_port_state_str(attr.state),
+            link_layer: get_link_layer_str(attr.link_layer),
+            active_mtu: mtu_to_bytes(attr.active_mtu),
+        }
+    }
+}
+
+/// Queries the capabilities of the device behind `context`.
+///
+/// `context` must be null or an open device context, e.g. from `ibv_open_device`.
+///
+/// # Errors
+///
+/// * `Err(RdmaError::Device)` - `context` is null or `ibv_query_device` failed
+pub fn query_device(context: *mut rdmaxcel_sys::ibv_context) -> Result<DeviceCaps, RdmaError> {
+    if context.is_null() {
+        return Err(RdmaError::Device(
+            "cannot query device: null context".to_string(),
+        ));
+    }
+    let mut attr = rdmaxcel_sys::ibv_device_attr::default();
+    // SAFETY: `context` is a non-null open device context and `attr` outlives the call.
+    let ret = unsafe { rdmaxcel_sys::ibv_query_device(context, &mut attr) };
+    if ret != 0 {
+        return Err(RdmaError::Device(format!(
+            "ibv_query_device failed: {}",
+            std::io::Error::from_raw_os_error(ret)
+        )));
+    }
+    Ok(DeviceCaps::from(&attr))
+}
+
+/// Queries the attributes of port `port_num` (1-based) of the device behind `context`.
+///
+/// `context` must be null or an open device context, e.g. from `ibv_open_device`.
+///
+/// # Errors
+///
+/// * `Err(RdmaError::Device)` - `context` is null or `ibv_query_port` failed, e.g.
+///   because the port does not exist
+pub fn query_port(
+    context: *mut rdmaxcel_sys::ibv_context,
+    port_num: u8,
+) -> Result<PortAttr, RdmaError> {
+    if context.is_null() {
+        return Err(RdmaError::Device(
+            "cannot query port: null context".to_string(),
+        ));
+    }
+    let mut attr = rdmaxcel_sys::ibv_port_attr::default();
+    // SAFETY: `context` is a non-null open device context and `attr` outlives the call.
+    let ret = unsafe {
+        rdmaxcel_sys::ibv_query_port(
+            context,
+            port_num,
+            &mut attr as *mut rdmaxcel_sys::ibv_port_attr as *mut _,
+        )
+    };
+    if ret != 0 {
+        return Err(RdmaError::Device(format!(
+            "ibv_query_port failed for port {}: {}",
+            port_num,
+            std::io::Error::from_raw_os_error(ret)
+        )));
+    }
+    Ok(PortAttr::from_raw(port_num, &attr))
+}
+
+/// Summary of the RDMA device and port that RDMA transfers use by default.
+///
+/// Intended for diagnostics, e.g. to check from Python that the expected NIC
+/// is active and negotiated the expected link layer and MTU.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RdmaDeviceInfo {
+    /// `name` - The name of the RDMA device (e.g., "mlx5_0").
+    pub name: String,
+    /// `port_num` - The physical port number that was queried.
+    pub port_num: u8,
+    /// `port_state` - The current state of the port (e.g., "PORT_ACTIVE").
+    pub port_state: String,
+    /// `link_layer` - The link layer type (e.g., InfiniBand, Ethernet).
+    pub link_layer: String,
+    /// `active_mtu` - The active MTU of the port in bytes (0 if unknown).
+    pub active_mtu: u32,
+}
+
+/// Returns information about the RDMA device that is selected by default,
+/// queried on the default port of `IbverbsConfig`.
+///
+/// Uses the same selection logic as `RdmaDevice::default()`, but returns an
+/// error instead of panicking when no RDMA device is present.
+pub fn rdma_device_info() -> Result<RdmaDeviceInfo, anyhow::Error> {
+    let device = crate::device_selection::select_optimal_rdma_device(Some("cpu:0"))
+        .or_else(|| get_all_devices().into_iter().next())
+        .ok_or_else(|| anyhow::anyhow!("no RDMA devices found"))?;
+    // Matches the default `IbverbsConfig::port_num`.
+    let port_num = 1;
+    let port = device
+        .ports()
+        .iter()
+        .find(|port| port.port_num() == port_num)
+        .ok_or_else(|| {
+            anyhow::anyhow!("port {} not found on device {}", port_num, device.name())
+        })?;
+    Ok(RdmaDeviceInfo {
+        name: device.name().clone(),
+        port_num,
+        port_state: port.state().clone(),
+        link_layer: port.link_layer().clone(),
+        active_mtu: port.active_mtu(),
+    })
+}
+
 /// Cached result of mlx5dv support check.
 static MLX5DV_SUPPORTED_CACHE: OnceLock<bool> = OnceLock::new();
 
@@ -883,6 +1494,103 @@ impl std::fmt::Debug for RdmaQpInfo {
     }
 }
 
+/// Plain connection parameters for a queue pair, for out-of-band exchange.
+///
+/// Unlike `RdmaQpInfo`, every field is a fixed-size value, which makes this type easy to
+/// ship over any side channel (a file, a socket, an actor message). An all-zero `gid`
+/// denotes LID-based routing, equivalent to an `RdmaQpInfo` without a GID.
+///
+/// It serializes as its `to_bytes` form, with every integer in network byte order, so
+/// hosts of different endianness agree on `qp_num`, `lid` and `psn` whichever serde
+/// format carries it.
+#[derive(
+    Debug,
+    Default,
+    Named,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize
+)]
+#[serde(
+    into = "[u8; QP_CONNECTION_INFO_LEN]",
+    from = "[u8; QP_CONNECTION_INFO_LEN]"
+)]
+pub struct QpConnectionInfo {
+    /// `qp_num` - Queue Pair Number, uniquely identifies a queue pair on the remote device
+    pub qp_num: u32,
+    /// `lid` - Local Identifier, used for addressing in InfiniBand subnet
+    pub lid: u16,
+    /// `gid` - Raw Global Identifier bytes, all zeros when routing by LID
+    pub gid: [u8; 16],
+    /// `psn` - Packet Sequence Number, used for ordering packets
+    pub psn: u32,
+}
+
+/// Length of `QpConnectionInfo`'s wire form.
+pub const QP_CONNECTION_INFO_LEN: usize = 26;
+
+impl QpConnectionInfo {
+    /// Encodes the connection info as `qp_num`, `lid`, `gid` and `psn`, in that order,
+    /// with the integers big-endian (network byte order).
+    pub fn to_bytes(&self) -> [u8; QP_CONNECTION_INFO_LEN] {
+        let mut bytes = [0u8; QP_CONNECTION_INFO_LEN];
+        bytes[0..4].copy_from_slice(&self.qp_num.to_be_bytes());
+        bytes[4..6].copy_from_slice(&self.lid.to_be_bytes());
+        bytes[6..22].copy_from_slice(&self.gid);
+        bytes[22..26].copy_from_slice(&self.psn.to_be_bytes());
+        bytes
+    }
+
+    /// Decodes connection info produced by `to_bytes` on any host.
+    pub fn from_bytes(bytes: &[u8; QP_CONNECTION_INFO_LEN]) -> Self {
+        let mut gid = [0u8; 16];
+        gid.copy_from_slice(&bytes[6..22]);
+        Self {
+            qp_num: u32::from_be_bytes(bytes[0..4].try_into().unwrap()),
+            lid: u16::from_be_bytes(bytes[4..6].try_into().unwrap()),
+            gid,
+            psn: u32::from_be_bytes(bytes[22..26].try_into().unwrap()),
+        }
+    }
+}
+
+impl From<QpConnectionInfo> for [u8; QP_CONNECTION_INFO_LEN] {
+    fn from(info: QpConnectionInfo) -> Self {
+        info.to_bytes()
+    }
+}
+
+impl From<[u8; QP_CONNECTION_INFO_LEN]> for QpConnectionInfo {
+    fn from(bytes: [u8; QP_CONNECTION_INFO_LEN]) -> Self {
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl From<RdmaQpInfo> for QpConnectionInfo {
+    fn from(info: RdmaQpInfo) -> Self {
+        Self {
+            qp_num: info.qp_num,
+            lid: info.lid,
+            gid: info.gid.map(|gid| gid.raw).unwrap_or_default(),
+            psn: info.psn,
+        }
+    }
+}
+
+impl From<QpConnectionInfo> for RdmaQpInfo {
+    fn from(info: QpConnectionInfo) -> Self {
+        Self {
+            qp_num: info.qp_num,
+            lid: info.lid,
+            gid: (info.gid != [0u8; 16]).then_some(Gid { raw: info.gid }),
+            psn: info.psn,
+        }
+    }
+}
+
 /// Wrapper around ibv_wc (ibverbs work completion).
 ///
 /// This exposes only the public fields of rdmaxcel_sys::ibv_wc, allowing us to more easily
@@ -958,6 +1666,41 @@ impl IbvWc {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_gpu_direct_mode_parse() {
+        assert_eq!(GpuDirectMode::parse("0").unwrap(), GpuDirectMode::Off);
+        assert_eq!(GpuDirectMode::parse("1").unwrap(), GpuDirectMode::On);
+        assert_eq!(GpuDirectMode::parse("auto").unwrap(), GpuDirectMode::Auto);
+        assert_eq!(GpuDirectMode::parse(" AUTO ").unwrap(), GpuDirectMode::Auto);
+        assert!(matches!(
+            GpuDirectMode::parse("yes"),
+            Err(RdmaError::InvalidConfig(_))
+        ));
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 10)]
+    async fn test_gpu_direct_override_beats_detection() {
+        use std::sync::atomic::AtomicBool;
+        use std::sync::atomic::Ordering;
+
+        // Forcing wins over detection in both directions, without running detection.
+        let detected = AtomicBool::new(false);
+        let detect = |result: bool| {
+            let detected = &detected;
+            async move {
+                detected.store(true, Ordering::SeqCst);
+                result
+            }
+        };
+        assert!(!GpuDirectMode::Off.resolve(detect(true)).await);
+        assert!(GpuDirectMode::On.resolve(detect(false)).await);
+        assert!(!detected.load(Ordering::SeqCst));
+
+        // Auto follows detection.
+        assert!(GpuDirectMode::Auto.resolve(async { true }).await);
+        assert!(!GpuDirectMode::Auto.resolve(async { false }).await);
+    }
+
     #[test]
     fn test_get_all_devices() {
         // Skip test if RDMA devices are not available
@@ -975,6 +1718,89 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rdma_device_info() {
+        let devices = get_all_devices();
+        if devices.is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            assert!(rdma_device_info().is_err());
+            return;
+        }
+        let info = rdma_device_info().expect("failed to query RDMA device info");
+        assert!(
+            devices.iter().any(|device| device.name() == &info.name),
+            "{} is not an enumerated device",
+            info.name
+        );
+        assert_eq!(info.port_num, 1);
+        assert!(!info.port_state.is_empty());
+        assert!(["InfiniBand", "Ethernet", "Unknown"].contains(&info.link_layer.as_str()));
+        assert!([0, 256, 512, 1024, 2048, 4096].contains(&info.active_mtu));
+    }
+
+    #[test]
+    fn test_caps_from_raw_attrs() {
+        let device_attr = rdmaxcel_sys::ibv_device_attr {
+            max_qp_wr: 32768,
+            max_cqe: 4194303,
+            max_sge: 30,
+            ..Default::default()
+        };
+        assert_eq!(
+            DeviceCaps::from(&device_attr),
+            DeviceCaps {
+                max_qp_wr: 32768,
+                max_cqe: 4194303,
+                max_sge: 30,
+                max_inline_data: None,
+            }
+        );
+
+        let port_attr = rdmaxcel_sys::ibv_port_attr {
+            state: rdmaxcel_sys::ibv_port_state::IBV_PORT_ACTIVE,
+            link_layer: 2,
+            active_mtu: rdmaxcel_sys::IBV_MTU_1024,
+            ..Default::default()
+        };
+        let port = PortAttr::from_raw(1, &port_attr);
+        assert_eq!(port.port_num, 1);
+        assert_eq!(port.state, get_port_state_str(port_attr.state));
+        assert_eq!(port.link_layer, "Ethernet");
+        assert_eq!(port.active_mtu, 1024);
+
+        assert!(query_device(std::ptr::null_mut()).is_err());
+        assert!(query_port(std::ptr::null_mut(), 1).is_err());
+    }
+
+    #[test]
+    fn test_query_device_and_port() {
+        if get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+        // SAFETY: We are calling several C functions from libibverbs.
+        unsafe {
+            let mut num_devices = 0;
+            let device_list = rdmaxcel_sys::ibv_get_device_list(&mut num_devices);
+            assert!(!device_list.is_null() && num_devices > 0);
+            let context = rdmaxcel_sys::ibv_open_device(*device_list);
+            assert!(!context.is_null());
+
+            let caps = query_device(context);
+            let port = query_port(context, 1);
+            rdmaxcel_sys::ibv_close_device(context);
+            rdmaxcel_sys::ibv_free_device_list(device_list);
+
+            let caps = caps.expect("failed to query device");
+            assert!(caps.max_qp_wr > 0);
+            assert!(caps.max_cqe > 0);
+            assert!(caps.max_sge > 0);
+            let port = port.expect("failed to query port 1");
+            assert_eq!(port.port_num, 1);
+            assert!(!port.state.is_empty());
+        }
+    }
+
     #[test]
     fn test_first_available() {
         // Skip test if RDMA is not available
@@ -995,6 +1821,7 @@ mod tests {
         assert_eq!(dev.node_guid(), dev.node_guid);
         assert_eq!(dev.max_qp(), dev.max_qp);
         assert_eq!(dev.max_cq(), dev.max_cq);
+        assert_eq!(dev.max_cqe(), dev.max_cqe);
         assert_eq!(dev.max_mr(), dev.max_mr);
         assert_eq!(dev.max_pd(), dev.max_pd);
         assert_eq!(dev.max_qp_wr(), dev.max_qp_wr);
@@ -1070,6 +1897,222 @@ mod tests {
         assert!(debug_str.contains("psn: 0x5678"));
     }
 
+    #[test]
+    fn test_clamp_to_device_limit() {
+        // An over-large depth is clamped to the device limit.
+        assert_eq!(clamp_to_device_limit("cq_entries", 1 << 24, 4096), 4096);
+        assert_eq!(
+            clamp_to_device_limit("max_send_wr", 1u32 << 20, 8192),
+            8192u32
+        );
+        // A valid depth is applied as-is.
+        assert_eq!(clamp_to_device_limit("cq_entries", 1024, 4096), 1024);
+        assert_eq!(clamp_to_device_limit("max_recv_wr", 512u32, 8192), 512u32);
+        // A missing device limit leaves the requested depth untouched.
+        assert_eq!(clamp_to_device_limit("max_recv_wr", 512u32, 0), 512u32);
+    }
+
+    #[test]
+    fn test_clamp_to_device_limits() {
+        // Skip test if RDMA is not available
+        if get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let mut config = IbverbsConfig {
+            cq_entries: i32::MAX,
+            send_cq_depth: Some(i32::MAX),
+            recv_cq_depth: Some(64),
+            max_send_wr: u32::MAX,
+            max_recv_wr: 256,
+            ..Default::default()
+        };
+        config.clamp_to_device_limits();
+        assert_eq!(config.cq_entries, config.device.max_cqe());
+        assert_eq!(config.send_cq_entries(), config.device.max_cqe());
+        assert_eq!(config.recv_cq_entries(), 64);
+        assert_eq!(config.max_send_wr, config.device.max_qp_wr() as u32);
+        assert_eq!(config.max_recv_wr, 256);
+    }
+
+    #[test]
+    fn test_validate_reliability() {
+        let config = IbverbsConfig {
+            psn: 0xffffff,
+            retry_cnt: 7,
+            rnr_retry: 7,
+            qp_timeout: 31,
+            min_rnr_timer: 31,
+            ..Default::default()
+        };
+        assert!(config.validate_reliability().is_ok());
+        assert!(IbverbsConfig::default().validate_reliability().is_ok());
+
+        let invalid = [
+            IbverbsConfig {
+                psn: 0x1000000,
+                ..config.clone()
+            },
+            IbverbsConfig {
+                retry_cnt: 8,
+                ..config.clone()
+            },
+            IbverbsConfig {
+                rnr_retry: 8,
+                ..config.clone()
+            },
+            IbverbsConfig {
+                qp_timeout: 32,
+                ..config.clone()
+            },
+            IbverbsConfig {
+                min_rnr_timer: 32,
+                ..config.clone()
+            },
+        ];
+        for config in invalid {
+            assert!(matches!(
+                config.validate_reliability(),
+                Err(RdmaError::InvalidConfig(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_validate_num_qps() {
+        let config = IbverbsConfig {
+            num_qps: 2,
+            ..Default::default()
+        };
+        assert!(config.validate_num_qps().is_ok());
+        assert!(IbverbsConfig::default().validate_num_qps().is_ok());
+
+        for num_qps in [0, config.device.max_qp() as usize + 1] {
+            let config = IbverbsConfig {
+                num_qps,
+                ..config.clone()
+            };
+            assert!(matches!(
+                config.validate_num_qps(),
+                Err(RdmaError::InvalidConfig(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_targeting_cuda_sets_cuda_device() {
+        // Skip test if RDMA devices are not available
+        if get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        assert_eq!(IbverbsConfig::targeting("cuda").cuda_device, Some(0));
+        assert_eq!(IbverbsConfig::targeting("cpu:0").cuda_device, None);
+        let config = IbverbsConfig::targeting("cuda:1");
+        assert_eq!(config.cuda_device, Some(1));
+
+        // A buffer on another device is rejected at registration.
+        let err = crate::rdma_components::validate_buffer_device(
+            0x1000,
+            4096,
+            crate::rdma_components::BufferMemoryType::Device(0),
+            config.cuda_device.unwrap(),
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("expects CUDA device 1"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_validate_poll_batch_size() {
+        let config = IbverbsConfig {
+            recv_cq_depth: Some(16),
+            poll_batch_size: 16,
+            ..Default::default()
+        };
+        assert!(config.validate_poll_batch_size().is_ok());
+        assert!(IbverbsConfig::default().validate_poll_batch_size().is_ok());
+
+        for poll_batch_size in [0, 17] {
+            let config = IbverbsConfig {
+                poll_batch_size,
+                ..config.clone()
+            };
+            assert!(matches!(
+                config.validate_poll_batch_size(),
+                Err(RdmaError::InvalidConfig(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_qp_connection_info_round_trip() {
+        let mut gid = [0u8; 16];
+        gid[10] = 0xff;
+        gid[11] = 0xff;
+        gid[12..].copy_from_slice(&[10, 0, 0, 1]);
+        let info = QpConnectionInfo {
+            qp_num: 42,
+            lid: 123,
+            gid,
+            psn: 0x5678,
+        };
+
+        let serialized = hyperactor::data::Serialized::serialize(&info).unwrap();
+        let deserialized: QpConnectionInfo = serialized.deserialized().unwrap();
+        assert_eq!(info, deserialized);
+
+        // Converting through RdmaQpInfo preserves the GID...
+        let qp_info = RdmaQpInfo::from(info);
+        assert!(qp_info.gid.is_some());
+        assert_eq!(QpConnectionInfo::from(qp_info), info);
+
+        // ...and an all-zero GID maps to LID-based routing.
+        let lid_only = RdmaQpInfo::from(QpConnectionInfo {
+            gid: [0u8; 16],
+            ..info
+        });
+        assert!(lid_only.gid.is_none());
+    }
+
+    #[test]
+    fn test_qp_connection_info_network_byte_order() {
+        let info = QpConnectionInfo {
+            qp_num: 0x0102_0304,
+            lid: 0x0506,
+            gid: [0xaa; 16],
+            psn: 0x00a0_b0c0,
+        };
+        let bytes = info.to_bytes();
+        // The most significant byte comes first, whatever the host's byte order.
+        assert_eq!(&bytes[0..4], &[0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(&bytes[4..6], &[0x05, 0x06]);
+        assert_eq!(&bytes[6..22], &[0xaa; 16]);
+        assert_eq!(&bytes[22..26], &[0x00, 0xa0, 0xb0, 0xc0]);
+        assert_eq!(QpConnectionInfo::from_bytes(&bytes), info);
+
+        // Bytes as a little-endian peer would have sent them without normalization
+        // decode to byte-swapped values, not to the original ones.
+        let mut swapped = bytes;
+        swapped[0..4].copy_from_slice(&info.qp_num.to_le_bytes());
+        swapped[4..6].copy_from_slice(&info.lid.to_le_bytes());
+        swapped[22..26].copy_from_slice(&info.psn.to_le_bytes());
+        let decoded = QpConnectionInfo::from_bytes(&swapped);
+        assert_eq!(decoded.qp_num, info.qp_num.swap_bytes());
+        assert_eq!(decoded.lid, info.lid.swap_bytes());
+        assert_eq!(decoded.psn, info.psn.swap_bytes());
+
+        // Serialization goes through the network byte order form.
+        let serialized = hyperactor::data::Serialized::serialize(&info).unwrap();
+        let as_bytes: [u8; QP_CONNECTION_INFO_LEN] = serialized.deserialized_unchecked().unwrap();
+        assert_eq!(as_bytes, bytes);
+    }
+
     #[test]
     fn test_ibv_wc() {
         let mut wc = rdmaxcel_sys::ibv_wc::default();
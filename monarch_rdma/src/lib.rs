@@ -9,17 +9,34 @@
 // RDMA requires frequent unsafe code blocks
 #![allow(clippy::undocumented_unsafe_blocks)]
 
+mod completion_dispatcher;
+mod device_guard;
 pub mod device_selection;
+pub mod half_precision;
 mod ibverbs_primitives;
+mod local_copy;
+mod multicast;
 mod rdma_components;
+mod rdma_error;
 mod rdma_manager_actor;
+mod rdma_transfer;
+mod segment_registry;
+mod self_test;
 
 #[macro_use]
 mod macros;
 
+pub use completion_dispatcher::*;
+pub use device_guard::*;
 pub use ibverbs_primitives::*;
+pub use local_copy::*;
+pub use multicast::*;
 pub use rdma_components::*;
+pub use rdma_error::*;
 pub use rdma_manager_actor::*;
+pub use rdma_transfer::*;
+pub use segment_registry::*;
+pub use self_test::*;
 pub use test_utils::is_cuda_available;
 
 /// Print comprehensive RDMA device information for debugging.
@@ -0,0 +1,155 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ * All rights reserved.
+ *
+ * This source code is licensed under the BSD-style license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! # Local Copy
+//!
+//! When both buffers of an `RdmaBuffer::read_into` or `write_from` were
+//! registered by `RdmaManagerActor`s in this process, the transfer does not
+//! need to go through the NIC at all. Actors with `IbverbsConfig::local_copy`
+//! enabled record each registration's virtual address here, and transfers
+//! between two recorded buffers become a plain `memcpy` (or `cuMemcpy` if
+//! either side is device memory). The copy has finished when it returns, so
+//! callers see the same completion semantics as the RDMA path.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use hyperactor::ActorId;
+
+use crate::device_guard::with_device;
+use crate::rdma_components::BufferMemoryType;
+use crate::rdma_components::RdmaBuffer;
+use crate::rdma_components::buffer_memory_type;
+use crate::rdma_error::RdmaError;
+
+/// A registered buffer's location in this process's address space.
+#[derive(Debug, Clone, Copy)]
+struct LocalRegion {
+    addr: usize,
+    size: usize,
+}
+
+/// Regions registered in this process, keyed by owning actor and registration ID.
+static LOCAL_REGIONS: LazyLock<Mutex<HashMap<(ActorId, usize), LocalRegion>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Number of transfers served by `try_local_copy`.
+static LOCAL_COPIES: AtomicU64 = AtomicU64::new(0);
+
+/// Records that `owner`'s registration `mr_id` covers `size` bytes at `addr` in
+/// this process.
+pub(crate) fn register_local_region(owner: &ActorId, mr_id: usize, addr: usize, size: usize) {
+    LOCAL_REGIONS
+        .lock()
+        .unwrap()
+        .insert((owner.clone(), mr_id), LocalRegion { addr, size });
+}
+
+/// Forgets `owner`'s registration `mr_id`.
+pub(crate) fn deregister_local_region(owner: &ActorId, mr_id: usize) {
+    LOCAL_REGIONS
+        .lock()
+        .unwrap()
+        .remove(&(owner.clone(), mr_id));
+}
+
+/// Forgets every registration made by `owner`.
+pub(crate) fn deregister_local_owner(owner: &ActorId) {
+    LOCAL_REGIONS
+        .lock()
+        .unwrap()
+        .retain(|(id, _), _| id != owner);
+}
+
+/// Returns how many transfers in this process were served by a local copy
+/// instead of the NIC.
+pub fn local_copy_count() -> u64 {
+    LOCAL_COPIES.load(Ordering::Relaxed)
+}
+
+fn local_region(buffer: &RdmaBuffer) -> Option<LocalRegion> {
+    LOCAL_REGIONS
+        .lock()
+        .unwrap()
+        .get(&(buffer.owner.actor_id().clone(), buffer.mr_id))
+        .copied()
+}
+
+/// Copies `len` bytes from `src` to `dst` if both were registered in this
+/// process.
+///
+/// # Returns
+///
+/// * `Ok(true)` - The bytes were copied and the copy has completed
+/// * `Ok(false)` - At least one buffer is not local; nothing was copied
+/// * `Err(RdmaError::SizeMismatch)` - `len` exceeds one of the buffers
+/// * `Err(RdmaError::Device)` - The CUDA copy failed
+pub(crate) fn try_local_copy(
+    src: &RdmaBuffer,
+    dst: &RdmaBuffer,
+    len: usize,
+) -> Result<bool, RdmaError> {
+    let (Some(src_region), Some(dst_region)) = (local_region(src), local_region(dst)) else {
+        return Ok(false);
+    };
+    if len > src_region.size || len > dst_region.size {
+        return Err(RdmaError::SizeMismatch {
+            required: len,
+            available: src_region.size.min(dst_region.size),
+        });
+    }
+
+    let device = match (
+        buffer_memory_type(src_region.addr),
+        buffer_memory_type(dst_region.addr),
+    ) {
+        (_, BufferMemoryType::Device(device)) | (BufferMemoryType::Device(device), _) => {
+            Some(device)
+        }
+        (BufferMemoryType::Host, BufferMemoryType::Host) => None,
+    };
+    match device {
+        // SAFETY: Both regions are registered, so they stay mapped until their owners
+        // release them, and `len` fits in each.
+        None => unsafe {
+            std::ptr::copy(
+                src_region.addr as *const u8,
+                dst_region.addr as *mut u8,
+                len,
+            );
+        },
+        Some(device) => with_device(device, || {
+            // SAFETY: As above; unified addressing lets `cuMemcpy` infer the direction.
+            let result = unsafe {
+                rdmaxcel_sys::rdmaxcel_cuMemcpy(
+                    dst_region.addr as rdmaxcel_sys::CUdeviceptr,
+                    src_region.addr as rdmaxcel_sys::CUdeviceptr,
+                    len,
+                )
+            };
+            if result != rdmaxcel_sys::CUDA_SUCCESS {
+                return Err(RdmaError::Device(format!("cuMemcpy failed: {:?}", result)));
+            }
+            // Device-to-device copies may return early; wait so the copy is complete.
+            // SAFETY: `with_device` keeps a context current on this thread.
+            let result = unsafe { rdmaxcel_sys::rdmaxcel_cuCtxSynchronize() };
+            if result != rdmaxcel_sys::CUDA_SUCCESS {
+                return Err(RdmaError::Device(format!(
+                    "cuCtxSynchronize failed: {:?}",
+                    result
+                )));
+            }
+            Ok(())
+        })??,
+    }
+    LOCAL_COPIES.fetch_add(1, Ordering::Relaxed);
+    Ok(true)
+}
@@ -9,14 +9,17 @@
 #[macro_export]
 macro_rules! cu_check {
     ($result:expr) => {
-        if $result != rdmaxcel_sys::CUDA_SUCCESS {
+        // Evaluate the call exactly once; re-evaluating it to build the error message
+        // would re-issue non-idempotent calls (e.g. cuMemMap) and report the wrong error.
+        let result = $result;
+        if result != rdmaxcel_sys::CUDA_SUCCESS {
             let mut error_string: *const std::os::raw::c_char = std::ptr::null();
-            rdmaxcel_sys::rdmaxcel_cuGetErrorString($result, &mut error_string);
+            rdmaxcel_sys::rdmaxcel_cuGetErrorString(result, &mut error_string);
             panic!(
                 "cuda failure {}:{} {:?} '{}'",
                 file!(),
                 line!(),
-                $result,
+                result,
                 std::ffi::CStr::from_ptr(error_string).to_string_lossy()
             );
         }
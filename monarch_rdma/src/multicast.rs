@@ -0,0 +1,558 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ * All rights reserved.
+ *
+ * This source code is licensed under the BSD-style license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! # Multicast
+//!
+//! Connected (RC) queue pairs move data to exactly one peer, so broadcasting to N
+//! peers costs N transfers from the sender's NIC. A `MulticastQueuePair` is an
+//! unreliable datagram (UD) queue pair instead: receivers attach it to a multicast
+//! group with `join_multicast`, and one `send_multicast` to the group's GID is
+//! replicated by the fabric to every attached queue pair.
+//!
+//! ## Unreliable semantics
+//!
+//! UD gives no delivery guarantee. A datagram is silently dropped if a receiver has
+//! no receive buffer posted, if the fabric is congested, or if the group is not
+//! routed to the receiver, and the sender is never told. Datagrams arrive whole or
+//! not at all and are limited to one MTU. Callers that need every byte must detect
+//! loss themselves (e.g. with sequence numbers) and recover over a reliable
+//! transfer.
+//!
+//! Groups are addressed by GID with multicast LID 0, which is all RoCE needs. On
+//! InfiniBand the group must already be joined with the subnet manager so that it
+//! is routed; that join is outside the scope of this module.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::Error;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::ibverbs_primitives::Gid;
+use crate::ibverbs_primitives::IbverbsConfig;
+use crate::ibverbs_primitives::RdmaQpType;
+use crate::ibverbs_primitives::mtu_to_bytes;
+use crate::rdma_components::RdmaDomain;
+use crate::rdma_error::RdmaError;
+
+/// Bytes of Global Routing Header that precede every datagram in a UD receive buffer.
+const GRH_SIZE: usize = 40;
+
+/// Destination QP number that addresses every queue pair attached to a group.
+const MULTICAST_QPN: u32 = 0xff_ffff;
+
+/// Q_Key shared by all group members; UD receivers drop datagrams with another Q_Key.
+const MULTICAST_QKEY: u32 = 0x1111_1111;
+
+/// Receive buffers kept posted, bounded by the queue pair's `max_recv_wr`.
+const RECV_SLOTS: usize = 64;
+
+/// How long `send_multicast` waits for the NIC to report the send complete.
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An unreliable datagram queue pair for one-to-many broadcasts over a multicast group.
+///
+/// The queue pair owns its domain and a registered staging buffer: one slot for
+/// outgoing datagrams and one per posted receive. Data is copied in and out, so
+/// callers work with plain byte slices. See the module documentation for the
+/// delivery semantics.
+pub struct MulticastQueuePair {
+    qp: *mut rdmaxcel_sys::ibv_qp,
+    send_cq: *mut rdmaxcel_sys::ibv_cq,
+    recv_cq: *mut rdmaxcel_sys::ibv_cq,
+    mr: *mut rdmaxcel_sys::ibv_mr,
+    buffer: Box<[u8]>,
+    /// Size of each slot in `buffer`: the largest datagram plus room for the GRH.
+    slot_size: usize,
+    recv_slots: usize,
+    config: IbverbsConfig,
+    /// Groups this queue pair is attached to.
+    joined: HashSet<Gid>,
+    /// Address handles for groups this queue pair has sent to.
+    address_handles: HashMap<Gid, *mut rdmaxcel_sys::ibv_ah>,
+    // Dropped after the queue pair's resources have been destroyed in `Drop::drop`.
+    domain: RdmaDomain,
+}
+
+impl std::fmt::Debug for MulticastQueuePair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MulticastQueuePair")
+            .field("qp", &self.qp)
+            .field("max_datagram_size", &self.max_datagram_size())
+            .field("joined", &self.joined)
+            .finish()
+    }
+}
+
+impl MulticastQueuePair {
+    /// Creates a datagram queue pair on `config.device`, brings it to RTS and posts its
+    /// receive buffers.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(RdmaError::InvalidConfig)` - `config.qp_type` is not `UnreliableDatagram`
+    /// * `Err(RdmaError::Device)` - Creating or transitioning the queue pair failed
+    /// * `Err(RdmaError::Registration)` - Registering the staging buffer failed
+    pub fn new(mut config: IbverbsConfig) -> Result<Self, RdmaError> {
+        if config.qp_type != RdmaQpType::UnreliableDatagram {
+            return Err(RdmaError::InvalidConfig(format!(
+                "multicast requires qp_type UnreliableDatagram, got {:?}",
+                config.qp_type
+            )));
+        }
+        config.clamp_to_device_limits();
+        let domain = RdmaDomain::new(config.device.clone())?;
+
+        let mut max_datagram = mtu_to_bytes(config.path_mtu) as usize;
+        if let Some(port) = config
+            .device
+            .ports()
+            .iter()
+            .find(|port| port.port_num() == config.port_num)
+        {
+            if port.active_mtu() > 0 {
+                max_datagram = max_datagram.min(port.active_mtu() as usize);
+            }
+        }
+        let slot_size = max_datagram + GRH_SIZE;
+        let recv_slots = RECV_SLOTS.min(config.max_recv_wr as usize).max(1);
+
+        // SAFETY: The domain's context and PD are valid for its lifetime, and `domain`
+        // outlives the queue pair.
+        let qp = unsafe {
+            rdmaxcel_sys::create_qp(
+                domain.context,
+                domain.pd,
+                config.send_cq_entries(),
+                config.recv_cq_entries(),
+                config.max_send_wr.try_into().unwrap(),
+                recv_slots.try_into().unwrap(),
+                1,
+                1,
+                config.resolved_qp_type(),
+                std::ptr::null_mut(),
+            )
+        };
+        if qp.is_null() {
+            return Err(RdmaError::Device(format!(
+                "failed to create datagram queue pair: {}",
+                Error::last_os_error()
+            )));
+        }
+        // SAFETY: `qp` was just created and is non-null.
+        let (send_cq, recv_cq) = unsafe { ((*qp).send_cq, (*qp).recv_cq) };
+
+        let mut buffer = vec![0u8; slot_size * (recv_slots + 1)].into_boxed_slice();
+        let access = rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_LOCAL_WRITE;
+        // SAFETY: `buffer` is heap-allocated and owned by the queue pair, so it stays
+        // at the same address until the MR is deregistered in `Drop::drop`.
+        let mr = unsafe {
+            rdmaxcel_sys::ibv_reg_mr(
+                domain.pd,
+                buffer.as_mut_ptr() as *mut std::ffi::c_void,
+                buffer.len(),
+                access.0 as i32,
+            )
+        };
+
+        // From here on `Drop` releases whatever was created.
+        let mut queue_pair = MulticastQueuePair {
+            qp,
+            send_cq,
+            recv_cq,
+            mr,
+            buffer,
+            slot_size,
+            recv_slots,
+            config,
+            joined: HashSet::new(),
+            address_handles: HashMap::new(),
+            domain,
+        };
+        if mr.is_null() {
+            return Err(RdmaError::Registration(format!(
+                "ibv_reg_mr failed: {}",
+                Error::last_os_error()
+            )));
+        }
+        queue_pair.to_rts()?;
+        for slot in 1..=recv_slots {
+            queue_pair.post_recv(slot)?;
+        }
+        Ok(queue_pair)
+    }
+
+    /// Returns the largest payload `send_multicast` accepts, in bytes.
+    pub fn max_datagram_size(&self) -> usize {
+        self.slot_size - GRH_SIZE
+    }
+
+    /// Returns the number of this queue pair, which receivers see as the source of its
+    /// datagrams.
+    pub fn qp_num(&self) -> u32 {
+        // SAFETY: `qp` is valid for the lifetime of `self`.
+        unsafe { (*self.qp).qp_num }
+    }
+
+    /// Attaches this queue pair to the multicast group `gid`, so datagrams sent to the
+    /// group are delivered to it. Joining a group twice is a no-op.
+    pub fn join_multicast(&mut self, gid: Gid) -> Result<(), RdmaError> {
+        if self.joined.contains(&gid) {
+            return Ok(());
+        }
+        // SAFETY: `qp` is valid and `gid` is a valid GID for the duration of the call.
+        let ret = unsafe { rdmaxcel_sys::ibv_attach_mcast(self.qp, gid.as_ref(), 0) };
+        if ret != 0 {
+            return Err(RdmaError::Device(format!(
+                "failed to join multicast group {:?}: {}",
+                gid,
+                Error::from_raw_os_error(ret)
+            )));
+        }
+        self.joined.insert(gid);
+        Ok(())
+    }
+
+    /// Detaches this queue pair from the multicast group `gid`. Leaving a group that
+    /// was not joined is a no-op.
+    pub fn leave_multicast(&mut self, gid: Gid) -> Result<(), RdmaError> {
+        if !self.joined.remove(&gid) {
+            return Ok(());
+        }
+        // SAFETY: `qp` is valid and attached to `gid`.
+        let ret = unsafe { rdmaxcel_sys::ibv_detach_mcast(self.qp, gid.as_ref(), 0) };
+        if ret != 0 {
+            return Err(RdmaError::Device(format!(
+                "failed to leave multicast group {:?}: {}",
+                gid,
+                Error::from_raw_os_error(ret)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Sends `data` as one datagram to every queue pair attached to the group `gid`.
+    ///
+    /// The sender does not need to join the group. Returns once the datagram has left
+    /// the NIC; that says nothing about whether any receiver got it.
+    ///
+    /// # Errors
+    ///
+    /// * `Err(RdmaError::InvalidConfig)` - `data` exceeds `max_datagram_size()`
+    /// * `Err(RdmaError::Device)` - Creating the address handle or posting failed
+    /// * `Err(RdmaError::CompletionStatus)` - The send completed with an error
+    /// * `Err(RdmaError::Timeout)` - The send did not complete within `SEND_TIMEOUT`
+    pub fn send_multicast(&mut self, gid: Gid, data: &[u8]) -> Result<(), RdmaError> {
+        if data.len() > self.max_datagram_size() {
+            return Err(RdmaError::InvalidConfig(format!(
+                "datagram of {} bytes exceeds the maximum of {}",
+                data.len(),
+                self.max_datagram_size()
+            )));
+        }
+        let ah = self.address_handle(gid)?;
+        // Slot 0 is reserved for sends and is reused once the previous send completed.
+        self.buffer[..data.len()].copy_from_slice(data);
+
+        // SAFETY: `qp`, `ah` and `mr` are valid for the lifetime of `self`, and slot 0
+        // of the registered buffer holds `data`.
+        unsafe {
+            let mut sge = rdmaxcel_sys::ibv_sge {
+                addr: self.buffer.as_ptr() as u64,
+                length: data.len() as u32,
+                lkey: (*self.mr).lkey,
+            };
+            let mut wr = rdmaxcel_sys::ibv_send_wr {
+                wr_id: 0,
+                next: std::ptr::null_mut(),
+                sg_list: &mut sge as *mut _,
+                num_sge: 1,
+                opcode: rdmaxcel_sys::ibv_wr_opcode::IBV_WR_SEND,
+                send_flags: rdmaxcel_sys::ibv_send_flags::IBV_SEND_SIGNALED.0,
+                wr: Default::default(),
+                qp_type: Default::default(),
+                __bindgen_anon_1: Default::default(),
+                __bindgen_anon_2: Default::default(),
+            };
+            wr.wr.ud.ah = ah;
+            wr.wr.ud.remote_qpn = MULTICAST_QPN;
+            wr.wr.ud.remote_qkey = MULTICAST_QKEY;
+            let mut bad_wr: *mut rdmaxcel_sys::ibv_send_wr = std::ptr::null_mut();
+            let ops = &mut (*(*self.qp).context).ops;
+            let errno = ops.post_send.as_mut().unwrap()(self.qp, &mut wr, &mut bad_wr);
+            if errno != 0 {
+                return Err(RdmaError::Device(format!(
+                    "failed to post multicast send: {}",
+                    Error::from_raw_os_error(errno)
+                )));
+            }
+        }
+        let deadline = Instant::now() + SEND_TIMEOUT;
+        loop {
+            if let Some(wc) = self.poll_cq(self.send_cq)? {
+                return match RdmaError::from_wc(&wc) {
+                    Some(err) => Err(err),
+                    None => Ok(()),
+                };
+            }
+            if Instant::now() >= deadline {
+                return Err(RdmaError::Timeout(SEND_TIMEOUT));
+            }
+            std::hint::spin_loop();
+        }
+    }
+
+    /// Returns the payload of the next datagram received from a joined group, or
+    /// `None` if none has arrived.
+    ///
+    /// Datagrams that arrived while every receive buffer was in use were dropped.
+    pub fn recv_multicast(&mut self) -> Result<Option<Vec<u8>>, RdmaError> {
+        let Some(wc) = self.poll_cq(self.recv_cq)? else {
+            return Ok(None);
+        };
+        let slot = wc.wr_id() as usize;
+        let result = match RdmaError::from_wc(&wc) {
+            Some(err) => Err(err),
+            None => {
+                let start = slot * self.slot_size + GRH_SIZE;
+                let len = wc.len().saturating_sub(GRH_SIZE);
+                Ok(Some(self.buffer[start..start + len].to_vec()))
+            }
+        };
+        self.post_recv(slot)?;
+        result
+    }
+
+    /// Brings the fresh queue pair from RESET to RTS. A datagram queue pair has no
+    /// peer, so RTR and RTS only need the state and send PSN.
+    fn to_rts(&mut self) -> Result<(), RdmaError> {
+        let mut init = rdmaxcel_sys::ibv_qp_attr {
+            qp_state: rdmaxcel_sys::ibv_qp_state::IBV_QPS_INIT,
+            pkey_index: self.config.pkey_index,
+            port_num: self.config.port_num,
+            qkey: MULTICAST_QKEY,
+            ..Default::default()
+        };
+        self.modify_qp(
+            &mut init,
+            rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_STATE
+                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_PKEY_INDEX
+                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_PORT
+                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_QKEY,
+            "INIT",
+        )?;
+        let mut rtr = rdmaxcel_sys::ibv_qp_attr {
+            qp_state: rdmaxcel_sys::ibv_qp_state::IBV_QPS_RTR,
+            ..Default::default()
+        };
+        self.modify_qp(
+            &mut rtr,
+            rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_STATE,
+            "RTR",
+        )?;
+        let mut rts = rdmaxcel_sys::ibv_qp_attr {
+            qp_state: rdmaxcel_sys::ibv_qp_state::IBV_QPS_RTS,
+            sq_psn: self.config.psn,
+            ..Default::default()
+        };
+        self.modify_qp(
+            &mut rts,
+            rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_STATE
+                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_SQ_PSN,
+            "RTS",
+        )
+    }
+
+    fn modify_qp(
+        &mut self,
+        qp_attr: &mut rdmaxcel_sys::ibv_qp_attr,
+        mask: rdmaxcel_sys::ibv_qp_attr_mask,
+        target: &str,
+    ) -> Result<(), RdmaError> {
+        // SAFETY: `qp` is valid and `qp_attr` is initialized for the duration of the call.
+        let errno = unsafe { rdmaxcel_sys::ibv_modify_qp(self.qp, qp_attr, mask.0 as i32) };
+        if errno != 0 {
+            return Err(RdmaError::Device(format!(
+                "failed to transition datagram QP to {}: {}",
+                target,
+                Error::from_raw_os_error(errno)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Posts receive slot `slot` of the staging buffer, using the slot as the work
+    /// request ID.
+    fn post_recv(&mut self, slot: usize) -> Result<(), RdmaError> {
+        // SAFETY: `qp` and `mr` are valid, and `slot` lies within the registered buffer.
+        unsafe {
+            let mut sge = rdmaxcel_sys::ibv_sge {
+                addr: self.buffer.as_ptr().add(slot * self.slot_size) as u64,
+                length: self.slot_size as u32,
+                lkey: (*self.mr).lkey,
+            };
+            let mut wr = rdmaxcel_sys::ibv_recv_wr {
+                wr_id: slot as u64,
+                sg_list: &mut sge as *mut _,
+                num_sge: 1,
+                ..Default::default()
+            };
+            let mut bad_wr: *mut rdmaxcel_sys::ibv_recv_wr = std::ptr::null_mut();
+            let ops = &mut (*(*self.qp).context).ops;
+            let errno = ops.post_recv.as_mut().unwrap()(self.qp, &mut wr, &mut bad_wr);
+            if errno != 0 {
+                return Err(RdmaError::Device(format!(
+                    "failed to post datagram receive: {}",
+                    Error::from_raw_os_error(errno)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Polls `cq` for a single completion.
+    fn poll_cq(
+        &mut self,
+        cq: *mut rdmaxcel_sys::ibv_cq,
+    ) -> Result<Option<rdmaxcel_sys::ibv_wc>, RdmaError> {
+        // SAFETY: `cq` belongs to this queue pair and `wc` is a valid destination for a
+        // single completion.
+        unsafe {
+            let ops = &mut (*(*self.qp).context).ops;
+            let mut wc = std::mem::MaybeUninit::<rdmaxcel_sys::ibv_wc>::zeroed().assume_init();
+            let ret = ops.poll_cq.as_mut().unwrap()(cq, 1, &mut wc);
+            if ret < 0 {
+                return Err(RdmaError::Device(format!(
+                    "failed to poll datagram CQ: {}",
+                    Error::last_os_error()
+                )));
+            }
+            Ok((ret > 0).then_some(wc))
+        }
+    }
+
+    /// Returns the address handle for sending to the group `gid`, creating it on first use.
+    fn address_handle(&mut self, gid: Gid) -> Result<*mut rdmaxcel_sys::ibv_ah, RdmaError> {
+        if let Some(ah) = self.address_handles.get(&gid) {
+            return Ok(*ah);
+        }
+        let mut ah_attr = rdmaxcel_sys::ibv_ah_attr {
+            is_global: 1,
+            port_num: self.config.port_num,
+            ..Default::default()
+        };
+        ah_attr.grh.dgid = gid.into();
+        ah_attr.grh.sgid_index = self.config.gid_index;
+        ah_attr.grh.hop_limit = 1;
+        // SAFETY: The PD is valid for the lifetime of `self` and `ah_attr` is initialized.
+        let ah = unsafe { rdmaxcel_sys::ibv_create_ah(self.domain.pd, &mut ah_attr) };
+        if ah.is_null() {
+            return Err(RdmaError::Device(format!(
+                "failed to create address handle for multicast group {:?}: {}",
+                gid,
+                Error::last_os_error()
+            )));
+        }
+        self.address_handles.insert(gid, ah);
+        Ok(ah)
+    }
+}
+
+impl Drop for MulticastQueuePair {
+    fn drop(&mut self) {
+        // SAFETY: Every resource was created by `new` or on demand, is exclusively owned by
+        // this struct and is destroyed exactly once, before the domain.
+        unsafe {
+            for gid in self.joined.drain() {
+                rdmaxcel_sys::ibv_detach_mcast(self.qp, gid.as_ref(), 0);
+            }
+            for (_, ah) in self.address_handles.drain() {
+                rdmaxcel_sys::ibv_destroy_ah(ah);
+            }
+            let ret = rdmaxcel_sys::ibv_destroy_qp(self.qp);
+            if ret != 0 {
+                tracing::warn!("ibv_destroy_qp returned {}", ret);
+            }
+            rdmaxcel_sys::ibv_destroy_cq(self.send_cq);
+            rdmaxcel_sys::ibv_destroy_cq(self.recv_cq);
+            if !self.mr.is_null() {
+                rdmaxcel_sys::ibv_dereg_mr(self.mr);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use super::*;
+
+    fn multicast_config() -> IbverbsConfig {
+        IbverbsConfig {
+            use_gpu_direct: false,
+            qp_type: RdmaQpType::UnreliableDatagram,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_multicast_requires_datagram_qp_type() {
+        let config = IbverbsConfig {
+            qp_type: RdmaQpType::Standard,
+            ..multicast_config()
+        };
+        assert!(matches!(
+            MulticastQueuePair::new(config),
+            Err(RdmaError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_loopback_multicast_group() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        // ff0e::ffff:e000:101, the RoCE mapping of the IPv4 group 224.0.1.1.
+        let group = Gid::from([
+            0xff, 0x0e, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, 0xe0, 0x00, 0x01, 0x01,
+        ]);
+        let mut receiver = MulticastQueuePair::new(multicast_config()).unwrap();
+        let mut sender = MulticastQueuePair::new(multicast_config()).unwrap();
+        receiver.join_multicast(group).unwrap();
+
+        let payload = b"broadcast parameters";
+        sender.send_multicast(group, payload).unwrap();
+
+        // Delivery is unreliable in general, but a loopback datagram to a posted
+        // receive on an idle device arrives.
+        let start = Instant::now();
+        let received = loop {
+            if let Some(datagram) = receiver.recv_multicast().unwrap() {
+                break datagram;
+            }
+            assert!(
+                start.elapsed() < Duration::from_secs(5),
+                "no datagram received"
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        };
+        assert_eq!(received, payload);
+
+        receiver.leave_multicast(group).unwrap();
+        let oversized = vec![0u8; sender.max_datagram_size() + 1];
+        assert!(matches!(
+            sender.send_multicast(group, &oversized),
+            Err(RdmaError::InvalidConfig(_))
+        ));
+    }
+}
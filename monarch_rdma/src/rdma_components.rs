@@ -43,10 +43,18 @@
 /// Maximum size for a single RDMA operation in bytes (1 GiB)
 const MAX_RDMA_MSG_SIZE: usize = 1024 * 1024 * 1024;
 
+use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::ffi::CStr;
 use std::fs;
 use std::io::Error;
+use std::os::fd::AsRawFd;
+use std::os::fd::RawFd;
 use std::result::Result;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::OnceLock;
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -64,9 +72,14 @@ use crate::RdmaManagerMessageClient;
 use crate::ibverbs_primitives::Gid;
 use crate::ibverbs_primitives::IbvWc;
 use crate::ibverbs_primitives::IbverbsConfig;
+use crate::ibverbs_primitives::PollStrategy;
+use crate::ibverbs_primitives::QpConnectionInfo;
 use crate::ibverbs_primitives::RdmaOperation;
+use crate::ibverbs_primitives::RdmaProvider;
 use crate::ibverbs_primitives::RdmaQpInfo;
-use crate::ibverbs_primitives::resolve_qp_type;
+use crate::ibverbs_primitives::RdmaQpType;
+use crate::local_copy::try_local_copy;
+use crate::rdma_error::RdmaError;
 
 #[derive(Debug, Named, Clone, Serialize, Deserialize)]
 pub struct DoorBell {
@@ -83,8 +96,8 @@ impl DoorBell {
     ///
     /// # Returns
     /// * `Ok(())` if the operation is successful.
-    /// * `Err(anyhow::Error)` if an error occurs during the operation.
-    pub fn ring(&self) -> Result<(), anyhow::Error> {
+    /// * `Err(RdmaError)` if an error occurs during the operation.
+    pub fn ring(&self) -> Result<(), RdmaError> {
         unsafe {
             let src_ptr = self.src_ptr as *mut std::ffi::c_void;
             let dst_ptr = self.dst_ptr as *mut std::ffi::c_void;
@@ -110,6 +123,8 @@ impl RdmaBuffer {
     ///
     /// This method transfers data from the buffer into the local memory region provided over RDMA.
     /// This involves calling a `Put` operation on the RdmaBuffer's actor side.
+    /// If both buffers were registered in this process with `local_copy` enabled, the data is
+    /// copied directly instead.
     ///
     /// # Arguments
     /// * `client` - The actor who is reading.
@@ -117,26 +132,30 @@ impl RdmaBuffer {
     /// * `timeout` - Timeout in seconds for the RDMA operation to complete.
     ///
     /// # Returns
-    /// `Ok(bool)` indicating if the operation completed successfully.
+    /// `Ok(bool)` indicating if the operation completed successfully, or an `RdmaError`
+    /// describing why it did not.
     pub async fn read_into(
         &self,
         client: &impl context::Actor,
         remote: RdmaBuffer,
         timeout: u64,
-    ) -> Result<bool, anyhow::Error> {
+    ) -> Result<bool, RdmaError> {
         tracing::debug!(
             "[buffer] reading from {:?} into remote ({:?}) at {:?}",
             self,
             remote.owner.actor_id(),
             remote,
         );
+        if try_local_copy(self, &remote, self.size)? {
+            return Ok(true);
+        }
         let remote_owner = remote.owner.clone();
 
         let local_device = self.device_name.clone();
         let remote_device = remote.device_name.clone();
-        let mut qp = self
+        let mut qps = self
             .owner
-            .request_queue_pair(
+            .request_queue_pairs(
                 client,
                 remote_owner.clone(),
                 local_device.clone(),
@@ -144,14 +163,14 @@ impl RdmaBuffer {
             )
             .await?;
 
-        qp.put(self.clone(), remote)?;
-        let result = self
-            .wait_for_completion(&mut qp, PollTarget::Send, timeout)
-            .await;
+        let result = match RdmaQueuePair::put_striped(&mut qps, self, &remote) {
+            Ok(()) => self.wait_for_striped_completion(&mut qps, timeout).await,
+            Err(e) => Err(e),
+        };
 
-        // Release the queue pair back to the actor
+        // Release the queue pairs back to the actor
         self.owner
-            .release_queue_pair(client, remote_owner, local_device, remote_device, qp)
+            .release_queue_pairs(client, remote_owner, local_device, remote_device, qps)
             .await?;
 
         result
@@ -162,6 +181,8 @@ impl RdmaBuffer {
     /// This method performs an RDMA write operation, transferring data from the caller's
     /// memory region to this buffer.
     /// This involves calling a `Fetch` operation on the RdmaBuffer's actor side.
+    /// If both buffers were registered in this process with `local_copy` enabled, the data is
+    /// copied directly instead.
     ///
     /// # Arguments
     /// * `client` - The actor who is writing.
@@ -169,65 +190,156 @@ impl RdmaBuffer {
     /// * `timeout` - Timeout in seconds for the RDMA operation to complete.
     ///
     /// # Returns
-    /// `Ok(bool)` indicating if the operation completed successfully.
+    /// `Ok(bool)` indicating if the operation completed successfully, or an `RdmaError`
+    /// describing why it did not.
     pub async fn write_from(
         &self,
         client: &impl context::Actor,
         remote: RdmaBuffer,
         timeout: u64,
-    ) -> Result<bool, anyhow::Error> {
+    ) -> Result<bool, RdmaError> {
         tracing::debug!(
             "[buffer] writing into {:?} from remote ({:?}) at {:?}",
             self,
             remote.owner.actor_id(),
             remote,
         );
+        if try_local_copy(&remote, self, self.size)? {
+            return Ok(true);
+        }
         let remote_owner = remote.owner.clone(); // Clone before the move!
 
         // Extract device name from buffer, fallback to a default if not present
         let local_device = self.device_name.clone();
         let remote_device = remote.device_name.clone();
 
-        let mut qp = self
+        let mut qps = self
             .owner
-            .request_queue_pair(
+            .request_queue_pairs(
                 client,
                 remote_owner.clone(),
                 local_device.clone(),
                 remote_device.clone(),
             )
             .await?;
-        qp.get(self.clone(), remote)?;
-        let result = self
-            .wait_for_completion(&mut qp, PollTarget::Send, timeout)
-            .await;
+        let result = match RdmaQueuePair::get_striped(&mut qps, self, &remote) {
+            Ok(()) => self.wait_for_striped_completion(&mut qps, timeout).await,
+            Err(e) => Err(e),
+        };
 
-        // Release the queue pair back to the actor
+        // Release the queue pairs back to the actor
         self.owner
-            .release_queue_pair(client, remote_owner, local_device, remote_device, qp)
+            .release_queue_pairs(client, remote_owner, local_device, remote_device, qps)
             .await?;
 
         result
     }
+
+    /// Waits for the work posted by `put_striped` or `get_striped` to complete on
+    /// each of `qps`. Queue pairs that received no chunks are skipped.
+    ///
+    /// `timeout` bounds the whole transfer rather than each stripe. Every stripe is
+    /// waited on, even after one fails, so that no queue pair is released with work
+    /// still in flight that the deadline allowed to finish; the first error is
+    /// returned once all stripes are done.
+    async fn wait_for_striped_completion(
+        &self,
+        qps: &mut [RdmaQueuePair],
+        timeout: u64,
+    ) -> Result<bool, RdmaError> {
+        let deadline = std::time::Instant::now() + Duration::from_secs(timeout);
+        let mut first_error = None;
+        for qp in qps.iter_mut() {
+            if qp.send_db_idx == qp.send_cq_idx {
+                continue;
+            }
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if let Err(e) = self
+                .wait_for_completion(qp, PollTarget::Send, remaining)
+                .await
+            {
+                first_error.get_or_insert(e);
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(true),
+        }
+    }
+
     /// Waits for the completion of an RDMA operation.
     ///
-    /// This method polls the completion queue until the specified work request completes
-    /// or until the timeout is reached.
+    /// This method waits until all posted work requests complete or until the timeout
+    /// is reached. If the process-wide completion dispatcher is running, the wait is
+    /// routed through it; otherwise the completion queue is polled directly.
     ///
     /// # Arguments
     /// * `qp` - The RDMA Queue Pair to poll for completion
-    /// * `timeout` - Timeout in seconds for the RDMA operation to complete.
+    /// * `timeout` - Timeout for the RDMA operation to complete.
     ///
     /// # Returns
     /// `Ok(true)` if the operation completes successfully within the timeout,
-    /// or an error if the timeout is reached
+    /// `Err(RdmaError::Timeout)` if the timeout is reached, or the polling error otherwise
     async fn wait_for_completion(
         &self,
         qp: &mut RdmaQueuePair,
         poll_target: PollTarget,
-        timeout: u64,
-    ) -> Result<bool, anyhow::Error> {
-        let timeout = Duration::from_secs(timeout);
+        timeout: Duration,
+    ) -> Result<bool, RdmaError> {
+        match qp.config.poll_strategy {
+            PollStrategy::BusyPoll => {}
+            PollStrategy::EventDriven => {
+                return self
+                    .wait_for_completion_event_driven(qp, poll_target, timeout)
+                    .await;
+            }
+            PollStrategy::Adaptive { spin_for } => {
+                let start_time = std::time::Instant::now();
+                match qp
+                    .spin_for_completion(poll_target, spin_for.min(timeout))
+                    .await
+                {
+                    Ok(Some(_wc)) => {
+                        tracing::debug!("work completed");
+                        return Ok(true);
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        self.log_completion_error(&e);
+                        return Err(e);
+                    }
+                }
+                return self
+                    .wait_for_completion_event_driven(
+                        qp,
+                        poll_target,
+                        timeout.saturating_sub(start_time.elapsed()),
+                    )
+                    .await;
+            }
+        }
+
+        let dispatcher = crate::completion_dispatcher::completion_dispatcher();
+        if dispatcher.is_running() {
+            let Some(transfer) = qp.transfer(poll_target) else {
+                return Ok(true);
+            };
+            match RealClock.timeout(timeout, transfer).await {
+                Ok(result) => {
+                    result.inspect_err(|e| self.log_completion_error(e))?;
+                }
+                Err(_) => {
+                    tracing::error!(
+                        "[buffer({:?})] timed out while waiting on request completion",
+                        self
+                    );
+                    return Err(RdmaError::Timeout(timeout));
+                }
+            }
+            tracing::debug!("work completed");
+            return Ok(true);
+        }
+
         let start_time = std::time::Instant::now();
 
         while start_time.elapsed() < timeout {
@@ -240,22 +352,139 @@ impl RdmaBuffer {
                     RealClock.sleep(Duration::from_millis(1)).await;
                 }
                 Err(e) => {
-                    return Err(anyhow::anyhow!(
-                        "RDMA polling completion failed: {} [lkey={}, rkey={}, addr=0x{:x}, size={}]",
-                        e,
-                        self.lkey,
-                        self.rkey,
-                        self.addr,
-                        self.size
-                    ));
+                    self.log_completion_error(&e);
+                    return Err(e);
                 }
             }
         }
-        tracing::error!("timed out while waiting on request completion");
-        Err(anyhow::anyhow!(
-            "[buffer({:?})] rdma operation did not complete in time",
+        tracing::error!(
+            "[buffer({:?})] timed out while waiting on request completion",
             self
-        ))
+        );
+        Err(RdmaError::Timeout(timeout))
+    }
+
+    /// Waits for all work posted to `poll_target` by sleeping on the queue pair's
+    /// completion channel between polls, instead of polling in a loop.
+    async fn wait_for_completion_event_driven(
+        &self,
+        qp: &mut RdmaQueuePair,
+        poll_target: PollTarget,
+        timeout: Duration,
+    ) -> Result<bool, RdmaError> {
+        let (db_idx, cq_idx) = match poll_target {
+            PollTarget::Send => (qp.send_db_idx, qp.send_cq_idx),
+            PollTarget::Recv => (qp.recv_db_idx, qp.recv_cq_idx),
+        };
+        if db_idx == cq_idx {
+            return Ok(true);
+        }
+
+        let start_time = std::time::Instant::now();
+        loop {
+            qp.arm_completion_target(poll_target)?;
+            // Drain after arming: completions that landed before the CQ was armed
+            // don't generate an event.
+            loop {
+                let before = match poll_target {
+                    PollTarget::Send => qp.send_cq_idx,
+                    PollTarget::Recv => qp.recv_cq_idx,
+                };
+                match qp.poll_completion_target(poll_target) {
+                    Ok(Some(_wc)) => {
+                        tracing::debug!("work completed");
+                        return Ok(true);
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        self.log_completion_error(&e);
+                        return Err(e);
+                    }
+                }
+                let after = match poll_target {
+                    PollTarget::Send => qp.send_cq_idx,
+                    PollTarget::Recv => qp.recv_cq_idx,
+                };
+                if after == before {
+                    break;
+                }
+            }
+
+            let remaining = timeout.saturating_sub(start_time.elapsed());
+            match qp.wait_for_completion_event(remaining).await {
+                Ok(()) => {}
+                Err(RdmaError::Timeout(_)) => {
+                    tracing::error!(
+                        "[buffer({:?})] timed out while waiting on request completion",
+                        self
+                    );
+                    return Err(RdmaError::Timeout(timeout));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn log_completion_error(&self, e: &RdmaError) {
+        tracing::error!(
+            "RDMA polling completion failed: {} [lkey={}, rkey={}, addr=0x{:x}, size={}]",
+            e,
+            self.lkey,
+            self.rkey,
+            self.addr,
+            self.size
+        );
+    }
+
+    /// Copy the contents of the buffer into a host `Vec<u8>`.
+    ///
+    /// Only valid in the process that registered the buffer. Device memory is copied
+    /// with `cuMemcpyDtoH`, which requires a CUDA context to be current on the
+    /// calling thread.
+    ///
+    /// # Returns
+    /// The `size` bytes at `addr`, or `RdmaError::Device` if the device copy fails.
+    pub fn read_to_vec(&self) -> Result<Vec<u8>, RdmaError> {
+        let mut bytes = vec![0u8; self.size];
+        match buffer_memory_type(self.addr) {
+            BufferMemoryType::Device(_) => {
+                // SAFETY: `bytes` holds `self.size` bytes and the device range belongs to this buffer.
+                let err = unsafe {
+                    rdmaxcel_sys::rdmaxcel_cuMemcpyDtoH_v2(
+                        bytes.as_mut_ptr() as *mut std::ffi::c_void,
+                        self.addr as rdmaxcel_sys::CUdeviceptr,
+                        self.size,
+                    )
+                };
+                if err != rdmaxcel_sys::CUDA_SUCCESS {
+                    return Err(RdmaError::Device(format!(
+                        "cuMemcpyDtoH failed for buffer at 0x{:x} ({} bytes): {:?}",
+                        self.addr, self.size, err
+                    )));
+                }
+            }
+            BufferMemoryType::Host => {
+                // SAFETY: The buffer describes `self.size` readable bytes at `self.addr`
+                // in this process, and `bytes` does not overlap it.
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        self.addr as *const u8,
+                        bytes.as_mut_ptr(),
+                        self.size,
+                    );
+                }
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Compute a 64-bit FNV-1a checksum of the buffer contents.
+    ///
+    /// The hash is stable across processes, so checksums computed on both ends of a
+    /// transfer can be compared to verify it. See [`RdmaBuffer::read_to_vec`] for the
+    /// requirements on device memory.
+    pub fn checksum(&self) -> Result<u64, RdmaError> {
+        Ok(fnv1a_64(&self.read_to_vec()?))
     }
 
     /// Drop the buffer and release remote handles.
@@ -269,13 +498,56 @@ impl RdmaBuffer {
     ///
     /// # Returns
     /// `Ok(())` if the operation completed successfully.
-    pub async fn drop_buffer(&self, client: &impl context::Actor) -> Result<(), anyhow::Error> {
+    pub async fn drop_buffer(&self, client: &impl context::Actor) -> Result<(), RdmaError> {
         tracing::debug!("[buffer] dropping buffer {:?}", self);
         self.owner.release_buffer(client, self.clone()).await?;
         Ok(())
     }
 }
 
+/// Splits a `total_size`-byte transfer into chunks of at most `max_chunk` bytes and
+/// deals them round-robin across `lanes` queue pairs.
+///
+/// Chunks are also capped at an even share of the transfer, so a transfer of at
+/// least `lanes` bytes keeps every lane busy. Returns the `(offset, len)` chunks
+/// assigned to each lane, in posting order.
+pub fn stripe_chunks(
+    total_size: usize,
+    max_chunk: usize,
+    lanes: usize,
+) -> Vec<Vec<(usize, usize)>> {
+    let lanes = lanes.max(1);
+    let chunk_size = max_chunk.min(total_size.div_ceil(lanes)).max(1);
+    let mut stripes = vec![Vec::new(); lanes];
+    let mut offset = 0;
+    while offset < total_size {
+        let len = chunk_size.min(total_size - offset);
+        stripes[(offset / chunk_size) % lanes].push((offset, len));
+        offset += len;
+    }
+    stripes
+}
+
+/// 64-bit FNV-1a hash of `bytes`.
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Checks that `rhandle` can hold the whole of `lhandle`.
+fn check_transfer_size(lhandle: &RdmaBuffer, rhandle: &RdmaBuffer) -> Result<(), RdmaError> {
+    if rhandle.size < lhandle.size {
+        return Err(RdmaError::SizeMismatch {
+            required: lhandle.size,
+            available: rhandle.size,
+        });
+    }
+    Ok(())
+}
+
 /// Represents a domain for RDMA operations, encapsulating the necessary resources
 /// for establishing and managing RDMA connections.
 ///
@@ -348,7 +620,7 @@ impl RdmaDomain {
     /// * Device context creation fails
     /// * Protection domain allocation fails
     /// * Memory region registration fails
-    pub fn new(device: RdmaDevice) -> Result<Self, anyhow::Error> {
+    pub fn new(device: RdmaDevice) -> Result<Self, RdmaError> {
         tracing::debug!("creating RdmaDomain for device {}", device.name());
         // SAFETY:
         // This code uses unsafe rdmaxcel_sys calls to interact with the RDMA device, but is safe because:
@@ -363,7 +635,7 @@ impl RdmaDomain {
             let devices = rdmaxcel_sys::ibv_get_device_list(&mut num_devices as *mut _);
 
             if devices.is_null() || num_devices == 0 {
-                return Err(anyhow::anyhow!("no RDMA devices found"));
+                return Err(RdmaError::Device("no RDMA devices found".to_string()));
             }
 
             // Find the device with the matching name
@@ -382,7 +654,10 @@ impl RdmaDomain {
             // If we didn't find the device, return an error
             if device_ptr.is_null() {
                 rdmaxcel_sys::ibv_free_device_list(devices);
-                return Err(anyhow::anyhow!("device '{}' not found", device_name));
+                return Err(RdmaError::Device(format!(
+                    "device '{}' not found",
+                    device_name
+                )));
             }
             tracing::info!("using RDMA device: {}", device_name);
 
@@ -391,7 +666,10 @@ impl RdmaDomain {
             if context.is_null() {
                 rdmaxcel_sys::ibv_free_device_list(devices);
                 let os_error = Error::last_os_error();
-                return Err(anyhow::anyhow!("failed to create context: {}", os_error));
+                return Err(RdmaError::Device(format!(
+                    "failed to create context: {}",
+                    os_error
+                )));
             }
 
             // Create protection domain
@@ -400,10 +678,10 @@ impl RdmaDomain {
                 rdmaxcel_sys::ibv_close_device(context);
                 rdmaxcel_sys::ibv_free_device_list(devices);
                 let os_error = Error::last_os_error();
-                return Err(anyhow::anyhow!(
+                return Err(RdmaError::Device(format!(
                     "failed to create protection domain (PD): {}",
                     os_error
-                ));
+                )));
             }
 
             // Avoids memory leaks
@@ -436,6 +714,7 @@ pub enum PollTarget {
 /// * `dv_qp` - Pointer to the mlx5 device-specific queue pair structure
 /// * `dv_send_cq` - Pointer to the mlx5 device-specific send completion queue structure
 /// * `dv_recv_cq` - Pointer to the mlx5 device-specific receive completion queue structure
+/// * `comp_channel` - Completion channel both CQs report to, only set for `PollStrategy::EventDriven` and `PollStrategy::Adaptive`
 /// * `context` - RDMA device context pointer
 /// * `config` - Configuration settings for the queue pair
 ///
@@ -449,13 +728,14 @@ pub enum PollTarget {
 /// 6. Poll for completions with `poll_send_completion()` or `poll_recv_completion()`
 #[derive(Debug, Serialize, Deserialize, Named, Clone)]
 pub struct RdmaQueuePair {
-    pub send_cq: usize,    // *mut rdmaxcel_sys::ibv_cq,
-    pub recv_cq: usize,    // *mut rdmaxcel_sys::ibv_cq,
-    pub qp: usize,         // *mut rdmaxcel_sys::ibv_qp,
-    pub dv_qp: usize,      // *mut rdmaxcel_sys::mlx5dv_qp,
-    pub dv_send_cq: usize, // *mut rdmaxcel_sys::mlx5dv_cq,
-    pub dv_recv_cq: usize, // *mut rdmaxcel_sys::mlx5dv_cq,
-    context: usize,        // *mut rdmaxcel_sys::ibv_context,
+    pub send_cq: usize,      // *mut rdmaxcel_sys::ibv_cq,
+    pub recv_cq: usize,      // *mut rdmaxcel_sys::ibv_cq,
+    pub qp: usize,           // *mut rdmaxcel_sys::ibv_qp,
+    pub dv_qp: usize,        // *mut rdmaxcel_sys::mlx5dv_qp,
+    pub dv_send_cq: usize,   // *mut rdmaxcel_sys::mlx5dv_cq,
+    pub dv_recv_cq: usize,   // *mut rdmaxcel_sys::mlx5dv_cq,
+    pub comp_channel: usize, // *mut rdmaxcel_sys::ibv_comp_channel, 0 for busy-polling
+    context: usize,          // *mut rdmaxcel_sys::ibv_context,
     config: IbverbsConfig,
     pub send_wqe_idx: u64,
     pub send_db_idx: u64,
@@ -464,6 +744,10 @@ pub struct RdmaQueuePair {
     pub recv_db_idx: u64,
     pub recv_cq_idx: u64,
     rts_timestamp: u64,
+    cq_poll_count: u64,
+    /// Receive buffers posted by `post_recv_pool`, with their work request IDs, in the
+    /// order they were posted.
+    recv_pool: VecDeque<(u64, RdmaBuffer)>,
 }
 
 impl RdmaQueuePair {
@@ -515,35 +799,86 @@ impl RdmaQueuePair {
     pub fn new(
         context: *mut rdmaxcel_sys::ibv_context,
         pd: *mut rdmaxcel_sys::ibv_pd,
-        config: IbverbsConfig,
-    ) -> Result<Self, anyhow::Error> {
+        mut config: IbverbsConfig,
+    ) -> Result<Self, RdmaError> {
+        config.clamp_to_device_limits();
+        config.validate_reliability()?;
+        if config.qp_type == RdmaQpType::UnreliableDatagram {
+            return Err(RdmaError::InvalidConfig(
+                "unreliable datagram queue pairs can't be connected; use MulticastQueuePair"
+                    .to_string(),
+            ));
+        }
         tracing::debug!("creating an RdmaQueuePair from config {}", config);
         unsafe {
-            // Resolve Auto to a concrete QP type based on device capabilities
-            let resolved_qp_type = resolve_qp_type(config.qp_type);
+            // Resolve Auto to a concrete QP type based on device capabilities and provider
+            let resolved_qp_type = config.resolved_qp_type();
+
+            let comp_channel = if config.poll_strategy.uses_completion_channel() {
+                create_comp_channel(context)?
+            } else {
+                std::ptr::null_mut()
+            };
 
             let qp = rdmaxcel_sys::create_qp(
                 context,
                 pd,
-                config.cq_entries,
+                config.send_cq_entries(),
+                config.recv_cq_entries(),
                 config.max_send_wr.try_into().unwrap(),
                 config.max_recv_wr.try_into().unwrap(),
                 config.max_send_sge.try_into().unwrap(),
                 config.max_recv_sge.try_into().unwrap(),
                 resolved_qp_type,
+                comp_channel,
             );
 
             if qp.is_null() {
                 let os_error = Error::last_os_error();
-                return Err(anyhow::anyhow!(
+                destroy_comp_channel(comp_channel);
+                return Err(RdmaError::Device(format!(
                     "failed to create queue pair (QP): {}",
                     os_error
-                ));
+                )));
             }
 
             let send_cq = (*qp).send_cq;
             let recv_cq = (*qp).recv_cq;
 
+            // The generic verbs provider never touches mlx5dv, so there are no
+            // device-specific structures (or GPU registrations) to set up.
+            if config.provider == RdmaProvider::Verbs {
+                if config.use_gpu_direct {
+                    rdmaxcel_sys::ibv_destroy_cq((*qp).recv_cq);
+                    rdmaxcel_sys::ibv_destroy_cq((*qp).send_cq);
+                    rdmaxcel_sys::ibv_destroy_qp(qp);
+                    destroy_comp_channel(comp_channel);
+                    return Err(RdmaError::InvalidConfig(
+                        "GPU Direct RDMA requires the Mlx5 provider".to_string(),
+                    ));
+                }
+                return Ok(RdmaQueuePair {
+                    send_cq: send_cq as usize,
+                    recv_cq: recv_cq as usize,
+                    qp: qp as usize,
+                    dv_qp: 0,
+                    dv_send_cq: 0,
+                    dv_recv_cq: 0,
+                    comp_channel: comp_channel as usize,
+                    context: context as usize,
+                    config,
+                    recv_db_idx: 0,
+                    recv_wqe_idx: 0,
+                    recv_cq_idx: 0,
+                    recv_pool: VecDeque::new(),
+                    send_db_idx: 0,
+                    send_wqe_idx: 0,
+                    send_cq_idx: 0,
+                    rts_timestamp: u64::MAX,
+                    cq_poll_count: 0,
+                });
+            }
+
             // mlx5dv provider APIs
             let dv_qp = rdmaxcel_sys::create_mlx5dv_qp(qp);
             let dv_send_cq = rdmaxcel_sys::create_mlx5dv_send_cq(qp);
@@ -553,8 +888,9 @@ impl RdmaQueuePair {
                 rdmaxcel_sys::ibv_destroy_cq((*qp).recv_cq);
                 rdmaxcel_sys::ibv_destroy_cq((*qp).send_cq);
                 rdmaxcel_sys::ibv_destroy_qp(qp);
-                return Err(anyhow::anyhow!(
-                    "failed to init mlx5dv_qp or completion queues"
+                destroy_comp_channel(comp_channel);
+                return Err(RdmaError::Device(
+                    "failed to init mlx5dv_qp or completion queues".to_string(),
                 ));
             }
 
@@ -565,10 +901,11 @@ impl RdmaQueuePair {
                     rdmaxcel_sys::ibv_destroy_cq((*qp).recv_cq);
                     rdmaxcel_sys::ibv_destroy_cq((*qp).send_cq);
                     rdmaxcel_sys::ibv_destroy_qp(qp);
-                    return Err(anyhow::anyhow!(
+                    destroy_comp_channel(comp_channel);
+                    return Err(RdmaError::Registration(format!(
                         "failed to register GPU Direct RDMA memory: {:?}",
                         ret
-                    ));
+                    )));
                 }
             }
             Ok(RdmaQueuePair {
@@ -578,15 +915,18 @@ impl RdmaQueuePair {
                 dv_qp: dv_qp as usize,
                 dv_send_cq: dv_send_cq as usize,
                 dv_recv_cq: dv_recv_cq as usize,
+                comp_channel: comp_channel as usize,
                 context: context as usize,
                 config,
                 recv_db_idx: 0,
                 recv_wqe_idx: 0,
                 recv_cq_idx: 0,
+                recv_pool: VecDeque::new(),
                 send_db_idx: 0,
                 send_wqe_idx: 0,
                 send_cq_idx: 0,
                 rts_timestamp: u64::MAX,
+                cq_poll_count: 0,
             })
         }
     }
@@ -606,7 +946,7 @@ impl RdmaQueuePair {
     /// This function may return errors if:
     /// * Port attribute query fails
     /// * GID query fails
-    pub fn get_qp_info(&mut self) -> Result<RdmaQpInfo, anyhow::Error> {
+    pub fn get_qp_info(&mut self) -> Result<RdmaQpInfo, RdmaError> {
         // SAFETY:
         // This code uses unsafe rdmaxcel_sys calls to query RDMA device information, but is safe because:
         // - All pointers are properly initialized before use
@@ -624,10 +964,10 @@ impl RdmaQueuePair {
             );
             if errno != 0 {
                 let os_error = Error::last_os_error();
-                return Err(anyhow::anyhow!(
+                return Err(RdmaError::Device(format!(
                     "Failed to query port attributes: {}",
                     os_error
-                ));
+                )));
             }
 
             let mut gid = Gid::default();
@@ -638,7 +978,7 @@ impl RdmaQueuePair {
                 gid.as_mut(),
             );
             if ret != 0 {
-                return Err(anyhow::anyhow!("Failed to query GID"));
+                return Err(RdmaError::Device("Failed to query GID".to_string()));
             }
 
             Ok(RdmaQpInfo {
@@ -650,7 +990,7 @@ impl RdmaQueuePair {
         }
     }
 
-    pub fn state(&mut self) -> Result<u32, anyhow::Error> {
+    pub fn state(&mut self) -> Result<u32, RdmaError> {
         // SAFETY: This block interacts with the RDMA device through rdmaxcel_sys calls.
         unsafe {
             let qp = self.qp as *mut rdmaxcel_sys::ibv_qp;
@@ -665,151 +1005,278 @@ impl RdmaQueuePair {
                 rdmaxcel_sys::ibv_query_qp(qp, &mut qp_attr, mask.0 as i32, &mut qp_init_attr);
             if errno != 0 {
                 let os_error = Error::last_os_error();
-                return Err(anyhow::anyhow!("failed to query QP state: {}", os_error));
+                return Err(RdmaError::Device(format!(
+                    "failed to query QP state: {}",
+                    os_error
+                )));
             }
             Ok(qp_attr.qp_state)
         }
     }
+    /// Queries the reliability parameters programmed into the QP by `to_rtr` and `to_rts`.
+    pub fn reliability_attrs(&mut self) -> Result<QpReliabilityAttrs, RdmaError> {
+        // SAFETY: This block interacts with the RDMA device through rdmaxcel_sys calls.
+        unsafe {
+            let qp = self.qp as *mut rdmaxcel_sys::ibv_qp;
+            let mut qp_attr = rdmaxcel_sys::ibv_qp_attr {
+                ..Default::default()
+            };
+            let mut qp_init_attr = rdmaxcel_sys::ibv_qp_init_attr {
+                ..Default::default()
+            };
+            let mask = rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_SQ_PSN
+                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_RETRY_CNT
+                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_RNR_RETRY
+                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_TIMEOUT
+                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_MIN_RNR_TIMER;
+            let errno =
+                rdmaxcel_sys::ibv_query_qp(qp, &mut qp_attr, mask.0 as i32, &mut qp_init_attr);
+            if errno != 0 {
+                let os_error = Error::last_os_error();
+                return Err(RdmaError::Device(format!(
+                    "failed to query QP reliability attributes: {}",
+                    os_error
+                )));
+            }
+            Ok(QpReliabilityAttrs {
+                psn: qp_attr.sq_psn,
+                retry_cnt: qp_attr.retry_cnt,
+                rnr_retry: qp_attr.rnr_retry,
+                qp_timeout: qp_attr.timeout,
+                min_rnr_timer: qp_attr.min_rnr_timer,
+            })
+        }
+    }
+
+    /// Returns this queue pair's connection parameters as a `QpConnectionInfo`.
+    ///
+    /// This is the serializable counterpart of `get_qp_info()`, meant to be sent to the
+    /// remote peer over any side channel and passed to its `connect()`.
+    pub fn connection_info(&mut self) -> Result<QpConnectionInfo, RdmaError> {
+        Ok(self.get_qp_info()?.into())
+    }
+
     /// Connect to a remote Rdma connection point.
     ///
     /// This performs the necessary QP state transitions (INIT->RTR->RTS) to establish a connection.
     ///
     /// # Arguments
     ///
-    /// * `connection_info` - The remote connection info to connect to
-    pub fn connect(&mut self, connection_info: &RdmaQpInfo) -> Result<(), anyhow::Error> {
-        // SAFETY:
-        // This unsafe block is necessary because we're interacting with the RDMA device through rdmaxcel_sys calls.
-        // The operations are safe because:
-        // 1. We're following the documented ibverbs API contract
-        // 2. All pointers used are properly initialized and owned by this struct
-        // 3. The QP state transitions (INIT->RTR->RTS) follow the required RDMA connection protocol
-        // 4. Memory access is properly bounded by the registered memory regions
-        unsafe {
-            // Transition to INIT
-            let qp = self.qp as *mut rdmaxcel_sys::ibv_qp;
+    /// * `connection_info` - The remote connection info to connect to, either an `RdmaQpInfo`
+    ///   or a `QpConnectionInfo`
+    pub fn connect<T>(&mut self, connection_info: &T) -> Result<(), RdmaError>
+    where
+        T: Clone + Into<RdmaQpInfo>,
+    {
+        let connection_info: RdmaQpInfo = connection_info.clone().into();
+        self.to_init(self.config.port_num, self.config.pkey_index)?;
+        self.to_rtr(&connection_info)?;
+        self.to_rts()?;
+        tracing::debug!(
+            "connection sequence has successfully completed (qp: 0x{:x})",
+            self.qp
+        );
+        Ok(())
+    }
+
+    /// Transitions the queue pair from RESET to INIT.
+    ///
+    /// Binds the QP to a physical port and partition key and enables local write,
+    /// remote read/write and remote atomic access.
+    ///
+    /// # Arguments
+    ///
+    /// * `port_num` - The physical port number on the device
+    /// * `pkey_index` - The partition key index
+    pub fn to_init(&mut self, port_num: u8, pkey_index: u16) -> Result<(), RdmaError> {
+        let qp_access_flags = rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_LOCAL_WRITE
+            | rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_REMOTE_WRITE
+            | rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_REMOTE_READ
+            | rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_REMOTE_ATOMIC;
+
+        let mut qp_attr = rdmaxcel_sys::ibv_qp_attr {
+            qp_state: rdmaxcel_sys::ibv_qp_state::IBV_QPS_INIT,
+            qp_access_flags: qp_access_flags.0,
+            pkey_index,
+            port_num,
+            ..Default::default()
+        };
 
-            let qp_access_flags = rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_LOCAL_WRITE
-                | rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_REMOTE_WRITE
-                | rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_REMOTE_READ
-                | rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_REMOTE_ATOMIC;
+        let mask = rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_STATE
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_PKEY_INDEX
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_PORT
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_ACCESS_FLAGS;
 
-            let mut qp_attr = rdmaxcel_sys::ibv_qp_attr {
-                qp_state: rdmaxcel_sys::ibv_qp_state::IBV_QPS_INIT,
-                qp_access_flags: qp_access_flags.0,
-                pkey_index: self.config.pkey_index,
+        self.modify_qp(&mut qp_attr, mask, "INIT")
+    }
+
+    /// Transitions the queue pair from INIT to RTR (Ready to Receive).
+    ///
+    /// Programs the path to the remote QP described by `remote_qp_info`. If the remote
+    /// info contains a GID the routing is global (RoCE), otherwise LID-based (InfiniBand).
+    ///
+    /// # Arguments
+    ///
+    /// * `remote_qp_info` - The remote connection info to receive from
+    pub fn to_rtr(&mut self, remote_qp_info: &RdmaQpInfo) -> Result<(), RdmaError> {
+        let mut qp_attr = rdmaxcel_sys::ibv_qp_attr {
+            qp_state: rdmaxcel_sys::ibv_qp_state::IBV_QPS_RTR,
+            path_mtu: self.config.path_mtu,
+            dest_qp_num: remote_qp_info.qp_num,
+            rq_psn: remote_qp_info.psn,
+            max_dest_rd_atomic: self.config.max_dest_rd_atomic,
+            min_rnr_timer: self.config.min_rnr_timer,
+            ah_attr: rdmaxcel_sys::ibv_ah_attr {
+                dlid: remote_qp_info.lid,
+                sl: 0,
+                src_path_bits: 0,
                 port_num: self.config.port_num,
+                grh: Default::default(),
                 ..Default::default()
-            };
+            },
+            ..Default::default()
+        };
 
-            let mask = rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_STATE
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_PKEY_INDEX
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_PORT
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_ACCESS_FLAGS;
+        // If the remote connection info contains a Gid, the routing will be global.
+        // Otherwise, it will be local, i.e. using LID.
+        if let Some(gid) = remote_qp_info.gid {
+            qp_attr.ah_attr.is_global = 1;
+            qp_attr.ah_attr.grh.dgid = gid.into();
+            qp_attr.ah_attr.grh.hop_limit = 0xff;
+            qp_attr.ah_attr.grh.sgid_index = self.config.gid_index;
+        } else {
+            // Use LID-based routing, e.g. for Infiniband/RoCEv1
+            qp_attr.ah_attr.is_global = 0;
+        }
 
-            let errno = rdmaxcel_sys::ibv_modify_qp(qp, &mut qp_attr, mask.0 as i32);
-            if errno != 0 {
-                let os_error = Error::last_os_error();
-                return Err(anyhow::anyhow!(
-                    "failed to transition QP to INIT: {}",
-                    os_error
-                ));
-            }
+        let mask = rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_STATE
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_AV
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_PATH_MTU
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_DEST_QPN
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_RQ_PSN
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_MAX_DEST_RD_ATOMIC
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_MIN_RNR_TIMER;
 
-            // Transition to RTR (Ready to Receive)
-            let mut qp_attr = rdmaxcel_sys::ibv_qp_attr {
-                qp_state: rdmaxcel_sys::ibv_qp_state::IBV_QPS_RTR,
-                path_mtu: self.config.path_mtu,
-                dest_qp_num: connection_info.qp_num,
-                rq_psn: connection_info.psn,
-                max_dest_rd_atomic: self.config.max_dest_rd_atomic,
-                min_rnr_timer: self.config.min_rnr_timer,
-                ah_attr: rdmaxcel_sys::ibv_ah_attr {
-                    dlid: connection_info.lid,
-                    sl: 0,
-                    src_path_bits: 0,
-                    port_num: self.config.port_num,
-                    grh: Default::default(),
-                    ..Default::default()
-                },
-                ..Default::default()
-            };
+        self.modify_qp(&mut qp_attr, mask, "RTR")
+    }
 
-            // If the remote connection info contains a Gid, the routing will be global.
-            // Otherwise, it will be local, i.e. using LID.
-            if let Some(gid) = connection_info.gid {
-                qp_attr.ah_attr.is_global = 1;
-                qp_attr.ah_attr.grh.dgid = gid.into();
-                qp_attr.ah_attr.grh.hop_limit = 0xff;
-                qp_attr.ah_attr.grh.sgid_index = self.config.gid_index;
-            } else {
-                // Use LID-based routing, e.g. for Infiniband/RoCEv1
-                qp_attr.ah_attr.is_global = 0;
-            }
+    /// Transitions the queue pair from RTR to RTS (Ready to Send).
+    ///
+    /// Sets the send-side PSN, timeout and retry parameters from the config, and records
+    /// the RTS timestamp used to apply `hw_init_delay_ms` before the first operation.
+    pub fn to_rts(&mut self) -> Result<(), RdmaError> {
+        let mut qp_attr = rdmaxcel_sys::ibv_qp_attr {
+            qp_state: rdmaxcel_sys::ibv_qp_state::IBV_QPS_RTS,
+            sq_psn: self.config.psn,
+            max_rd_atomic: self.config.max_rd_atomic,
+            retry_cnt: self.config.retry_cnt,
+            rnr_retry: self.config.rnr_retry,
+            timeout: self.config.qp_timeout,
+            ..Default::default()
+        };
 
-            let mask = rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_STATE
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_AV
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_PATH_MTU
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_DEST_QPN
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_RQ_PSN
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_MAX_DEST_RD_ATOMIC
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_MIN_RNR_TIMER;
+        let mask = rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_STATE
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_TIMEOUT
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_RETRY_CNT
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_SQ_PSN
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_RNR_RETRY
+            | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_MAX_QP_RD_ATOMIC;
 
-            let errno = rdmaxcel_sys::ibv_modify_qp(qp, &mut qp_attr, mask.0 as i32);
-            if errno != 0 {
-                let os_error = Error::last_os_error();
-                return Err(anyhow::anyhow!(
-                    "failed to transition QP to RTR: {}",
-                    os_error
-                ));
-            }
+        self.modify_qp(&mut qp_attr, mask, "RTS")?;
 
-            // Transition to RTS (Ready to Send)
-            let mut qp_attr = rdmaxcel_sys::ibv_qp_attr {
-                qp_state: rdmaxcel_sys::ibv_qp_state::IBV_QPS_RTS,
-                sq_psn: self.config.psn,
-                max_rd_atomic: self.config.max_rd_atomic,
-                retry_cnt: self.config.retry_cnt,
-                rnr_retry: self.config.rnr_retry,
-                timeout: self.config.qp_timeout,
-                ..Default::default()
-            };
+        // Record RTS time now that the queue pair is ready to send
+        self.rts_timestamp = RealClock
+            .system_time_now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
 
-            let mask = rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_STATE
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_TIMEOUT
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_RETRY_CNT
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_SQ_PSN
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_RNR_RETRY
-                | rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_MAX_QP_RD_ATOMIC;
+        Ok(())
+    }
 
-            let errno = rdmaxcel_sys::ibv_modify_qp(qp, &mut qp_attr, mask.0 as i32);
-            if errno != 0 {
-                let os_error = Error::last_os_error();
-                return Err(anyhow::anyhow!(
-                    "failed to transition QP to RTS: {}",
-                    os_error
-                ));
-            }
-            tracing::debug!(
-                "connection sequence has successfully completed (qp: {:?})",
-                qp
-            );
+    /// Moves the queue pair back to RESET so that it can be connected again, e.g. when a
+    /// connection pool recycles it for another peer.
+    ///
+    /// Outstanding work requests are discarded and the device purges their completions, so
+    /// the software indices are cleared with `reset_indices` to match. Completions the
+    /// dispatcher still holds for the queue pair's CQs are dropped too, since the restarted
+    /// work request ids would otherwise match them. The queue pair must go through
+    /// `connect()` (or `to_init`/`to_rtr`/`to_rts`) before it is used again.
+    pub fn reset(&mut self) -> Result<(), RdmaError> {
+        let mut qp_attr = rdmaxcel_sys::ibv_qp_attr {
+            qp_state: rdmaxcel_sys::ibv_qp_state::IBV_QPS_RESET,
+            ..Default::default()
+        };
+        self.modify_qp(
+            &mut qp_attr,
+            rdmaxcel_sys::ibv_qp_attr_mask::IBV_QP_STATE,
+            "RESET",
+        )?;
+        self.reset_indices();
+        let dispatcher = crate::completion_dispatcher::completion_dispatcher();
+        dispatcher.purge_cq(self.send_cq);
+        dispatcher.purge_cq(self.recv_cq);
+        // The first operation after reconnecting waits out `hw_init_delay_ms` again.
+        self.rts_timestamp = u64::MAX;
+        Ok(())
+    }
 
-            // Record RTS time now that the queue pair is ready to send
-            self.rts_timestamp = RealClock
-                .system_time_now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_nanos() as u64;
+    /// Clears the send and receive work queue, doorbell and completion indices, and forgets
+    /// the posted receive pool.
+    ///
+    /// Only correct once the device has dropped every outstanding work request, i.e. while
+    /// the queue pair is in RESET; `reset()` does both.
+    pub fn reset_indices(&mut self) {
+        self.send_wqe_idx = 0;
+        self.send_db_idx = 0;
+        self.send_cq_idx = 0;
+        self.recv_wqe_idx = 0;
+        self.recv_db_idx = 0;
+        self.recv_cq_idx = 0;
+        self.recv_pool.clear();
+    }
 
-            Ok(())
+    /// Applies `qp_attr` to the queue pair with `ibv_modify_qp`.
+    ///
+    /// `target` names the state being transitioned to and is reported in
+    /// `RdmaError::StateTransition` on failure.
+    fn modify_qp(
+        &mut self,
+        qp_attr: &mut rdmaxcel_sys::ibv_qp_attr,
+        mask: rdmaxcel_sys::ibv_qp_attr_mask,
+        target: &'static str,
+    ) -> Result<(), RdmaError> {
+        // SAFETY:
+        // This unsafe block is necessary because we're interacting with the RDMA device through rdmaxcel_sys calls.
+        // The operation is safe because:
+        // 1. We're following the documented ibverbs API contract
+        // 2. The QP pointer is properly initialized and owned by this struct
+        // 3. `qp_attr` is a valid, initialized attribute struct for the duration of the call
+        let ret = unsafe {
+            rdmaxcel_sys::ibv_modify_qp(
+                self.qp as *mut rdmaxcel_sys::ibv_qp,
+                qp_attr,
+                mask.0 as i32,
+            )
+        };
+        if ret != 0 {
+            // Providers return the errno directly, but some return -1 and set errno instead.
+            let errno = if ret > 0 {
+                ret
+            } else {
+                Error::last_os_error().raw_os_error().unwrap_or(libc::EIO)
+            };
+            return Err(RdmaError::StateTransition { target, errno });
         }
+        Ok(())
     }
 
-    pub fn recv(&mut self, lhandle: RdmaBuffer, rhandle: RdmaBuffer) -> Result<(), anyhow::Error> {
+    /// Posts a zero-length receive that a peer's write-with-immediate consumes.
+    ///
+    /// Goes through `ibv_post_recv`, so this works with every provider.
+    pub fn recv(&mut self, lhandle: RdmaBuffer, rhandle: RdmaBuffer) -> Result<(), RdmaError> {
         let idx = self.recv_wqe_idx;
-        self.recv_wqe_idx += 1;
-        self.send_wqe(
+        self.post_op(
             0,
             lhandle.lkey,
             0,
@@ -818,16 +1285,115 @@ impl RdmaQueuePair {
             RdmaOperation::Recv,
             0,
             rhandle.rkey,
-        )
-        .unwrap();
+        )?;
+        self.recv_wqe_idx += 1;
+        Ok(())
+    }
+
+    /// Posts a receive work request for each of `buffers` and keeps them as a receive pool.
+    ///
+    /// Pooled buffers catch incoming sends and writes-with-immediate whenever the peer issues
+    /// them, so the receiver does not need to know when data will arrive. Completions are
+    /// consumed with `poll_recv_pool`, which posts each buffer again once it has been handed
+    /// back. Receives go through `ibv_post_recv`, so this works with every provider.
+    pub fn post_recv_pool(&mut self, buffers: &[RdmaBuffer]) -> Result<(), RdmaError> {
+        for buffer in buffers {
+            self.post_pooled_recv(buffer.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Returns how many pooled receive buffers are posted and waiting for data.
+    pub fn recv_pool_len(&self) -> usize {
+        self.recv_pool.len()
+    }
+
+    fn post_pooled_recv(&mut self, buffer: RdmaBuffer) -> Result<(), RdmaError> {
+        let wr_id = self.recv_wqe_idx;
+        self.post_op(
+            buffer.addr,
+            buffer.lkey,
+            buffer.size,
+            wr_id,
+            true,
+            RdmaOperation::Recv,
+            0,
+            0,
+        )?;
+        self.recv_wqe_idx += 1;
+        self.recv_db_idx += 1;
+        self.recv_pool.push_back((wr_id, buffer));
         Ok(())
     }
 
+    /// Polls the receive completion queue for one completed pooled receive.
+    ///
+    /// `consume` is called with the work completion and the buffer the data landed in, and
+    /// the buffer is posted again afterwards so the pool stays full.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(wc))` - A pooled receive completed and its buffer was re-posted
+    /// * `Ok(None)` - Nothing has arrived yet, or no receive pool is posted
+    /// * `Err(RdmaError::CompletionStatus)` - The completion reported an error
+    /// * `Err(RdmaError::Device)` - Polling the completion queue failed
+    pub fn poll_recv_pool<F>(&mut self, consume: F) -> Result<Option<IbvWc>, RdmaError>
+    where
+        F: FnOnce(&IbvWc, &RdmaBuffer),
+    {
+        if self.recv_pool.is_empty() {
+            return Ok(None);
+        }
+        self.cq_poll_count += 1;
+        // SAFETY: `recv_cq` and `context` were created with this queue pair and outlive it,
+        // and `wc` is a valid destination for a single completion.
+        let wc = unsafe {
+            let context = self.context as *mut rdmaxcel_sys::ibv_context;
+            let recv_cq = self.recv_cq as *mut rdmaxcel_sys::ibv_cq;
+            let ops = &mut (*context).ops;
+            let mut wc = std::mem::MaybeUninit::<rdmaxcel_sys::ibv_wc>::zeroed().assume_init();
+            let ret = ops.poll_cq.as_mut().unwrap()(recv_cq, 1, &mut wc);
+            if ret < 0 {
+                return Err(RdmaError::Device(format!(
+                    "Failed to poll receive CQ: {}",
+                    Error::last_os_error()
+                )));
+            }
+            if ret == 0 {
+                return Ok(None);
+            }
+            wc
+        };
+        if let Some(err) = RdmaError::from_wc(&wc) {
+            tracing::error!(
+                "pooled recv work completion failed: {}, recv_cq_idx: {}",
+                err,
+                self.recv_cq_idx
+            );
+            return Err(err);
+        }
+
+        // Receives complete in the order they were posted.
+        let (wr_id, buffer) = self.recv_pool.pop_front().unwrap();
+        if wc.wr_id() != wr_id {
+            return Err(RdmaError::Other(anyhow::anyhow!(
+                "pooled receive completed out of order: expected wr_id {}, got {}",
+                wr_id,
+                wc.wr_id()
+            )));
+        }
+        self.recv_cq_idx += 1;
+        let wc = IbvWc::from(wc);
+        consume(&wc, &buffer);
+        self.post_pooled_recv(buffer)?;
+        Ok(Some(wc))
+    }
+
     pub fn put_with_recv(
         &mut self,
         lhandle: RdmaBuffer,
         rhandle: RdmaBuffer,
-    ) -> Result<(), anyhow::Error> {
+    ) -> Result<(), RdmaError> {
         let idx = self.send_wqe_idx;
         self.send_wqe_idx += 1;
         self.post_op(
@@ -839,44 +1405,102 @@ impl RdmaQueuePair {
             RdmaOperation::WriteWithImm,
             rhandle.addr,
             rhandle.rkey,
-        )
-        .unwrap();
+        )?;
         self.send_db_idx += 1;
         Ok(())
     }
 
-    pub fn put(&mut self, lhandle: RdmaBuffer, rhandle: RdmaBuffer) -> Result<(), anyhow::Error> {
-        let total_size = lhandle.size;
-        if rhandle.size < total_size {
-            return Err(anyhow::anyhow!(
-                "Remote buffer size ({}) is smaller than local buffer size ({})",
-                rhandle.size,
-                total_size
+    pub fn put(&mut self, lhandle: RdmaBuffer, rhandle: RdmaBuffer) -> Result<(), RdmaError> {
+        self.post_chunked(&lhandle, &rhandle, RdmaOperation::Write)
+    }
+
+    /// Posts `op` from `lhandle` to `rhandle`, split into work requests of at most
+    /// `max_transfer_chunk` bytes (and never more than `MAX_RDMA_MSG_SIZE`).
+    ///
+    /// All chunks are posted back to back. Only the last one is guaranteed to be
+    /// signaled, and since a reliable connection completes work requests in order,
+    /// its completion means every chunk has landed.
+    fn post_chunked(
+        &mut self,
+        lhandle: &RdmaBuffer,
+        rhandle: &RdmaBuffer,
+        op: RdmaOperation,
+    ) -> Result<(), RdmaError> {
+        check_transfer_size(lhandle, rhandle)?;
+        let chunks = stripe_chunks(lhandle.size, self.max_chunk(), 1);
+        self.post_chunks(lhandle, rhandle, &chunks[0], op)
+    }
+
+    /// Like `put`, but stripes the transfer round-robin across `qps`, which should all
+    /// connect the same two devices. Each queue pair's last chunk is signaled, so wait
+    /// for completion on every queue pair that received work.
+    pub fn put_striped(
+        qps: &mut [RdmaQueuePair],
+        lhandle: &RdmaBuffer,
+        rhandle: &RdmaBuffer,
+    ) -> Result<(), RdmaError> {
+        Self::post_striped(qps, lhandle, rhandle, RdmaOperation::Write)
+    }
+
+    /// Like `get`, but stripes the transfer round-robin across `qps`. See `put_striped`.
+    pub fn get_striped(
+        qps: &mut [RdmaQueuePair],
+        lhandle: &RdmaBuffer,
+        rhandle: &RdmaBuffer,
+    ) -> Result<(), RdmaError> {
+        Self::post_striped(qps, lhandle, rhandle, RdmaOperation::Read)
+    }
+
+    fn post_striped(
+        qps: &mut [RdmaQueuePair],
+        lhandle: &RdmaBuffer,
+        rhandle: &RdmaBuffer,
+        op: RdmaOperation,
+    ) -> Result<(), RdmaError> {
+        let Some(first) = qps.first() else {
+            return Err(RdmaError::InvalidConfig(
+                "cannot stripe a transfer across zero queue pairs".to_string(),
             ));
+        };
+        check_transfer_size(lhandle, rhandle)?;
+        let stripes = stripe_chunks(lhandle.size, first.max_chunk(), qps.len());
+        for (qp, chunks) in qps.iter_mut().zip(&stripes) {
+            qp.post_chunks(lhandle, rhandle, chunks, op)?;
         }
+        Ok(())
+    }
+
+    /// Largest work request `post_chunked` will post: `max_transfer_chunk`, capped at
+    /// `MAX_RDMA_MSG_SIZE`.
+    fn max_chunk(&self) -> usize {
+        self.config.max_transfer_chunk.clamp(1, MAX_RDMA_MSG_SIZE)
+    }
 
-        let mut remaining = total_size;
-        let mut offset = 0;
-        while remaining > 0 {
-            let chunk_size = std::cmp::min(remaining, MAX_RDMA_MSG_SIZE);
+    /// Posts `op` for each `(offset, len)` chunk, from `lhandle` to the same offset in
+    /// `rhandle`. Only the last chunk is guaranteed to be signaled.
+    fn post_chunks(
+        &mut self,
+        lhandle: &RdmaBuffer,
+        rhandle: &RdmaBuffer,
+        chunks: &[(usize, usize)],
+        op: RdmaOperation,
+    ) -> Result<(), RdmaError> {
+        for (i, &(offset, len)) in chunks.iter().enumerate() {
             let idx = self.send_wqe_idx;
             self.send_wqe_idx += 1;
+            let signaled = self.should_signal(idx, i + 1 == chunks.len());
             self.post_op(
                 lhandle.addr + offset,
                 lhandle.lkey,
-                chunk_size,
+                len,
                 idx,
-                true,
-                RdmaOperation::Write,
+                signaled,
+                op,
                 rhandle.addr + offset,
                 rhandle.rkey,
             )?;
             self.send_db_idx += 1;
-
-            remaining -= chunk_size;
-            offset += chunk_size;
         }
-
         Ok(())
     }
 
@@ -887,15 +1511,21 @@ impl RdmaQueuePair {
     ///
     /// # Returns
     ///
-    /// * `Result<DoorBell, anyhow::Error>` - A doorbell for the queue pair
-    pub fn ring_doorbell(&mut self) -> Result<(), anyhow::Error> {
+    /// * `Ok(())` - All enqueued operations were submitted
+    /// * `Err(RdmaError::Overflow)` - More operations are enqueued than the send queue holds
+    pub fn ring_doorbell(&mut self) -> Result<(), RdmaError> {
+        self.require_mlx5("ring_doorbell")?;
         unsafe {
             let dv_qp = self.dv_qp as *mut rdmaxcel_sys::mlx5dv_qp;
             let base_ptr = (*dv_qp).sq.buf as *mut u8;
             let wqe_cnt = (*dv_qp).sq.wqe_cnt;
             let stride = (*dv_qp).sq.stride;
-            if (wqe_cnt as u64) < (self.send_wqe_idx - self.send_db_idx) {
-                return Err(anyhow::anyhow!("Overflow of WQE, possible data loss"));
+            let outstanding = self.send_wqe_idx - self.send_db_idx;
+            if (wqe_cnt as u64) < outstanding {
+                return Err(RdmaError::Overflow {
+                    outstanding,
+                    capacity: wqe_cnt as u64,
+                });
             }
             self.apply_first_op_delay(self.send_db_idx);
             while self.send_db_idx < self.send_wqe_idx {
@@ -920,12 +1550,13 @@ impl RdmaQueuePair {
     ///
     /// # Returns
     ///
-    /// * `Result<(), anyhow::Error>` - Success or error
+    /// * `Result<(), RdmaError>` - Success or error
     pub fn enqueue_put(
         &mut self,
         lhandle: RdmaBuffer,
         rhandle: RdmaBuffer,
-    ) -> Result<(), anyhow::Error> {
+    ) -> Result<(), RdmaError> {
+        self.check_send_capacity()?;
         let idx = self.send_wqe_idx;
         self.send_wqe_idx += 1;
         self.send_wqe(
@@ -953,12 +1584,13 @@ impl RdmaQueuePair {
     ///
     /// # Returns
     ///
-    /// * `Result<(), anyhow::Error>` - Success or error
+    /// * `Result<(), RdmaError>` - Success or error
     pub fn enqueue_put_with_recv(
         &mut self,
         lhandle: RdmaBuffer,
         rhandle: RdmaBuffer,
-    ) -> Result<(), anyhow::Error> {
+    ) -> Result<(), RdmaError> {
+        self.check_send_capacity()?;
         let idx = self.send_wqe_idx;
         self.send_wqe_idx += 1;
         self.send_wqe(
@@ -986,12 +1618,13 @@ impl RdmaQueuePair {
     ///
     /// # Returns
     ///
-    /// * `Result<(), anyhow::Error>` - Success or error
+    /// * `Result<(), RdmaError>` - Success or error
     pub fn enqueue_get(
         &mut self,
         lhandle: RdmaBuffer,
         rhandle: RdmaBuffer,
-    ) -> Result<(), anyhow::Error> {
+    ) -> Result<(), RdmaError> {
+        self.check_send_capacity()?;
         let idx = self.send_wqe_idx;
         self.send_wqe_idx += 1;
         self.send_wqe(
@@ -1007,39 +1640,46 @@ impl RdmaQueuePair {
         Ok(())
     }
 
-    pub fn get(&mut self, lhandle: RdmaBuffer, rhandle: RdmaBuffer) -> Result<(), anyhow::Error> {
-        let total_size = lhandle.size;
-        if rhandle.size < total_size {
-            return Err(anyhow::anyhow!(
-                "Remote buffer size ({}) is smaller than local buffer size ({})",
-                rhandle.size,
-                total_size
-            ));
-        }
-
-        let mut remaining = total_size;
-        let mut offset = 0;
+    pub fn get(&mut self, lhandle: RdmaBuffer, rhandle: RdmaBuffer) -> Result<(), RdmaError> {
+        self.post_chunked(&lhandle, &rhandle, RdmaOperation::Read)
+    }
 
-        while remaining > 0 {
-            let chunk_size = std::cmp::min(remaining, MAX_RDMA_MSG_SIZE);
-            let idx = self.send_wqe_idx;
-            self.send_wqe_idx += 1;
-            self.post_op(
-                lhandle.addr + offset,
-                lhandle.lkey,
-                chunk_size,
-                idx,
-                true,
-                RdmaOperation::Read,
-                rhandle.addr + offset,
-                rhandle.rkey,
-            )?;
-            self.send_db_idx += 1;
+    /// Returns the number of WQEs the send queue can hold.
+    ///
+    /// Only available with the `Mlx5` provider, which exposes the send queue ring buffer.
+    pub fn send_queue_capacity(&self) -> Result<u64, RdmaError> {
+        self.require_mlx5("send_queue_capacity")?;
+        // SAFETY: `require_mlx5` checked that `dv_qp` points to the mlx5dv view of this QP.
+        Ok(unsafe { (*(self.dv_qp as *mut rdmaxcel_sys::mlx5dv_qp)).sq.wqe_cnt as u64 })
+    }
 
-            remaining -= chunk_size;
-            offset += chunk_size;
+    /// Returns `RdmaError::Overflow` if the send queue has no free slot for another WQE.
+    ///
+    /// Enqueued WQEs sit in the send queue ring buffer until the doorbell is rung, so
+    /// posting past its capacity would overwrite WQEs the device has not consumed yet.
+    fn check_send_capacity(&self) -> Result<(), RdmaError> {
+        let capacity = self.send_queue_capacity()?;
+        let outstanding = self.send_wqe_idx - self.send_db_idx;
+        if outstanding >= capacity {
+            return Err(RdmaError::Overflow {
+                outstanding: outstanding + 1,
+                capacity,
+            });
         }
+        Ok(())
+    }
 
+    /// Returns an error if this queue pair was not created with the mlx5 provider.
+    ///
+    /// Direct WQE construction and doorbell ringing dereference the mlx5dv
+    /// structures, which are never created for the `Verbs` provider.
+    fn require_mlx5(&self, op: &str) -> Result<(), RdmaError> {
+        if self.config.provider != RdmaProvider::Mlx5 || self.dv_qp == 0 {
+            return Err(RdmaError::InvalidConfig(format!(
+                "{} requires the Mlx5 provider (configured provider: {:?})",
+                op, self.config.provider
+            )));
+        }
         Ok(())
     }
 
@@ -1064,7 +1704,7 @@ impl RdmaQueuePair {
         op_type: RdmaOperation,
         raddr: usize,
         rkey: u32,
-    ) -> Result<(), anyhow::Error> {
+    ) -> Result<(), RdmaError> {
         // SAFETY:
         // This code uses unsafe rdmaxcel_sys calls to post work requests to the RDMA device, but is safe because:
         // - All pointers (send_sge, send_wr) are properly initialized on the stack before use
@@ -1131,7 +1771,10 @@ impl RdmaQueuePair {
 
             if errno != 0 {
                 let os_error = Error::last_os_error();
-                return Err(anyhow::anyhow!("Failed to post send request: {}", os_error));
+                return Err(RdmaError::Device(format!(
+                    "Failed to post send request: {}",
+                    os_error
+                )));
             }
             tracing::debug!(
                 "completed sending {:?} request (lkey: {}, addr: 0x{:x}, length {}) to (raddr 0x{:x}, rkey {})",
@@ -1157,7 +1800,8 @@ impl RdmaQueuePair {
         op_type: RdmaOperation,
         raddr: usize,
         rkey: u32,
-    ) -> Result<DoorBell, anyhow::Error> {
+    ) -> Result<DoorBell, RdmaError> {
+        self.require_mlx5("send_wqe")?;
         unsafe {
             let op_type_val = match op_type {
                 RdmaOperation::Write => rdmaxcel_sys::MLX5_OPCODE_RDMA_WRITE,
@@ -1224,11 +1868,13 @@ impl RdmaQueuePair {
     ///
     /// * `Ok(Some(wc))` - A completion was found
     /// * `Ok(None)` - No completion was found
-    /// * `Err(e)` - An error occurred
+    /// * `Err(RdmaError::CompletionStatus)` - The completion reported an error
+    /// * `Err(RdmaError::Device)` - Polling the completion queue failed
     pub fn poll_completion_target(
         &mut self,
         target: PollTarget,
-    ) -> Result<Option<IbvWc>, anyhow::Error> {
+    ) -> Result<Option<IbvWc>, RdmaError> {
+        self.cq_poll_count += 1;
         unsafe {
             let context = self.context as *mut rdmaxcel_sys::ibv_context;
             let _outstanding_wqe =
@@ -1242,28 +1888,26 @@ impl RdmaQueuePair {
                 let ret = ops.poll_cq.as_mut().unwrap()(send_cq, 1, &mut wc);
 
                 if ret < 0 {
-                    return Err(anyhow::anyhow!(
+                    return Err(RdmaError::Device(format!(
                         "Failed to poll send CQ: {}",
                         Error::last_os_error()
-                    ));
+                    )));
                 }
 
                 if ret > 0 {
-                    if !wc.is_valid() {
-                        if let Some((status, vendor_err)) = wc.error() {
-                            return Err(anyhow::anyhow!(
-                                "Send work completion failed with status: {:?}, vendor error: {}, wr_id: {}, send_cq_idx: {}",
-                                status,
-                                vendor_err,
-                                wc.wr_id(),
-                                self.send_cq_idx,
-                            ));
-                        }
+                    if let Some(err) = RdmaError::from_wc(&wc) {
+                        tracing::error!(
+                            "send work completion failed: {}, send_cq_idx: {}",
+                            err,
+                            self.send_cq_idx
+                        );
+                        return Err(err);
                     }
 
-                    // This should be a send completion - verify it's the one we're waiting for
-                    if wc.wr_id() == self.send_cq_idx {
-                        self.send_cq_idx += 1;
+                    // Send WQEs complete in order, so a signaled completion also covers
+                    // the unsignaled WQEs posted before it.
+                    if wc.wr_id() >= self.send_cq_idx {
+                        self.send_cq_idx = wc.wr_id() + 1;
                     }
                     // finished polling, return the last completion
                     if self.send_cq_idx == self.send_db_idx {
@@ -1280,23 +1924,20 @@ impl RdmaQueuePair {
                 let ret = ops.poll_cq.as_mut().unwrap()(recv_cq, 1, &mut wc);
 
                 if ret < 0 {
-                    return Err(anyhow::anyhow!(
+                    return Err(RdmaError::Device(format!(
                         "Failed to poll receive CQ: {}",
                         Error::last_os_error()
-                    ));
+                    )));
                 }
 
                 if ret > 0 {
-                    if !wc.is_valid() {
-                        if let Some((status, vendor_err)) = wc.error() {
-                            return Err(anyhow::anyhow!(
-                                "Recv work completion failed with status: {:?}, vendor error: {}, wr_id: {}, send_cq_idx: {}",
-                                status,
-                                vendor_err,
-                                wc.wr_id(),
-                                self.recv_cq_idx,
-                            ));
-                        }
+                    if let Some(err) = RdmaError::from_wc(&wc) {
+                        tracing::error!(
+                            "recv work completion failed: {}, recv_cq_idx: {}",
+                            err,
+                            self.recv_cq_idx
+                        );
+                        return Err(err);
                     }
 
                     // This should be a send completion - verify it's the one we're waiting for
@@ -1315,55 +1956,563 @@ impl RdmaQueuePair {
         }
     }
 
-    pub fn poll_send_completion(&mut self) -> Result<Option<IbvWc>, anyhow::Error> {
+    pub fn poll_send_completion(&mut self) -> Result<Option<IbvWc>, RdmaError> {
         self.poll_completion_target(PollTarget::Send)
     }
 
-    pub fn poll_recv_completion(&mut self) -> Result<Option<IbvWc>, anyhow::Error> {
+    pub fn poll_recv_completion(&mut self) -> Result<Option<IbvWc>, RdmaError> {
         self.poll_completion_target(PollTarget::Recv)
     }
+
+    /// Drains up to `max` completions from the `target` completion queue in a single poll.
+    ///
+    /// Unlike `poll_completion_target`, which takes one completion per call, this amortizes
+    /// the cost of polling when many work requests complete at once. `send_cq_idx` and
+    /// `recv_cq_idx` advance past every completion drained.
+    ///
+    /// If a drained completion reported an error, every other completion from the same
+    /// poll is handed to the completion dispatcher, where waiters can still claim it, and
+    /// the first error is returned.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(wcs)` - The completions drained, oldest first; empty if none were ready
+    /// * `Err(RdmaError::InvalidConfig)` - `max` is 0 or exceeds the completion queue's depth
+    /// * `Err(RdmaError::CompletionStatus)` - A drained completion reported an error
+    /// * `Err(RdmaError::Device)` - Polling the completion queue failed
+    pub fn poll_completions(
+        &mut self,
+        target: PollTarget,
+        max: usize,
+    ) -> Result<Vec<IbvWc>, RdmaError> {
+        let cq = match target {
+            PollTarget::Send => self.send_cq,
+            PollTarget::Recv => self.recv_cq,
+        };
+        // SAFETY: The CQ was created with this queue pair and outlives it.
+        let depth = unsafe { (*(cq as *mut rdmaxcel_sys::ibv_cq)).cqe }.max(0) as usize;
+        if max == 0 || max > depth {
+            return Err(RdmaError::InvalidConfig(format!(
+                "poll batch of {} must be between 1 and the completion queue depth ({})",
+                max, depth
+            )));
+        }
+        self.cq_poll_count += 1;
+        let wcs =
+            crate::completion_dispatcher::poll_cq_batch(cq, max).map_err(RdmaError::Device)?;
+        let mut completions = Vec::with_capacity(wcs.len());
+        let mut first_error = None;
+        for wc in wcs {
+            let wr_id = wc.wr_id();
+            match target {
+                // Send WQEs complete in order, so a signaled completion also covers
+                // the unsignaled WQEs posted before it.
+                PollTarget::Send if wr_id >= self.send_cq_idx => {
+                    self.send_cq_idx = wr_id + 1;
+                }
+                PollTarget::Recv if wr_id == self.recv_cq_idx => self.recv_cq_idx += 1,
+                _ => {}
+            }
+            let result = match RdmaError::from_wc(&wc) {
+                Some(err) => {
+                    tracing::error!("{:?} work completion failed: {}", target, err);
+                    Err(err)
+                }
+                None => Ok(IbvWc::from(wc)),
+            };
+            match result {
+                Err(err) if first_error.is_none() => first_error = Some(err),
+                result => completions.push((wr_id, result)),
+            }
+        }
+        match first_error {
+            None => Ok(completions
+                .into_iter()
+                .filter_map(|(_, result)| result.ok())
+                .collect()),
+            Some(err) => {
+                // Nothing else can observe these completions once they are off the CQ.
+                let dispatcher = crate::completion_dispatcher::completion_dispatcher();
+                for (wr_id, result) in completions {
+                    dispatcher.hold(cq, wr_id, result);
+                }
+                Err(err)
+            }
+        }
+    }
+
+    /// Returns whether the send work request `wr_id` should generate a completion.
+    ///
+    /// With `signal_every_n` set to N, only every Nth work request is signaled, plus the
+    /// `last` one of a batch so that the batch can be waited on. Completions of unsignaled
+    /// work requests are inferred from the next signaled one.
+    pub fn should_signal(&self, wr_id: u64, last: bool) -> bool {
+        let every_n = u64::from(self.config.signal_every_n.max(1));
+        last || (wr_id + 1) % every_n == 0
+    }
+
+    /// Returns the most completions drained from one of this queue pair's CQs per poll.
+    pub fn poll_batch_size(&self) -> usize {
+        self.config.poll_batch_size
+    }
+
+    /// Returns how many times a completion queue of this queue pair has been polled.
+    pub fn cq_poll_count(&self) -> u64 {
+        self.cq_poll_count
+    }
+
+    /// Polls `target`'s CQ without sleeping for up to `spin_for`, yielding to other
+    /// tasks between empty polls. The CQ is polled at least once.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(wc))` - A work completion arrived while spinning
+    /// * `Ok(None)` - Nothing completed within `spin_for`
+    /// * `Err(RdmaError)` - Polling failed or the completion reported an error
+    pub async fn spin_for_completion(
+        &mut self,
+        target: PollTarget,
+        spin_for: Duration,
+    ) -> Result<Option<IbvWc>, RdmaError> {
+        let start_time = std::time::Instant::now();
+        loop {
+            if let Some(wc) = self.poll_completion_target(target)? {
+                return Ok(Some(wc));
+            }
+            if start_time.elapsed() >= spin_for {
+                return Ok(None);
+            }
+            tokio::task::yield_now().await;
+        }
+    }
+
+    /// Requests a completion event for the next work completion on `target`'s CQ.
+    ///
+    /// The event is delivered to the queue pair's completion channel, so this is only
+    /// meaningful for queue pairs created with `PollStrategy::EventDriven` or
+    /// `PollStrategy::Adaptive`.
+    pub fn arm_completion_target(&self, target: PollTarget) -> Result<(), RdmaError> {
+        let cq = match target {
+            PollTarget::Send => self.send_cq,
+            PollTarget::Recv => self.recv_cq,
+        } as *mut rdmaxcel_sys::ibv_cq;
+        // SAFETY: `cq` and `context` were created together in `new()` and outlive `self`.
+        unsafe {
+            let context = self.context as *mut rdmaxcel_sys::ibv_context;
+            let ops = &mut (*context).ops;
+            let ret = ops.req_notify_cq.as_mut().unwrap()(cq, 0);
+            if ret != 0 {
+                return Err(RdmaError::Device(format!(
+                    "ibv_req_notify_cq failed: {}",
+                    Error::from_raw_os_error(ret)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Waits until the completion channel reports an event on an armed CQ.
+    ///
+    /// The calling task sleeps on the channel's file descriptor instead of polling. All
+    /// pending events are acknowledged before returning; the caller should then poll the
+    /// CQ for the completions themselves.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - At least one completion event arrived
+    /// * `Err(RdmaError::Timeout)` - No event arrived within `timeout`
+    /// * `Err(RdmaError::Device)` - The queue pair has no completion channel, or waiting on it failed
+    pub async fn wait_for_completion_event(&self, timeout: Duration) -> Result<(), RdmaError> {
+        if self.comp_channel == 0 {
+            return Err(RdmaError::Device(
+                "queue pair has no completion channel, use PollStrategy::EventDriven or PollStrategy::Adaptive".to_string(),
+            ));
+        }
+        let async_fd = channel_async_fd(self.comp_channel)?;
+        let mut guard = match RealClock.timeout(timeout, async_fd.readable()).await {
+            Ok(Ok(guard)) => guard,
+            Ok(Err(e)) => {
+                return Err(RdmaError::Device(format!(
+                    "failed to wait on completion channel: {}",
+                    e
+                )));
+            }
+            Err(_) => return Err(RdmaError::Timeout(timeout)),
+        };
+        self.ack_completion_events();
+        guard.clear_ready();
+        Ok(())
+    }
+
+    /// Takes and acknowledges every pending event on the completion channel.
+    fn ack_completion_events(&self) {
+        // SAFETY: the channel's fd is non-blocking, so `ibv_get_cq_event` returns an
+        // error instead of blocking once no events are left.
+        unsafe {
+            let channel = self.comp_channel as *mut rdmaxcel_sys::ibv_comp_channel;
+            let mut cq: *mut rdmaxcel_sys::ibv_cq = std::ptr::null_mut();
+            let mut cq_context: *mut std::ffi::c_void = std::ptr::null_mut();
+            while rdmaxcel_sys::ibv_get_cq_event(channel, &mut cq, &mut cq_context) == 0 {
+                rdmaxcel_sys::ibv_ack_cq_events(cq, 1);
+            }
+        }
+    }
 }
 
-/// Utility to validate execution context.
-///
-/// Remote Execution environments do not always have access to the nvidia_peermem module
-/// and/or set the PeerMappingOverride parameter due to security. This function can be
-/// used to validate that the execution context when running operations that need this
-/// functionality (ie. cudaHostRegisterIoMemory).
-///
-/// # Returns
+/// A completion channel's file descriptor, borrowed for registration with tokio.
+struct ChannelFd(RawFd);
+
+impl AsRawFd for ChannelFd {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+/// Completion channel fds registered with tokio, keyed by channel address. A channel
+/// is registered on its first wait and deregistered by `destroy_comp_channel`, so
+/// every queue pair sharing the channel reuses one registration.
+static CHANNEL_FDS: LazyLock<Mutex<HashMap<usize, Arc<tokio::io::unix::AsyncFd<ChannelFd>>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the tokio registration for `channel`, creating it on first use.
+fn channel_async_fd(channel: usize) -> Result<Arc<tokio::io::unix::AsyncFd<ChannelFd>>, RdmaError> {
+    let mut fds = CHANNEL_FDS.lock().unwrap();
+    if let Some(async_fd) = fds.get(&channel) {
+        return Ok(async_fd.clone());
+    }
+    // SAFETY: the channel was created by `create_comp_channel` and stays alive until
+    // `destroy_comp_channel`, which removes this entry first.
+    let fd = unsafe { (*(channel as *mut rdmaxcel_sys::ibv_comp_channel)).fd };
+    let async_fd =
+        Arc::new(tokio::io::unix::AsyncFd::new(ChannelFd(fd)).map_err(|e| {
+            RdmaError::Device(format!("failed to watch completion channel: {}", e))
+        })?);
+    fds.insert(channel, async_fd.clone());
+    Ok(async_fd)
+}
+
+/// Creates a completion channel on `context` with a non-blocking file descriptor,
+/// so that it can be waited on from async code.
+fn create_comp_channel(
+    context: *mut rdmaxcel_sys::ibv_context,
+) -> Result<*mut rdmaxcel_sys::ibv_comp_channel, RdmaError> {
+    // SAFETY: `context` is an open device context and the channel is only used
+    // after its creation has been checked.
+    unsafe {
+        let channel = rdmaxcel_sys::ibv_create_comp_channel(context);
+        if channel.is_null() {
+            return Err(RdmaError::Device(format!(
+                "failed to create completion channel: {}",
+                Error::last_os_error()
+            )));
+        }
+        let fd = (*channel).fd;
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if flags < 0 || libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) < 0 {
+            let os_error = Error::last_os_error();
+            rdmaxcel_sys::ibv_destroy_comp_channel(channel);
+            return Err(RdmaError::Device(format!(
+                "failed to make completion channel non-blocking: {}",
+                os_error
+            )));
+        }
+        Ok(channel)
+    }
+}
+
+/// Destroys a completion channel created by `create_comp_channel`. A null channel is
+/// ignored. The completion queues attached to it must already be destroyed.
+pub(crate) fn destroy_comp_channel(channel: *mut rdmaxcel_sys::ibv_comp_channel) {
+    if channel.is_null() {
+        return;
+    }
+    // Deregister the fd from tokio before it is closed.
+    CHANNEL_FDS.lock().unwrap().remove(&(channel as usize));
+    // SAFETY: the channel is not referenced by any completion queue anymore.
+    let ret = unsafe { rdmaxcel_sys::ibv_destroy_comp_channel(channel) };
+    if ret != 0 {
+        tracing::warn!("ibv_destroy_comp_channel returned {}", ret);
+    }
+}
+
+impl RdmaQueuePair {
+    /// Creates a queue pair that owns its device resources.
+    ///
+    /// Opens a new `RdmaDomain` on `config.device`, creates the QP and its send/receive
+    /// completion queues (plus the mlx5dv views when using the `Mlx5` provider), and
+    /// zeroes the WQE/doorbell/CQ indices. The returned `ManagedQueuePair` destroys the
+    /// QP and CQs when dropped, so callers never handle the raw ibverbs pointers.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Configuration settings for the queue pair and its device
+    ///
+    /// # Errors
+    ///
+    /// * `RdmaError::InvalidConfig` - `config` can't be used to create a queue pair
+    /// * `RdmaError::Device` - Opening the device or creating the CQs or QP failed
+    /// * `RdmaError::Registration` - Registering the queue pair for GPU Direct RDMA failed
+    pub fn create(config: &IbverbsConfig) -> Result<ManagedQueuePair, RdmaError> {
+        let domain = RdmaDomain::new(config.device.clone())?;
+        let qp = RdmaQueuePair::new(domain.context, domain.pd, config.clone())?;
+        Ok(ManagedQueuePair {
+            qp,
+            _domain: domain,
+        })
+    }
+}
+
+/// Reliability parameters of a queue pair as read back from the device, named after
+/// the corresponding `IbverbsConfig` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QpReliabilityAttrs {
+    pub psn: u32,
+    pub retry_cnt: u8,
+    pub rnr_retry: u8,
+    pub qp_timeout: u8,
+    pub min_rnr_timer: u8,
+}
+
+/// An `RdmaQueuePair` that owns its domain, queue pair and completion queues.
 ///
-/// * `Ok(())` if the execution context is valid
-/// * `Err(anyhow::Error)` if the execution context is invalid
-pub async fn validate_execution_context() -> Result<(), anyhow::Error> {
-    // Check for nvidia peermem
-    match fs::read_to_string("/proc/modules") {
-        Ok(contents) => {
-            if !contents.contains("nvidia_peermem") {
-                return Err(anyhow::anyhow!(
-                    "nvidia_peermem module not found in /proc/modules"
-                ));
+/// A bare `RdmaQueuePair` is a cloneable handle whose resources are owned (and destroyed)
+/// by the `RdmaManagerActor`. A `ManagedQueuePair`, created with `RdmaQueuePair::create()`,
+/// is the sole owner of its resources and releases them on drop. It dereferences to the
+/// underlying `RdmaQueuePair`, so all queue pair operations are available on it directly.
+#[derive(Debug)]
+pub struct ManagedQueuePair {
+    qp: RdmaQueuePair,
+    // Dropped after the QP and CQs have been destroyed in `Drop::drop`.
+    _domain: RdmaDomain,
+}
+
+impl std::ops::Deref for ManagedQueuePair {
+    type Target = RdmaQueuePair;
+
+    fn deref(&self) -> &Self::Target {
+        &self.qp
+    }
+}
+
+impl std::ops::DerefMut for ManagedQueuePair {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.qp
+    }
+}
+
+impl Drop for ManagedQueuePair {
+    fn drop(&mut self) {
+        // SAFETY: the QP and CQs were created by `RdmaQueuePair::new` and are exclusively
+        // owned by this struct, so they are destroyed exactly once, before the domain.
+        unsafe {
+            if self.qp.qp != 0 {
+                let ret = rdmaxcel_sys::ibv_destroy_qp(self.qp.qp as *mut rdmaxcel_sys::ibv_qp);
+                if ret != 0 {
+                    tracing::warn!("ibv_destroy_qp returned {}", ret);
+                }
+            }
+            if self.qp.send_cq != 0 {
+                let ret =
+                    rdmaxcel_sys::ibv_destroy_cq(self.qp.send_cq as *mut rdmaxcel_sys::ibv_cq);
+                if ret != 0 {
+                    tracing::warn!("ibv_destroy_cq (send) returned {}", ret);
+                }
+            }
+            if self.qp.recv_cq != 0 {
+                let ret =
+                    rdmaxcel_sys::ibv_destroy_cq(self.qp.recv_cq as *mut rdmaxcel_sys::ibv_cq);
+                if ret != 0 {
+                    tracing::warn!("ibv_destroy_cq (recv) returned {}", ret);
+                }
             }
         }
-        Err(e) => {
-            return Err(anyhow::anyhow!(e));
-        }
+        destroy_comp_channel(self.qp.comp_channel as *mut rdmaxcel_sys::ibv_comp_channel);
     }
+}
 
-    // Test file access to nvidia params
-    match fs::read_to_string("/proc/driver/nvidia/params") {
-        Ok(contents) => {
-            if !contents.contains("PeerMappingOverride=1") {
-                return Err(anyhow::anyhow!(
-                    "PeerMappingOverride=1 not found in /proc/driver/nvidia/params"
-                ));
+impl ManagedQueuePair {
+    /// Connects a new queue pair on `config.device` to itself and RDMA-writes the first
+    /// half of a `2 * size` byte host buffer into the second half, checking the copy.
+    ///
+    /// Exercises the whole host-memory data path (QP state transitions, memory
+    /// registration, posting and polling) without needing a peer.
+    pub(crate) fn loopback_write(
+        config: &IbverbsConfig,
+        size: usize,
+        timeout: Duration,
+    ) -> Result<(), RdmaError> {
+        let mut queue_pair = RdmaQueuePair::create(config)?;
+        queue_pair.to_init(config.port_num, config.pkey_index)?;
+        let self_info = queue_pair.get_qp_info()?;
+        queue_pair.to_rtr(&self_info)?;
+        queue_pair.to_rts()?;
+
+        let mut buffer: Vec<u8> = (0..2 * size).map(|i| (i % 251) as u8 + 1).collect();
+        buffer[size..].fill(0);
+        let access = rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_LOCAL_WRITE
+            | rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_REMOTE_WRITE;
+        // SAFETY: `buffer` outlives the memory region, which is deregistered below.
+        let mr = unsafe {
+            rdmaxcel_sys::ibv_reg_mr(
+                queue_pair._domain.pd,
+                buffer.as_mut_ptr() as *mut std::ffi::c_void,
+                buffer.len(),
+                access.0 as i32,
+            )
+        };
+        if mr.is_null() {
+            return Err(RdmaError::Registration(format!(
+                "ibv_reg_mr failed: {}",
+                Error::last_os_error()
+            )));
+        }
+        // SAFETY: `mr` was checked to be non-null.
+        let (lkey, rkey) = unsafe { ((*mr).lkey, (*mr).rkey) };
+        let addr = buffer.as_ptr() as usize;
+
+        let result = (|| {
+            let wr_id = queue_pair.send_wqe_idx;
+            queue_pair.send_wqe_idx += 1;
+            queue_pair.post_op(
+                addr,
+                lkey,
+                size,
+                wr_id,
+                true,
+                RdmaOperation::Write,
+                addr + size,
+                rkey,
+            )?;
+            queue_pair.send_db_idx += 1;
+
+            let start = std::time::Instant::now();
+            while queue_pair.poll_send_completion()?.is_none() {
+                if start.elapsed() >= timeout {
+                    return Err(RdmaError::Timeout(timeout));
+                }
+                sleep(Duration::from_millis(1));
             }
+            Ok(())
+        })();
+
+        // SAFETY: `mr` is registered above, and the queue pair that may still refer to it
+        // is not used again.
+        unsafe {
+            rdmaxcel_sys::ibv_dereg_mr(mr);
         }
-        Err(e) => {
-            return Err(anyhow::anyhow!(e));
+        result?;
+        if buffer[size..] != buffer[..size] {
+            return Err(RdmaError::Other(anyhow::anyhow!(
+                "loopback write completed but the destination does not match the source"
+            )));
         }
+        Ok(())
     }
-    Ok(())
+}
+
+/// What the execution context supports for GPUDirect RDMA.
+///
+/// Remote Execution environments do not always have access to the nvidia_peermem module
+/// and/or set the PeerMappingOverride parameter due to security. Both are needed for
+/// operations that register device memory with the NIC (ie. cudaHostRegisterIoMemory).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecContext {
+    /// Whether device memory can be registered with the NIC: nvidia_peermem is loaded and
+    /// the driver has PeerMappingOverride=1.
+    pub gpudirect_capable: bool,
+    /// Whether PyTorch's CUDA caching allocator uses expandable segments, which RDMA over
+    /// CUDA tensors requires.
+    pub allocator_compatible: bool,
+    /// Whether the first CUDA device can export memory as dma-buf.
+    pub dmabuf_backend: bool,
+    /// Why `gpudirect_capable` is false, one entry per missing prerequisite. Empty when it
+    /// is true.
+    pub reasons: Vec<String>,
+}
+
+impl ExecContext {
+    /// Builds the context from the contents of `/proc/modules` and
+    /// `/proc/driver/nvidia/params` (or the errors reading them) and the other probes.
+    fn from_probes(
+        modules: std::io::Result<String>,
+        nvidia_params: std::io::Result<String>,
+        allocator_compatible: bool,
+        dmabuf_backend: bool,
+    ) -> Self {
+        let mut reasons = Vec::new();
+        match modules {
+            Ok(contents) if contents.contains("nvidia_peermem") => {}
+            Ok(_) => reasons.push("nvidia_peermem module not found in /proc/modules".to_string()),
+            Err(e) => reasons.push(format!("failed to read /proc/modules: {}", e)),
+        }
+        match nvidia_params {
+            Ok(contents) if contents.contains("PeerMappingOverride=1") => {}
+            Ok(_) => reasons
+                .push("PeerMappingOverride=1 not found in /proc/driver/nvidia/params".to_string()),
+            Err(e) => reasons.push(format!("failed to read /proc/driver/nvidia/params: {}", e)),
+        }
+        Self {
+            gpudirect_capable: reasons.is_empty(),
+            allocator_compatible,
+            dmabuf_backend,
+            reasons,
+        }
+    }
+
+    fn probe() -> Self {
+        Self::from_probes(
+            fs::read_to_string("/proc/modules"),
+            fs::read_to_string("/proc/driver/nvidia/params"),
+            pt_cuda_allocator_compatibility(),
+            dmabuf_supported(),
+        )
+    }
+
+    /// Returns an error listing the reasons if the context isn't GPUDirect capable.
+    pub fn ensure_gpudirect(&self) -> Result<(), anyhow::Error> {
+        if self.gpudirect_capable {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "GPUDirect RDMA unavailable: {}",
+                self.reasons.join("; ")
+            ))
+        }
+    }
+}
+
+/// Whether CUDA device 0 reports `CU_DEVICE_ATTRIBUTE_DMA_BUF_SUPPORTED`.
+fn dmabuf_supported() -> bool {
+    if !crate::is_cuda_available() {
+        return false;
+    }
+    // SAFETY: Plain CUDA driver queries writing into local out-parameters.
+    unsafe {
+        let mut device: rdmaxcel_sys::CUdevice = std::mem::zeroed();
+        if rdmaxcel_sys::rdmaxcel_cuDeviceGet(&mut device, 0) != rdmaxcel_sys::CUDA_SUCCESS {
+            return false;
+        }
+        let mut value: i32 = 0;
+        rdmaxcel_sys::rdmaxcel_cuDeviceGetAttribute(
+            &mut value,
+            rdmaxcel_sys::CU_DEVICE_ATTRIBUTE_DMA_BUF_SUPPORTED,
+            device,
+        ) == rdmaxcel_sys::CUDA_SUCCESS
+            && value != 0
+    }
+}
+
+static EXEC_CONTEXT: OnceLock<ExecContext> = OnceLock::new();
+
+/// Utility to validate execution context.
+///
+/// The context is probed once per process and cached, like `is_cuda_available`.
+///
+/// # Returns
+///
+/// The [`ExecContext`]; check `gpudirect_capable`, and `reasons` for why it is false.
+pub async fn validate_execution_context() -> ExecContext {
+    EXEC_CONTEXT.get_or_init(ExecContext::probe).clone()
 }
 
 /// Get all segments that have been registered with MRs
@@ -1409,10 +2558,155 @@ pub fn pt_cuda_allocator_compatibility() -> bool {
     unsafe { rdmaxcel_sys::pt_cuda_allocator_compatibility() }
 }
 
+/// `CU_MEMORYTYPE_DEVICE` from `CUmemorytype` in cuda.h.
+const CU_MEMORYTYPE_DEVICE: u32 = 2;
+
+/// Where the memory behind a pointer lives, as reported by the CUDA driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferMemoryType {
+    /// Memory that CUDA does not manage as device memory, e.g. pageable or pinned host memory.
+    Host,
+    /// Device memory on the given CUDA device ordinal.
+    Device(i32),
+}
+
+/// Query the CUDA driver for the memory type and device ordinal of `addr`.
+///
+/// Pointers unknown to CUDA (or any driver error, e.g. when no CUDA driver is loaded)
+/// are reported as `BufferMemoryType::Host`.
+pub fn buffer_memory_type(addr: usize) -> BufferMemoryType {
+    let ptr = addr as rdmaxcel_sys::CUdeviceptr;
+    let mut mem_type: u32 = 0;
+    // SAFETY: We are calling a CUDA driver API that only writes to `mem_type`.
+    let err = unsafe {
+        rdmaxcel_sys::rdmaxcel_cuPointerGetAttribute(
+            &mut mem_type as *mut _ as *mut std::ffi::c_void,
+            rdmaxcel_sys::CU_POINTER_ATTRIBUTE_MEMORY_TYPE,
+            ptr,
+        )
+    };
+    if err != rdmaxcel_sys::CUDA_SUCCESS || mem_type != CU_MEMORYTYPE_DEVICE {
+        return BufferMemoryType::Host;
+    }
+
+    let mut ordinal: i32 = -1;
+    // SAFETY: We are calling a CUDA driver API that only writes to `ordinal`.
+    let err = unsafe {
+        rdmaxcel_sys::rdmaxcel_cuPointerGetAttribute(
+            &mut ordinal as *mut _ as *mut std::ffi::c_void,
+            rdmaxcel_sys::CU_POINTER_ATTRIBUTE_DEVICE_ORDINAL,
+            ptr,
+        )
+    };
+    if err != rdmaxcel_sys::CUDA_SUCCESS {
+        ordinal = -1;
+    }
+    BufferMemoryType::Device(ordinal)
+}
+
+/// Get the PCI address of the GPU that owns the device memory at `ptr`.
+///
+/// # Arguments
+///
+/// * `ptr` - A CUDA device pointer
+///
+/// # Returns
+///
+/// * `Ok(String)` - The PCI address in `dddd:bb:dd.f` form
+/// * `Err(RdmaError::Device)` if `ptr` is not device memory or the device could not be queried
+pub fn pci_address_for_ptr(ptr: u64) -> Result<String, RdmaError> {
+    // Enough space for "ffff:ff:ff.0\0"; the C function requires at least 16 bytes.
+    let mut pci_addr_buf: [std::os::raw::c_char; 16] = [0; 16];
+    // SAFETY: The buffer outlives the call and its length is passed along.
+    let err = unsafe {
+        rdmaxcel_sys::get_cuda_pci_address_from_ptr(
+            ptr,
+            pci_addr_buf.as_mut_ptr(),
+            pci_addr_buf.len(),
+        )
+    };
+    if err != 0 {
+        return Err(RdmaError::Device(format!(
+            "RdmaXcel get_cuda_pci_address_from_ptr failed (addr: 0x{:x}): {}",
+            ptr,
+            crate::rdma_manager_actor::get_rdmaxcel_error_message(err)
+        )));
+    }
+    // SAFETY: On success the C function wrote a NUL-terminated string into the buffer.
+    let pci_addr = unsafe { std::ffi::CStr::from_ptr(pci_addr_buf.as_ptr()) };
+    Ok(pci_addr.to_string_lossy().into_owned())
+}
+
+/// Check that a buffer about to be registered lives on the expected CUDA device.
+///
+/// # Arguments
+///
+/// * `addr` - The starting address of the buffer
+/// * `size` - The size of the buffer in bytes
+/// * `memory_type` - The buffer's memory type, as returned by `buffer_memory_type`
+/// * `expected_device` - The CUDA device ordinal the buffer must live on
+///
+/// # Returns
+///
+/// * `Ok(())` if the buffer is device memory on `expected_device`
+/// * `Err(RdmaError::Registration)` describing the mismatch otherwise
+pub fn validate_buffer_device(
+    addr: usize,
+    size: usize,
+    memory_type: BufferMemoryType,
+    expected_device: i32,
+) -> Result<(), RdmaError> {
+    match memory_type {
+        BufferMemoryType::Device(ordinal) if ordinal == expected_device => Ok(()),
+        BufferMemoryType::Device(ordinal) => Err(RdmaError::Registration(format!(
+            "buffer (addr: 0x{:x}, size: {}) is on CUDA device {}, but this RDMA manager expects CUDA device {}",
+            addr, size, ordinal, expected_device
+        ))),
+        BufferMemoryType::Host => Err(RdmaError::Registration(format!(
+            "buffer (addr: 0x{:x}, size: {}) is host memory, but this RDMA manager expects device memory on CUDA device {}",
+            addr, size, expected_device
+        ))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_host_pointer_rejected_for_cuda_device() {
+        let buffer = vec![0u8; 4096];
+        let addr = buffer.as_ptr() as usize;
+
+        let memory_type = buffer_memory_type(addr);
+        assert_eq!(memory_type, BufferMemoryType::Host);
+
+        let err = validate_buffer_device(addr, buffer.len(), memory_type, 0).unwrap_err();
+        assert!(matches!(err, RdmaError::Registration(_)));
+        assert!(
+            err.to_string().contains("is host memory"),
+            "unexpected error: {}",
+            err
+        );
+        assert!(validate_buffer_device(addr, 4096, BufferMemoryType::Device(1), 0).is_err());
+        assert!(validate_buffer_device(addr, 4096, BufferMemoryType::Device(0), 0).is_ok());
+    }
+
+    #[test]
+    fn test_pci_address_for_host_pointer_is_device_error() {
+        if !crate::is_cuda_available() {
+            println!("Skipping test: CUDA not available");
+            return;
+        }
+        let buffer = vec![0u8; 4096];
+        let err = pci_address_for_ptr(buffer.as_ptr() as u64).unwrap_err();
+        assert!(
+            matches!(err, RdmaError::Device(_)),
+            "expected RdmaError::Device, got {:?}",
+            err
+        );
+    }
+
     #[test]
     fn test_create_connection() {
         // Skip test if RDMA devices are not available
@@ -1472,4 +2766,883 @@ mod tests {
         assert!(server_qp.connect(&client_connection_info).is_ok());
         assert!(client_qp.connect(&server_connection_info).is_ok());
     }
+
+    #[test]
+    fn test_managed_queue_pair_create_and_drop() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            ..Default::default()
+        };
+        // Repeatedly creating and dropping QPs would exhaust device resources
+        // if `Drop` leaked the QP or its completion queues.
+        for _ in 0..256 {
+            let mut queue_pair = RdmaQueuePair::create(&config).unwrap();
+            assert_ne!(queue_pair.qp, 0);
+            assert_eq!(queue_pair.send_wqe_idx, 0);
+            assert_eq!(queue_pair.recv_wqe_idx, 0);
+            assert_eq!(
+                queue_pair.state().unwrap(),
+                rdmaxcel_sys::ibv_qp_state::IBV_QPS_RESET
+            );
+        }
+    }
+
+    #[test]
+    fn test_loopback_connection_info() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            ..Default::default()
+        };
+        let mut server_qp = RdmaQueuePair::create(&config).unwrap();
+        let mut client_qp = RdmaQueuePair::create(&config).unwrap();
+
+        // Exchange connection info through its serialized form, as a side channel would.
+        let server_info =
+            hyperactor::data::Serialized::serialize(&server_qp.connection_info().unwrap()).unwrap();
+        let client_info =
+            hyperactor::data::Serialized::serialize(&client_qp.connection_info().unwrap()).unwrap();
+
+        let client_info: QpConnectionInfo = client_info.deserialized().unwrap();
+        let server_info: QpConnectionInfo = server_info.deserialized().unwrap();
+        server_qp.connect(&client_info).unwrap();
+        client_qp.connect(&server_info).unwrap();
+        assert_eq!(
+            server_qp.state().unwrap(),
+            rdmaxcel_sys::ibv_qp_state::IBV_QPS_RTS
+        );
+        assert_eq!(
+            client_qp.state().unwrap(),
+            rdmaxcel_sys::ibv_qp_state::IBV_QPS_RTS
+        );
+    }
+
+    #[test]
+    fn test_reliability_attrs_readback() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            psn: 0x123456,
+            retry_cnt: 3,
+            rnr_retry: 5,
+            qp_timeout: 18,
+            min_rnr_timer: 20,
+            ..Default::default()
+        };
+        let mut queue_pair = loopback_queue_pair(&config);
+        assert_eq!(
+            queue_pair.reliability_attrs().unwrap(),
+            QpReliabilityAttrs {
+                psn: 0x123456,
+                retry_cnt: 3,
+                rnr_retry: 5,
+                qp_timeout: 18,
+                min_rnr_timer: 20,
+            }
+        );
+
+        let invalid = IbverbsConfig {
+            retry_cnt: 8,
+            ..config
+        };
+        assert!(matches!(
+            RdmaQueuePair::create(&invalid),
+            Err(RdmaError::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_asymmetric_cq_depths() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            send_cq_depth: Some(4096),
+            recv_cq_depth: Some(16),
+            ..Default::default()
+        };
+        let queue_pair = RdmaQueuePair::create(&config).unwrap();
+        // Devices may round a CQ up, but never below the requested depth.
+        let (send_cqe, recv_cqe) = unsafe {
+            (
+                (*(queue_pair.send_cq as *mut rdmaxcel_sys::ibv_cq)).cqe,
+                (*(queue_pair.recv_cq as *mut rdmaxcel_sys::ibv_cq)).cqe,
+            )
+        };
+        assert!(send_cqe >= 4096, "send CQ has {} entries", send_cqe);
+        assert!(recv_cqe >= 16, "recv CQ has {} entries", recv_cqe);
+        assert!(recv_cqe < send_cqe);
+    }
+
+    #[test]
+    fn test_loopback_state_transitions() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            ..Default::default()
+        };
+        let mut queue_pair = RdmaQueuePair::create(&config).unwrap();
+        assert_eq!(
+            queue_pair.state().unwrap(),
+            rdmaxcel_sys::ibv_qp_state::IBV_QPS_RESET
+        );
+
+        queue_pair
+            .to_init(config.port_num, config.pkey_index)
+            .unwrap();
+        assert_eq!(
+            queue_pair.state().unwrap(),
+            rdmaxcel_sys::ibv_qp_state::IBV_QPS_INIT
+        );
+
+        // Loop the queue pair back onto itself.
+        let self_info = queue_pair.get_qp_info().unwrap();
+        queue_pair.to_rtr(&self_info).unwrap();
+        assert_eq!(
+            queue_pair.state().unwrap(),
+            rdmaxcel_sys::ibv_qp_state::IBV_QPS_RTR
+        );
+
+        queue_pair.to_rts().unwrap();
+        assert_eq!(
+            queue_pair.state().unwrap(),
+            rdmaxcel_sys::ibv_qp_state::IBV_QPS_RTS
+        );
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 30)]
+    async fn test_reset_allows_reuse() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        const SIZE: usize = 64;
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            ..Default::default()
+        };
+        let mut queue_pair = loopback_queue_pair(&config);
+        let mut buffer = vec![0u8; 2 * SIZE];
+        let mr = register_host_buffer(&queue_pair, &mut buffer);
+        let (lkey, rkey) = unsafe { ((*mr).lkey, (*mr).rkey) };
+        let addr = buffer.as_ptr() as usize;
+
+        // Writes `value` into the first half and copies it to the second with one write.
+        let write_and_wait = |queue_pair: &mut ManagedQueuePair, value: u8| {
+            unsafe { std::ptr::write_bytes(addr as *mut u8, value, SIZE) };
+            let wr_id = queue_pair.send_wqe_idx;
+            queue_pair.send_wqe_idx += 1;
+            queue_pair
+                .post_op(
+                    addr,
+                    lkey,
+                    SIZE,
+                    wr_id,
+                    true,
+                    RdmaOperation::Write,
+                    addr + SIZE,
+                    rkey,
+                )
+                .unwrap();
+            queue_pair.send_db_idx += 1;
+            let start_time = std::time::Instant::now();
+            while queue_pair.poll_send_completion().unwrap().is_none() {
+                assert!(start_time.elapsed() < Duration::from_secs(5));
+            }
+        };
+
+        write_and_wait(&mut queue_pair, 1);
+        write_and_wait(&mut queue_pair, 2);
+        assert_eq!(queue_pair.send_cq_idx, 2);
+
+        queue_pair.reset().unwrap();
+        assert_eq!(
+            queue_pair.state().unwrap(),
+            rdmaxcel_sys::ibv_qp_state::IBV_QPS_RESET
+        );
+        assert_eq!(
+            (
+                queue_pair.send_wqe_idx,
+                queue_pair.send_db_idx,
+                queue_pair.send_cq_idx,
+                queue_pair.recv_wqe_idx,
+                queue_pair.recv_db_idx,
+                queue_pair.recv_cq_idx,
+            ),
+            (0, 0, 0, 0, 0, 0)
+        );
+
+        // Reconnected, the queue pair starts over at work request 0.
+        let self_info = queue_pair.get_qp_info().unwrap();
+        queue_pair.connect(&self_info).unwrap();
+        write_and_wait(&mut queue_pair, 3);
+        assert_eq!(queue_pair.send_cq_idx, 1);
+        assert_eq!(queue_pair.send_db_idx, 1);
+        assert!(buffer[SIZE..].iter().all(|&byte| byte == 3));
+
+        unsafe {
+            rdmaxcel_sys::ibv_dereg_mr(mr);
+        }
+    }
+
+    #[test]
+    fn test_verbs_provider_skips_mlx5dv() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            provider: RdmaProvider::Verbs,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.resolved_qp_type(),
+            rdmaxcel_sys::RDMA_QP_TYPE_STANDARD
+        );
+
+        let domain = RdmaDomain::new(config.device.clone()).unwrap();
+        let mut queue_pair = RdmaQueuePair::new(domain.context, domain.pd, config).unwrap();
+        assert_ne!(queue_pair.qp, 0);
+        assert_eq!(queue_pair.dv_qp, 0);
+        assert_eq!(queue_pair.dv_send_cq, 0);
+        assert_eq!(queue_pair.dv_recv_cq, 0);
+        assert!(queue_pair.ring_doorbell().is_err());
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 30)]
+    async fn test_event_driven_wait_sleeps_until_completion() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            poll_strategy: PollStrategy::EventDriven,
+            ..Default::default()
+        };
+        let mut queue_pair = loopback_queue_pair(&config);
+        assert_ne!(queue_pair.comp_channel, 0);
+
+        // Nothing has been posted, so the armed CQ must not wake the waiter.
+        queue_pair.arm_completion_target(PollTarget::Send).unwrap();
+        let result = queue_pair
+            .wait_for_completion_event(Duration::from_millis(100))
+            .await;
+        assert!(
+            matches!(result, Err(RdmaError::Timeout(_))),
+            "expected RdmaError::Timeout, got {:?}",
+            result
+        );
+        assert_eq!(queue_pair.cq_poll_count(), 0);
+
+        // Loop a write from the first half of the buffer into the second half.
+        let mut buffer = vec![0u8; 64];
+        buffer[..32].fill(7);
+        let mr = register_host_buffer(&queue_pair, &mut buffer);
+        let (lkey, rkey) = unsafe { ((*mr).lkey, (*mr).rkey) };
+        let addr = buffer.as_ptr() as usize;
+        let wr_id = queue_pair.send_wqe_idx;
+        queue_pair.send_wqe_idx += 1;
+        queue_pair
+            .post_op(
+                addr,
+                lkey,
+                32,
+                wr_id,
+                true,
+                RdmaOperation::Write,
+                addr + 32,
+                rkey,
+            )
+            .unwrap();
+        queue_pair.send_db_idx += 1;
+
+        queue_pair
+            .wait_for_completion_event(Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(queue_pair.poll_send_completion().unwrap().is_some());
+        // The waiter slept until the completion arrived, so a single poll found it.
+        assert_eq!(queue_pair.cq_poll_count(), 1);
+        assert_eq!(&buffer[32..], &[7u8; 32]);
+
+        unsafe {
+            rdmaxcel_sys::ibv_dereg_mr(mr);
+        }
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 30)]
+    async fn test_adaptive_wait_spins_then_sleeps() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let spin_for = Duration::from_millis(50);
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            poll_strategy: PollStrategy::Adaptive { spin_for },
+            ..Default::default()
+        };
+        let mut queue_pair = loopback_queue_pair(&config);
+        assert_ne!(queue_pair.comp_channel, 0);
+
+        let mut buffer = vec![0u8; 64];
+        buffer[..32].fill(7);
+        let mr = register_host_buffer(&queue_pair, &mut buffer);
+        let (lkey, rkey) = unsafe { ((*mr).lkey, (*mr).rkey) };
+        let addr = buffer.as_ptr() as usize;
+        let post_write = |queue_pair: &mut ManagedQueuePair| {
+            let wr_id = queue_pair.send_wqe_idx;
+            queue_pair.send_wqe_idx += 1;
+            queue_pair
+                .post_op(
+                    addr,
+                    lkey,
+                    32,
+                    wr_id,
+                    true,
+                    RdmaOperation::Write,
+                    addr + 32,
+                    rkey,
+                )
+                .unwrap();
+            queue_pair.send_db_idx += 1;
+        };
+
+        // A loopback write completes well within the spin phase.
+        post_write(&mut queue_pair);
+        let start = std::time::Instant::now();
+        let wc = queue_pair
+            .spin_for_completion(PollTarget::Send, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(wc.is_some());
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // With nothing in flight the spin phase gives up after `spin_for`...
+        let start = std::time::Instant::now();
+        let wc = queue_pair
+            .spin_for_completion(PollTarget::Send, spin_for)
+            .await
+            .unwrap();
+        assert!(wc.is_none());
+        assert!(start.elapsed() >= spin_for);
+
+        // ...and an operation that completes later is picked up by the event phase.
+        queue_pair.arm_completion_target(PollTarget::Send).unwrap();
+        let polls = queue_pair.cq_poll_count();
+        post_write(&mut queue_pair);
+        queue_pair
+            .wait_for_completion_event(Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(queue_pair.poll_send_completion().unwrap().is_some());
+        assert_eq!(queue_pair.cq_poll_count(), polls + 1);
+        assert_eq!(&buffer[32..], &[7u8; 32]);
+
+        unsafe {
+            rdmaxcel_sys::ibv_dereg_mr(mr);
+        }
+    }
+
+    /// Creates a queue pair connected back onto itself.
+    #[timed_test::async_timed_test(timeout_secs = 30)]
+    async fn test_event_driven_wait_reuses_channel_registration() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            poll_strategy: PollStrategy::EventDriven,
+            ..Default::default()
+        };
+        let queue_pair = loopback_queue_pair(&config);
+        let channel = queue_pair.comp_channel;
+
+        queue_pair.arm_completion_target(PollTarget::Send).unwrap();
+        for _ in 0..2 {
+            let result = queue_pair
+                .wait_for_completion_event(Duration::from_millis(10))
+                .await;
+            assert!(matches!(result, Err(RdmaError::Timeout(_))));
+        }
+        let first = channel_async_fd(channel).unwrap();
+        let second = channel_async_fd(channel).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        drop((first, second));
+
+        // Destroying the channel drops its registration.
+        drop(queue_pair);
+        assert!(!CHANNEL_FDS.lock().unwrap().contains_key(&channel));
+    }
+
+    fn loopback_queue_pair(config: &IbverbsConfig) -> ManagedQueuePair {
+        let mut queue_pair = RdmaQueuePair::create(config).unwrap();
+        queue_pair
+            .to_init(config.port_num, config.pkey_index)
+            .unwrap();
+        let self_info = queue_pair.get_qp_info().unwrap();
+        queue_pair.to_rtr(&self_info).unwrap();
+        queue_pair.to_rts().unwrap();
+        queue_pair
+    }
+
+    /// Registers `buffer` for local and remote writes in `queue_pair`'s protection domain.
+    fn register_host_buffer(
+        queue_pair: &ManagedQueuePair,
+        buffer: &mut [u8],
+    ) -> *mut rdmaxcel_sys::ibv_mr {
+        let access = rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_LOCAL_WRITE
+            | rdmaxcel_sys::ibv_access_flags::IBV_ACCESS_REMOTE_WRITE;
+        let mr = unsafe {
+            rdmaxcel_sys::ibv_reg_mr(
+                queue_pair._domain.pd,
+                buffer.as_mut_ptr() as *mut std::ffi::c_void,
+                buffer.len(),
+                access.0 as i32,
+            )
+        };
+        assert!(!mr.is_null());
+        mr
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 30)]
+    async fn test_await_transfer_to_completion() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        // No dispatcher task is started here, so the transfer polls its CQ itself.
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            ..Default::default()
+        };
+        let mut queue_pair = loopback_queue_pair(&config);
+        assert!(queue_pair.transfer(PollTarget::Send).is_none());
+
+        let mut buffer = vec![0u8; 64];
+        buffer[..32].fill(7);
+        let mr = register_host_buffer(&queue_pair, &mut buffer);
+        let (lkey, rkey) = unsafe { ((*mr).lkey, (*mr).rkey) };
+        let addr = buffer.as_ptr() as usize;
+        let wr_id = queue_pair.send_wqe_idx;
+        queue_pair.send_wqe_idx += 1;
+        queue_pair
+            .post_op(
+                addr,
+                lkey,
+                32,
+                wr_id,
+                true,
+                RdmaOperation::Write,
+                addr + 32,
+                rkey,
+            )
+            .unwrap();
+        queue_pair.send_db_idx += 1;
+
+        let wc = queue_pair
+            .transfer(PollTarget::Send)
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(wc.wr_id(), wr_id);
+        // Awaiting consumed the completion, so nothing is outstanding any more.
+        assert_eq!(queue_pair.send_cq_idx, queue_pair.send_db_idx);
+        assert!(queue_pair.transfer(PollTarget::Send).is_none());
+        assert_eq!(&buffer[32..], &[7u8; 32]);
+
+        unsafe {
+            rdmaxcel_sys::ibv_dereg_mr(mr);
+        }
+    }
+
+    #[test]
+    fn test_exec_context_reports_missing_prerequisites() {
+        let not_found = || {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "No such file",
+            ))
+        };
+
+        let context = ExecContext::from_probes(
+            Ok("nvidia 1 0 - Live\nnvidia_peermem 1 0 - Live\n".to_string()),
+            Ok("PeerMappingOverride=1\n".to_string()),
+            true,
+            false,
+        );
+        assert!(context.gpudirect_capable);
+        assert!(context.reasons.is_empty());
+        assert!(context.ensure_gpudirect().is_ok());
+
+        let context = ExecContext::from_probes(
+            Ok("nvidia 1 0 - Live\n".to_string()),
+            not_found(),
+            false,
+            true,
+        );
+        assert!(!context.gpudirect_capable);
+        assert!(!context.allocator_compatible);
+        assert!(context.dmabuf_backend);
+        assert_eq!(context.reasons.len(), 2);
+        assert!(context.reasons[0].contains("nvidia_peermem"));
+        assert!(context.reasons[1].contains("/proc/driver/nvidia/params"));
+        let err = context.ensure_gpudirect().unwrap_err().to_string();
+        assert!(err.contains("nvidia_peermem"), "{}", err);
+    }
+
+    #[test]
+    fn test_should_signal_every_n() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            signal_every_n: 4,
+            ..Default::default()
+        };
+        let queue_pair = RdmaQueuePair::create(&config).unwrap();
+        let signaled: Vec<u64> = (0..10)
+            .filter(|&wr_id| queue_pair.should_signal(wr_id, wr_id == 9))
+            .collect();
+        assert_eq!(signaled, vec![3, 7, 9]);
+    }
+
+    #[test]
+    fn test_stripe_chunks() {
+        // Chunks are dealt round-robin, capped at max_chunk.
+        assert_eq!(
+            stripe_chunks(10, 3, 2),
+            vec![vec![(0, 3), (6, 3)], vec![(3, 3), (9, 1)]]
+        );
+        // Small transfers are split so every lane gets a share.
+        assert_eq!(stripe_chunks(5, 1024, 2), vec![vec![(0, 3)], vec![(3, 2)]]);
+        // A single lane matches plain chunking.
+        assert_eq!(stripe_chunks(5, 2, 1), vec![vec![(0, 2), (2, 2), (4, 1)]]);
+        // Lanes without work get no chunks.
+        assert_eq!(
+            stripe_chunks(1, 1024, 3),
+            vec![vec![(0, 1)], vec![], vec![]]
+        );
+        assert_eq!(stripe_chunks(0, 1024, 2), vec![vec![], vec![]]);
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 30)]
+    async fn test_signal_every_n_completes_only_signaled_writes() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        const WRITES: usize = 10;
+        const CHUNK: usize = 8;
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            signal_every_n: 4,
+            ..Default::default()
+        };
+        let mut queue_pair = loopback_queue_pair(&config);
+
+        // Each write copies one chunk of the first half into the second half.
+        let mut buffer = vec![0u8; 2 * WRITES * CHUNK];
+        for (i, byte) in buffer[..WRITES * CHUNK].iter_mut().enumerate() {
+            *byte = (i % 251) as u8 + 1;
+        }
+        let mr = register_host_buffer(&queue_pair, &mut buffer);
+        let (lkey, rkey) = unsafe { ((*mr).lkey, (*mr).rkey) };
+        let addr = buffer.as_ptr() as usize;
+
+        let post_writes = |queue_pair: &mut ManagedQueuePair| {
+            for i in 0..WRITES {
+                let wr_id = queue_pair.send_wqe_idx;
+                queue_pair.send_wqe_idx += 1;
+                let signaled = queue_pair.should_signal(wr_id, i == WRITES - 1);
+                queue_pair
+                    .post_op(
+                        addr + i * CHUNK,
+                        lkey,
+                        CHUNK,
+                        wr_id,
+                        signaled,
+                        RdmaOperation::Write,
+                        addr + (WRITES + i) * CHUNK,
+                        rkey,
+                    )
+                    .unwrap();
+                queue_pair.send_db_idx += 1;
+            }
+        };
+
+        // Drain the send CQ directly to see which work requests produced completions.
+        post_writes(&mut queue_pair);
+        let mut completed = Vec::new();
+        let start_time = std::time::Instant::now();
+        while completed.last() != Some(&(WRITES as u64 - 1)) {
+            assert!(start_time.elapsed() < Duration::from_secs(5));
+            let mut wc = rdmaxcel_sys::ibv_wc::default();
+            let ret = unsafe {
+                let context = queue_pair.context as *mut rdmaxcel_sys::ibv_context;
+                let ops = &mut (*context).ops;
+                ops.poll_cq.as_mut().unwrap()(
+                    queue_pair.send_cq as *mut rdmaxcel_sys::ibv_cq,
+                    1,
+                    &mut wc,
+                )
+            };
+            assert!(ret >= 0);
+            if ret > 0 {
+                assert!(RdmaError::from_wc(&wc).is_none());
+                completed.push(wc.wr_id());
+            }
+        }
+        assert_eq!(completed, vec![3, 7, 9]);
+        assert_eq!(buffer[WRITES * CHUNK..], buffer[..WRITES * CHUNK]);
+
+        // The unsignaled writes are inferred complete from the signaled ones.
+        queue_pair.send_cq_idx = queue_pair.send_db_idx;
+        post_writes(&mut queue_pair);
+        let start_time = std::time::Instant::now();
+        while queue_pair.poll_send_completion().unwrap().is_none() {
+            assert!(start_time.elapsed() < Duration::from_secs(5));
+            RealClock.sleep(Duration::from_millis(1)).await;
+        }
+        assert_eq!(queue_pair.send_cq_idx, 2 * WRITES as u64);
+
+        unsafe {
+            rdmaxcel_sys::ibv_dereg_mr(mr);
+        }
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 30)]
+    async fn test_poll_completions_drains_batch() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        const WRITES: usize = 6;
+        const CHUNK: usize = 8;
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            ..Default::default()
+        };
+        let mut queue_pair = loopback_queue_pair(&config);
+        let depth = config.send_cq_entries() as usize;
+        for max in [0, depth + 1] {
+            assert!(matches!(
+                queue_pair.poll_completions(PollTarget::Send, max),
+                Err(RdmaError::InvalidConfig(_))
+            ));
+        }
+
+        let mut buffer = vec![1u8; 2 * WRITES * CHUNK];
+        let mr = register_host_buffer(&queue_pair, &mut buffer);
+        let (lkey, rkey) = unsafe { ((*mr).lkey, (*mr).rkey) };
+        let addr = buffer.as_ptr() as usize;
+        for i in 0..WRITES {
+            let wr_id = queue_pair.send_wqe_idx;
+            queue_pair.send_wqe_idx += 1;
+            queue_pair
+                .post_op(
+                    addr + i * CHUNK,
+                    lkey,
+                    CHUNK,
+                    wr_id,
+                    true,
+                    RdmaOperation::Write,
+                    addr + (WRITES + i) * CHUNK,
+                    rkey,
+                )
+                .unwrap();
+            queue_pair.send_db_idx += 1;
+        }
+
+        // Loopback writes this small finish almost immediately; give them time to
+        // all land in the CQ so that a single poll sees every one.
+        RealClock.sleep(Duration::from_millis(100)).await;
+        let completions = queue_pair
+            .poll_completions(PollTarget::Send, WRITES)
+            .unwrap();
+        let wr_ids: Vec<u64> = completions.iter().map(|wc| wc.wr_id()).collect();
+        assert_eq!(wr_ids, (0..WRITES as u64).collect::<Vec<_>>());
+        assert_eq!(queue_pair.send_cq_idx, WRITES as u64);
+        assert!(
+            queue_pair
+                .poll_completions(PollTarget::Send, WRITES)
+                .unwrap()
+                .is_empty()
+        );
+
+        unsafe {
+            rdmaxcel_sys::ibv_dereg_mr(mr);
+        }
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 30)]
+    async fn test_recv_pool_replenishes_consumed_buffers() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        const POOL: usize = 2;
+        const WRITES: usize = 5;
+        const SIZE: usize = 64;
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            ..Default::default()
+        };
+        let mut queue_pair = loopback_queue_pair(&config);
+
+        // The first slot is the write source, then one slot per pooled receive buffer,
+        // then the write destination.
+        let mut buffer = vec![0u8; (POOL + 2) * SIZE];
+        let mr = register_host_buffer(&queue_pair, &mut buffer);
+        let (lkey, rkey) = unsafe { ((*mr).lkey, (*mr).rkey) };
+        let base = buffer.as_ptr() as usize;
+        let handle = |slot: usize| RdmaBuffer {
+            owner: ActorRef::attest(hyperactor::id!(test[0].rdma_manager[0])),
+            mr_id: 0,
+            lkey,
+            rkey,
+            addr: base + slot * SIZE,
+            size: SIZE,
+            device_name: config.device.name().clone(),
+        };
+
+        let pool: Vec<_> = (1..=POOL).map(handle).collect();
+        queue_pair.post_recv_pool(&pool).unwrap();
+        assert_eq!(queue_pair.recv_pool_len(), POOL);
+
+        // More writes-with-immediate than pooled buffers, so every one beyond the first
+        // `POOL` lands in a re-posted buffer.
+        let mut consumed = Vec::new();
+        for _ in 0..WRITES {
+            queue_pair
+                .put_with_recv(handle(0), handle(POOL + 1))
+                .unwrap();
+            let start_time = std::time::Instant::now();
+            loop {
+                assert!(start_time.elapsed() < Duration::from_secs(5));
+                let completion = queue_pair
+                    .poll_recv_pool(|_, buffer| consumed.push(buffer.addr))
+                    .unwrap();
+                if completion.is_some() {
+                    break;
+                }
+                RealClock.sleep(Duration::from_millis(1)).await;
+            }
+            while queue_pair.send_cq_idx < queue_pair.send_db_idx {
+                assert!(start_time.elapsed() < Duration::from_secs(5));
+                queue_pair.poll_send_completion().unwrap();
+            }
+        }
+
+        let expected: Vec<_> = (0..WRITES).map(|i| handle(1 + i % POOL).addr).collect();
+        assert_eq!(consumed, expected);
+        assert_eq!(queue_pair.recv_pool_len(), POOL);
+        assert_eq!(queue_pair.recv_cq_idx, WRITES as u64);
+
+        unsafe {
+            rdmaxcel_sys::ibv_dereg_mr(mr);
+        }
+    }
+
+    #[timed_test::async_timed_test(timeout_secs = 30)]
+    async fn test_put_splits_into_max_transfer_chunks() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        const SIZE: usize = 64 * 1024 + 17;
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            max_transfer_chunk: 4096,
+            ..Default::default()
+        };
+        let mut queue_pair = loopback_queue_pair(&config);
+
+        // Write the first half of the buffer into the second half.
+        let mut buffer = vec![0u8; 2 * SIZE];
+        for (i, byte) in buffer[..SIZE].iter_mut().enumerate() {
+            *byte = (i % 251) as u8 + 1;
+        }
+        let mr = register_host_buffer(&queue_pair, &mut buffer);
+        let (lkey, rkey) = unsafe { ((*mr).lkey, (*mr).rkey) };
+        let addr = buffer.as_ptr() as usize;
+        let handle = |addr: usize| RdmaBuffer {
+            owner: ActorRef::attest(hyperactor::id!(test[0].rdma_manager[0])),
+            mr_id: 0,
+            lkey,
+            rkey,
+            addr,
+            size: SIZE,
+            device_name: config.device.name().clone(),
+        };
+
+        queue_pair.put(handle(addr), handle(addr + SIZE)).unwrap();
+        let chunks = SIZE.div_ceil(4096) as u64;
+        assert_eq!(queue_pair.send_wqe_idx, chunks);
+
+        let start_time = std::time::Instant::now();
+        while queue_pair.send_cq_idx < chunks {
+            assert!(start_time.elapsed() < Duration::from_secs(5));
+            if queue_pair.poll_send_completion().unwrap().is_none() {
+                RealClock.sleep(Duration::from_millis(1)).await;
+            }
+        }
+        assert_eq!(buffer[SIZE..], buffer[..SIZE]);
+
+        unsafe {
+            rdmaxcel_sys::ibv_dereg_mr(mr);
+        }
+    }
 }
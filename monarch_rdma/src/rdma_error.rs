@@ -0,0 +1,209 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ * All rights reserved.
+ *
+ * This source code is licensed under the BSD-style license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! # RDMA Errors
+//!
+//! [`RdmaError`] is returned by the public RDMA data-path APIs so callers can
+//! distinguish timeouts, failed work completions, device failures, memory
+//! registration failures and work queue overflow. It implements
+//! `std::error::Error`, so `?` converts it into an `anyhow::Error`, and any
+//! `anyhow::Error` converts back into [`RdmaError::Other`].
+
+use std::time::Duration;
+
+/// Errors returned by the RDMA APIs.
+#[derive(Debug, thiserror::Error)]
+pub enum RdmaError {
+    /// The operation did not complete within the timeout.
+    #[error("RDMA operation timed out after {0:?}")]
+    Timeout(Duration),
+
+    /// A work completion reported a status other than `IBV_WC_SUCCESS`. `hint`
+    /// suggests how to fix the likely cause; see [`completion_status_hint`].
+    #[error(
+        "work completion failed with status: {status:?}, vendor error: {vendor_err}, wr_id: {wr_id} ({hint})"
+    )]
+    CompletionStatus {
+        status: rdmaxcel_sys::ibv_wc_status::Type,
+        vendor_err: u32,
+        wr_id: u64,
+        hint: &'static str,
+    },
+
+    /// The RDMA or CUDA device failed, e.g. polling a CQ or querying a pointer.
+    #[error("device error: {0}")]
+    Device(String),
+
+    /// Memory registration failed or the buffer is not valid for registration.
+    #[error("memory registration failed: {0}")]
+    Registration(String),
+
+    /// More work requests are outstanding than the work queue can hold.
+    #[error(
+        "work queue overflow: {outstanding} outstanding work requests exceed capacity {capacity}"
+    )]
+    Overflow { outstanding: u64, capacity: u64 },
+
+    /// `ibv_modify_qp` rejected a queue pair state transition. `target` names
+    /// the state, e.g. `"RTR"`, and `errno` is the error the provider reported.
+    #[error("failed to transition QP to {target}: {}", os_error(.errno))]
+    StateTransition { target: &'static str, errno: i32 },
+
+    /// A transfer is larger than the buffer it reads from or writes to.
+    #[error("transfer of {required} bytes exceeds a buffer of {available} bytes")]
+    SizeMismatch { required: usize, available: usize },
+
+    /// A configuration value is outside the range the device or ibverbs accepts.
+    #[error("invalid RDMA configuration: {0}")]
+    InvalidConfig(String),
+
+    /// Any other failure.
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl RdmaError {
+    /// Returns the error for a failed work completion, or `None` if `wc` succeeded.
+    pub fn from_wc(wc: &rdmaxcel_sys::ibv_wc) -> Option<Self> {
+        wc.error()
+            .map(|(status, vendor_err)| RdmaError::CompletionStatus {
+                status,
+                vendor_err,
+                wr_id: wc.wr_id(),
+                hint: completion_status_hint(status),
+            })
+    }
+}
+
+/// Returns the OS error for `errno`, for display in error messages.
+fn os_error(errno: &i32) -> std::io::Error {
+    std::io::Error::from_raw_os_error(*errno)
+}
+
+/// Returns a short remediation hint for a failed work completion status.
+pub fn completion_status_hint(status: rdmaxcel_sys::ibv_wc_status::Type) -> &'static str {
+    use rdmaxcel_sys::ibv_wc_status;
+
+    match status {
+        ibv_wc_status::IBV_WC_SUCCESS => "the work request succeeded",
+        ibv_wc_status::IBV_WC_LOC_LEN_ERR => {
+            "the message is longer than the receive buffer or max_msg_sz; post larger recv buffers or split the transfer"
+        }
+        ibv_wc_status::IBV_WC_LOC_QP_OP_ERR => {
+            "the work request is invalid for this queue pair; check its sge count, inline size and opcode against the QP caps"
+        }
+        ibv_wc_status::IBV_WC_LOC_PROT_ERR => {
+            "a local buffer is outside its memory region or uses the wrong lkey; check the registration covers the whole buffer"
+        }
+        ibv_wc_status::IBV_WC_WR_FLUSH_ERR => {
+            "the queue pair entered the error state; look for an earlier failed completion and reconnect the QP"
+        }
+        ibv_wc_status::IBV_WC_MW_BIND_ERR => {
+            "the memory window bind failed; check the MR allows binding and the access flags"
+        }
+        ibv_wc_status::IBV_WC_BAD_RESP_ERR => {
+            "the responder sent an unexpected reply; check both sides use the same transport and MTU"
+        }
+        ibv_wc_status::IBV_WC_LOC_ACCESS_ERR => {
+            "a local buffer was registered without the needed access flags; register it with LOCAL_WRITE"
+        }
+        ibv_wc_status::IBV_WC_REM_INV_REQ_ERR => {
+            "the remote side rejected the request; check the message fits its recv buffer and the opcode is enabled on its QP"
+        }
+        ibv_wc_status::IBV_WC_REM_ACCESS_ERR => {
+            "the remote address or rkey is invalid or lacks REMOTE_READ/REMOTE_WRITE; check the remote buffer is still registered"
+        }
+        ibv_wc_status::IBV_WC_REM_OP_ERR => {
+            "the remote side failed to execute the operation; check its completion queue for errors"
+        }
+        ibv_wc_status::IBV_WC_RETRY_EXC_ERR => {
+            "the remote side did not acknowledge in time; check the link and the remote QP are up, or increase retry_cnt and qp_timeout"
+        }
+        ibv_wc_status::IBV_WC_RNR_RETRY_EXC_ERR => {
+            "the remote side had no receive buffer posted; increase rnr_retry or post more recv buffers"
+        }
+        ibv_wc_status::IBV_WC_REM_INV_RD_REQ_ERR => {
+            "the remote side rejected the RDMA read; check max_rd_atomic and max_dest_rd_atomic"
+        }
+        ibv_wc_status::IBV_WC_REM_ABORT_ERR => {
+            "the remote side aborted the operation; check its logs for errors"
+        }
+        ibv_wc_status::IBV_WC_FATAL_ERR => {
+            "the device reported a fatal error; check the NIC and driver state, it may need a reset"
+        }
+        ibv_wc_status::IBV_WC_RESP_TIMEOUT_ERR => {
+            "the response timed out; increase qp_timeout or check for congestion"
+        }
+        _ => "check the vendor error code in the device documentation",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_wc_reports_completion_status() {
+        // The default work completion carries IBV_WC_GENERAL_ERR.
+        let wc = rdmaxcel_sys::ibv_wc::default();
+        match RdmaError::from_wc(&wc) {
+            Some(RdmaError::CompletionStatus { status, wr_id, .. }) => {
+                assert_eq!(status, rdmaxcel_sys::ibv_wc_status::IBV_WC_GENERAL_ERR);
+                assert_eq!(wr_id, 0);
+            }
+            other => panic!("expected CompletionStatus, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_completion_status_hints() {
+        use rdmaxcel_sys::ibv_wc_status;
+
+        assert!(
+            completion_status_hint(ibv_wc_status::IBV_WC_RNR_RETRY_EXC_ERR)
+                .contains("increase rnr_retry or post more recv buffers")
+        );
+        assert!(completion_status_hint(ibv_wc_status::IBV_WC_RETRY_EXC_ERR).contains("retry_cnt"));
+        assert!(completion_status_hint(ibv_wc_status::IBV_WC_REM_ACCESS_ERR).contains("rkey"));
+        assert!(completion_status_hint(ibv_wc_status::IBV_WC_GENERAL_ERR).contains("vendor error"));
+
+        // The default work completion carries IBV_WC_GENERAL_ERR.
+        let err = RdmaError::from_wc(&rdmaxcel_sys::ibv_wc::default()).unwrap();
+        assert!(err.to_string().contains("check the vendor error code"));
+    }
+
+    #[test]
+    fn test_anyhow_round_trip() {
+        let err: anyhow::Error = RdmaError::Timeout(Duration::from_secs(1)).into();
+        assert!(matches!(
+            err.downcast_ref::<RdmaError>(),
+            Some(RdmaError::Timeout(_))
+        ));
+
+        let err: anyhow::Error = RdmaError::StateTransition {
+            target: "RTR",
+            errno: libc::EINVAL,
+        }
+        .into();
+        assert!(matches!(
+            err.downcast_ref::<RdmaError>(),
+            Some(RdmaError::StateTransition {
+                target: "RTR",
+                errno: libc::EINVAL
+            })
+        ));
+        assert!(
+            err.to_string()
+                .starts_with("failed to transition QP to RTR: ")
+        );
+
+        let err: RdmaError = anyhow::anyhow!("boom").into();
+        assert!(matches!(err, RdmaError::Other(_)));
+        assert_eq!(err.to_string(), "boom");
+    }
+}
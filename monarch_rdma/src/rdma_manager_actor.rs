@@ -21,13 +21,15 @@
 //!
 //! - Connection establishment with partner actors
 //! - RDMA operations (put/write, get/read)
-//! - Completion polling
+//! - Completion polling, via a single background dispatcher per process
 //! - Memory region management
 //!
 //! ## Usage
 //!
 //! See test examples: `test_rdma_write_loopback` and `test_rdma_read_loopback`.
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::SystemTime;
 
 use async_trait::async_trait;
 use hyperactor::Actor;
@@ -40,19 +42,33 @@ use hyperactor::Instance;
 use hyperactor::Named;
 use hyperactor::OncePortRef;
 use hyperactor::RefClient;
+use hyperactor::clock::Clock;
+use hyperactor::clock::RealClock;
 use hyperactor::supervision::ActorSupervisionEvent;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::completion_dispatcher::CompletionDispatcher;
+use crate::completion_dispatcher::DispatcherHandle;
+use crate::completion_dispatcher::DispatcherStats;
+use crate::completion_dispatcher::completion_dispatcher;
+use crate::ibverbs_primitives::GpuDirectMode;
 use crate::ibverbs_primitives::IbverbsConfig;
 use crate::ibverbs_primitives::RdmaMemoryRegionView;
 use crate::ibverbs_primitives::RdmaQpInfo;
 use crate::ibverbs_primitives::ibverbs_supported;
-use crate::ibverbs_primitives::resolve_qp_type;
+use crate::local_copy::deregister_local_owner;
+use crate::local_copy::deregister_local_region;
+use crate::local_copy::register_local_region;
+use crate::rdma_components::BufferMemoryType;
 use crate::rdma_components::RdmaBuffer;
 use crate::rdma_components::RdmaDomain;
 use crate::rdma_components::RdmaQueuePair;
+use crate::rdma_components::buffer_memory_type;
 use crate::rdma_components::get_registered_cuda_segments;
+use crate::rdma_components::pci_address_for_ptr;
+use crate::rdma_components::validate_buffer_device;
+use crate::rdma_error::RdmaError;
 use crate::validate_execution_context;
 
 /// Represents the state of a queue pair in the manager, either available or checked out.
@@ -72,6 +88,25 @@ pub fn get_rdmaxcel_error_message(error_code: i32) -> String {
     }
 }
 
+/// Describes a memory region currently registered by an `RdmaManagerActor`.
+#[derive(Debug, Clone, PartialEq, Named, Serialize, Deserialize)]
+pub struct RdmaBufferInfo {
+    /// Registration ID, matching `RdmaBuffer::mr_id`.
+    pub mr_id: usize,
+    /// Address of the region as seen by the RDMA device.
+    pub addr: usize,
+    /// Size of the region in bytes.
+    pub size: usize,
+    /// Local key of the memory region.
+    pub lkey: u32,
+    /// Remote key of the memory region.
+    pub rkey: u32,
+    /// Name of the RDMA device the region is registered with.
+    pub device_name: String,
+    /// When the region was registered.
+    pub registered_at: SystemTime,
+}
+
 /// Represents a reference to a remote RDMA buffer that can be accessed via RDMA operations.
 /// This struct encapsulates all the information needed to identify and access a memory region
 /// on a remote host using RDMA.
@@ -95,6 +130,14 @@ pub enum RdmaManagerMessage {
         /// `reply` - Reply channel to return the queue pair for communication
         reply: OncePortRef<RdmaQueuePair>,
     },
+    RequestQueuePairs {
+        other: ActorRef<RdmaManagerActor>,
+        self_device: String,
+        other_device: String,
+        #[reply]
+        /// `reply` - Reply channel to return one queue pair per lane (`IbverbsConfig::num_qps`)
+        reply: OncePortRef<Vec<RdmaQueuePair>>,
+    },
     Connect {
         /// `other` - The ActorId of the actor to connect to
         other: ActorRef<RdmaManagerActor>,
@@ -102,11 +145,14 @@ pub enum RdmaManagerMessage {
         other_device: String,
         /// `endpoint` - Connection information needed to establish the RDMA connection
         endpoint: RdmaQpInfo,
+        /// `lane` - Which of the queue pairs between the two devices to connect
+        lane: usize,
     },
     InitializeQP {
         other: ActorRef<RdmaManagerActor>,
         self_device: String,
         other_device: String,
+        lane: usize,
         #[reply]
         /// `reply` - Reply channel to return the queue pair for communication
         reply: OncePortRef<bool>,
@@ -116,6 +162,7 @@ pub enum RdmaManagerMessage {
         other: ActorRef<RdmaManagerActor>,
         self_device: String,
         other_device: String,
+        lane: usize,
         #[reply]
         /// `reply` - Reply channel to return the connection info
         reply: OncePortRef<RdmaQpInfo>,
@@ -128,6 +175,23 @@ pub enum RdmaManagerMessage {
         /// `qp` - The queue pair to return (ownership transferred back)
         qp: RdmaQueuePair,
     },
+    ReleaseQueuePairs {
+        other: ActorRef<RdmaManagerActor>,
+        self_device: String,
+        other_device: String,
+        /// `qps` - The queue pairs from `RequestQueuePairs`, in lane order
+        qps: Vec<RdmaQueuePair>,
+    },
+    CompletionStats {
+        #[reply]
+        /// `reply` - Reply channel to return the completion dispatcher's counters
+        reply: OncePortRef<DispatcherStats>,
+    },
+    ListBuffers {
+        #[reply]
+        /// `reply` - Reply channel to return the currently registered buffers
+        reply: OncePortRef<Vec<RdmaBufferInfo>>,
+    },
 }
 
 #[derive(Debug)]
@@ -138,8 +202,8 @@ pub enum RdmaManagerMessage {
     ],
 )]
 pub struct RdmaManagerActor {
-    // Nested map: local_device -> (ActorId, remote_device) -> QueuePairState
-    device_qps: HashMap<String, HashMap<(ActorId, String), QueuePairState>>,
+    // Nested map: local_device -> (ActorId, remote_device, lane) -> QueuePairState
+    device_qps: HashMap<String, HashMap<(ActorId, String, usize), QueuePairState>>,
 
     // Map of RDMA device names to their domains and loopback QPs
     // Created lazily when memory is registered for a specific device
@@ -156,12 +220,22 @@ pub struct RdmaManagerActor {
     // Map of unique RdmaMemoryRegionView to ibv_mr*.  In case of cuda w/ pytorch its -1
     // since its managed independently.  Only used for registration/deregistration purposes
     mr_map: HashMap<usize, usize>,
+    // Description of each registration in `mr_map`, reported by `list_buffers`
+    buffer_infos: HashMap<usize, RdmaBufferInfo>,
     // Id for next mrv created
     mrv_id: usize,
 
     // Map of PCI addresses to their optimal RDMA devices
     // This is populated during actor initialization using the device selection algorithm
     pci_to_device: HashMap<String, crate::ibverbs_primitives::RdmaDevice>,
+
+    // Background task that drains completion queues and routes completions to waiters
+    completion_dispatcher: Arc<CompletionDispatcher>,
+    // Keeps the dispatcher's polling task running while this actor is alive; set in `init`
+    dispatcher_handle: Option<DispatcherHandle>,
+
+    // This actor's ID, set in `init`; used to forget its local-copy regions on drop
+    self_id: Option<ActorId>,
 }
 
 impl Drop for RdmaManagerActor {
@@ -203,11 +277,14 @@ impl Drop for RdmaManagerActor {
                     }
                 }
             }
+            crate::rdma_components::destroy_comp_channel(
+                qp.comp_channel as *mut rdmaxcel_sys::ibv_comp_channel,
+            );
         }
 
         // 1. Clean up all queue pairs (both regular and loopback)
         for (device_name, device_map) in self.device_qps.drain() {
-            for ((actor_id, remote_device), qp_state) in device_map {
+            for ((actor_id, remote_device, _lane), qp_state) in device_map {
                 match qp_state {
                     QueuePairState::Available(qp) => {
                         destroy_queue_pair(&qp, &format!("actor {:?}", actor_id));
@@ -233,6 +310,10 @@ impl Drop for RdmaManagerActor {
             drop(domain);
         }
 
+        if let Some(self_id) = &self.self_id {
+            deregister_local_owner(self_id);
+        }
+
         // 3. Clean up memory regions
         let _mr_count = self.mr_map.len();
         for (id, mr_ptr) in self.mr_map.drain() {
@@ -363,42 +444,19 @@ impl RdmaManagerActor {
         &mut self,
         addr: usize,
         size: usize,
-    ) -> Result<(RdmaMemoryRegionView, String), anyhow::Error> {
+    ) -> Result<(RdmaMemoryRegionView, String), RdmaError> {
+        let memory_type = buffer_memory_type(addr);
+        if let Some(expected_device) = self.config.cuda_device {
+            validate_buffer_device(addr, size, memory_type, expected_device)?;
+        }
         unsafe {
-            let mut mem_type: i32 = 0;
-            let ptr = addr as rdmaxcel_sys::CUdeviceptr;
-            let err = rdmaxcel_sys::rdmaxcel_cuPointerGetAttribute(
-                &mut mem_type as *mut _ as *mut std::ffi::c_void,
-                rdmaxcel_sys::CU_POINTER_ATTRIBUTE_MEMORY_TYPE,
-                ptr,
-            );
-            let is_cuda = err == rdmaxcel_sys::CUDA_SUCCESS;
+            let is_cuda = matches!(memory_type, BufferMemoryType::Device(_));
 
             let mut selected_rdma_device = None;
 
             if is_cuda {
-                // Use rdmaxcel utility to get PCI address from CUDA pointer
-                let mut pci_addr_buf: [std::os::raw::c_char; 16] = [0; 16]; // Enough space for "ffff:ff:ff.0\0"
-                let err = rdmaxcel_sys::get_cuda_pci_address_from_ptr(
-                    addr as u64,
-                    pci_addr_buf.as_mut_ptr(),
-                    pci_addr_buf.len(),
-                );
-                if err != 0 {
-                    let error_msg = get_rdmaxcel_error_message(err);
-                    return Err(anyhow::anyhow!(
-                        "RdmaXcel get_cuda_pci_address_from_ptr failed (addr: 0x{:x}, size: {}): {}",
-                        addr,
-                        size,
-                        error_msg
-                    ));
-                }
-
-                // Convert C string to Rust string
-                let pci_addr = std::ffi::CStr::from_ptr(pci_addr_buf.as_ptr())
-                    .to_str()
-                    .unwrap();
-                selected_rdma_device = self.pci_to_device.get(pci_addr).cloned();
+                let pci_addr = pci_address_for_ptr(addr as u64)?;
+                selected_rdma_device = self.pci_to_device.get(&pci_addr).cloned();
             }
 
             // Determine the RDMA device to use
@@ -436,23 +494,20 @@ impl RdmaManagerActor {
                     let err = rdmaxcel_sys::register_segments(domain_pd, loopback_qp_ptr);
                     if err != 0 {
                         let error_msg = get_rdmaxcel_error_message(err);
-                        return Err(anyhow::anyhow!(
+                        return Err(RdmaError::Registration(format!(
                             "RdmaXcel register_segments failed (addr: 0x{:x}, size: {}): {}",
-                            addr,
-                            size,
-                            error_msg
-                        ));
+                            addr, size, error_msg
+                        )));
                     }
 
                     maybe_mrv = self.find_cuda_segment_for_address(addr, size);
                 }
                 // if still not found, throw exception
                 if maybe_mrv.is_none() {
-                    return Err(anyhow::anyhow!(
+                    return Err(RdmaError::Registration(format!(
                         "MR registration failed for cuda (addr: 0x{:x}, size: {}), unable to find segment in CudaCachingAllocator",
-                        addr,
-                        size
-                    ));
+                        addr, size
+                    )));
                 }
                 mrv = maybe_mrv.unwrap();
             } else if is_cuda {
@@ -466,7 +521,10 @@ impl RdmaManagerActor {
                 );
                 mr = rdmaxcel_sys::ibv_reg_dmabuf_mr(domain_pd, 0, size, 0, fd, access.0 as i32);
                 if mr.is_null() {
-                    return Err(anyhow::anyhow!("Failed to register dmabuf MR"));
+                    return Err(RdmaError::Registration(format!(
+                        "failed to register dmabuf MR (addr: 0x{:x}, size: {})",
+                        addr, size
+                    )));
                 }
                 mrv = RdmaMemoryRegionView {
                     id: self.mrv_id,
@@ -487,7 +545,10 @@ impl RdmaManagerActor {
                 );
 
                 if mr.is_null() {
-                    return Err(anyhow::anyhow!("failed to register standard MR"));
+                    return Err(RdmaError::Registration(format!(
+                        "failed to register standard MR (addr: 0x{:x}, size: {})",
+                        addr, size
+                    )));
                 }
 
                 mrv = RdmaMemoryRegionView {
@@ -501,11 +562,24 @@ impl RdmaManagerActor {
                 self.mrv_id += 1;
             }
             self.mr_map.insert(mrv.id, mr as usize);
+            self.buffer_infos.insert(
+                mrv.id,
+                RdmaBufferInfo {
+                    mr_id: mrv.id,
+                    addr: mrv.rdma_addr,
+                    size: mrv.size,
+                    lkey: mrv.lkey,
+                    rkey: mrv.rkey,
+                    device_name: device_name.clone(),
+                    registered_at: RealClock.system_time_now(),
+                },
+            );
             Ok((mrv, device_name))
         }
     }
 
     fn deregister_mr(&mut self, id: usize) -> Result<(), anyhow::Error> {
+        self.buffer_infos.remove(&id);
         if let Some(mr_ptr) = self.mr_map.remove(&id) {
             if mr_ptr != 0 {
                 unsafe {
@@ -515,6 +589,199 @@ impl RdmaManagerActor {
         }
         Ok(())
     }
+
+    /// Checks out queue pair `lane` for communication with `other`, creating and
+    /// connecting it first if it doesn't exist yet. Each lane is a separate queue
+    /// pair between the same two devices.
+    async fn checkout_queue_pair(
+        &mut self,
+        cx: &Context<Self>,
+        other: ActorRef<RdmaManagerActor>,
+        self_device: String,
+        other_device: String,
+        lane: usize,
+    ) -> Result<RdmaQueuePair, anyhow::Error> {
+        let other_id = other.actor_id().clone();
+
+        // Use the nested map structure: local_device -> (actor_id, remote_device, lane) -> QueuePairState
+        let inner_key = (other_id.clone(), other_device.clone(), lane);
+
+        // Check if queue pair exists in map
+        if let Some(device_map) = self.device_qps.get(&self_device) {
+            if let Some(qp_state) = device_map.get(&inner_key).cloned() {
+                match qp_state {
+                    QueuePairState::Available(qp) => {
+                        // Queue pair exists and is available - return it
+                        self.device_qps
+                            .get_mut(&self_device)
+                            .unwrap()
+                            .insert(inner_key, QueuePairState::CheckedOut);
+                        return Ok(qp);
+                    }
+                    QueuePairState::CheckedOut => {
+                        return Err(anyhow::anyhow!(
+                            "queue pair {} for actor {} on device {} is already checked out",
+                            lane,
+                            other_id,
+                            other_device
+                        ));
+                    }
+                }
+            }
+        }
+
+        // Queue pair doesn't exist - need to create connection
+        let is_loopback = other_id == cx.bind::<RdmaManagerActor>().actor_id().clone()
+            && self_device == other_device;
+
+        if is_loopback {
+            // Loopback connection setup
+            self.initialize_qp(
+                cx,
+                other.clone(),
+                self_device.clone(),
+                other_device.clone(),
+                lane,
+            )
+            .await?;
+            let endpoint = self
+                .connection_info(
+                    cx,
+                    other.clone(),
+                    other_device.clone(),
+                    self_device.clone(),
+                    lane,
+                )
+                .await?;
+            self.connect(
+                cx,
+                other.clone(),
+                self_device.clone(),
+                other_device.clone(),
+                endpoint,
+                lane,
+            )
+            .await?;
+        } else {
+            // Remote connection setup
+            self.initialize_qp(
+                cx,
+                other.clone(),
+                self_device.clone(),
+                other_device.clone(),
+                lane,
+            )
+            .await?;
+            other
+                .initialize_qp(
+                    cx,
+                    cx.bind().clone(),
+                    other_device.clone(),
+                    self_device.clone(),
+                    lane,
+                )
+                .await?;
+            let other_endpoint: RdmaQpInfo = other
+                .connection_info(
+                    cx,
+                    cx.bind().clone(),
+                    other_device.clone(),
+                    self_device.clone(),
+                    lane,
+                )
+                .await?;
+            self.connect(
+                cx,
+                other.clone(),
+                self_device.clone(),
+                other_device.clone(),
+                other_endpoint,
+                lane,
+            )
+            .await?;
+            let local_endpoint = self
+                .connection_info(
+                    cx,
+                    other.clone(),
+                    self_device.clone(),
+                    other_device.clone(),
+                    lane,
+                )
+                .await?;
+            other
+                .connect(
+                    cx,
+                    cx.bind().clone(),
+                    other_device.clone(),
+                    self_device.clone(),
+                    local_endpoint,
+                    lane,
+                )
+                .await?;
+        }
+
+        // Now that connection is established, get the queue pair
+        if let Some(device_map) = self.device_qps.get(&self_device) {
+            if let Some(QueuePairState::Available(qp)) = device_map.get(&inner_key).cloned() {
+                self.device_qps
+                    .get_mut(&self_device)
+                    .unwrap()
+                    .insert(inner_key, QueuePairState::CheckedOut);
+                Ok(qp)
+            } else {
+                Err(anyhow::anyhow!(
+                    "Failed to create connection for actor {} on device {}",
+                    other_id,
+                    other_device
+                ))
+            }
+        } else {
+            Err(anyhow::anyhow!(
+                "Failed to create connection for actor {} on device {} - no device map",
+                other_id,
+                other_device
+            ))
+        }
+    }
+
+    /// Returns queue pair `lane` for `other` to the map after a checkout.
+    fn checkin_queue_pair(
+        &mut self,
+        other: &ActorRef<RdmaManagerActor>,
+        self_device: &str,
+        other_device: &str,
+        lane: usize,
+        qp: RdmaQueuePair,
+    ) -> Result<(), anyhow::Error> {
+        let inner_key = (other.actor_id().clone(), other_device.to_string(), lane);
+
+        match self
+            .device_qps
+            .get_mut(self_device)
+            .unwrap()
+            .get_mut(&inner_key)
+        {
+            Some(QueuePairState::CheckedOut) => {
+                self.device_qps
+                    .get_mut(self_device)
+                    .unwrap()
+                    .insert(inner_key, QueuePairState::Available(qp));
+                Ok(())
+            }
+            Some(QueuePairState::Available(_)) => Err(anyhow::anyhow!(
+                "Cannot release queue pair: queue pair for actor {} is already available between devices {} and {}",
+                other.actor_id(),
+                self_device,
+                other_device,
+            )),
+            None => Err(anyhow::anyhow!(
+                "No queue pair found for actor {}, between devices {} and {}",
+                other.actor_id(),
+                self_device,
+                other_device,
+            )),
+        }
+    }
 }
 
 #[async_trait]
@@ -531,15 +798,17 @@ impl Actor for RdmaManagerActor {
         // Use provided config or default if none provided
         let mut config = params.unwrap_or_default();
         tracing::debug!("rdma is enabled, config device hint: {}", config.device);
+        config.validate_num_qps()?;
+        config.validate_poll_batch_size()?;
 
         let pt_cuda_alloc = crate::rdma_components::pt_cuda_allocator_compatibility();
 
-        let mlx5dv_enabled = resolve_qp_type(config.qp_type) == rdmaxcel_sys::RDMA_QP_TYPE_MLX5DV;
+        let mlx5dv_enabled = config.resolved_qp_type() == rdmaxcel_sys::RDMA_QP_TYPE_MLX5DV;
 
-        // check config and hardware support align
-        if config.use_gpu_direct {
-            match validate_execution_context().await {
-                Ok(_) => {
+        // check config and hardware support align, unless GPUDirect is forced on
+        if config.use_gpu_direct && GpuDirectMode::from_env() != GpuDirectMode::On {
+            match validate_execution_context().await.ensure_gpudirect() {
+                Ok(()) => {
                     tracing::info!("GPU Direct RDMA execution context validated successfully");
                 }
                 Err(e) => {
@@ -566,12 +835,18 @@ impl Actor for RdmaManagerActor {
             pt_cuda_alloc,
             mlx5dv_enabled,
             mr_map: HashMap::new(),
+            buffer_infos: HashMap::new(),
             mrv_id: 0,
             pci_to_device,
+            completion_dispatcher: completion_dispatcher(),
+            dispatcher_handle: None,
+            self_id: None,
         })
     }
 
-    async fn init(&mut self, _this: &Instance<Self>) -> Result<(), anyhow::Error> {
+    async fn init(&mut self, this: &Instance<Self>) -> Result<(), anyhow::Error> {
+        self.self_id = Some(this.self_id().clone());
+        self.dispatcher_handle = Some(self.completion_dispatcher.start());
         tracing::debug!("RdmaManagerActor initialized with lazy domain/QP creation");
         Ok(())
     }
@@ -612,6 +887,9 @@ impl RdmaManagerMessageHandler for RdmaManagerActor {
         size: usize,
     ) -> Result<RdmaBuffer, anyhow::Error> {
         let (mrv, device_name) = self.register_mr(addr, size)?;
+        if self.config.local_copy {
+            register_local_region(cx.self_id(), mrv.id, mrv.virtual_addr, mrv.size);
+        }
 
         Ok(RdmaBuffer {
             owner: cx.bind().clone(),
@@ -642,6 +920,7 @@ impl RdmaManagerMessageHandler for RdmaManagerActor {
         _cx: &Context<Self>,
         buffer: RdmaBuffer,
     ) -> Result<(), anyhow::Error> {
+        deregister_local_region(buffer.owner.actor_id(), buffer.mr_id);
         self.deregister_mr(buffer.mr_id)
             .map_err(|e| anyhow::anyhow!("could not deregister buffer: {}", e))?;
         Ok(())
@@ -668,117 +947,48 @@ impl RdmaManagerMessageHandler for RdmaManagerActor {
         self_device: String,
         other_device: String,
     ) -> Result<RdmaQueuePair, anyhow::Error> {
-        let other_id = other.actor_id().clone();
-
-        // Use the nested map structure: local_device -> (actor_id, remote_device) -> QueuePairState
-        let inner_key = (other_id.clone(), other_device.clone());
-
-        // Check if queue pair exists in map
-        if let Some(device_map) = self.device_qps.get(&self_device) {
-            if let Some(qp_state) = device_map.get(&inner_key).cloned() {
-                match qp_state {
-                    QueuePairState::Available(qp) => {
-                        // Queue pair exists and is available - return it
-                        self.device_qps
-                            .get_mut(&self_device)
-                            .unwrap()
-                            .insert(inner_key, QueuePairState::CheckedOut);
-                        return Ok(qp);
-                    }
-                    QueuePairState::CheckedOut => {
-                        return Err(anyhow::anyhow!(
-                            "queue pair for actor {} on device {} is already checked out",
-                            other_id,
-                            other_device
-                        ));
-                    }
-                }
-            }
-        }
-
-        // Queue pair doesn't exist - need to create connection
-        let is_loopback = other_id == cx.bind::<RdmaManagerActor>().actor_id().clone()
-            && self_device == other_device;
+        self.checkout_queue_pair(cx, other, self_device, other_device, 0)
+            .await
+    }
 
-        if is_loopback {
-            // Loopback connection setup
-            self.initialize_qp(cx, other.clone(), self_device.clone(), other_device.clone())
-                .await?;
-            let endpoint = self
-                .connection_info(cx, other.clone(), other_device.clone(), self_device.clone())
-                .await?;
-            self.connect(
-                cx,
-                other.clone(),
-                self_device.clone(),
-                other_device.clone(),
-                endpoint,
-            )
-            .await?;
-        } else {
-            // Remote connection setup
-            self.initialize_qp(cx, other.clone(), self_device.clone(), other_device.clone())
-                .await?;
-            other
-                .initialize_qp(
-                    cx,
-                    cx.bind().clone(),
-                    other_device.clone(),
-                    self_device.clone(),
-                )
-                .await?;
-            let other_endpoint: RdmaQpInfo = other
-                .connection_info(
+    /// Requests `IbverbsConfig::num_qps` queue pairs for communication with a remote
+    /// RDMA manager actor, so a transfer can be striped across all of them.
+    ///
+    /// Either every lane is checked out or none is.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<RdmaQueuePair>, anyhow::Error>` - The queue pairs, indexed by lane.
+    async fn request_queue_pairs(
+        &mut self,
+        cx: &Context<Self>,
+        other: ActorRef<RdmaManagerActor>,
+        self_device: String,
+        other_device: String,
+    ) -> Result<Vec<RdmaQueuePair>, anyhow::Error> {
+        let mut qps = Vec::with_capacity(self.config.num_qps);
+        for lane in 0..self.config.num_qps {
+            match self
+                .checkout_queue_pair(
                     cx,
-                    cx.bind().clone(),
-                    other_device.clone(),
+                    other.clone(),
                     self_device.clone(),
-                )
-                .await?;
-            self.connect(
-                cx,
-                other.clone(),
-                self_device.clone(),
-                other_device.clone(),
-                other_endpoint,
-            )
-            .await?;
-            let local_endpoint = self
-                .connection_info(cx, other.clone(), self_device.clone(), other_device.clone())
-                .await?;
-            other
-                .connect(
-                    cx,
-                    cx.bind().clone(),
                     other_device.clone(),
-                    self_device.clone(),
-                    local_endpoint,
+                    lane,
                 )
-                .await?;
-        }
-
-        // Now that connection is established, get the queue pair
-        if let Some(device_map) = self.device_qps.get(&self_device) {
-            if let Some(QueuePairState::Available(qp)) = device_map.get(&inner_key).cloned() {
-                self.device_qps
-                    .get_mut(&self_device)
-                    .unwrap()
-                    .insert(inner_key, QueuePairState::CheckedOut);
-                Ok(qp)
-            } else {
-                Err(anyhow::anyhow!(
-                    "Failed to create connection for actor {} on device {}",
-                    other_id,
-                    other_device
-                ))
+                .await
+            {
+                Ok(qp) => qps.push(qp),
+                Err(e) => {
+                    // Return the lanes already checked out so a later request can use them.
+                    for (lane, qp) in qps.into_iter().enumerate() {
+                        self.checkin_queue_pair(&other, &self_device, &other_device, lane, qp)?;
+                    }
+                    return Err(e);
+                }
             }
-        } else {
-            Err(anyhow::anyhow!(
-                "Failed to create connection for actor {} on device {} - no device map",
-                other_id,
-                other_device
-            ))
         }
+        Ok(qps)
     }
 
     async fn initialize_qp(
@@ -787,9 +997,10 @@ impl RdmaManagerMessageHandler for RdmaManagerActor {
         other: ActorRef<RdmaManagerActor>,
         self_device: String,
         other_device: String,
+        lane: usize,
     ) -> Result<bool, anyhow::Error> {
         let other_id = other.actor_id().clone();
-        let inner_key = (other_id.clone(), other_device.clone());
+        let inner_key = (other_id.clone(), other_device.clone(), lane);
 
         // Check if QP already exists in nested structure
         if let Some(device_map) = self.device_qps.get(&self_device) {
@@ -854,12 +1065,12 @@ impl RdmaManagerMessageHandler for RdmaManagerActor {
         self_device: String,
         other_device: String,
         endpoint: RdmaQpInfo,
+        lane: usize,
     ) -> Result<(), anyhow::Error> {
         tracing::debug!("connecting with {:?}", other);
         let other_id = other.actor_id().clone();
 
-        // For backward compatibility, use default device
-        let inner_key = (other_id.clone(), other_device.clone());
+        let inner_key = (other_id.clone(), other_device.clone(), lane);
 
         if let Some(device_map) = self.device_qps.get_mut(&self_device) {
             match device_map.get_mut(&inner_key) {
@@ -899,11 +1110,12 @@ impl RdmaManagerMessageHandler for RdmaManagerActor {
         other: ActorRef<RdmaManagerActor>,
         self_device: String,
         other_device: String,
+        lane: usize,
     ) -> Result<RdmaQpInfo, anyhow::Error> {
         tracing::debug!("getting connection info with {:?}", other);
         let other_id = other.actor_id().clone();
 
-        let inner_key = (other_id.clone(), other_device.clone());
+        let inner_key = (other_id.clone(), other_device.clone(), lane);
 
         if let Some(device_map) = self.device_qps.get_mut(&self_device) {
             match device_map.get_mut(&inner_key) {
@@ -944,33 +1156,41 @@ impl RdmaManagerMessageHandler for RdmaManagerActor {
         other_device: String,
         qp: RdmaQueuePair,
     ) -> Result<(), anyhow::Error> {
-        let inner_key = (other.actor_id().clone(), other_device.clone());
+        self.checkin_queue_pair(&other, &self_device, &other_device, 0, qp)
+    }
 
-        match self
-            .device_qps
-            .get_mut(&self_device)
-            .unwrap()
-            .get_mut(&inner_key)
-        {
-            Some(QueuePairState::CheckedOut) => {
-                self.device_qps
-                    .get_mut(&self_device)
-                    .unwrap()
-                    .insert(inner_key, QueuePairState::Available(qp));
-                Ok(())
-            }
-            Some(QueuePairState::Available(_)) => Err(anyhow::anyhow!(
-                "Cannot release queue pair: queue pair for actor {} is already available between devices {} and {}",
-                other.actor_id(),
-                self_device,
-                other_device,
-            )),
-            None => Err(anyhow::anyhow!(
-                "No queue pair found for actor {}, between devices {} and {}",
-                other.actor_id(),
-                self_device,
-                other_device,
-            )),
+    /// Releases the queue pairs from `request_queue_pairs` back to the HashMap.
+    async fn release_queue_pairs(
+        &mut self,
+        _cx: &Context<Self>,
+        other: ActorRef<RdmaManagerActor>,
+        self_device: String,
+        other_device: String,
+        qps: Vec<RdmaQueuePair>,
+    ) -> Result<(), anyhow::Error> {
+        for (lane, qp) in qps.into_iter().enumerate() {
+            self.checkin_queue_pair(&other, &self_device, &other_device, lane, qp)?;
         }
+        Ok(())
+    }
+
+    /// Returns the completion dispatcher's polling counters, including the
+    /// number of completions drained per poll.
+    async fn completion_stats(
+        &mut self,
+        _cx: &Context<Self>,
+    ) -> Result<DispatcherStats, anyhow::Error> {
+        Ok(self.completion_dispatcher.stats())
+    }
+
+    /// Lists the memory regions this actor currently holds registered, ordered by
+    /// registration ID. Useful for tracking down buffers that were never released.
+    async fn list_buffers(
+        &mut self,
+        _cx: &Context<Self>,
+    ) -> Result<Vec<RdmaBufferInfo>, anyhow::Error> {
+        let mut buffers: Vec<_> = self.buffer_infos.values().cloned().collect();
+        buffers.sort_by_key(|info| info.mr_id);
+        Ok(buffers)
     }
 }
@@ -16,8 +16,11 @@
 #[cfg(test)]
 mod tests {
     use crate::PollTarget;
+    use crate::cu_check;
+    use crate::device_guard::device_synchronize;
     use crate::ibverbs_primitives::get_all_devices;
     use crate::rdma_components::validate_execution_context;
+    use crate::rdma_error::RdmaError;
     use crate::rdma_manager_actor::RdmaManagerMessageClient;
     use crate::test_utils::test_utils::RdmaManagerTestEnv;
     use crate::test_utils::test_utils::*;
@@ -60,6 +63,94 @@ mod tests {
         Ok(())
     }
 
+    #[timed_test::async_timed_test(timeout_secs = 60)]
+    async fn test_list_buffers_tracks_registrations() -> Result<(), anyhow::Error> {
+        const BSIZE: usize = 32;
+        // Skip test if RDMA devices are not available
+        let devices = get_all_devices();
+        if devices.is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return Ok(());
+        }
+        let env = RdmaManagerTestEnv::setup(BSIZE, "cpu:0", "cpu:0").await?;
+        let baseline = env.actor_1.list_buffers(&env.client_1).await?.len();
+
+        let mut first = vec![0u8; BSIZE];
+        let mut second = vec![0u8; 2 * BSIZE];
+        let first_handle = env
+            .actor_1
+            .request_buffer(&env.client_1, first.as_mut_ptr() as usize, first.len())
+            .await?;
+        let second_handle = env
+            .actor_1
+            .request_buffer(&env.client_1, second.as_mut_ptr() as usize, second.len())
+            .await?;
+
+        let buffers = env.actor_1.list_buffers(&env.client_1).await?;
+        assert_eq!(buffers.len(), baseline + 2);
+        let info = buffers
+            .iter()
+            .find(|info| info.mr_id == second_handle.mr_id)
+            .expect("second buffer is listed");
+        assert_eq!(info.addr, second_handle.addr);
+        assert_eq!(info.size, 2 * BSIZE);
+        assert_eq!(info.lkey, second_handle.lkey);
+        assert_eq!(info.rkey, second_handle.rkey);
+
+        env.actor_1
+            .release_buffer(&env.client_1, first_handle.clone())
+            .await?;
+        let buffers = env.actor_1.list_buffers(&env.client_1).await?;
+        assert_eq!(buffers.len(), baseline + 1);
+        assert!(buffers.iter().all(|info| info.mr_id != first_handle.mr_id));
+
+        env.actor_1
+            .release_buffer(&env.client_1, second_handle)
+            .await?;
+        env.cleanup().await?;
+        Ok(())
+    }
+
+    // Waiting with nothing posted must surface as a typed timeout.
+    #[timed_test::async_timed_test(timeout_secs = 60)]
+    async fn test_wait_for_completion_times_out_with_rdma_error() -> Result<(), anyhow::Error> {
+        const BSIZE: usize = 32;
+        // Skip test if RDMA devices are not available
+        let devices = get_all_devices();
+        if devices.is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return Ok(());
+        }
+        let env = RdmaManagerTestEnv::setup(BSIZE, "cpu:0", "cpu:0").await?;
+        let mut qp_1 = env
+            .actor_1
+            .request_queue_pair(
+                &env.client_1,
+                env.actor_2.clone(),
+                env.rdma_handle_1.device_name.clone(),
+                env.rdma_handle_2.device_name.clone(),
+            )
+            .await?;
+
+        let result = wait_for_completion(&mut qp_1, PollTarget::Send, 1).await;
+        assert!(
+            matches!(result, Err(RdmaError::Timeout(_))),
+            "expected RdmaError::Timeout, got {:?}",
+            result
+        );
+
+        env.actor_1
+            .release_queue_pair(
+                &env.client_1,
+                env.actor_2.clone(),
+                env.rdma_handle_1.device_name.clone(),
+                env.rdma_handle_2.device_name.clone(),
+                qp_1,
+            )
+            .await?;
+        Ok(())
+    }
+
     #[timed_test::async_timed_test(timeout_secs = 60)]
     async fn test_rdma_write_loopback() -> Result<(), anyhow::Error> {
         const BSIZE: usize = 32;
@@ -97,6 +188,122 @@ mod tests {
         Ok(())
     }
 
+    // Test that checksums differ before a write and match once the contents are copied.
+    #[timed_test::async_timed_test(timeout_secs = 60)]
+    async fn test_checksum_matches_after_write() -> Result<(), anyhow::Error> {
+        const BSIZE: usize = 32;
+        // Skip test if RDMA devices are not available
+        let devices = get_all_devices();
+        if devices.is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return Ok(());
+        }
+        let env = RdmaManagerTestEnv::setup(BSIZE, "cpu:0", "cpu:0").await?;
+        assert_ne!(env.rdma_handle_1.checksum()?, env.rdma_handle_2.checksum()?);
+
+        let mut qp_1 = env
+            .actor_1
+            .request_queue_pair(
+                &env.client_1,
+                env.actor_2.clone(),
+                env.rdma_handle_1.device_name.clone(),
+                env.rdma_handle_2.device_name.clone(),
+            )
+            .await?;
+        qp_1.put(env.rdma_handle_1.clone(), env.rdma_handle_2.clone())?;
+
+        wait_for_completion(&mut qp_1, PollTarget::Send, 2).await?;
+
+        env.actor_1
+            .release_queue_pair(
+                &env.client_1,
+                env.actor_2.clone(),
+                env.rdma_handle_1.device_name.clone(),
+                env.rdma_handle_2.device_name.clone(),
+                qp_1,
+            )
+            .await?;
+
+        assert_eq!(
+            env.rdma_handle_1.read_to_vec()?,
+            env.rdma_handle_2.read_to_vec()?
+        );
+        assert_eq!(env.rdma_handle_1.checksum()?, env.rdma_handle_2.checksum()?);
+        Ok(())
+    }
+
+    // Test that many outstanding writes all complete through the single completion dispatcher.
+    #[timed_test::async_timed_test(timeout_secs = 60)]
+    async fn test_concurrent_writes_complete_via_dispatcher() -> Result<(), anyhow::Error> {
+        const BSIZE: usize = 32;
+        const NUM_WRITES: usize = 16;
+        let devices = get_all_devices();
+        if devices.is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return Ok(());
+        }
+        let env = RdmaManagerTestEnv::setup(BSIZE, "cpu:0", "cpu:0").await?;
+        let dispatcher = crate::completion_dispatcher();
+        let stats_before = env.actor_1.completion_stats(&env.client_1).await?;
+
+        let mut qp_1 = env
+            .actor_1
+            .request_queue_pair(
+                &env.client_1,
+                env.actor_2.clone(),
+                env.rdma_handle_1.device_name.clone(),
+                env.rdma_handle_2.device_name.clone(),
+            )
+            .await?;
+
+        let mut waiters = Vec::new();
+        for _ in 0..NUM_WRITES {
+            let wr_id = qp_1.send_wqe_idx;
+            qp_1.put(env.rdma_handle_1.clone(), env.rdma_handle_2.clone())?;
+            let dispatcher = dispatcher.clone();
+            let send_cq = qp_1.send_cq;
+            let batch_size = qp_1.poll_batch_size();
+            waiters.push((
+                wr_id,
+                tokio::spawn(async move {
+                    dispatcher
+                        .wait_for(
+                            send_cq,
+                            wr_id,
+                            batch_size,
+                            std::time::Duration::from_secs(5),
+                        )
+                        .await
+                }),
+            ));
+        }
+        // Nothing else polls this CQ, so each completion was drained by the dispatcher.
+        for (wr_id, waiter) in waiters {
+            let wc = waiter.await??;
+            assert!(wc.is_valid());
+            assert_eq!(wc.wr_id(), wr_id);
+        }
+        qp_1.send_cq_idx = qp_1.send_db_idx;
+
+        env.actor_1
+            .release_queue_pair(
+                &env.client_1,
+                env.actor_2.clone(),
+                env.rdma_handle_1.device_name.clone(),
+                env.rdma_handle_2.device_name.clone(),
+                qp_1,
+            )
+            .await?;
+
+        let stats_after = env.actor_1.completion_stats(&env.client_1).await?;
+        // Other tests in this process may share the dispatcher, so only lower bounds hold.
+        assert!(stats_after.completions - stats_before.completions >= NUM_WRITES as u64);
+        assert!(stats_after.max_drained >= 1);
+
+        env.verify_buffers(BSIZE).await?;
+        Ok(())
+    }
+
     // Test that RDMA read can be performed between two actors on separate devices.
     #[timed_test::async_timed_test(timeout_secs = 60)]
     async fn test_rdma_read_separate_devices() -> Result<(), anyhow::Error> {
@@ -193,6 +400,46 @@ mod tests {
         Ok(())
     }
 
+    // Filling the send queue without ringing the doorbell must be rejected before
+    // a WQE is overwritten, not detected when the doorbell is rung.
+    #[timed_test::async_timed_test(timeout_secs = 60)]
+    async fn test_enqueue_past_send_queue_capacity_overflows() -> Result<(), anyhow::Error> {
+        const BSIZE: usize = 32;
+        if get_all_devices().is_empty() || !crate::ibverbs_primitives::mlx5dv_supported() {
+            println!("Skipping test: mlx5 RDMA devices not available");
+            return Ok(());
+        }
+        let env = RdmaManagerTestEnv::setup(BSIZE, "cpu:0", "cpu:0").await?;
+        let mut qp_1 = env
+            .actor_1
+            .request_queue_pair(
+                &env.client_1,
+                env.actor_2.clone(),
+                env.rdma_handle_1.device_name.clone(),
+                env.rdma_handle_2.device_name.clone(),
+            )
+            .await?;
+
+        let capacity = qp_1.send_queue_capacity()?;
+        for _ in 0..capacity {
+            qp_1.enqueue_put(env.rdma_handle_1.clone(), env.rdma_handle_2.clone())?;
+        }
+        let result = qp_1.enqueue_put(env.rdma_handle_1.clone(), env.rdma_handle_2.clone());
+        match result {
+            Err(RdmaError::Overflow {
+                outstanding,
+                capacity: reported,
+            }) => {
+                assert_eq!(outstanding, capacity + 1);
+                assert_eq!(reported, capacity);
+            }
+            other => panic!("expected RdmaError::Overflow, got {:?}", other),
+        }
+        // The rejected WQE was not posted.
+        assert_eq!(qp_1.send_wqe_idx - qp_1.send_db_idx, capacity);
+        Ok(())
+    }
+
     #[timed_test::async_timed_test(timeout_secs = 60)]
     #[ignore = "This test needed to be run in isolation"]
     async fn test_rdma_write_separate_devices_db() -> Result<(), anyhow::Error> {
@@ -275,6 +522,72 @@ mod tests {
         Ok(())
     }
 
+    // Buffers registered in the same process are copied without touching the NIC.
+    #[timed_test::async_timed_test(timeout_secs = 60)]
+    async fn test_read_into_same_process_uses_local_copy() -> Result<(), anyhow::Error> {
+        const BSIZE: usize = 32;
+        // Skip test if RDMA devices are not available
+        let devices = get_all_devices();
+        if devices.is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return Ok(());
+        }
+        let env = RdmaManagerTestEnv::setup_with_local_copy(BSIZE, "cpu:0", "cpu:0").await?;
+        let copies_before = crate::local_copy_count();
+
+        env.rdma_handle_1
+            .read_into(env.client_1, env.rdma_handle_2.clone(), 2)
+            .await?;
+
+        env.verify_buffers(BSIZE).await?;
+        assert!(crate::local_copy_count() > copies_before);
+        env.cleanup().await?;
+        Ok(())
+    }
+
+    // With `num_qps` > 1, a transfer is striped across every queue pair.
+    #[timed_test::async_timed_test(timeout_secs = 60)]
+    async fn test_read_into_stripes_across_num_qps() -> Result<(), anyhow::Error> {
+        const BSIZE: usize = 64 * 1024;
+        // Skip test if RDMA devices are not available
+        let devices = get_all_devices();
+        if devices.is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return Ok(());
+        }
+        let env = RdmaManagerTestEnv::setup_with_num_qps(BSIZE, "cpu:0", "cpu:0", 2).await?;
+        env.rdma_handle_1
+            .read_into(env.client_1, env.rdma_handle_2.clone(), 2)
+            .await?;
+        env.verify_buffers(BSIZE).await?;
+
+        let qps = env
+            .actor_1
+            .request_queue_pairs(
+                &env.client_1,
+                env.actor_2.clone(),
+                env.rdma_handle_1.device_name.clone(),
+                env.rdma_handle_2.device_name.clone(),
+            )
+            .await?;
+        assert_eq!(qps.len(), 2);
+        for qp in &qps {
+            assert!(qp.send_wqe_idx > 0);
+            assert_eq!(qp.send_cq_idx, qp.send_wqe_idx);
+        }
+        env.actor_1
+            .release_queue_pairs(
+                &env.client_1,
+                env.actor_2.clone(),
+                env.rdma_handle_1.device_name.clone(),
+                env.rdma_handle_2.device_name.clone(),
+                qps,
+            )
+            .await?;
+        env.cleanup().await?;
+        Ok(())
+    }
+
     // Tests RdmaBufer's `write_from` API
     #[timed_test::async_timed_test(timeout_secs = 60)]
     async fn test_rdma_write_from_cpu_vs_cpu() -> Result<(), anyhow::Error> {
@@ -305,7 +618,71 @@ mod tests {
 
     // Helper function to check if GPU supports P2P
     async fn does_gpu_support_p2p() -> bool {
-        validate_execution_context().await.is_ok()
+        validate_execution_context().await.gpudirect_capable
+    }
+
+    // Test that a VMM buffer mapped with `reserve_and_map` is writable and readable.
+    #[timed_test::async_timed_test(timeout_secs = 30)]
+    async fn test_reserve_and_map_buffer_is_writable() -> Result<(), anyhow::Error> {
+        if is_cpu_only_mode() {
+            println!("Skipping CUDA test in CPU-only mode");
+            return Ok(());
+        }
+        unsafe {
+            let buffer = VmmBuffer::new()?;
+            let (dptr, size) = (buffer.ptr, buffer.size);
+
+            let expected: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+            let mut actual = vec![0u8; size];
+            cu_check!(rdmaxcel_sys::rdmaxcel_cuMemcpyHtoD_v2(
+                dptr,
+                expected.as_ptr() as *const std::ffi::c_void,
+                size
+            ));
+            cu_check!(rdmaxcel_sys::rdmaxcel_cuMemcpyDtoH_v2(
+                actual.as_mut_ptr() as *mut std::ffi::c_void,
+                dptr,
+                size
+            ));
+            assert_eq!(expected, actual);
+        }
+        Ok(())
+    }
+
+    // Test that a device pointer resolves to device memory with a well-formed PCI address.
+    #[timed_test::async_timed_test(timeout_secs = 30)]
+    async fn test_pci_address_for_device_ptr() -> Result<(), anyhow::Error> {
+        if is_cpu_only_mode() {
+            println!("Skipping CUDA test in CPU-only mode");
+            return Ok(());
+        }
+        unsafe {
+            let buffer = VmmBuffer::new()?;
+            let dptr = buffer.ptr;
+
+            assert_eq!(
+                crate::rdma_components::buffer_memory_type(dptr as usize),
+                crate::rdma_components::BufferMemoryType::Device(0)
+            );
+
+            let pci_addr = crate::rdma_components::pci_address_for_ptr(dptr)?;
+            // Expect "dddd:bb:dd.f" with lowercase hex digits.
+            let parts: Vec<&str> = pci_addr.split([':', '.']).collect();
+            assert_eq!(
+                parts.iter().map(|part| part.len()).collect::<Vec<_>>(),
+                vec![4, 2, 2, 1],
+                "malformed PCI address: {}",
+                pci_addr
+            );
+            assert!(
+                parts
+                    .iter()
+                    .all(|part| part.chars().all(|c| c.is_ascii_hexdigit())),
+                "malformed PCI address: {}",
+                pci_addr
+            );
+        }
+        Ok(())
     }
 
     // Test that RDMA write can be performed between two actors on separate devices with CUDA.
@@ -346,6 +723,46 @@ mod tests {
         Ok(())
     }
 
+    // Test that synchronizing both devices succeeds once a doorbell kernel has been queued.
+    #[timed_test::async_timed_test(timeout_secs = 60)]
+    async fn test_device_synchronize_after_queued_write() -> Result<(), anyhow::Error> {
+        if is_cpu_only_mode() {
+            println!("Skipping CUDA test in CPU-only mode");
+            return Ok(());
+        }
+        if !does_gpu_support_p2p().await {
+            println!("Skipping test: GPU P2P not supported");
+            return Ok(());
+        }
+        const BSIZE: usize = 2 * 1024 * 1024;
+        let devices = get_all_devices();
+        if devices.len() < 4 {
+            println!(
+                "skipping this test as it is only configured on H100 nodes with backend network"
+            );
+            return Ok(());
+        }
+        let env = RdmaManagerTestEnv::setup(BSIZE, "cuda:0", "cuda:1").await?;
+        let mut qp_1 = env
+            .actor_1
+            .request_queue_pair(
+                &env.client_1,
+                env.actor_2.clone(),
+                env.rdma_handle_1.device_name.clone(),
+                env.rdma_handle_2.device_name.clone(),
+            )
+            .await?;
+        qp_1.enqueue_put(env.rdma_handle_1.clone(), env.rdma_handle_2.clone())?;
+        ring_db_gpu(&mut qp_1).await?;
+        device_synchronize(0)?;
+        device_synchronize(1)?;
+        wait_for_completion_gpu(&mut qp_1, PollTarget::Send, 5).await?;
+
+        env.verify_buffers(BSIZE).await?;
+        env.cleanup().await?;
+        Ok(())
+    }
+
     // Test that RDMA read can be performed between two actors on separate devices with CUDA.
     #[timed_test::async_timed_test(timeout_secs = 60)]
     #[ignore = "This test needed to be run in isolation"]
@@ -435,6 +852,7 @@ mod tests {
             &env.rdma_handle_2.clone(),
             &env.rdma_handle_1.clone(),
             rdmaxcel_sys::MLX5_OPCODE_RDMA_WRITE_IMM,
+            true,
         )
         .await?;
         ring_db_gpu(&mut qp_2).await?;
@@ -0,0 +1,103 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ * All rights reserved.
+ *
+ * This source code is licensed under the BSD-style license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! # RDMA Transfer
+//!
+//! [`RdmaTransfer`] is a future that resolves when the work posted to one of a
+//! queue pair's completion queues has completed, letting async code `.await`
+//! an RDMA operation instead of spinning on `poll_completion_target`.
+//!
+//! Each poll checks the CQ without blocking; while the work is outstanding the
+//! task's waker is registered with the [`crate::completion_dispatcher`], which
+//! wakes the task once it drains the completion. When no dispatcher task is
+//! live, the future reschedules itself and keeps polling the CQ on its own.
+//! Wrap the future in a clock timeout to bound the wait.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use crate::completion_dispatcher::CompletionDispatcher;
+use crate::completion_dispatcher::completion_dispatcher;
+use crate::ibverbs_primitives::IbvWc;
+use crate::rdma_components::PollTarget;
+use crate::rdma_components::RdmaQueuePair;
+use crate::rdma_error::RdmaError;
+
+/// A future for the work outstanding on a queue pair's send or receive CQ.
+///
+/// Created by [`RdmaQueuePair::transfer`]. Resolves to the completion of the
+/// last work request posted before it was created; since work requests
+/// complete in order, that implies all earlier ones have completed too.
+#[must_use = "futures do nothing unless polled"]
+pub struct RdmaTransfer<'a> {
+    qp: &'a mut RdmaQueuePair,
+    target: PollTarget,
+    cq: usize,
+    wr_id: u64,
+    dispatcher: Arc<CompletionDispatcher>,
+    done: bool,
+}
+
+impl RdmaQueuePair {
+    /// Returns a future for the work posted so far to `target`'s queue.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(RdmaTransfer)` - Work is outstanding on `target`
+    /// * `None` - Everything posted to `target` has already completed
+    pub fn transfer(&mut self, target: PollTarget) -> Option<RdmaTransfer<'_>> {
+        let (cq, db_idx, cq_idx) = match target {
+            PollTarget::Send => (self.send_cq, self.send_db_idx, self.send_cq_idx),
+            PollTarget::Recv => (self.recv_cq, self.recv_db_idx, self.recv_cq_idx),
+        };
+        if db_idx == cq_idx {
+            return None;
+        }
+        Some(RdmaTransfer {
+            qp: self,
+            target,
+            cq,
+            wr_id: db_idx - 1,
+            dispatcher: completion_dispatcher(),
+            done: false,
+        })
+    }
+}
+
+impl Future for RdmaTransfer<'_> {
+    type Output = Result<IbvWc, RdmaError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let result = std::task::ready!(this.dispatcher.poll_completion(
+            this.cq,
+            this.wr_id,
+            this.qp.poll_batch_size(),
+            cx.waker()
+        ));
+        this.done = true;
+        if result.is_ok() {
+            match this.target {
+                PollTarget::Send => this.qp.send_cq_idx = this.wr_id + 1,
+                PollTarget::Recv => this.qp.recv_cq_idx = this.wr_id + 1,
+            }
+        }
+        Poll::Ready(result)
+    }
+}
+
+impl Drop for RdmaTransfer<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            self.dispatcher.cancel_wake(self.cq, self.wr_id);
+        }
+    }
+}
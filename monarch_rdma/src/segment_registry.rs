@@ -0,0 +1,193 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ * All rights reserved.
+ *
+ * This source code is licensed under the BSD-style license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! # Segment Registry
+//!
+//! [`SegmentRegistry`] tracks which PyTorch CUDA caching allocator segments have
+//! been registered with rdmaxcel and deregisters them when it is dropped, so an
+//! owner that shuts down without cleaning up doesn't leak memory regions.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use crate::rdma_components::get_registered_cuda_segments;
+use crate::rdma_error::RdmaError;
+use crate::rdma_manager_actor::get_rdmaxcel_error_message;
+
+/// Tracks registered allocator segments and deregisters them on drop.
+///
+/// Segment registration in rdmaxcel is process-wide: `register` may register
+/// segments outside the requested range, but only the tracked ones are
+/// deregistered when the registry is dropped.
+#[derive(Debug)]
+pub struct SegmentRegistry {
+    pd: *mut rdmaxcel_sys::ibv_pd,
+    qp: *mut rdmaxcel_sys::ibv_qp,
+    /// Size of each tracked segment, keyed by its start address.
+    segments: BTreeMap<usize, usize>,
+}
+
+// SAFETY: The PD and QP pointers are only passed to rdmaxcel, which serializes
+// access to its segment table.
+unsafe impl Send for SegmentRegistry {}
+
+impl SegmentRegistry {
+    /// Creates an empty registry that registers segments in `pd`, binding them
+    /// through `qp`.
+    pub fn new(pd: *mut rdmaxcel_sys::ibv_pd, qp: *mut rdmaxcel_sys::ibv_qp) -> Self {
+        Self {
+            pd,
+            qp,
+            segments: BTreeMap::new(),
+        }
+    }
+
+    /// Syncs with the allocator, registers any new segments and starts tracking
+    /// the ones overlapping `range`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - At least one registered segment overlaps `range`
+    /// * `Err(RdmaError::Registration)` - Registration failed or no segment overlaps `range`
+    pub fn register(&mut self, range: Range<usize>) -> Result<(), RdmaError> {
+        // SAFETY: `pd` and `qp` were provided by the caller and outlive the registry.
+        let err = unsafe { rdmaxcel_sys::register_segments(self.pd, self.qp) };
+        if err != 0 {
+            return Err(RdmaError::Registration(format!(
+                "RdmaXcel register_segments failed (range: 0x{:x}..0x{:x}): {}",
+                range.start,
+                range.end,
+                get_rdmaxcel_error_message(err)
+            )));
+        }
+
+        let mut found = false;
+        for segment in get_registered_cuda_segments() {
+            if overlaps(&range, segment.phys_address, segment.phys_size) {
+                self.segments
+                    .insert(segment.phys_address, segment.phys_size);
+                found = true;
+            }
+        }
+        if !found {
+            return Err(RdmaError::Registration(format!(
+                "no allocator segment overlaps range 0x{:x}..0x{:x}",
+                range.start, range.end
+            )));
+        }
+        Ok(())
+    }
+
+    /// Deregisters the tracked segments overlapping `range` and stops tracking them.
+    pub fn deregister(&mut self, range: Range<usize>) -> Result<(), RdmaError> {
+        let starts: Vec<usize> = self
+            .segments
+            .iter()
+            .filter(|(start, size)| overlaps(&range, **start, **size))
+            .map(|(start, _)| *start)
+            .collect();
+        for start in starts {
+            self.segments.remove(&start);
+            // SAFETY: rdmaxcel looks the segment up by address under its own lock.
+            let err = unsafe { rdmaxcel_sys::deregister_segment(start) };
+            if err != 0 {
+                return Err(RdmaError::Registration(format!(
+                    "RdmaXcel deregister_segment failed (addr: 0x{:x}): {}",
+                    start,
+                    get_rdmaxcel_error_message(err)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `addr` falls in a tracked segment.
+    pub fn contains(&self, addr: usize) -> bool {
+        self.segments
+            .range(..=addr)
+            .next_back()
+            .is_some_and(|(start, size)| addr < start + size)
+    }
+
+    /// The number of tracked segments.
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Whether no segments are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+impl Drop for SegmentRegistry {
+    fn drop(&mut self) {
+        // Only release the segments this registry tracks; others in the process
+        // (e.g. registered by the RDMA manager) must stay registered.
+        for (start, _) in std::mem::take(&mut self.segments) {
+            // SAFETY: rdmaxcel looks the segment up by address under its own lock.
+            let err = unsafe { rdmaxcel_sys::deregister_segment(start) };
+            if err != 0 {
+                tracing::error!(
+                    "Failed to deregister CUDA segment 0x{:x}: {} (error code: {})",
+                    start,
+                    get_rdmaxcel_error_message(err),
+                    err
+                );
+            }
+        }
+    }
+}
+
+fn overlaps(range: &Range<usize>, start: usize, size: usize) -> bool {
+    start < range.end && range.start < start + size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IbverbsConfig;
+    use crate::RdmaDomain;
+    use crate::RdmaQueuePair;
+
+    #[test]
+    fn test_drop_deregisters_all_segments() {
+        // Skip test if RDMA devices are not available
+        if crate::ibverbs_primitives::get_all_devices().is_empty() {
+            println!("Skipping test: RDMA devices not available");
+            return;
+        }
+
+        let config = IbverbsConfig {
+            use_gpu_direct: false,
+            ..Default::default()
+        };
+        let domain = RdmaDomain::new(config.device.clone()).unwrap();
+        let queue_pair = RdmaQueuePair::new(domain.context, domain.pd, config).unwrap();
+        {
+            let mut registry =
+                SegmentRegistry::new(domain.pd, queue_pair.qp as *mut rdmaxcel_sys::ibv_qp);
+            // Only builds using the PyTorch caching allocator have segments to register.
+            if registry.register(0..usize::MAX).is_err() {
+                println!("Skipping test: no CUDA caching allocator segments to register");
+                return;
+            }
+            assert!(!registry.is_empty());
+            assert!(unsafe { rdmaxcel_sys::rdma_get_active_segment_count() } > 0);
+        }
+        assert_eq!(unsafe { rdmaxcel_sys::rdma_get_active_segment_count() }, 0);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        assert!(overlaps(&(10..20), 15, 10));
+        assert!(overlaps(&(10..20), 0, 11));
+        assert!(!overlaps(&(10..20), 20, 5));
+        assert!(!overlaps(&(10..20), 0, 10));
+    }
+}
@@ -0,0 +1,251 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ * All rights reserved.
+ *
+ * This source code is licensed under the BSD-style license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! # RDMA Self Test
+//!
+//! [`self_test`] checks, one step at a time, that a host can run RDMA
+//! transfers: that ibverbs devices are present and queryable, whether a GPU
+//! and the PyTorch allocator integration are usable, and that a loopback write
+//! through a real queue pair succeeds. Each check reports its own outcome, so a
+//! missing GPU shows up as skipped rather than hiding whether host transfers work.
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::ibverbs_primitives::IbverbsConfig;
+use crate::ibverbs_primitives::get_all_devices;
+use crate::ibverbs_primitives::query_device;
+use crate::rdma_components::ManagedQueuePair;
+use crate::rdma_components::RdmaDomain;
+use crate::rdma_components::pt_cuda_allocator_compatibility;
+use crate::test_utils::is_cuda_available;
+
+/// Name of the check that ibverbs devices are present.
+pub const CHECK_DEVICES: &str = "ibverbs_devices";
+/// Name of the check that the default device can be opened and queried.
+pub const CHECK_DEVICE_CAPS: &str = "device_caps";
+/// Name of the check that a CUDA device is available.
+pub const CHECK_CUDA: &str = "cuda";
+/// Name of the check that the PyTorch CUDA allocator is compatible with RDMA.
+pub const CHECK_ALLOCATOR: &str = "allocator_compatibility";
+/// Name of the check that a loopback RDMA write succeeds.
+pub const CHECK_LOOPBACK: &str = "loopback_write";
+
+/// Bytes written by the loopback check.
+const LOOPBACK_SIZE: usize = 4096;
+/// How long the loopback check waits for its completion.
+const LOOPBACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of a single self-test check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckStatus {
+    /// The check succeeded.
+    Passed,
+    /// The check ran and failed, with the reason.
+    Failed(String),
+    /// The check did not run, e.g. because the hardware it needs is absent.
+    Skipped(String),
+}
+
+/// A named self-test check and its outcome.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfTestCheck {
+    pub name: String,
+    pub status: CheckStatus,
+}
+
+/// Results of [`self_test`], one entry per check in the order they ran.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    /// Whether no check failed. Skipped checks do not count as failures.
+    pub fn passed(&self) -> bool {
+        !self
+            .checks
+            .iter()
+            .any(|check| matches!(check.status, CheckStatus::Failed(_)))
+    }
+
+    /// Returns the status of the check called `name`, if it ran.
+    pub fn status(&self, name: &str) -> Option<&CheckStatus> {
+        self.checks
+            .iter()
+            .find(|check| check.name == name)
+            .map(|check| &check.status)
+    }
+
+    fn push(&mut self, name: &str, status: CheckStatus) {
+        self.checks.push(SelfTestCheck {
+            name: name.to_string(),
+            status,
+        });
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            match &check.status {
+                CheckStatus::Passed => writeln!(f, "[PASS] {}", check.name)?,
+                CheckStatus::Failed(reason) => writeln!(f, "[FAIL] {}: {}", check.name, reason)?,
+                CheckStatus::Skipped(reason) => writeln!(f, "[SKIP] {}: {}", check.name, reason)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the RDMA stack on this host works end to end.
+///
+/// Runs, in order: device discovery, a capability query on the default device,
+/// CUDA availability, PyTorch allocator compatibility (skipped without CUDA) and a
+/// loopback write through a queue pair on the default device (skipped without
+/// devices). Never panics or returns early; every check is reported.
+pub fn self_test() -> SelfTestReport {
+    let mut report = SelfTestReport { checks: Vec::new() };
+
+    let devices = get_all_devices();
+    let has_devices = !devices.is_empty();
+    report.push(
+        CHECK_DEVICES,
+        if has_devices {
+            CheckStatus::Passed
+        } else {
+            CheckStatus::Failed("no RDMA devices found".to_string())
+        },
+    );
+
+    // `IbverbsConfig::default()` panics without devices, so only build it with some.
+    let config = has_devices.then(|| IbverbsConfig {
+        use_gpu_direct: false,
+        ..IbverbsConfig::targeting("cpu")
+    });
+    let no_devices = || CheckStatus::Skipped("no RDMA devices found".to_string());
+    report.push(
+        CHECK_DEVICE_CAPS,
+        if let Some(config) = &config {
+            match RdmaDomain::new(config.device.clone()) {
+                Ok(domain) => match query_device(domain.context) {
+                    Ok(caps) if caps.max_qp_wr > 0 && caps.max_cqe > 0 => CheckStatus::Passed,
+                    Ok(caps) => {
+                        CheckStatus::Failed(format!("device reports no capacity: {:?}", caps))
+                    }
+                    Err(e) => CheckStatus::Failed(e.to_string()),
+                },
+                Err(e) => CheckStatus::Failed(format!("failed to open device: {}", e)),
+            }
+        } else {
+            no_devices()
+        },
+    );
+
+    let has_cuda = is_cuda_available();
+    report.push(
+        CHECK_CUDA,
+        if has_cuda {
+            CheckStatus::Passed
+        } else {
+            CheckStatus::Skipped("CUDA is not available".to_string())
+        },
+    );
+    report.push(
+        CHECK_ALLOCATOR,
+        if !has_cuda {
+            CheckStatus::Skipped("CUDA is not available".to_string())
+        } else if pt_cuda_allocator_compatibility() {
+            CheckStatus::Passed
+        } else {
+            CheckStatus::Failed(
+                "the PyTorch CUDA allocator is not configured for RDMA (expandable segments)"
+                    .to_string(),
+            )
+        },
+    );
+
+    report.push(
+        CHECK_LOOPBACK,
+        if let Some(config) = &config {
+            match ManagedQueuePair::loopback_write(config, LOOPBACK_SIZE, LOOPBACK_TIMEOUT) {
+                Ok(()) => CheckStatus::Passed,
+                Err(e) => CheckStatus::Failed(e.to_string()),
+            }
+        } else {
+            no_devices()
+        },
+    );
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test_report() {
+        let report = self_test();
+        let names: Vec<&str> = report.checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                CHECK_DEVICES,
+                CHECK_DEVICE_CAPS,
+                CHECK_CUDA,
+                CHECK_ALLOCATOR,
+                CHECK_LOOPBACK
+            ]
+        );
+        assert_eq!(report.to_string().lines().count(), report.checks.len());
+
+        if get_all_devices().is_empty() {
+            assert!(!report.passed());
+            assert!(matches!(
+                report.status(CHECK_DEVICES),
+                Some(CheckStatus::Failed(_))
+            ));
+            assert!(matches!(
+                report.status(CHECK_DEVICE_CAPS),
+                Some(CheckStatus::Skipped(_))
+            ));
+            assert!(matches!(
+                report.status(CHECK_LOOPBACK),
+                Some(CheckStatus::Skipped(_))
+            ));
+        } else {
+            assert_eq!(report.status(CHECK_DEVICES), Some(&CheckStatus::Passed));
+            assert_eq!(report.status(CHECK_LOOPBACK), Some(&CheckStatus::Passed));
+        }
+        if !is_cuda_available() {
+            assert!(matches!(
+                report.status(CHECK_ALLOCATOR),
+                Some(CheckStatus::Skipped(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_report_passed_ignores_skipped() {
+        let mut report = SelfTestReport { checks: Vec::new() };
+        report.push(CHECK_DEVICES, CheckStatus::Passed);
+        report.push(CHECK_CUDA, CheckStatus::Skipped("no GPU".to_string()));
+        assert!(report.passed());
+        assert_eq!(report.status(CHECK_LOOPBACK), None);
+        report.push(CHECK_LOOPBACK, CheckStatus::Failed("timed out".to_string()));
+        assert!(!report.passed());
+        assert_eq!(
+            report.to_string(),
+            "[PASS] ibverbs_devices\n[SKIP] cuda: no GPU\n[FAIL] loopback_write: timed out\n"
+        );
+    }
+}
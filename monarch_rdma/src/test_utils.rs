@@ -91,11 +91,16 @@ pub mod test_utils {
     use hyperactor_mesh::alloc::LocalAllocator;
     use ndslice::extent;
 
+    use crate::GpuDirectMode;
     use crate::IbverbsConfig;
     use crate::RdmaBuffer;
     use crate::cu_check;
+    use crate::device_guard::DeviceGuard;
+    use crate::device_guard::device_synchronize;
+    use crate::device_guard::primary_context;
     use crate::rdma_components::PollTarget;
     use crate::rdma_components::RdmaQueuePair;
+    use crate::rdma_error::RdmaError;
     use crate::rdma_manager_actor::RdmaManagerActor;
     use crate::rdma_manager_actor::RdmaManagerMessageClient;
     use crate::validate_execution_context;
@@ -110,7 +115,7 @@ pub mod test_utils {
         qp: &mut RdmaQueuePair,
         poll_target: PollTarget,
         timeout_secs: u64,
-    ) -> Result<bool, anyhow::Error> {
+    ) -> Result<bool, RdmaError> {
         let timeout = Duration::from_secs(timeout_secs);
         let start_time = Instant::now();
         while start_time.elapsed() < timeout {
@@ -122,29 +127,42 @@ pub mod test_utils {
                     RealClock.sleep(Duration::from_millis(1)).await;
                 }
                 Err(e) => {
-                    return Err(anyhow::anyhow!(e));
+                    return Err(e);
                 }
             }
         }
-        Err(anyhow::Error::msg("Timeout while waiting for completion"))
+        Err(RdmaError::Timeout(timeout))
     }
 
     /// Posts a work request to the send queue of the given RDMA queue pair.
+    ///
+    /// The work request is signaled according to the queue pair's `signal_every_n`,
+    /// and always if it is the `last` one before the doorbell is rung.
+    /// Fails with `RdmaError::Overflow` instead of posting if the send queue is full.
     pub async fn send_wqe_gpu(
         qp: &mut RdmaQueuePair,
         lhandle: &RdmaBuffer,
         rhandle: &RdmaBuffer,
         op_type: u32,
-    ) -> Result<(), anyhow::Error> {
+        last: bool,
+    ) -> Result<(), RdmaError> {
         unsafe {
             let ibv_qp = qp.qp as *mut rdmaxcel_sys::ibv_qp;
             let dv_qp = qp.dv_qp as *mut rdmaxcel_sys::mlx5dv_qp;
+            let capacity = (*dv_qp).sq.wqe_cnt as u64;
+            let outstanding = qp.send_wqe_idx - qp.send_db_idx;
+            if outstanding >= capacity {
+                return Err(RdmaError::Overflow {
+                    outstanding: outstanding + 1,
+                    capacity,
+                });
+            }
             let params = rdmaxcel_sys::wqe_params_t {
                 laddr: lhandle.addr,
                 length: lhandle.size,
                 lkey: lhandle.lkey,
                 wr_id: qp.send_wqe_idx,
-                signaled: true,
+                signaled: qp.should_signal(qp.send_wqe_idx, last),
                 op_type,
                 raddr: rhandle.addr,
                 rkey: rhandle.rkey,
@@ -191,15 +209,19 @@ pub mod test_utils {
         Ok(())
     }
 
-    pub async fn ring_db_gpu(qp: &mut RdmaQueuePair) -> Result<(), anyhow::Error> {
+    pub async fn ring_db_gpu(qp: &mut RdmaQueuePair) -> Result<(), RdmaError> {
         RealClock.sleep(Duration::from_millis(2)).await;
         unsafe {
             let dv_qp = qp.dv_qp as *mut rdmaxcel_sys::mlx5dv_qp;
             let base_ptr = (*dv_qp).sq.buf as *mut u8;
             let wqe_cnt = (*dv_qp).sq.wqe_cnt;
             let stride = (*dv_qp).sq.stride;
-            if (wqe_cnt as u64) < (qp.send_wqe_idx - qp.send_db_idx) {
-                return Err(anyhow::anyhow!("Overflow of WQE, possible data loss"));
+            let outstanding = qp.send_wqe_idx - qp.send_db_idx;
+            if (wqe_cnt as u64) < outstanding {
+                return Err(RdmaError::Overflow {
+                    outstanding,
+                    capacity: wqe_cnt as u64,
+                });
             }
             while qp.send_db_idx < qp.send_wqe_idx {
                 let offset = (qp.send_db_idx % wqe_cnt as u64) * stride as u64;
@@ -216,7 +238,7 @@ pub mod test_utils {
         qp: &mut RdmaQueuePair,
         poll_target: PollTarget,
         timeout_secs: u64,
-    ) -> Result<bool, anyhow::Error> {
+    ) -> Result<bool, RdmaError> {
         let timeout = Duration::from_secs(timeout_secs);
         let start_time = Instant::now();
 
@@ -249,7 +271,10 @@ pub mod test_utils {
                     return Ok(true);
                 }
                 rdmaxcel_sys::CQE_POLL_ERROR => {
-                    return Err(anyhow::anyhow!("Error polling {} completion", cq_type_str));
+                    return Err(RdmaError::Device(format!(
+                        "Error polling {} completion",
+                        cq_type_str
+                    )));
                 }
                 _ => {
                     // No completion yet, sleep and try again
@@ -258,7 +283,7 @@ pub mod test_utils {
             }
         }
 
-        Err(anyhow::Error::msg("Timeout while waiting for completion"))
+        Err(RdmaError::Timeout(timeout))
     }
 
     pub struct RdmaManagerTestEnv<'a> {
@@ -287,12 +312,146 @@ pub mod test_utils {
         let parsed_idx = idx.parse::<usize>().unwrap();
 
         if backend == "cuda" {
-            config.use_gpu_direct = validate_execution_context().await.is_ok();
+            config.use_gpu_direct = GpuDirectMode::from_env()
+                .resolve(async { validate_execution_context().await.gpudirect_capable })
+                .await;
         }
 
         (backend.to_string(), parsed_idx)
     }
 
+    /// Reserves a `granularity`-aligned virtual address range and maps `handle` into it.
+    ///
+    /// `cuMemMap` requires the reserved range to be aligned to the allocation granularity,
+    /// so the alignment is passed to `cuMemAddressReserve` explicitly rather than left to
+    /// the driver's default. If the map fails, the reservation is released before the
+    /// error is returned.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(dptr)` - The mapped device pointer; the caller owns the reservation and mapping
+    /// * `Err(result)` - The `CUresult` of the reservation or map that failed
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a live allocation handle of at least `size` bytes, and a CUDA
+    /// context must be current on the calling thread.
+    pub unsafe fn reserve_and_map(
+        handle: rdmaxcel_sys::CUmemGenericAllocationHandle,
+        size: usize,
+        granularity: usize,
+    ) -> Result<rdmaxcel_sys::CUdeviceptr, rdmaxcel_sys::CUresult> {
+        // SAFETY: the caller guarantees a current context and a valid handle; the
+        // reservation is released again if the map fails.
+        unsafe {
+            let mut dptr: rdmaxcel_sys::CUdeviceptr = std::mem::zeroed();
+            let err = rdmaxcel_sys::rdmaxcel_cuMemAddressReserve(
+                &mut dptr as *mut rdmaxcel_sys::CUdeviceptr,
+                size,
+                granularity,
+                0,
+                0,
+            );
+            if err != rdmaxcel_sys::CUDA_SUCCESS {
+                return Err(err);
+            }
+
+            let err = rdmaxcel_sys::rdmaxcel_cuMemMap(dptr, size, 0, handle, 0);
+            if err != rdmaxcel_sys::CUDA_SUCCESS {
+                rdmaxcel_sys::rdmaxcel_cuMemAddressFree(dptr, size);
+                return Err(err);
+            }
+            Ok(dptr)
+        }
+    }
+
+    /// A single-granule buffer allocated with the CUDA virtual memory management API
+    /// on device 0 and mapped read/write with `reserve_and_map`. The mapping, the
+    /// reservation and the allocation are released when it is dropped.
+    pub struct VmmBuffer {
+        pub ptr: rdmaxcel_sys::CUdeviceptr,
+        pub size: usize,
+        handle: rdmaxcel_sys::CUmemGenericAllocationHandle,
+    }
+
+    impl VmmBuffer {
+        /// Creates a CUDA context on device 0, makes it current on the calling thread
+        /// and allocates the buffer in it.
+        ///
+        /// # Safety
+        ///
+        /// CUDA must be available, and the buffer must be dropped on a thread where the
+        /// context is current.
+        pub unsafe fn new() -> Result<Self, anyhow::Error> {
+            // SAFETY: the caller guarantees CUDA is available; every handle created
+            // here is owned by the returned buffer.
+            unsafe {
+                cu_check!(rdmaxcel_sys::rdmaxcel_cuInit(0));
+                let mut device: rdmaxcel_sys::CUdevice = std::mem::zeroed();
+                cu_check!(rdmaxcel_sys::rdmaxcel_cuDeviceGet(&mut device, 0));
+                let mut context: rdmaxcel_sys::CUcontext = std::mem::zeroed();
+                cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxCreate_v2(
+                    &mut context,
+                    0,
+                    device
+                ));
+                cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxSetCurrent(context));
+
+                let mut prop: rdmaxcel_sys::CUmemAllocationProp = std::mem::zeroed();
+                prop.type_ = rdmaxcel_sys::CU_MEM_ALLOCATION_TYPE_PINNED;
+                prop.location.type_ = rdmaxcel_sys::CU_MEM_LOCATION_TYPE_DEVICE;
+                prop.location.id = device;
+                let mut granularity: usize = 0;
+                cu_check!(rdmaxcel_sys::rdmaxcel_cuMemGetAllocationGranularity(
+                    &mut granularity as *mut usize,
+                    &prop,
+                    rdmaxcel_sys::CU_MEM_ALLOC_GRANULARITY_MINIMUM,
+                ));
+                let size = granularity;
+
+                let mut handle: rdmaxcel_sys::CUmemGenericAllocationHandle = std::mem::zeroed();
+                cu_check!(rdmaxcel_sys::rdmaxcel_cuMemCreate(
+                    &mut handle,
+                    size,
+                    &prop,
+                    0
+                ));
+                let ptr = match reserve_and_map(handle, size, granularity) {
+                    Ok(ptr) => ptr,
+                    Err(err) => {
+                        rdmaxcel_sys::rdmaxcel_cuMemRelease(handle);
+                        return Err(anyhow::anyhow!("reserve_and_map failed: {:?}", err));
+                    }
+                };
+                let buffer = Self { ptr, size, handle };
+
+                let mut access_desc: rdmaxcel_sys::CUmemAccessDesc = std::mem::zeroed();
+                access_desc.location.type_ = rdmaxcel_sys::CU_MEM_LOCATION_TYPE_DEVICE;
+                access_desc.location.id = device;
+                access_desc.flags = rdmaxcel_sys::CU_MEM_ACCESS_FLAGS_PROT_READWRITE;
+                cu_check!(rdmaxcel_sys::rdmaxcel_cuMemSetAccess(
+                    ptr,
+                    size,
+                    &access_desc,
+                    1
+                ));
+                Ok(buffer)
+            }
+        }
+    }
+
+    impl Drop for VmmBuffer {
+        fn drop(&mut self) {
+            // SAFETY: `ptr` and `handle` were created by `VmmBuffer::new` and are
+            // released exactly once here.
+            unsafe {
+                rdmaxcel_sys::rdmaxcel_cuMemUnmap(self.ptr, self.size);
+                rdmaxcel_sys::rdmaxcel_cuMemAddressFree(self.ptr, self.size);
+                rdmaxcel_sys::rdmaxcel_cuMemRelease(self.handle);
+            }
+        }
+    }
+
     impl RdmaManagerTestEnv<'_> {
         /// Sets up the RDMA test environment with a specified QP type.
         ///
@@ -306,11 +465,62 @@ pub mod test_utils {
         /// * `accel1` - Accelerator for first actor (e.g., "cpu:0", "cuda:0")
         /// * `accel2` - Accelerator for second actor (e.g., "cpu:0", "cuda:1")
         /// * `qp_type` - The queue pair type to use (Auto, Standard, or Mlx5dv)
+        ///
+        /// Local copies are disabled, so transfers between the two buffers always go
+        /// through the NIC.
         pub async fn setup_with_qp_type(
             buffer_size: usize,
             accel1: &str,
             accel2: &str,
             qp_type: crate::ibverbs_primitives::RdmaQpType,
+        ) -> Result<Self, anyhow::Error> {
+            Self::setup_with_options(buffer_size, accel1, accel2, qp_type, false, 1).await
+        }
+
+        /// Like `setup`, but with `local_copy` enabled on both actors, so transfers
+        /// between the two buffers are copied in-process instead of using the NIC.
+        pub async fn setup_with_local_copy(
+            buffer_size: usize,
+            accel1: &str,
+            accel2: &str,
+        ) -> Result<Self, anyhow::Error> {
+            Self::setup_with_options(
+                buffer_size,
+                accel1,
+                accel2,
+                crate::ibverbs_primitives::RdmaQpType::Auto,
+                true,
+                1,
+            )
+            .await
+        }
+
+        /// Like `setup`, but with `num_qps` queue pairs per connection on both actors,
+        /// so transfers between the two buffers are striped across them.
+        pub async fn setup_with_num_qps(
+            buffer_size: usize,
+            accel1: &str,
+            accel2: &str,
+            num_qps: usize,
+        ) -> Result<Self, anyhow::Error> {
+            Self::setup_with_options(
+                buffer_size,
+                accel1,
+                accel2,
+                crate::ibverbs_primitives::RdmaQpType::Auto,
+                false,
+                num_qps,
+            )
+            .await
+        }
+
+        async fn setup_with_options(
+            buffer_size: usize,
+            accel1: &str,
+            accel2: &str,
+            qp_type: crate::ibverbs_primitives::RdmaQpType,
+            local_copy: bool,
+            num_qps: usize,
         ) -> Result<Self, anyhow::Error> {
             // Use device selection logic to find optimal RDMA devices
             let mut config1 = IbverbsConfig::targeting(accel1);
@@ -319,6 +529,10 @@ pub mod test_utils {
             // Set the QP type
             config1.qp_type = qp_type;
             config2.qp_type = qp_type;
+            config1.local_copy = local_copy;
+            config2.local_copy = local_copy;
+            config1.num_qps = num_qps;
+            config2.num_qps = num_qps;
 
             let parsed_accel1 = parse_accel(accel1, &mut config1).await;
             let parsed_accel2 = parse_accel(accel2, &mut config2).await;
@@ -377,7 +591,6 @@ pub mod test_utils {
                 unsafe {
                     cu_check!(rdmaxcel_sys::rdmaxcel_cuInit(0));
 
-                    let mut dptr: rdmaxcel_sys::CUdeviceptr = std::mem::zeroed();
                     let mut handle: rdmaxcel_sys::CUmemGenericAllocationHandle = std::mem::zeroed();
 
                     let mut device: rdmaxcel_sys::CUdevice = std::mem::zeroed();
@@ -386,13 +599,8 @@ pub mod test_utils {
                         accel.1 as i32
                     ));
 
-                    let mut context: rdmaxcel_sys::CUcontext = std::mem::zeroed();
-                    cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxCreate_v2(
-                        &mut context,
-                        0,
-                        accel.1 as i32
-                    ));
-                    cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxSetCurrent(context));
+                    let context = primary_context(accel.1 as i32)?;
+                    let _guard = DeviceGuard::set(context)?;
 
                     let mut granularity: usize = 0;
                     let mut prop: rdmaxcel_sys::CUmemAllocationProp = std::mem::zeroed();
@@ -420,27 +628,18 @@ pub mod test_utils {
                         0
                     ));
                     // reserve and map the memory
-                    cu_check!(rdmaxcel_sys::rdmaxcel_cuMemAddressReserve(
-                        &mut dptr as *mut rdmaxcel_sys::CUdeviceptr,
-                        padded_size,
-                        0,
-                        0,
-                        0,
-                    ));
-                    assert!((dptr as usize).is_multiple_of(granularity));
                     assert!(padded_size.is_multiple_of(granularity));
-
-                    // fails if a add cu_check macro; but passes if we don't
-                    let err = rdmaxcel_sys::rdmaxcel_cuMemMap(
-                        dptr as rdmaxcel_sys::CUdeviceptr,
-                        padded_size,
-                        0,
-                        handle as rdmaxcel_sys::CUmemGenericAllocationHandle,
-                        0,
-                    );
-                    if err != rdmaxcel_sys::CUDA_SUCCESS {
-                        panic!("failed reserving and mapping memory {:?}", err);
-                    }
+                    let dptr = match reserve_and_map(handle, padded_size, granularity) {
+                        Ok(dptr) => dptr,
+                        Err(err) => {
+                            rdmaxcel_sys::rdmaxcel_cuMemRelease(handle);
+                            return Err(anyhow::anyhow!(
+                                "failed reserving and mapping memory: {:?}",
+                                err
+                            ));
+                        }
+                    };
+                    assert!((dptr as usize).is_multiple_of(granularity));
 
                     // set access
                     let mut access_desc: rdmaxcel_sys::CUmemAccessDesc = std::mem::zeroed();
@@ -468,12 +667,9 @@ pub mod test_utils {
                 for (i, val) in temp_buffer.iter_mut().enumerate() {
                     *val = (i % 256) as u8;
                 }
+                // Use the CUDA context that was created for the first buffer
+                let _guard = DeviceGuard::set(cuda_contexts[0].expect("No CUDA context found"))?;
                 unsafe {
-                    // Use the CUDA context that was created for the first buffer
-                    cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxSetCurrent(
-                        cuda_contexts[0].expect("No CUDA context found")
-                    ));
-
                     cu_check!(rdmaxcel_sys::rdmaxcel_cuMemcpyHtoD_v2(
                         buf_vec[0].ptr,
                         temp_buffer.as_ptr() as *const std::ffi::c_void,
@@ -522,11 +718,15 @@ pub mod test_utils {
             self.actor_2
                 .release_buffer(self.client_2, self.rdma_handle_2.clone())
                 .await?;
-            if self.cuda_context_1.is_some() {
+            if let Some(context) = self.cuda_context_1 {
+                let _guard = DeviceGuard::set(context)?;
+                // Make sure no queued work still targets the buffer before unmapping it.
+                let mut device: rdmaxcel_sys::CUdevice = 0;
+                unsafe {
+                    cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxGetDevice(&mut device));
+                }
+                device_synchronize(device)?;
                 unsafe {
-                    cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxSetCurrent(
-                        self.cuda_context_1.expect("No CUDA context found")
-                    ));
                     cu_check!(rdmaxcel_sys::rdmaxcel_cuMemUnmap(
                         self.buffer_1.ptr as rdmaxcel_sys::CUdeviceptr,
                         self.buffer_1.len
@@ -537,11 +737,15 @@ pub mod test_utils {
                     ));
                 }
             }
-            if self.cuda_context_2.is_some() {
+            if let Some(context) = self.cuda_context_2 {
+                let _guard = DeviceGuard::set(context)?;
+                // Make sure no queued work still targets the buffer before unmapping it.
+                let mut device: rdmaxcel_sys::CUdevice = 0;
+                unsafe {
+                    cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxGetDevice(&mut device));
+                }
+                device_synchronize(device)?;
                 unsafe {
-                    cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxSetCurrent(
-                        self.cuda_context_2.expect("No CUDA context found")
-                    ));
                     cu_check!(rdmaxcel_sys::rdmaxcel_cuMemUnmap(
                         self.buffer_2.ptr as rdmaxcel_sys::CUdeviceptr,
                         self.buffer_2.len
@@ -580,46 +784,16 @@ pub mod test_utils {
         }
 
         pub async fn verify_buffers(&self, size: usize) -> Result<(), anyhow::Error> {
-            let mut buf_vec = Vec::new();
-            for (virtual_addr, cuda_context) in [
-                (self.buffer_1.ptr, self.cuda_context_1),
-                (self.buffer_2.ptr, self.cuda_context_2),
+            let mut contents = Vec::new();
+            for (handle, cuda_context) in [
+                (&self.rdma_handle_1, self.cuda_context_1),
+                (&self.rdma_handle_2, self.cuda_context_2),
             ] {
-                if cuda_context.is_some() {
-                    let mut temp_buffer = vec![0u8; size].into_boxed_slice();
-                    // SAFETY: The buffer is allocated with the correct size and the pointer is valid.
-                    unsafe {
-                        cu_check!(rdmaxcel_sys::rdmaxcel_cuCtxSetCurrent(
-                            cuda_context.expect("No CUDA context found")
-                        ));
-                        cu_check!(rdmaxcel_sys::rdmaxcel_cuMemcpyDtoH_v2(
-                            temp_buffer.as_mut_ptr() as *mut std::ffi::c_void,
-                            virtual_addr as rdmaxcel_sys::CUdeviceptr,
-                            size
-                        ));
-                    }
-                    buf_vec.push(Buffer {
-                        ptr: temp_buffer.as_mut_ptr() as u64,
-                        len: size,
-                        cpu_ref: Some(temp_buffer),
-                    });
-                } else {
-                    buf_vec.push(Buffer {
-                        ptr: virtual_addr,
-                        len: size,
-                        cpu_ref: None,
-                    });
-                }
+                let _guard = cuda_context.map(DeviceGuard::set).transpose()?;
+                contents.push(handle.read_to_vec()?);
             }
-            // SAFETY: The pointers are valid and the buffers have the same length.
-            unsafe {
-                let ptr1 = buf_vec[0].ptr as *mut u8;
-                let ptr2: *mut u8 = buf_vec[1].ptr as *mut u8;
-                for i in 0..buf_vec[0].len {
-                    if *ptr1.add(i) != *ptr2.add(i) {
-                        return Err(anyhow::anyhow!("Buffers are not equal at index {}", i));
-                    }
-                }
+            if let Some(i) = (0..size).find(|&i| contents[0][i] != contents[1][i]) {
+                return Err(anyhow::anyhow!("Buffers are not equal at index {}", i));
             }
             Ok(())
         }
@@ -429,6 +429,10 @@ impl WorkerMessageHandler for WorkerActor {
         Ok(())
     }
 
+    async fn barrier(&mut self, cx: &hyperactor::Context<Self>, seq: Seq) -> Result<()> {
+        self.request_status(cx, seq, false).await
+    }
+
     async fn reduce(
         &mut self,
         _cx: &hyperactor::Context<Self>,
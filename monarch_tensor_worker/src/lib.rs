@@ -599,6 +599,10 @@ impl WorkerMessageHandler for WorkerActor {
         Ok(())
     }
 
+    async fn barrier(&mut self, cx: &hyperactor::Context<Self>, seq: Seq) -> Result<()> {
+        self.request_status(cx, seq, false).await
+    }
+
     async fn reduce(
         &mut self,
         cx: &hyperactor::Context<Self>,
@@ -1953,6 +1957,77 @@ mod tests {
         Ok(())
     }
 
+    #[async_timed_test(timeout_secs = 60)]
+    async fn barrier() -> Result<()> {
+        test_setup()?;
+
+        let proc = Proc::local();
+        let (client, controller_ref, mut controller_rx) = proc.attach_actor("controller").unwrap();
+
+        let worker_handle = proc
+            .spawn::<WorkerActor>(
+                "worker",
+                WorkerParams {
+                    world_size: 1,
+                    rank: 0,
+                    device_index: None,
+                    controller_actor: controller_ref,
+                },
+            )
+            .await
+            .unwrap();
+        worker_handle
+            .command_group(
+                &client,
+                vec![
+                    WorkerMessage::CreateStream {
+                        id: 0.into(),
+                        stream_creation: StreamCreationMode::CreateNewStream,
+                    },
+                    WorkerMessage::CreateStream {
+                        id: 1.into(),
+                        stream_creation: StreamCreationMode::CreateNewStream,
+                    },
+                    WorkerMessage::CallFunction(CallFunctionParams {
+                        seq: 0.into(),
+                        results: vec![Some(Ref { id: 2 })],
+                        mutates: vec![],
+                        function: "torch.ops.aten.ones.default".into(),
+                        args: vec![WireValue::IntList(vec![2, 3])],
+                        kwargs: HashMap::new(),
+                        stream: 1.into(),
+                        remote_process_groups: vec![],
+                    }),
+                    WorkerMessage::Barrier { seq: 1.into() },
+                ],
+            )
+            .await
+            .unwrap();
+
+        worker_handle.drain_and_stop().unwrap();
+        worker_handle.await;
+
+        // The barrier reports status just like RequestStatus, once every stream is done.
+        let mut responses = controller_rx.drain();
+        assert_eq!(
+            responses.len(),
+            1,
+            "Expected one response, got: {:#?}",
+            responses
+        );
+        match responses.pop().unwrap() {
+            ControllerMessage::Status {
+                seq, controller, ..
+            } => {
+                assert_eq!(seq, 2.into());
+                assert!(!controller);
+            }
+            response => panic!("unexpected response {:#?}", response),
+        };
+
+        Ok(())
+    }
+
     #[async_timed_test(timeout_secs = 60)]
     async fn backend_network_init() {
         let proc = Proc::local();
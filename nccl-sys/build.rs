@@ -21,7 +21,7 @@ fn main() {
         .clang_arg("-std=gnu++20")
         .clang_arg(format!(
             "-I{}/include",
-            build_utils::find_cuda_home().unwrap()
+            build_utils::find_target_cuda_home().unwrap()
         ))
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
         // Communicator creation and management
@@ -64,6 +64,9 @@ fn main() {
         // Random nccl stuff we want
         .allowlist_function("cudaStream.*")
         .allowlist_function("cudaSetDevice")
+        .allowlist_function("cudaGetDevice")
+        .allowlist_function("cudaLaunchHostFunc")
+        .allowlist_type("cudaDeviceProp")
         .allowlist_type("ncclComm_t")
         .allowlist_type("ncclResult_t")
         .allowlist_type("ncclDataType_t")
@@ -96,6 +99,10 @@ fn main() {
     if let Some(include_dir) = &python_config.include_dir {
         builder = builder.clang_arg(format!("-I{}", include_dir));
     }
+    builder = builder.clang_args(
+        build_utils::bindgen_extra_clang_args()
+            .expect("failed to parse MONARCH_BINDGEN_EXTRA_CLANG_ARGS"),
+    );
     if let Some(lib_dir) = &python_config.lib_dir {
         println!("cargo::rustc-link-search=native={}", lib_dir);
         // Set cargo metadata to inform dependent binaries about how to set their
@@ -105,9 +112,11 @@ fn main() {
 
     // Write the bindings to the $OUT_DIR/bindings.rs file.
     let out_path = PathBuf::from(std::env::var("OUT_DIR").unwrap());
-    builder
-        .generate()
-        .expect("Unable to generate bindings")
+    let bindings = builder.generate().expect("Unable to generate bindings");
+    if let Err(err) = build_utils::check_bindings(&bindings.to_string(), "ncclComm_t") {
+        panic!("{}", err);
+    }
+    bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Couldn't write bindings!");
 
@@ -22,6 +22,13 @@ unsafe impl ExternType for ncclConfig_t {
     type Kind = cxx::kind::Trivial;
 }
 
+/// SAFETY: bindings
+/// Trivial because this is POD struct
+unsafe impl ExternType for cudaDeviceProp {
+    type Id = type_id!("cudaDeviceProp");
+    type Kind = cxx::kind::Trivial;
+}
+
 /// SAFETY: bindings
 unsafe impl ExternType for ncclComm {
     type Id = type_id!("ncclComm");
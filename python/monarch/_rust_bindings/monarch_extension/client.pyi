@@ -246,6 +246,17 @@ class ClientActor:
         """
         ...
 
+    def drain_completed(
+        self, max: int
+    ) -> List[LogMessage | WorkerResponse | DebuggerMessage]:
+        """Get up to `max` messages that have already been received, without
+        blocking. Returns an empty list if no messages are ready.
+
+        Arguments:
+        - `max`: Maximum number of messages to return.
+        """
+        ...
+
     def stop_worlds(self, world_names: List[str]) -> None:
         """Stop the system."""
         ...
@@ -0,0 +1,58 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+# All rights reserved.
+#
+# This source code is licensed under the BSD-style license found in the
+# LICENSE file in the root directory of this source tree.
+
+# pyre-unsafe
+
+from typing import List, Literal, Optional
+
+import torch
+
+class Communicator:
+    """
+    A NCCL communicator for running collectives on torch tensors without going
+    through torch.distributed. Every rank must issue the same collectives in the
+    same order. The GIL is released while collectives are enqueued.
+    """
+
+    def __init__(
+        self, device: int, world_size: int, unique_id: bytes, rank: int
+    ) -> None:
+        """
+        Create the communicator for `rank` on CUDA device `device`. Blocks until
+        all `world_size` ranks, sharing the same `unique_id`, have joined.
+        """
+        ...
+    @staticmethod
+    def unique_id() -> bytes:
+        """Generate a new id to share with every rank of a communicator."""
+        ...
+    def all_reduce(
+        self,
+        tensor: torch.Tensor,
+        op: Literal["sum", "prod", "avg", "min", "max"] = "sum",
+        stream: Optional[torch.cuda.Stream] = None,
+    ) -> None:
+        """
+        Reduce `tensor` across all ranks in place, on `stream` or the current
+        stream.
+        """
+        ...
+    def broadcast(
+        self,
+        tensor: torch.Tensor,
+        root: int = 0,
+        stream: Optional[torch.cuda.Stream] = None,
+    ) -> None:
+        """Overwrite `tensor` on every rank with its contents on `root`."""
+        ...
+    def all_gather(
+        self,
+        outputs: List[torch.Tensor],
+        tensor: torch.Tensor,
+        stream: Optional[torch.cuda.Stream] = None,
+    ) -> None:
+        """Gather `tensor` from every rank into `outputs`, one tensor per rank."""
+        ...
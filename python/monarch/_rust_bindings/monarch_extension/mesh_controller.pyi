@@ -7,7 +7,7 @@
 # pyre-unsafe
 
 from traceback import FrameSummary
-from typing import List, NamedTuple, Sequence, Tuple, Union
+from typing import Any, Iterable, List, NamedTuple, Sequence, Tuple, Union
 
 from monarch._rust_bindings.monarch_extension import client
 from monarch._rust_bindings.monarch_hyperactor.context import Instance
@@ -29,13 +29,36 @@ class _Controller:
         uses: Sequence[object],
         port: Tuple[PortId, NDSlice] | None,
         tracebacks: List[List[FrameSummary]],
-    ) -> None: ...
+        expected_type: type | None = None,
+    ) -> None:
+        """
+        Adds a node to the invocation graph. If `expected_type` is given, the results
+        are kept after completion so they can be fetched with `_get_typed_result`.
+        """
+        ...
     def drop_refs(self, refs: Sequence[object]) -> None: ...
+    def reassign_ranks(self) -> None:
+        """
+        Re-assign ranks to the current set of workers and resize completion
+        tracking to match, e.g. after the worker mesh was resized or a worker
+        restarted.
+        """
+        ...
     def send(
         self,
         ranks: Union[NDSlice, List[NDSlice]],
         msg: NamedTuple,
     ) -> None: ...
+    def send_batch(
+        self,
+        ranks: Union[NDSlice, List[NDSlice]],
+        msgs: Iterable[NamedTuple],
+    ) -> None:
+        """
+        Send every message in `msgs`, in order, to `ranks`. The messages are
+        converted in a single call; if any fails to convert, none are sent.
+        """
+        ...
     def _drain_and_stop(
         self, instance: Instance
     ) -> List[client.LogMessage | client.WorkerResponse | client.DebuggerMessage]: ...
@@ -47,5 +70,34 @@ class _Controller:
         """
         ...
 
+    def barrier(self, instance: Instance, timeout_msec: int) -> None:
+        """
+        Blocks until every rank has processed all messages sent before this call.
+        Raises TimeoutError if not all ranks report within timeout_msec.
+        """
+        ...
+
+    def wait_for_seq(self, instance: Instance, seq: int, timeout_msec: int) -> None:
+        """
+        Blocks until every rank has completed all invocations up to and including
+        seq. Raises TimeoutError if that does not happen within timeout_msec.
+        """
+        ...
+
+    def _export_graph(self, instance: Instance) -> str:
+        """
+        Returns the graph of in-flight invocations as a Graphviz DOT string, with
+        nodes labeled by seq and status and edges from each invocation to its users.
+        """
+        ...
+
+    def _get_typed_result(self, instance: Instance, seq: int) -> List[Any]:
+        """
+        Returns the per-rank results of a completed node that was added with an
+        `expected_type`, deserialized. Raises TypeError if a result is not an instance
+        of that type, and the remote exception if the node failed.
+        """
+        ...
+
     @property
     def broker_id(self) -> Tuple[str, int]: ...
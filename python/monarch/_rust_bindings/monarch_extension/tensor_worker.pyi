@@ -169,6 +169,9 @@ class ReductionType:
     Max: ReductionType
     Min: ReductionType
     Avg: ReductionType
+    BitOr: ReductionType
+    BitAnd: ReductionType
+    BitXor: ReductionType
 
     def __eq__(self, value: ReductionType) -> bool: ...
     def __ne__(self, value: ReductionType) -> bool: ...
@@ -495,6 +498,23 @@ class RequestStatus(WorkerMessage):
         """If this message was sent by the controller."""
         ...
 
+@final
+class Barrier(WorkerMessage):
+    """
+    Instruct the worker to report its status to the controller once all the
+    messages before this message have been processed on all streams. The
+    controller releases the barrier when every rank has reported.
+
+    Args:
+    - seq: Sequence number of the barrier.
+    """
+
+    def __init__(self, *, seq: int) -> None: ...
+    @property
+    def seq(self) -> int:
+        """Sequence number of the barrier."""
+        ...
+
 @final
 class Reduce(WorkerMessage):
     """
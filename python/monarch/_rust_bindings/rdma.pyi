@@ -9,6 +9,36 @@ from typing import Any, final
 
 from monarch._rust_bindings.monarch_hyperactor.pytokio import PythonTask
 
+class RdmaError(Exception):
+    """Base class for errors raised by RDMA operations."""
+
+    ...
+
+class RdmaTimeoutError(RdmaError):
+    """The RDMA operation did not complete within its timeout."""
+
+    ...
+
+class RdmaCompletionError(RdmaError):
+    """A work completion reported a failure status."""
+
+    ...
+
+class RdmaDeviceError(RdmaError):
+    """The RDMA or CUDA device failed."""
+
+    ...
+
+class RdmaRegistrationError(RdmaError):
+    """Memory registration failed or the buffer cannot be registered."""
+
+    ...
+
+class RdmaOverflowError(RdmaError):
+    """More work requests are outstanding than the work queue can hold."""
+
+    ...
+
 @final
 class _RdmaMemoryRegionView:
     def __init__(self, addr: int, size_in_bytes: int) -> None: ...
@@ -63,3 +93,28 @@ class _RdmaBuffer:
     def rdma_supported(cls) -> bool: ...
     @classmethod
     def pt_cuda_allocator_compatibility(cls) -> bool: ...
+
+@final
+class _RdmaHandle:
+    @classmethod
+    def create_rdma_handle_nonblocking(
+        cls, addr: int, size: int, proc_id: str, client: Any
+    ) -> PythonTask[_RdmaHandle]: ...
+    def read_into(
+        self, peer: _RdmaHandle, client: Any, timeout: int
+    ) -> PythonTask[None]: ...
+    def write_from(
+        self, peer: _RdmaHandle, client: Any, timeout: int
+    ) -> PythonTask[None]: ...
+    def drop(self, client: Any) -> PythonTask[None]: ...
+    def size(self) -> int: ...
+    def owner_actor_id(self) -> str: ...
+    def __reduce__(self) -> tuple[Any, ...]: ...
+    def __repr__(self) -> str: ...
+
+def rdma_device_info() -> dict[str, Any]:
+    """
+    Returns the name, port_num, port_state, link_layer and active_mtu of the
+    RDMA device used by default on this host. Raises if no device is found.
+    """
+    ...
@@ -17,6 +17,7 @@ from logging import Logger
 from typing import (
     Any,
     cast,
+    Iterable,
     List,
     NamedTuple,
     Optional,
@@ -98,6 +99,14 @@ class Controller(_Controller):
         with torch.utils._python_dispatch._disable_current_modes():
             return super().send(ranks, msg)
 
+    def send_batch(
+        self,
+        ranks: Union[NDSlice, List[NDSlice]],
+        msgs: Iterable[NamedTuple],
+    ) -> None:
+        with torch.utils._python_dispatch._disable_current_modes():
+            return super().send_batch(ranks, msgs)
+
     def drain_and_stop(
         self,
     ) -> List[LogMessage | MessageResult | client.DebuggerMessage]:
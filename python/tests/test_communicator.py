@@ -0,0 +1,69 @@
+# Copyright (c) Meta Platforms, Inc. and affiliates.
+# All rights reserved.
+#
+# This source code is licensed under the BSD-style license found in the
+# LICENSE file in the root directory of this source tree.
+
+# pyre-unsafe
+
+from concurrent.futures import ThreadPoolExecutor
+
+import pytest
+import torch
+from monarch._rust_bindings import has_tensor_engine
+
+
+def _run_ranks(fn):
+    # One thread per GPU; the communicator releases the GIL so the ranks can
+    # rendezvous inside a single process.
+    world_size = 2
+    from monarch._rust_bindings.monarch_extension.communicator import Communicator
+
+    unique_id = Communicator.unique_id()
+
+    def rank_main(rank):
+        torch.cuda.set_device(rank)
+        comm = Communicator(rank, world_size, unique_id, rank)
+        result = fn(comm, rank, world_size)
+        torch.cuda.synchronize(rank)
+        return result
+
+    with ThreadPoolExecutor(max_workers=world_size) as pool:
+        return list(pool.map(rank_main, range(world_size)))
+
+
+@pytest.mark.skipif(
+    not has_tensor_engine() or torch.cuda.device_count() < 2,
+    reason="Not enough GPUs, this test requires at least 2 GPUs",
+)
+class TestCommunicator:
+    def test_all_reduce(self) -> None:
+        def fn(comm, rank, world_size):
+            t = torch.full((4,), float(rank + 1), device=f"cuda:{rank}")
+            comm.all_reduce(t, "sum")
+            return t
+
+        for t in _run_ranks(fn):
+            assert torch.equal(t.cpu(), torch.full((4,), 3.0))
+
+    def test_broadcast_on_stream(self) -> None:
+        def fn(comm, rank, world_size):
+            stream = torch.cuda.Stream(device=rank)
+            t = torch.full((4,), float(rank), device=f"cuda:{rank}")
+            stream.wait_stream(torch.cuda.current_stream(rank))
+            comm.broadcast(t, root=1, stream=stream)
+            stream.synchronize()
+            return t
+
+        for t in _run_ranks(fn):
+            assert torch.equal(t.cpu(), torch.full((4,), 1.0))
+
+    def test_all_gather(self) -> None:
+        def fn(comm, rank, world_size):
+            t = torch.full((2,), float(rank), device=f"cuda:{rank}")
+            outputs = [torch.empty_like(t) for _ in range(world_size)]
+            comm.all_gather(outputs, t)
+            return outputs
+
+        for outputs in _run_ranks(fn):
+            assert [o.cpu().tolist() for o in outputs] == [[0.0, 0.0], [1.0, 1.0]]
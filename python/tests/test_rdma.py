@@ -10,9 +10,14 @@ import os
 # required to enable RDMA support
 os.environ["PYTORCH_CUDA_ALLOC_CONF"] = "expandable_segments:True"
 
+import signal
+
 import pytest
 import torch
-from monarch.actor import Actor, current_rank, endpoint, this_host
+from monarch._rust_bindings.rdma import _RdmaHandle, RdmaError, RdmaTimeoutError
+from monarch._src.actor.future import Future
+from monarch._src.rdma.rdma import _ensure_init_rdma_manager
+from monarch.actor import Actor, context, current_rank, endpoint, this_host
 from monarch.rdma import is_rdma_available, RDMAAction, RDMABuffer
 
 
@@ -711,3 +716,110 @@ async def test_rdma_action_slicing():
     assert (
         operation_results["data_a_sum"] == sum_a_initial * 2.5
     )  # multi-filled from 100 to 250
+
+
+class HandleOwnerActor(Actor):
+    def __init__(self, fill: float, device: str):
+        self.data = torch.full((10, 10), fill, dtype=torch.float32, device=device)
+
+    @endpoint
+    async def create_handle(self) -> _RdmaHandle:
+        byte_tensor = self.data.view(torch.uint8).flatten()
+        ctx = context()
+
+        async def create() -> _RdmaHandle:
+            await _ensure_init_rdma_manager()
+            return await _RdmaHandle.create_rdma_handle_nonblocking(
+                byte_tensor.data_ptr(),
+                byte_tensor.numel(),
+                ctx.actor_instance.proc_id,
+                ctx.actor_instance,
+            )
+
+        self.handle = await Future(coro=create())
+        return self.handle
+
+    @endpoint
+    async def read_into(self, peer: _RdmaHandle, timeout: int = 5) -> None:
+        client = context().actor_instance
+
+        async def transfer() -> None:
+            await self.handle.read_into(peer, client, timeout)
+
+        await Future(coro=transfer())
+
+    @endpoint
+    async def read_into_error(self, peer: _RdmaHandle, timeout: int) -> list[str]:
+        """Returns the RDMA exception classes the failed transfer is an instance of."""
+        client = context().actor_instance
+
+        async def transfer() -> None:
+            await self.handle.read_into(peer, client, timeout)
+
+        try:
+            await Future(coro=transfer())
+        except Exception as e:
+            return [
+                cls.__name__
+                for cls in (RdmaError, RdmaTimeoutError)
+                if isinstance(e, cls)
+            ]
+        return []
+
+    @endpoint
+    async def get_sum(self) -> float:
+        return self.data.sum().item()
+
+    @endpoint
+    async def pid(self) -> int:
+        return os.getpid()
+
+
+async def _rdma_handle_round_trip(device: str) -> None:
+    per_host = {"gpus": 1} if device == "cuda" else {"processes": 1}
+    src_proc = this_host().spawn_procs(per_host=per_host)
+    dst_proc = this_host().spawn_procs(per_host=per_host)
+    src = src_proc.spawn("src", HandleOwnerActor, 1.0, device)
+    dst = dst_proc.spawn("dst", HandleOwnerActor, 0.0, device)
+
+    await src.create_handle.call_one()
+    dst_handle = await dst.create_handle.call_one()
+    assert dst_handle.size() == 400
+
+    await src.read_into.call_one(dst_handle)
+    assert await dst.get_sum.call_one() == 100.0
+
+
+@needs_rdma
+async def test_rdma_handle_read_into_peer_cpu():
+    """Smoke test for the handle-to-handle _RdmaHandle API on host memory."""
+    await _rdma_handle_round_trip("cpu")
+
+
+@needs_rdma
+async def test_rdma_handle_timeout_raises_rdma_timeout_error():
+    """A transfer that can't complete within its timeout raises RdmaTimeoutError."""
+    src_proc = this_host().spawn_procs(per_host={"processes": 1})
+    dst_proc = this_host().spawn_procs(per_host={"processes": 1})
+    src = src_proc.spawn("src", HandleOwnerActor, 1.0, "cpu")
+    dst = dst_proc.spawn("dst", HandleOwnerActor, 0.0, "cpu")
+
+    await src.create_handle.call_one()
+    dst_handle = await dst.create_handle.call_one()
+    dst_pid = await dst.pid.call_one()
+
+    # No queue pair to dst exists yet, and a stopped dst can't answer the
+    # connection handshake, so the transfer can't complete.
+    os.kill(dst_pid, signal.SIGSTOP)
+    try:
+        error_types = await src.read_into_error.call_one(dst_handle, 1)
+    finally:
+        os.kill(dst_pid, signal.SIGCONT)
+    assert error_types == ["RdmaError", "RdmaTimeoutError"]
+
+
+@needs_rdma
+@needs_cuda
+async def test_rdma_handle_read_into_peer_gpu():
+    """Smoke test for the handle-to-handle _RdmaHandle API on GPU tensors."""
+    await _rdma_handle_round_trip("cuda")
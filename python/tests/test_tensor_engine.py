@@ -47,6 +47,37 @@ def test_tensor_engine() -> None:
     dm.exit()
 
 
+@two_gpu
+def test_send_batch(monkeypatch) -> None:
+    pm = this_host().spawn_procs(per_host={"gpus": 2})
+    dm = spawn_tensor_engine(pm)
+    client = dm.client
+
+    # Capture the messages produced by a chain of ops instead of sending them,
+    # then submit them all in one batch.
+    captured = []
+    monkeypatch.setattr(
+        client, "send_nocoalesce", lambda ranks, msg: captured.append((ranks, msg))
+    )
+    n = 16
+    with dm.activate():
+        t = torch.zeros(3, 4)
+        for _ in range(n):
+            t = t + 1
+    monkeypatch.undo()
+
+    assert len(captured) >= n
+    ranks = captured[0][0]
+    assert all(r == ranks for r, _ in captured)
+    client.inner.send_batch(ranks, [msg for _, msg in captured])
+
+    with dm.activate():
+        r = monarch.inspect(t)
+    assert torch.allclose(torch.full((3, 4), float(n)), r)
+
+    dm.exit()
+
+
 @two_gpu
 def test_proc_mesh_tensor_engine() -> None:
     pm = this_host().spawn_procs(per_host={"gpus": 2})
@@ -10,19 +10,61 @@ use std::env;
 use std::path::Path;
 use std::path::PathBuf;
 
+/// Custom functions from the rdmaxcel sources that bindgen must bind by exact
+/// name. Checked against the generated bindings so a rename in the C sources
+/// fails the build instead of silently dropping the binding.
+#[cfg(not(target_os = "macos"))]
+const CUSTOM_FUNCTIONS: &[&str] = &[
+    "create_qp",
+    "register_cuda_memory",
+    "db_ring",
+    "cqe_poll",
+    "send_wqe",
+    "recv_wqe",
+    "launch_db_ring",
+    "launch_cqe_poll",
+    "launch_send_wqe",
+    "launch_recv_wqe",
+    "rdma_get_active_segment_count",
+    "rdma_get_all_segment_info",
+    "pt_cuda_allocator_compatibility",
+    "register_segments",
+    "deregister_segments",
+    "deregister_segment",
+    "get_cuda_pci_address_from_ptr",
+    "rdmaxcel_print_device_info",
+    "rdmaxcel_error_string",
+];
+
 #[cfg(target_os = "macos")]
 fn main() {}
 
 #[cfg(not(target_os = "macos"))]
 fn main() {
-    // Link against the ibverbs library
-    println!("cargo:rustc-link-lib=ibverbs");
+    // With `bindings-only`, only generate bindings.rs: nothing is compiled or
+    // linked, so the crate type-checks without nvcc or a C++ toolchain.
+    let bindings_only = env::var("CARGO_FEATURE_BINDINGS_ONLY").is_ok();
+    // With TORCH_SYS_USE_PYTORCH_APIS=0, build for pure RDMA use without PyTorch:
+    // the c10-dependent parts of rdmaxcel.cpp are compiled out and torch isn't linked.
+    let use_pytorch_apis = build_utils::use_pytorch_apis();
+    // With RDMAXCEL_SYS_USE_SYSTEM_LIB=<prefix>, link the prebuilt rdmaxcel installed
+    // there instead of compiling the sources, and bind its installed header.
+    let system_lib = build_utils::rdmaxcel_system_prefix().map(|prefix| {
+        build_utils::find_system_rdmaxcel(&prefix).unwrap_or_else(|err| {
+            panic!("RDMAXCEL_SYS_USE_SYSTEM_LIB={}: {}", prefix.display(), err)
+        })
+    });
 
-    // Link against the mlx5 library
-    println!("cargo:rustc-link-lib=mlx5");
+    if !bindings_only {
+        // Link against the ibverbs library
+        println!("cargo:rustc-link-lib=ibverbs");
 
-    // Link against dl for dynamic loading
-    println!("cargo:rustc-link-lib=dl");
+        // Link against the mlx5 library
+        println!("cargo:rustc-link-lib=mlx5");
+
+        // Link against dl for dynamic loading
+        println!("cargo:rustc-link-lib=dl");
+    }
 
     // Tell cargo to invalidate the built crate whenever the wrapper changes
     println!("cargo:rerun-if-changed=src/rdmaxcel.h");
@@ -58,7 +100,13 @@ fn main() {
     });
 
     // Create the absolute path to the header file
-    let header_path = format!("{}/src/rdmaxcel.h", manifest_dir);
+    let header_path = match &system_lib {
+        Some(system) => {
+            println!("cargo:rerun-if-changed={}", system.header.display());
+            system.header.to_string_lossy().into_owned()
+        }
+        None => format!("{}/src/rdmaxcel.h", manifest_dir),
+    };
 
     // Check if the header file exists
     if !Path::new(&header_path).exists() {
@@ -77,26 +125,8 @@ fn main() {
         .allowlist_function("ibv_.*")
         .allowlist_function("mlx5dv_.*")
         .allowlist_function("mlx5_wqe_.*")
-        .allowlist_function("create_qp")
         .allowlist_function("create_mlx5dv_.*")
-        .allowlist_function("register_cuda_memory")
-        .allowlist_function("db_ring")
-        .allowlist_function("cqe_poll")
-        .allowlist_function("send_wqe")
-        .allowlist_function("recv_wqe")
-        .allowlist_function("launch_db_ring")
-        .allowlist_function("launch_cqe_poll")
-        .allowlist_function("launch_send_wqe")
-        .allowlist_function("launch_recv_wqe")
-        .allowlist_function("rdma_get_active_segment_count")
-        .allowlist_function("rdma_get_all_segment_info")
-        .allowlist_function("pt_cuda_allocator_compatibility")
-        .allowlist_function("register_segments")
-        .allowlist_function("deregister_segments")
         .allowlist_function("rdmaxcel_cu.*")
-        .allowlist_function("get_cuda_pci_address_from_ptr")
-        .allowlist_function("rdmaxcel_print_device_info")
-        .allowlist_function("rdmaxcel_error_string")
         .allowlist_type("ibv_.*")
         .allowlist_type("mlx5dv_.*")
         .allowlist_type("mlx5_wqe_.*")
@@ -123,6 +153,15 @@ fn main() {
         .constified_enum_module("ibv_wc_status")
         .derive_default(true)
         .prepend_enum_name(false);
+    for function in CUSTOM_FUNCTIONS {
+        builder = builder.allowlist_function(function);
+    }
+    // Extra ibverbs/mlx5 symbols requested through MONARCH_RDMA_EXTRA_ALLOWLIST.
+    for pattern in build_utils::rdma_extra_allowlist() {
+        builder = builder
+            .allowlist_function(&pattern)
+            .allowlist_type(&pattern);
+    }
 
     // Add CUDA include path (we already validated it exists)
     let cuda_include_path = format!("{}/include", cuda_home);
@@ -130,7 +169,7 @@ fn main() {
     builder = builder.clang_arg(format!("-I{}", cuda_include_path));
 
     // Include headers and libs from the active environment.
-    let python_config = match build_utils::python_env_dirs_with_interpreter("python3") {
+    let python_config = match build_utils::python_env_dirs() {
         Ok(config) => config,
         Err(_) => {
             eprintln!("Warning: Failed to get Python environment directories");
@@ -144,50 +183,72 @@ fn main() {
     if let Some(include_dir) = &python_config.include_dir {
         builder = builder.clang_arg(format!("-I{}", include_dir));
     }
+    builder = builder.clang_args(
+        build_utils::bindgen_extra_clang_args()
+            .expect("failed to parse MONARCH_BINDGEN_EXTRA_CLANG_ARGS"),
+    );
     if let Some(lib_dir) = &python_config.lib_dir {
         println!("cargo:rustc-link-search=native={}", lib_dir);
         println!("cargo:metadata=LIB_PATH={}", lib_dir);
     }
 
-    // Get CUDA library directory and emit link directives
-    let cuda_lib_dir = match build_utils::get_cuda_lib_dir() {
-        Ok(dir) => dir,
-        Err(_) => {
-            build_utils::print_cuda_lib_error_help();
+    if !bindings_only {
+        // Get CUDA library directory and emit link directives
+        let cuda_lib_dir = match build_utils::get_cuda_lib_dir() {
+            Ok(dir) => dir,
+            Err(_) => {
+                build_utils::print_cuda_lib_error_help();
+                std::process::exit(1);
+            }
+        };
+        println!("cargo:rustc-link-search=native={}", cuda_lib_dir);
+        // Note: libcuda is now loaded dynamically via dlopen in driver_api.cpp
+        // Only link cudart (CUDA Runtime API), statically if CUDA_LINK_STATIC=1
+        if let Err(err) = build_utils::link_cudart(&cuda_lib_dir) {
+            eprintln!("Error: {}", err);
             std::process::exit(1);
         }
-    };
-    println!("cargo:rustc-link-search=native={}", cuda_lib_dir);
-    // Note: libcuda is now loaded dynamically via dlopen in driver_api.cpp
-    // Only link cudart (CUDA Runtime API)
-    println!("cargo:rustc-link-lib=cudart");
-
-    // Link PyTorch C++ libraries for c10 symbols
-    let use_pytorch_apis = build_utils::get_env_var_with_rerun("TORCH_SYS_USE_PYTORCH_APIS")
-        .unwrap_or_else(|_| "1".to_owned());
-    if use_pytorch_apis == "1" {
-        // Try to get PyTorch library directory
-        let python_interpreter = std::path::PathBuf::from("python");
-        if let Ok(output) = std::process::Command::new(&python_interpreter)
-            .arg("-c")
-            .arg(build_utils::PYTHON_PRINT_PYTORCH_DETAILS)
-            .output()
+        // Tell dependents which toolkit we built against so they don't re-probe.
+        for directive in
+            build_utils::BackendMetadata::cuda(&cuda_home, Some(&cuda_lib_dir)).directives()
         {
-            for line in String::from_utf8_lossy(&output.stdout).lines() {
-                if let Some(path) = line.strip_prefix("LIBTORCH_LIB: ") {
-                    println!("cargo:rustc-link-search=native={}", path);
-                    break;
-                }
+            println!("{}", directive);
+        }
+
+        // Link PyTorch C++ libraries for c10 symbols. With TORCH_SYS_USE_PYTORCH_APIS=0
+        // nothing is linked and Python isn't run.
+        let mut libtorch_lib = None;
+        if use_pytorch_apis {
+            // Try to get PyTorch library directory
+            if let Ok(mut details) = build_utils::run_python_kv(
+                build_utils::find_python_interpreter(),
+                build_utils::PYTHON_PRINT_PYTORCH_DETAILS,
+            ) {
+                libtorch_lib = details
+                    .remove("LIBTORCH_LIB")
+                    .and_then(|dirs| dirs.into_iter().next());
             }
         }
-        // Link core PyTorch libraries needed for C10 symbols
-        println!("cargo:rustc-link-lib=torch_cpu");
-        println!("cargo:rustc-link-lib=torch");
-        println!("cargo:rustc-link-lib=c10");
+        for directive in
+            build_utils::torch_link_directives(use_pytorch_apis, libtorch_lib.as_deref())
+        {
+            println!("{}", directive);
+        }
     }
 
     // Generate bindings
     let bindings = builder.generate().expect("Unable to generate bindings");
+    if let Err(err) = build_utils::check_bindings(&bindings.to_string(), "ibv_qp") {
+        panic!("{}", err);
+    }
+    let missing = missing_functions(&bindings.to_string(), CUSTOM_FUNCTIONS);
+    if !missing.is_empty() {
+        panic!(
+            "Allowlisted functions missing from generated bindings (renamed in {}?): {}",
+            header_path,
+            missing.join(", ")
+        );
+    }
 
     // Write the bindings to the $OUT_DIR/bindings.rs file
     match env::var("OUT_DIR") {
@@ -203,6 +264,24 @@ fn main() {
                 }
                 Err(e) => eprintln!("Warning: Couldn't write bindings: {}", e),
             }
+            std::fs::write(
+                out_path.join("allowlist_test.rs"),
+                allowlist_test(CUSTOM_FUNCTIONS),
+            )
+            .expect("Couldn't write allowlist test");
+
+            if bindings_only {
+                return;
+            }
+
+            if let Some(system) = &system_lib {
+                for directive in system.link_directives() {
+                    println!("{}", directive);
+                }
+                // Tell dependents the libraries aren't in OUT_DIR.
+                println!("cargo:system_lib_dir={}", system.lib_dir.display());
+                return;
+            }
 
             // Compile the C source file
             let c_source_path = format!("{}/src/rdmaxcel.c", manifest_dir);
@@ -226,33 +305,22 @@ fn main() {
             let driver_api_cpp_path = format!("{}/src/driver_api.cpp", manifest_dir);
             if Path::new(&cpp_source_path).exists() && Path::new(&driver_api_cpp_path).exists() {
                 let mut libtorch_include_dirs: Vec<PathBuf> = vec![];
-
-                // Use the same approach as torch-sys: Python discovery first, env vars as fallback
-                let use_pytorch_apis =
-                    build_utils::get_env_var_with_rerun("TORCH_SYS_USE_PYTORCH_APIS")
-                        .unwrap_or_else(|_| "1".to_owned());
-
-                if use_pytorch_apis == "1" {
-                    // Use Python to get PyTorch include paths (same as torch-sys)
-                    let python_interpreter = PathBuf::from("python");
-                    let output = std::process::Command::new(&python_interpreter)
-                        .arg("-c")
-                        .arg(build_utils::PYTHON_PRINT_PYTORCH_DETAILS)
-                        .output()
-                        .unwrap_or_else(|_| panic!("error running {python_interpreter:?}"));
-
-                    for line in String::from_utf8_lossy(&output.stdout).lines() {
-                        if let Some(path) = line.strip_prefix("LIBTORCH_INCLUDE: ") {
-                            libtorch_include_dirs.push(PathBuf::from(path));
-                        }
-                    }
-                } else {
-                    // Use environment variables (fallback approach)
+                let mut cxx11_abi = None;
+
+                // Use the same approach as torch-sys: Python discovery for PyTorch include paths
+                if use_pytorch_apis {
+                    let python_interpreter = build_utils::find_python_interpreter();
+                    let mut details = build_utils::run_python_kv(
+                        &python_interpreter,
+                        build_utils::PYTHON_PRINT_PYTORCH_DETAILS,
+                    )
+                    .unwrap_or_else(|_| panic!("error running {python_interpreter:?}"));
+                    cxx11_abi = build_utils::parse_torch_cxx11_abi(&details);
                     libtorch_include_dirs.extend(
-                        build_utils::get_env_var_with_rerun("LIBTORCH_INCLUDE")
+                        details
+                            .remove("LIBTORCH_INCLUDE")
                             .unwrap_or_default()
-                            .split(':')
-                            .filter(|s| !s.is_empty())
+                            .into_iter()
                             .map(PathBuf::from),
                     );
                 }
@@ -270,7 +338,23 @@ fn main() {
                 // Add CUDA include paths
                 cpp_build.include(&cuda_include_path);
 
-                // Add PyTorch/C10 include paths
+                // Add PyTorch/C10 include paths and enable the allocator integration
+                if use_pytorch_apis {
+                    cpp_build.define("RDMAXCEL_USE_PYTORCH", "1");
+                }
+                // c10 must be compiled with torch's C++11 ABI setting, or c10 symbols
+                // that take std::string fail to link.
+                match cxx11_abi {
+                    Some(abi) => {
+                        cpp_build.define("_GLIBCXX_USE_CXX11_ABI", if abi { "1" } else { "0" });
+                    }
+                    None if use_pytorch_apis => println!(
+                        "cargo:warning=Could not determine torch's _GLIBCXX_USE_CXX11_ABI; \
+                         rdmaxcel is built with the compiler default, which may not link \
+                         against c10"
+                    ),
+                    None => {}
+                }
                 for include_dir in &libtorch_include_dirs {
                     cpp_build.include(include_dir);
                 }
@@ -295,8 +379,10 @@ fn main() {
             // Compile the CUDA source file
             let cuda_source_path = format!("{}/src/rdmaxcel.cu", manifest_dir);
             if Path::new(&cuda_source_path).exists() {
-                // Use the CUDA home path we already validated
-                let nvcc_path = format!("{}/bin/nvcc", cuda_home);
+                // nvcc comes from the host toolkit, even when headers and libraries
+                // come from a cross-compilation target's toolkit
+                let nvcc_path = build_utils::find_host_nvcc()
+                    .unwrap_or_else(|| format!("{}/bin/nvcc", cuda_home));
 
                 // Set up fixed output directory - use a predictable path instead of dynamic OUT_DIR
                 let cuda_build_dir = format!("{}/target/cuda_build", manifest_dir);
@@ -306,40 +392,33 @@ fn main() {
                 let cuda_obj_path = format!("{}/rdmaxcel_cuda.o", cuda_build_dir);
                 let cuda_lib_path = format!("{}/librdmaxcel_cuda.a", cuda_build_dir);
 
-                // Use nvcc to compile the CUDA file
-                let nvcc_output = std::process::Command::new(&nvcc_path)
-                    .args(&[
-                        "-c",
-                        &cuda_source_path,
-                        "-o",
-                        &cuda_obj_path,
-                        "--compiler-options",
-                        "-fPIC",
-                        "-std=c++20",
-                        "--expt-extended-lambda",
-                        "-Xcompiler",
-                        "-fPIC",
-                        &format!("-I{}", cuda_include_path),
-                        &format!("-I{}/src", manifest_dir),
-                        &format!("-I/usr/include"),
-                        &format!("-I/usr/include/infiniband"),
-                    ])
-                    .output();
-
-                match nvcc_output {
-                    Ok(output) => {
-                        if !output.status.success() {
-                            eprintln!("nvcc stderr: {}", String::from_utf8_lossy(&output.stderr));
-                            eprintln!("nvcc stdout: {}", String::from_utf8_lossy(&output.stdout));
-                            panic!("Failed to compile CUDA source with nvcc");
-                        }
-                        println!("cargo:rerun-if-changed={}", cuda_source_path);
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to run nvcc: {}", e);
-                        panic!("nvcc not found or failed to execute");
-                    }
+                let gencode_flags = build_utils::cuda_architectures()
+                    .map(|archs| build_utils::gencode_flags(&archs))
+                    .unwrap_or_default();
+                let nvcc_args = nvcc_args(
+                    &cuda_source_path,
+                    &cuda_obj_path,
+                    &cuda_include_path,
+                    &manifest_dir,
+                    &gencode_flags,
+                );
+
+                // Record the nvcc command line so that changing any flag rebuilds the object
+                let command_path = format!("{}/rdmaxcel_cuda.cmd", cuda_build_dir);
+                build_utils::write_command_stamp(&command_path, &nvcc_path, &nvcc_args)
+                    .expect("Failed to record nvcc command line");
+
+                // Only invoke nvcc when the object is older than its inputs
+                let cuda_inputs = [
+                    cuda_source_path.clone(),
+                    format!("{}/src/rdmaxcel.h", manifest_dir),
+                    format!("{}/src/driver_api.h", manifest_dir),
+                    command_path,
+                ];
+                if build_utils::needs_rebuild(&cuda_obj_path, &cuda_inputs) {
+                    compile_cuda_object(&nvcc_path, &nvcc_args);
                 }
+                println!("cargo:rerun-if-changed={}", cuda_source_path);
 
                 // Create static library from the compiled CUDA object
                 let ar_output = std::process::Command::new("ar")
@@ -377,3 +456,81 @@ fn main() {
         }
     }
 }
+
+/// The nvcc arguments that compile `rdmaxcel.cu` into `cuda_obj_path`.
+#[cfg(not(target_os = "macos"))]
+fn nvcc_args(
+    cuda_source_path: &str,
+    cuda_obj_path: &str,
+    cuda_include_path: &str,
+    manifest_dir: &str,
+    gencode_flags: &[String],
+) -> Vec<String> {
+    let mut args = gencode_flags.to_vec();
+    args.extend([
+        "-c".to_string(),
+        cuda_source_path.to_string(),
+        "-o".to_string(),
+        cuda_obj_path.to_string(),
+        "--compiler-options".to_string(),
+        "-fPIC".to_string(),
+        "-std=c++20".to_string(),
+        "--expt-extended-lambda".to_string(),
+        "-Xcompiler".to_string(),
+        "-fPIC".to_string(),
+        format!("-I{}", cuda_include_path),
+        format!("-I{}/src", manifest_dir),
+        "-I/usr/include".to_string(),
+        "-I/usr/include/infiniband".to_string(),
+    ]);
+    args
+}
+
+/// Compile `rdmaxcel.cu` by running nvcc with `nvcc_args`.
+#[cfg(not(target_os = "macos"))]
+fn compile_cuda_object(nvcc_path: &str, nvcc_args: &[String]) {
+    // Use nvcc to compile the CUDA file
+    let nvcc_output = std::process::Command::new(nvcc_path)
+        .args(nvcc_args)
+        .output();
+
+    match nvcc_output {
+        Ok(output) => {
+            if !output.status.success() {
+                eprintln!("nvcc stderr: {}", String::from_utf8_lossy(&output.stderr));
+                eprintln!("nvcc stdout: {}", String::from_utf8_lossy(&output.stdout));
+                panic!("Failed to compile CUDA source with nvcc");
+            }
+        }
+        Err(e) => {
+            eprintln!("Failed to run nvcc: {}", e);
+            panic!("nvcc not found or failed to execute");
+        }
+    }
+}
+
+/// Names from `functions` that have no `fn` declaration in `bindings`.
+#[cfg(not(target_os = "macos"))]
+fn missing_functions<'a>(bindings: &str, functions: &[&'a str]) -> Vec<&'a str> {
+    // Compare without whitespace so the check doesn't depend on how bindgen formats.
+    let bindings: String = bindings.split_whitespace().collect();
+    functions
+        .iter()
+        .filter(|name| !bindings.contains(&format!("pubfn{}(", name)))
+        .copied()
+        .collect()
+}
+
+/// Source for a test that takes the address of every function in `functions`,
+/// so a binding that goes missing fails to compile with its name.
+#[cfg(not(target_os = "macos"))]
+fn allowlist_test(functions: &[&str]) -> String {
+    let mut test = String::from(
+        "#[test]\nfn allowlisted_functions_are_bound() {\n    let functions: &[*const ()] = &[\n",
+    );
+    for name in functions {
+        test.push_str(&format!("        crate::inner::{} as *const (),\n", name));
+    }
+    test.push_str("    ];\n    assert!(functions.iter().all(|f| !f.is_null()));\n}\n");
+    test
+}
@@ -24,13 +24,20 @@
   _(cuMemRelease)                   \
   _(cuMemcpyHtoD_v2)                \
   _(cuMemcpyDtoH_v2)                \
+  _(cuMemcpy)                       \
   _(cuPointerGetAttribute)          \
   _(cuInit)                         \
   _(cuDeviceGet)                    \
   _(cuDeviceGetCount)               \
   _(cuDeviceGetAttribute)           \
+  _(cuDevicePrimaryCtxRetain)       \
+  _(cuDevicePrimaryCtxRelease_v2)   \
+  _(cuDevicePrimaryCtxReset_v2)     \
   _(cuCtxCreate_v2)                 \
   _(cuCtxSetCurrent)                \
+  _(cuCtxGetCurrent)                \
+  _(cuCtxGetDevice)                 \
+  _(cuCtxSynchronize)               \
   _(cuGetErrorString)
 
 namespace rdmaxcel {
@@ -169,6 +176,10 @@ CUresult rdmaxcel_cuMemcpyDtoH_v2(
       dstHost, srcDevice, ByteCount);
 }
 
+CUresult rdmaxcel_cuMemcpy(CUdeviceptr dst, CUdeviceptr src, size_t ByteCount) {
+  return rdmaxcel::DriverAPI::get()->cuMemcpy_(dst, src, ByteCount);
+}
+
 // Pointer queries
 CUresult rdmaxcel_cuPointerGetAttribute(
     void* data,
@@ -198,6 +209,18 @@ CUresult rdmaxcel_cuDeviceGetAttribute(
   return rdmaxcel::DriverAPI::get()->cuDeviceGetAttribute_(pi, attrib, dev);
 }
 
+CUresult rdmaxcel_cuDevicePrimaryCtxRetain(CUcontext* pctx, CUdevice dev) {
+  return rdmaxcel::DriverAPI::get()->cuDevicePrimaryCtxRetain_(pctx, dev);
+}
+
+CUresult rdmaxcel_cuDevicePrimaryCtxRelease_v2(CUdevice dev) {
+  return rdmaxcel::DriverAPI::get()->cuDevicePrimaryCtxRelease_v2_(dev);
+}
+
+CUresult rdmaxcel_cuDevicePrimaryCtxReset_v2(CUdevice dev) {
+  return rdmaxcel::DriverAPI::get()->cuDevicePrimaryCtxReset_v2_(dev);
+}
+
 // Context management
 CUresult
 rdmaxcel_cuCtxCreate_v2(CUcontext* pctx, unsigned int flags, CUdevice dev) {
@@ -208,6 +231,18 @@ CUresult rdmaxcel_cuCtxSetCurrent(CUcontext ctx) {
   return rdmaxcel::DriverAPI::get()->cuCtxSetCurrent_(ctx);
 }
 
+CUresult rdmaxcel_cuCtxGetCurrent(CUcontext* pctx) {
+  return rdmaxcel::DriverAPI::get()->cuCtxGetCurrent_(pctx);
+}
+
+CUresult rdmaxcel_cuCtxGetDevice(CUdevice* device) {
+  return rdmaxcel::DriverAPI::get()->cuCtxGetDevice_(device);
+}
+
+CUresult rdmaxcel_cuCtxSynchronize(void) {
+  return rdmaxcel::DriverAPI::get()->cuCtxSynchronize_();
+}
+
 // Error handling
 CUresult rdmaxcel_cuGetErrorString(CUresult error, const char** pStr) {
   return rdmaxcel::DriverAPI::get()->cuGetErrorString_(error, pStr);
@@ -71,6 +71,8 @@ CUresult rdmaxcel_cuMemcpyDtoH_v2(
     CUdeviceptr srcDevice,
     size_t ByteCount);
 
+CUresult rdmaxcel_cuMemcpy(CUdeviceptr dst, CUdeviceptr src, size_t ByteCount);
+
 // Pointer queries
 CUresult rdmaxcel_cuPointerGetAttribute(
     void* data,
@@ -87,12 +89,24 @@ CUresult rdmaxcel_cuDeviceGetCount(int* count);
 CUresult
 rdmaxcel_cuDeviceGetAttribute(int* pi, CUdevice_attribute attrib, CUdevice dev);
 
+CUresult rdmaxcel_cuDevicePrimaryCtxRetain(CUcontext* pctx, CUdevice dev);
+
+CUresult rdmaxcel_cuDevicePrimaryCtxRelease_v2(CUdevice dev);
+
+CUresult rdmaxcel_cuDevicePrimaryCtxReset_v2(CUdevice dev);
+
 // Context management
 CUresult
 rdmaxcel_cuCtxCreate_v2(CUcontext* pctx, unsigned int flags, CUdevice dev);
 
 CUresult rdmaxcel_cuCtxSetCurrent(CUcontext ctx);
 
+CUresult rdmaxcel_cuCtxGetCurrent(CUcontext* pctx);
+
+CUresult rdmaxcel_cuCtxGetDevice(CUdevice* device);
+
+CUresult rdmaxcel_cuCtxSynchronize(void);
+
 // Error handling
 CUresult rdmaxcel_cuGetErrorString(CUresult error, const char** pStr);
 
@@ -238,6 +238,11 @@ mod inner {
 
 pub use inner::*;
 
+#[cfg(all(test, cargo))]
+mod allowlist_tests {
+    include!(concat!(env!("OUT_DIR"), "/allowlist_test.rs"));
+}
+
 // RDMA error string function and CUDA utility functions
 unsafe extern "C" {
     pub fn rdmaxcel_error_string(error_code: std::os::raw::c_int) -> *const std::os::raw::c_char;
@@ -250,3 +255,72 @@ unsafe extern "C" {
     /// Debug: Print comprehensive device attributes
     pub fn rdmaxcel_print_device_info(context: *mut ibv_context);
 }
+
+/// Layout checks for the structs shared between Rust and `rdmaxcel.h`.
+///
+/// The expected offsets are those of the C definitions on LP64 targets; a
+/// field reorder on either side makes these fail instead of silently
+/// corrupting the WQE/CQE parameters passed to the device functions.
+#[cfg(all(test, target_pointer_width = "64"))]
+mod layout_tests {
+    use std::mem::align_of;
+    use std::mem::offset_of;
+    use std::mem::size_of;
+
+    use super::*;
+
+    #[test]
+    fn test_wqe_params_t_layout() {
+        assert_eq!(offset_of!(wqe_params_t, laddr), 0);
+        assert_eq!(offset_of!(wqe_params_t, lkey), 8);
+        assert_eq!(offset_of!(wqe_params_t, length), 16);
+        assert_eq!(offset_of!(wqe_params_t, wr_id), 24);
+        assert_eq!(offset_of!(wqe_params_t, signaled), 32);
+        assert_eq!(offset_of!(wqe_params_t, op_type), 36);
+        assert_eq!(offset_of!(wqe_params_t, raddr), 40);
+        assert_eq!(offset_of!(wqe_params_t, rkey), 48);
+        assert_eq!(offset_of!(wqe_params_t, qp_num), 52);
+        assert_eq!(offset_of!(wqe_params_t, buf), 56);
+        assert_eq!(offset_of!(wqe_params_t, dbrec), 64);
+        assert_eq!(offset_of!(wqe_params_t, wqe_cnt), 72);
+        assert_eq!(size_of::<wqe_params_t>(), 80);
+        assert_eq!(align_of::<wqe_params_t>(), 8);
+    }
+
+    #[test]
+    fn test_cqe_poll_params_t_layout() {
+        assert_eq!(offset_of!(cqe_poll_params_t, cqe_buf), 0);
+        assert_eq!(offset_of!(cqe_poll_params_t, cqe_size), 8);
+        assert_eq!(offset_of!(cqe_poll_params_t, consumer_index), 12);
+        assert_eq!(offset_of!(cqe_poll_params_t, cqe_cnt), 16);
+        assert_eq!(offset_of!(cqe_poll_params_t, dbrec), 24);
+        assert_eq!(size_of::<cqe_poll_params_t>(), 32);
+        assert_eq!(align_of::<cqe_poll_params_t>(), 8);
+    }
+
+    #[test]
+    fn test_rdma_segment_info_t_layout() {
+        assert_eq!(offset_of!(rdma_segment_info_t, phys_address), 0);
+        assert_eq!(offset_of!(rdma_segment_info_t, phys_size), 8);
+        assert_eq!(offset_of!(rdma_segment_info_t, device), 16);
+        assert_eq!(offset_of!(rdma_segment_info_t, is_expandable), 20);
+        assert_eq!(offset_of!(rdma_segment_info_t, lkey), 24);
+        assert_eq!(offset_of!(rdma_segment_info_t, rkey), 28);
+        assert_eq!(offset_of!(rdma_segment_info_t, mr_size), 32);
+        assert_eq!(offset_of!(rdma_segment_info_t, mr_addr), 40);
+        assert_eq!(size_of::<rdma_segment_info_t>(), 48);
+        assert_eq!(align_of::<rdma_segment_info_t>(), 8);
+    }
+
+    #[test]
+    fn test_mlx5_wqe_ctrl_seg_layout() {
+        // Hand-written above; must match `struct mlx5_wqe_ctrl_seg` in mlx5dv.h.
+        assert_eq!(offset_of!(mlx5_wqe_ctrl_seg, opmod_idx_opcode), 0);
+        assert_eq!(offset_of!(mlx5_wqe_ctrl_seg, qpn_ds), 4);
+        assert_eq!(offset_of!(mlx5_wqe_ctrl_seg, signature), 8);
+        assert_eq!(offset_of!(mlx5_wqe_ctrl_seg, dci_stream_channel_id), 9);
+        assert_eq!(offset_of!(mlx5_wqe_ctrl_seg, fm_ce_se), 11);
+        assert_eq!(offset_of!(mlx5_wqe_ctrl_seg, imm), 12);
+        assert_eq!(size_of::<mlx5_wqe_ctrl_seg>(), 16);
+    }
+}
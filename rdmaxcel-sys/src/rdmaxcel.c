@@ -24,20 +24,25 @@ cudaError_t register_mmio_to_cuda(void* bf, size_t size) {
 struct ibv_qp* create_qp(
     struct ibv_context* context,
     struct ibv_pd* pd,
-    int cq_entries,
+    int send_cq_entries,
+    int recv_cq_entries,
     int max_send_wr,
     int max_recv_wr,
     int max_send_sge,
     int max_recv_sge,
-    rdma_qp_type_t qp_type) {
-  // Create separate completion queues for send and receive operations
-  struct ibv_cq* send_cq = ibv_create_cq(context, cq_entries, NULL, NULL, 0);
+    rdma_qp_type_t qp_type,
+    struct ibv_comp_channel* channel) {
+  // Create separate completion queues for send and receive operations. When a
+  // completion channel is given, both queues report completion events to it.
+  struct ibv_cq* send_cq =
+      ibv_create_cq(context, send_cq_entries, NULL, channel, 0);
   if (!send_cq) {
     perror("failed to create send completion queue (CQ)");
     return NULL;
   }
 
-  struct ibv_cq* recv_cq = ibv_create_cq(context, cq_entries, NULL, NULL, 0);
+  struct ibv_cq* recv_cq =
+      ibv_create_cq(context, recv_cq_entries, NULL, channel, 0);
   if (!recv_cq) {
     perror("failed to create receive completion queue (CQ)");
     ibv_destroy_cq(send_cq);
@@ -87,7 +92,8 @@ struct ibv_qp* create_qp(
       return qp;
     }
 
-    case RDMA_QP_TYPE_STANDARD: {
+    case RDMA_QP_TYPE_STANDARD:
+    case RDMA_QP_TYPE_UD: {
       // Initialize queue pair attributes
       struct ibv_qp_init_attr qp_init_attr = {
           .qp_context = NULL,
@@ -102,7 +108,7 @@ struct ibv_qp* create_qp(
                   .max_recv_sge = max_recv_sge,
                   .max_inline_data = 0,
               },
-          .qp_type = IBV_QPT_RC,
+          .qp_type = qp_type == RDMA_QP_TYPE_UD ? IBV_QPT_UD : IBV_QPT_RC,
           .sq_sig_all = 0,
       };
 
@@ -7,8 +7,10 @@
  */
 
 #include "rdmaxcel.h"
+#ifdef RDMAXCEL_USE_PYTORCH
 #include <c10/cuda/CUDAAllocatorConfig.h>
 #include <c10/cuda/CUDACachingAllocator.h>
+#endif
 #include <cuda.h>
 #include <unistd.h>
 #include <mutex>
@@ -62,8 +64,10 @@ struct SegmentInfo {
 static std::unordered_map<size_t, SegmentInfo> activeSegments;
 static std::mutex segmentsMutex;
 
-// Helper function to scan existing segments from allocator snapshot
+// Helper function to scan existing segments from allocator snapshot. Without
+// PyTorch there is no caching allocator, so no segments are ever tracked.
 void scan_existing_segments() {
+#ifdef RDMAXCEL_USE_PYTORCH
   std::lock_guard<std::mutex> lock(segmentsMutex);
 
   // Get current snapshot from the allocator
@@ -110,16 +114,21 @@ void scan_existing_segments() {
       ++it;
     }
   }
+#endif
 }
 
 extern "C" {
 
 // Simple check for PyTorch CUDA allocator compatibility
 bool pt_cuda_allocator_compatibility() {
+#ifdef RDMAXCEL_USE_PYTORCH
   return (
       c10::cuda::CUDACachingAllocator::isEnabled() &&
       c10::cuda::CUDACachingAllocator::CUDAAllocatorConfig::
           expandable_segments());
+#else
+  return false;
+#endif
 }
 
 // Get count of active segments
@@ -437,6 +446,30 @@ int deregister_segments() {
   return 0; // Success
 }
 
+// Deregister a single segment by its start address and stop tracking it. It is
+// picked up again by the next register_segments if the allocator still holds it.
+int deregister_segment(size_t phys_address) {
+  std::lock_guard<std::mutex> lock(segmentsMutex);
+
+  auto it = activeSegments.find(phys_address);
+  if (it == activeSegments.end()) {
+    return RDMAXCEL_INVALID_PARAMS; // Segment not tracked
+  }
+
+  SegmentInfo& seg = it->second;
+  for (auto* mr : seg.mrs) {
+    if (mr) {
+      ibv_dereg_mr(mr);
+    }
+  }
+  if (seg.mkey) {
+    mlx5dv_destroy_mkey(seg.mkey);
+  }
+  activeSegments.erase(it);
+
+  return 0; // Success
+}
+
 // Debug: Print comprehensive device attributes
 void rdmaxcel_print_device_info(struct ibv_context* context) {
   if (!context) {
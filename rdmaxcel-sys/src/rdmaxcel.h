@@ -28,7 +28,8 @@ typedef enum {
 // RDMA queue pair type selection
 typedef enum {
   RDMA_QP_TYPE_STANDARD = 1, // Standard ibverbs queue pair
-  RDMA_QP_TYPE_MLX5DV = 2 // mlx5dv extended queue pair
+  RDMA_QP_TYPE_MLX5DV = 2, // mlx5dv extended queue pair
+  RDMA_QP_TYPE_UD = 3 // Unreliable datagram queue pair, for multicast
 } rdma_qp_type_t;
 
 // C-compatible structure for CUDA segment information
@@ -44,7 +45,9 @@ typedef struct {
   uintptr_t mr_addr; // Registered MR address (0 if not registered)
 } rdma_segment_info_t;
 
-// Structure for WQE parameters
+// Structure for WQE parameters. The layouts of wqe_params_t,
+// cqe_poll_params_t and rdma_segment_info_t are asserted by the layout tests
+// in rdmaxcel-sys/src/lib.rs; update them together.
 typedef struct {
   uintptr_t laddr;
   uint32_t lkey;
@@ -72,12 +75,14 @@ typedef struct {
 struct ibv_qp* create_qp(
     struct ibv_context* context,
     struct ibv_pd* pd,
-    int cq_entries,
+    int send_cq_entries,
+    int recv_cq_entries,
     int max_send_wr,
     int max_recv_wr,
     int max_send_sge,
     int max_recv_sge,
-    rdma_qp_type_t qp_type);
+    rdma_qp_type_t qp_type,
+    struct ibv_comp_channel* channel);
 
 struct mlx5dv_qp* create_mlx5dv_qp(struct ibv_qp* qp);
 
@@ -149,6 +154,7 @@ int rdma_get_all_segment_info(rdma_segment_info_t* info_array, int max_count);
 bool pt_cuda_allocator_compatibility();
 int register_segments(struct ibv_pd* pd, struct ibv_qp* qp);
 int deregister_segments();
+int deregister_segment(size_t phys_address);
 
 // CUDA utility functions
 int get_cuda_pci_address_from_ptr(
@@ -26,6 +26,14 @@ std::shared_ptr<c10::cuda::CUDAStream> create_stream(
 
 void set_current_stream(const c10::cuda::CUDAStream& stream);
 
+/// The CUDA runtime headers may redirect `cudaGetDeviceProperties` to a
+/// versioned symbol whose `cudaDeviceProp` layout matches the headers. Calling
+/// it from C++ picks the right one; the result is returned as an int so Rust
+/// can check it with `cuda_check`.
+inline int32_t get_device_properties(int32_t device, cudaDeviceProp& prop) {
+  return static_cast<int32_t>(cudaGetDeviceProperties(&prop, device));
+}
+
 /// This function exists because ncclConfig initialization requires the use of
 /// a macro. We cannot reference the macro directly from Rust code, so we wrap
 /// the macro use in a function and bind that to Rust instead.
@@ -34,4 +42,21 @@ inline ncclConfig_t make_nccl_config() {
   return ret;
 }
 
+/// Like `make_nccl_config`, but with the fields exposed by `NcclConfig`
+/// populated. `netName` is left unset since the caller owns that string.
+inline ncclConfig_t make_nccl_config_with(
+    bool blocking,
+    int32_t cga_cluster_size,
+    int32_t min_ctas,
+    int32_t max_ctas,
+    bool split_share) {
+  ncclConfig_t ret = NCCL_CONFIG_INITIALIZER;
+  ret.blocking = blocking ? 1 : 0;
+  ret.cgaClusterSize = cga_cluster_size;
+  ret.minCTAs = min_ctas;
+  ret.maxCTAs = max_ctas;
+  ret.splitShare = split_share ? 1 : 0;
+  return ret;
+}
+
 } // namespace monarch
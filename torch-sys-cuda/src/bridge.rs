@@ -40,10 +40,22 @@ pub(crate) mod ffi {
         fn device_index(self: &CUDAStream) -> i8;
         fn stream(self: &CUDAStream) -> *mut CUstream_st;
 
+        // CUDA device APIs
+        #[namespace = ""]
+        type cudaDeviceProp = nccl_sys::cudaDeviceProp;
+        fn get_device_properties(device: i32, prop: &mut cudaDeviceProp) -> i32;
+
         // nccl helpers
         #[namespace = ""]
         type ncclConfig_t = nccl_sys::ncclConfig_t;
         fn make_nccl_config() -> ncclConfig_t;
+        fn make_nccl_config_with(
+            blocking: bool,
+            cga_cluster_size: i32,
+            min_ctas: i32,
+            max_ctas: i32,
+            split_share: bool,
+        ) -> ncclConfig_t;
     }
 }
 
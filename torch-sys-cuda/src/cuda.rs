@@ -7,12 +7,21 @@
  */
 
 //! Bindings for torch's wrappers around CUDA-related functionality.
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::ffi::c_void;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use cxx::SharedPtr;
 use cxx::UniquePtr;
 use derive_more::Into;
+use nccl_sys::cudaDeviceProp;
 use nccl_sys::cudaError_t;
+use nccl_sys::cudaGetDevice;
+use nccl_sys::cudaLaunchHostFunc;
 use nccl_sys::cudaSetDevice;
 use nccl_sys::cudaStream_t;
 use thiserror::Error;
@@ -104,6 +113,44 @@ impl Stream {
     pub fn stream(&self) -> cudaStream_t {
         self.inner.stream()
     }
+
+    /// Enqueue `f` to run on a host thread once all work submitted to this
+    /// stream so far has completed. Work submitted afterwards waits for `f` to
+    /// return, so `f` should be short.
+    ///
+    /// `f` runs on a thread owned by the CUDA runtime and must not call CUDA
+    /// APIs, which may deadlock there. A panic in `f` is caught and logged
+    /// rather than unwinding into the runtime.
+    pub fn add_callback(&self, f: Box<dyn FnOnce() + Send>) -> Result<(), CudaError> {
+        let data = Box::into_raw(Box::new(f));
+        // SAFETY: `host_callback_trampoline` takes ownership of `data` when the
+        // runtime calls it, which it does exactly once if the launch succeeds.
+        let result = unsafe {
+            cudaLaunchHostFunc(
+                self.stream(),
+                Some(host_callback_trampoline),
+                data as *mut c_void,
+            )
+        };
+        if let Err(err) = cuda_check(result) {
+            // SAFETY: the launch failed, so the runtime will never call the
+            // trampoline and `data` is still ours.
+            drop(unsafe { Box::from_raw(data) });
+            return Err(err);
+        }
+        Ok(())
+    }
+}
+
+/// Runs a callback enqueued by [`Stream::add_callback`].
+unsafe extern "C" fn host_callback_trampoline(data: *mut c_void) {
+    // SAFETY: `data` was produced by `Box::into_raw` in `add_callback`, and the
+    // runtime passes it to this function exactly once.
+    let f = unsafe { *Box::from_raw(data as *mut Box<dyn FnOnce() + Send>) };
+    // Unwinding across the FFI boundary would abort the process.
+    if std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).is_err() {
+        tracing::error!("CUDA stream callback panicked");
+    }
 }
 
 impl AsRef<ffi::CUDAStream> for Stream {
@@ -119,6 +166,69 @@ impl PartialEq for Stream {
     }
 }
 
+/// A pool of streams that are created lazily and handed out round-robin.
+///
+/// Each device gets up to `size` streams. Once a device's pool is full,
+/// [`StreamPool::acquire`] hands out the existing streams in turn, so ops that
+/// would otherwise create a stream per call can share a bounded set instead.
+/// Acquired streams are plain [`Stream`] handles; dropping one returns it to
+/// the pool. A stream may be handed to several callers at once, in which case
+/// their work on it runs in submission order.
+#[derive(Debug)]
+pub struct StreamPool {
+    size: usize,
+    devices: Mutex<HashMap<CudaDevice, DeviceStreams>>,
+    created: AtomicUsize,
+}
+
+#[derive(Debug, Default)]
+struct DeviceStreams {
+    streams: Vec<Stream>,
+    next: usize,
+}
+
+impl StreamPool {
+    /// Create a pool holding at most `size` streams per device.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "stream pool size must be positive");
+        Self {
+            size,
+            devices: Mutex::new(HashMap::new()),
+            created: AtomicUsize::new(0),
+        }
+    }
+
+    /// Get a stream on `device`, creating one if the device's pool is not yet
+    /// full and otherwise reusing the next pooled stream.
+    pub fn acquire(&self, device: CudaDevice) -> Stream {
+        let mut devices = self.devices.lock().unwrap();
+        let pool = devices.entry(device).or_default();
+        if pool.streams.len() < self.size {
+            let stream = Stream::new_with_device(device);
+            pool.streams.push(stream.clone());
+            self.created.fetch_add(1, Ordering::Relaxed);
+            return stream;
+        }
+        let stream = pool.streams[pool.next].clone();
+        pool.next = (pool.next + 1) % self.size;
+        stream
+    }
+
+    /// The maximum number of streams per device.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The number of streams this pool has created across all devices.
+    pub fn streams_created(&self) -> usize {
+        self.created.load(Ordering::Relaxed)
+    }
+}
+
 /// Wrapper around a CUDA event.
 ///
 /// CUDA events are synchronization markers that can be used to monitor the
@@ -145,6 +255,15 @@ impl Event {
         }
     }
 
+    /// Create a new event that records timing data, for use with
+    /// [`Event::elapsed_time`]. Timing events are more expensive to record, so
+    /// only use them when a measurement is needed.
+    pub fn new_with_timing() -> Self {
+        Self {
+            inner: ffi::create_cuda_event(true, false, false),
+        }
+    }
+
     /// Record the event on the current stream.
     ///
     /// Uses the current stream if no stream is provided.
@@ -179,9 +298,13 @@ impl Event {
     /// Return the time elapsed.
     ///
     /// Time reported in after the event was recorded and before the end_event
-    /// was recorded.
+    /// was recorded. Both events must have been created with
+    /// [`Event::new_with_timing`] and `end_event` must have completed.
     pub fn elapsed_time(&self, end_event: &Event) -> Duration {
-        Duration::from_millis(self.inner.elapsed_time(end_event.as_ref()) as u64)
+        // CUDA reports milliseconds as a float with ~0.5us resolution; keep the
+        // fractional part.
+        let millis = self.inner.elapsed_time(end_event.as_ref());
+        Duration::from_secs_f64(f64::from(millis.max(0.0)) / 1000.0)
     }
 
     /// Wait for the event to complete.
@@ -454,8 +577,207 @@ pub fn cuda_check(result: cudaError_t) -> Result<(), CudaError> {
     }
 }
 
+/// Makes `device` current on the calling thread.
+///
+/// The CUDA runtime's current device is the authoritative notion of "current
+/// device" in this crate: torch, NCCL and the runtime all bind the device's
+/// primary context through it. Prefer [`with_device`] unless the device should
+/// stay current after the call.
 pub fn set_device(device: CudaDevice) -> Result<(), CudaError> {
     let index: i8 = device.index().into();
     // SAFETY: intended usage of this function
     unsafe { cuda_check(cudaSetDevice(index.into())) }
 }
+
+/// Returns the index of the device current on the calling thread.
+pub fn current_device() -> Result<i32, CudaError> {
+    let mut index = 0;
+    // SAFETY: `index` is a valid out-pointer for the duration of the call.
+    unsafe { cuda_check(cudaGetDevice(&mut index))? };
+    Ok(index)
+}
+
+/// Runs `f` with `device` current on the calling thread, then restores the
+/// device that was current before, even if `f` panics.
+///
+/// Calls nest: each one restores exactly the device its caller had.
+pub fn with_device<T>(device: CudaDevice, f: impl FnOnce() -> T) -> Result<T, CudaError> {
+    struct Restore(i32);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            // SAFETY: intended usage of this function
+            if let Err(err) = unsafe { cuda_check(cudaSetDevice(self.0)) } {
+                tracing::error!("failed to restore CUDA device {}: {}", self.0, err);
+            }
+        }
+    }
+
+    let _restore = Restore(current_device()?);
+    set_device(device)?;
+    Ok(f())
+}
+
+/// Properties of a CUDA device, as reported by `cudaGetDeviceProperties`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceProperties {
+    /// The device's name, e.g. "NVIDIA H100 80GB HBM3".
+    pub name: String,
+    /// Major compute capability.
+    pub major: i32,
+    /// Minor compute capability.
+    pub minor: i32,
+    /// Global memory on the device, in bytes.
+    pub total_global_mem: usize,
+    /// Number of streaming multiprocessors.
+    pub multi_processor_count: i32,
+    /// Threads per warp.
+    pub warp_size: i32,
+}
+
+impl DeviceProperties {
+    /// The compute capability as `(major, minor)`.
+    pub fn compute_capability(&self) -> (i32, i32) {
+        (self.major, self.minor)
+    }
+
+    /// The architecture name used in compiler flags, e.g. "sm_90".
+    pub fn arch_name(&self) -> String {
+        format!("sm_{}{}", self.major, self.minor)
+    }
+}
+
+impl From<&cudaDeviceProp> for DeviceProperties {
+    fn from(prop: &cudaDeviceProp) -> Self {
+        // Guard against a name that fills the buffer without a terminator.
+        let name = prop.name.map(|c| c as u8);
+        let name = CStr::from_bytes_until_nul(&name)
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| String::from_utf8_lossy(&name).into_owned());
+        Self {
+            name,
+            major: prop.major,
+            minor: prop.minor,
+            total_global_mem: prop.totalGlobalMem,
+            multi_processor_count: prop.multiProcessorCount,
+            warp_size: prop.warpSize,
+        }
+    }
+}
+
+/// Returns the properties of the device at `index`.
+pub fn device_properties(index: i32) -> Result<DeviceProperties, CudaError> {
+    // SAFETY: `cudaDeviceProp` is a POD struct for which all zeroes is valid.
+    let mut prop: cudaDeviceProp = unsafe { std::mem::zeroed() };
+    let result = ffi::get_device_properties(index, &mut prop);
+    cuda_check(cudaError_t(result as _))?;
+    Ok(DeviceProperties::from(&prop))
+}
+
+#[cfg(test)]
+mod tests {
+    use torch_sys::DeviceIndex;
+    use torch_sys::factory_float_tensor;
+
+    use super::*;
+
+    #[test]
+    fn stream_pool_reuses_streams() {
+        let device = CudaDevice::new(DeviceIndex(0));
+        let pool = StreamPool::new(2);
+        let streams: Vec<_> = (0..5).map(|_| pool.acquire(device)).collect();
+        assert_eq!(pool.streams_created(), 2);
+        assert!(streams[0] != streams[1]);
+        assert!(streams[2] == streams[0]);
+        assert!(streams[3] == streams[1]);
+        assert!(streams[4] == streams[0]);
+
+        // Other devices get their own streams.
+        pool.acquire(CudaDevice::new(DeviceIndex(1)));
+        assert_eq!(pool.streams_created(), 3);
+    }
+
+    #[test]
+    fn stream_callback_runs_after_queued_copy() {
+        let device = CudaDevice::new(DeviceIndex(0));
+        let stream = Stream::new_with_device(device);
+        Stream::set_current_stream(&stream);
+
+        let src = factory_float_tensor(&vec![1.0; 1 << 20], device.into());
+        let mut dst = factory_float_tensor(&vec![0.0; 1 << 20], device.into());
+        dst.copy_(&src);
+        let copied = stream.record_event(None);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        stream
+            .add_callback(Box::new(move || tx.send(()).unwrap()))
+            .unwrap();
+        rx.recv_timeout(Duration::from_secs(30)).unwrap();
+        // The copy was queued before the callback, so it has completed.
+        assert!(copied.query());
+    }
+
+    #[test]
+    fn stream_pool_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<StreamPool>();
+    }
+
+    #[test]
+    fn nested_with_device_restores() {
+        set_device(CudaDevice::new(DeviceIndex(0))).unwrap();
+        with_device(CudaDevice::new(DeviceIndex(1)), || {
+            assert_eq!(current_device().unwrap(), 1);
+            with_device(CudaDevice::new(DeviceIndex(0)), || {
+                assert_eq!(current_device().unwrap(), 0);
+            })
+            .unwrap();
+            assert_eq!(current_device().unwrap(), 1);
+        })
+        .unwrap();
+        assert_eq!(current_device().unwrap(), 0);
+    }
+
+    #[test]
+    fn device_properties_from_raw() {
+        // SAFETY: `cudaDeviceProp` is a POD struct for which all zeroes is valid.
+        let mut prop: cudaDeviceProp = unsafe { std::mem::zeroed() };
+        for (dst, src) in prop.name.iter_mut().zip(b"Test GPU") {
+            *dst = *src as _;
+        }
+        prop.major = 9;
+        prop.minor = 0;
+        prop.totalGlobalMem = 80 << 30;
+        prop.multiProcessorCount = 132;
+        prop.warpSize = 32;
+
+        let props = DeviceProperties::from(&prop);
+        assert_eq!(
+            props,
+            DeviceProperties {
+                name: "Test GPU".to_string(),
+                major: 9,
+                minor: 0,
+                total_global_mem: 80 << 30,
+                multi_processor_count: 132,
+                warp_size: 32,
+            }
+        );
+        assert_eq!(props.compute_capability(), (9, 0));
+        assert_eq!(props.arch_name(), "sm_90");
+
+        // A name filling the whole buffer has no terminator.
+        prop.name = [b'x' as _; 256];
+        assert_eq!(DeviceProperties::from(&prop).name, "x".repeat(256));
+    }
+
+    #[test]
+    fn device_properties_reports_device() {
+        let props = device_properties(0).unwrap();
+        assert!(!props.name.is_empty());
+        assert!(props.major > 0);
+        assert!(props.total_global_mem > 0);
+        assert!(props.multi_processor_count > 0);
+        assert_eq!(props.warp_size, 32);
+        assert!(device_properties(i32::MAX).is_err());
+    }
+}
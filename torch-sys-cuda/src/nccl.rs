@@ -6,12 +6,15 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::HashMap;
+use std::ffi::CStr;
 use std::ffi::CString;
 use std::fmt;
 use std::fmt::Write;
 use std::hash::Hasher;
 use std::marker::PhantomData;
 use std::mem::MaybeUninit;
+use std::time::Duration;
 
 use fxhash::FxHasher32;
 use nccl_sys::*;
@@ -19,16 +22,20 @@ use serde::Deserialize;
 use serde::Serialize;
 use thiserror::Error;
 use torch_sys::CudaDevice;
+use torch_sys::DeviceIndex;
 use torch_sys::DeviceType;
+use torch_sys::Layout;
 use torch_sys::ScalarType;
 use torch_sys::Tensor;
 use torch_sys::TensorCell;
+use torch_sys::factory_empty;
 use torch_sys::factory_float_tensor;
 use torch_sys::is_float8_type;
 use torch_sys::suggest_memory_format;
 
-use crate::bridge::ffi::make_nccl_config;
+use crate::bridge::ffi::make_nccl_config_with;
 use crate::cuda::CudaError;
+use crate::cuda::Event;
 use crate::cuda::Stream;
 use crate::cuda::set_device;
 
@@ -90,6 +97,38 @@ pub enum NcclError {
 
     #[error("undefined tensor used for NCCL operation")]
     UndefinedTensor,
+
+    #[error("bitwise reduction {0:?} requires an integer tensor, got: {1:?}")]
+    InvalidBitwiseReduction(ReduceOp, ScalarType),
+
+    #[error("NCCL has no native {0:?} reduction for {1:?} tensors")]
+    UnsupportedReduceOp(ReduceOp, ScalarType),
+
+    #[error("{0:?} expects {1} tensor(s), got {2}")]
+    InvalidTensorCount(CollectiveOp, usize, usize),
+
+    #[error("rank {0} is out of range for world size {1}")]
+    InvalidRank(i32, i32),
+
+    #[error("a NCCL unique id is {UNIQUE_ID_BYTES} bytes, got: {0}")]
+    InvalidUniqueIdLength(usize),
+
+    #[error(
+        "tensor is on CUDA device {tensor:?} but the communicator is on CUDA device {communicator:?}"
+    )]
+    InvalidDeviceIndex {
+        tensor: DeviceIndex,
+        communicator: DeviceIndex,
+    },
+
+    #[error("communicator initialization did not complete within {0:?}")]
+    InitTimeout(Duration),
+
+    #[error("NCCL network name must not contain NUL bytes, got: {0:?}")]
+    InvalidNetName(String),
+
+    #[error("buffer is not registered with this communicator")]
+    UnknownRegistration,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -129,29 +168,62 @@ impl Default for NcclConfig {
     }
 }
 
-impl From<NcclConfig> for ncclConfig_t {
-    fn from(config: NcclConfig) -> Self {
-        let mut ret = make_nccl_config();
-        ret.blocking = config.blocking.into();
-        ret.cgaClusterSize = config.cga_cluster_size.into();
-        ret.minCTAs = config.min_ctas.into();
-        ret.maxCTAs = config.max_ctas.into();
-        if let Some(net_name) = config.net_name {
-            let c_string = CString::new(net_name)
-                .expect("failed to create CString")
-                .into_boxed_c_str();
-
-            // Just leak the string to avoid complicated ownership issues. I'm
-            // not aware of anywhere where we actually want to specify the
-            // network module name in configuration instead of letting nccl just
-            // choose it for us. If this happens + we are creating tons of
-            // config objects, we can revisit this.
-            let ptr = Box::leak(c_string).as_ptr();
-            ret.netName = ptr;
-        }
-        ret.splitShare = config.split_share.into();
-
-        ret
+/// An `ncclConfig_t` built from an [`NcclConfig`], together with the storage
+/// its `netName` points into. The raw config is only valid for as long as this
+/// value is alive, so keep it around for the duration of the NCCL call.
+pub(crate) struct RawNcclConfig {
+    inner: ncclConfig_t,
+    _net_name: Option<CString>,
+}
+
+impl RawNcclConfig {
+    pub(crate) fn new(config: &NcclConfig) -> Result<Self, NcclError> {
+        let mut inner = make_nccl_config_with(
+            config.blocking,
+            config.cga_cluster_size.into(),
+            config.min_ctas.into(),
+            config.max_ctas.into(),
+            config.split_share,
+        );
+        let net_name = config
+            .net_name
+            .as_ref()
+            .map(|name| {
+                CString::new(name.as_str()).map_err(|_| NcclError::InvalidNetName(name.clone()))
+            })
+            .transpose()?;
+        if let Some(net_name) = &net_name {
+            // The CString's heap buffer does not move when `net_name` does, so
+            // this pointer stays valid for as long as `self` is alive.
+            inner.netName = net_name.as_ptr();
+        }
+        Ok(Self {
+            inner,
+            _net_name: net_name,
+        })
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut ncclConfig_t {
+        &mut self.inner
+    }
+}
+
+impl fmt::Debug for RawNcclConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let net_name = if self.inner.netName.is_null() {
+            None
+        } else {
+            // SAFETY: non-null `netName` always points into `_net_name`.
+            Some(unsafe { CStr::from_ptr(self.inner.netName) })
+        };
+        f.debug_struct("RawNcclConfig")
+            .field("blocking", &self.inner.blocking)
+            .field("cga_cluster_size", &self.inner.cgaClusterSize)
+            .field("min_ctas", &self.inner.minCTAs)
+            .field("max_ctas", &self.inner.maxCTAs)
+            .field("net_name", &net_name)
+            .field("split_share", &self.inner.splitShare)
+            .finish()
     }
 }
 
@@ -198,6 +270,31 @@ pub fn group_end(_ticket: NcclGroupTicket) -> Result<(), NcclError> {
     Ok(())
 }
 
+/// The (major, minor, patch) version of the linked NCCL library.
+///
+/// Useful for confirming that the NCCL monarch links against matches the one
+/// PyTorch uses; mismatches can cause hangs.
+pub fn nccl_version() -> Result<(i32, i32, i32), RawNcclError> {
+    let mut code = 0;
+    // SAFETY: intended use of C function
+    nccl_check(unsafe { ncclGetVersion(&mut code) })?;
+    Ok(decode_nccl_version(code))
+}
+
+/// Decode a `NCCL_VERSION_CODE`. Releases before 2.9 use
+/// `major * 1000 + minor * 100 + patch`; later ones use
+/// `major * 10000 + minor * 100 + patch`.
+fn decode_nccl_version(code: i32) -> (i32, i32, i32) {
+    if code < 10000 {
+        (code / 1000, code % 1000 / 100, code % 100)
+    } else {
+        (code / 10000, code % 10000 / 100, code % 100)
+    }
+}
+
+/// Size in bytes of a `ncclUniqueId`.
+pub const UNIQUE_ID_BYTES: usize = 128;
+
 /// Binding for `ncclUniqueId`.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct UniqueId {
@@ -235,6 +332,33 @@ impl UniqueId {
         };
         Ok(Self { inner })
     }
+
+    /// Reconstruct a `UniqueId` from bytes produced by [`UniqueId::as_bytes`],
+    /// e.g. an id broadcast by another framework over its own transport.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NcclError> {
+        let bytes: &[u8; UNIQUE_ID_BYTES] = bytes
+            .try_into()
+            .map_err(|_| NcclError::InvalidUniqueIdLength(bytes.len()))?;
+        let mut inner = ncclUniqueId {
+            internal: [0; UNIQUE_ID_BYTES],
+        };
+        for (dst, src) in inner.internal.iter_mut().zip(bytes) {
+            *dst = *src as std::os::raw::c_char;
+        }
+        Ok(Self { inner })
+    }
+
+    /// The raw bytes of this id, suitable for exchanging out-of-band.
+    pub fn as_bytes(&self) -> &[u8] {
+        // SAFETY: `c_char` and `u8` have the same size and alignment, and the
+        // slice borrows from `self`.
+        unsafe {
+            std::slice::from_raw_parts(
+                self.inner.internal.as_ptr() as *const u8,
+                self.inner.internal.len(),
+            )
+        }
+    }
 }
 
 /// Rust version of `ncclDataType_t`.
@@ -252,6 +376,18 @@ pub enum DataType {
     Bfloat16 = 9,
 }
 
+impl DataType {
+    /// The size in bytes of a single element of this type.
+    pub fn size(&self) -> usize {
+        match self {
+            DataType::Int8 | DataType::Uint8 => 1,
+            DataType::Float16 | DataType::Bfloat16 => 2,
+            DataType::Int32 | DataType::Uint32 | DataType::Float32 => 4,
+            DataType::Int64 | DataType::Uint64 | DataType::Float64 => 8,
+        }
+    }
+}
+
 impl From<DataType> for ncclDataType_t {
     fn from(data_type: DataType) -> Self {
         Self(data_type as std::os::raw::c_uint)
@@ -281,7 +417,11 @@ impl TryFrom<ScalarType> for DataType {
     }
 }
 
-/// Rust version of `ncclRedOp_t`.
+/// Rust version of `ncclRedOp_t`, extended with bitwise reductions.
+///
+/// NCCL has no native bitwise reductions. For `Bool` tensors, `BitOr` and
+/// `BitAnd` map to `Max` and `Min`. [`Communicator::all_reduce`] emulates the
+/// remaining integer cases; other collectives reject them.
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum ReduceOp {
     Sum = 0,
@@ -289,21 +429,149 @@ pub enum ReduceOp {
     Max = 2,
     Min = 3,
     Avg = 4,
+    BitOr = 5,
+    BitAnd = 6,
+    BitXor = 7,
+}
+
+impl ReduceOp {
+    /// Whether this is one of the bitwise reductions.
+    pub fn is_bitwise(&self) -> bool {
+        matches!(self, ReduceOp::BitOr | ReduceOp::BitAnd | ReduceOp::BitXor)
+    }
+}
+
+fn is_integer_type(scalar_type: ScalarType) -> bool {
+    matches!(
+        scalar_type,
+        ScalarType::Bool
+            | ScalarType::Byte
+            | ScalarType::Char
+            | ScalarType::Short
+            | ScalarType::Int
+            | ScalarType::Long
+    )
 }
 
-impl From<ReduceOp> for ncclRedOp_t {
-    fn from(reduce_op: ReduceOp) -> Self {
-        Self(reduce_op as std::os::raw::c_uint)
+/// Map `reduce_op` to the NCCL reduction for tensors of `scalar_type`.
+/// Returns `None` for bitwise reductions that NCCL cannot express.
+fn nccl_reduce_op(
+    reduce_op: ReduceOp,
+    scalar_type: ScalarType,
+) -> Result<Option<ncclRedOp_t>, NcclError> {
+    if !reduce_op.is_bitwise() {
+        return Ok(Some(ncclRedOp_t(reduce_op as std::os::raw::c_uint)));
     }
+    if !is_integer_type(scalar_type) {
+        return Err(NcclError::InvalidBitwiseReduction(reduce_op, scalar_type));
+    }
+    Ok(match (reduce_op, scalar_type) {
+        (ReduceOp::BitOr, ScalarType::Bool) => Some(ncclRedOp_t(ReduceOp::Max as _)),
+        (ReduceOp::BitAnd, ScalarType::Bool) => Some(ncclRedOp_t(ReduceOp::Min as _)),
+        _ => None,
+    })
 }
 
-fn check_tensor(tensor: &Tensor, is_p2p: bool) -> Result<(), NcclError> {
+/// Fold the equally sized chunks of `gathered` into `out` with a bitwise
+/// reduction. Working on bytes gives the same result for any integer width.
+fn fold_bitwise(reduce_op: ReduceOp, gathered: &[u8], out: &mut [u8]) {
+    let mut chunks = gathered.chunks_exact(out.len());
+    out.copy_from_slice(chunks.next().expect("at least one chunk"));
+    for chunk in chunks {
+        for (acc, byte) in out.iter_mut().zip(chunk) {
+            match reduce_op {
+                ReduceOp::BitOr => *acc |= byte,
+                ReduceOp::BitAnd => *acc &= byte,
+                ReduceOp::BitXor => *acc ^= byte,
+                _ => unreachable!("not a bitwise reduction: {:?}", reduce_op),
+            }
+        }
+    }
+}
+
+/// A collective operation and its non-tensor arguments, for driving a
+/// [`Communicator`] generically through [`Communicator::execute`].
+///
+/// The tensors passed alongside an op follow the argument order of the
+/// corresponding `Communicator` method, with outputs before inputs:
+///
+/// | op                    | tensors                           |
+/// |-----------------------|-----------------------------------|
+/// | `AllReduce`           | `[tensor]`                        |
+/// | `Broadcast`           | `[tensor]`                        |
+/// | `Reduce`              | `[tensor]`                        |
+/// | `AllGather`           | `[output_0, .., output_n, input]` |
+/// | `AllGatherIntoTensor` | `[output, input]`                 |
+/// | `ReduceScatter`       | `[output, input]`                 |
+/// | `AllToAll`            | `[output, input]`                 |
+/// | `Send`, `Recv`        | `[tensor]`                        |
+/// | `Barrier`             | `[]`                              |
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CollectiveOp {
+    AllReduce { op: ReduceOp },
+    Broadcast { root: i32 },
+    Reduce { op: ReduceOp, root: i32 },
+    AllGather,
+    AllGatherIntoTensor,
+    ReduceScatter { op: ReduceOp },
+    AllToAll,
+    Send { dst: i32 },
+    Recv { src: i32 },
+    Barrier,
+}
+
+impl CollectiveOp {
+    /// The number of tensors this op takes in a communicator of `world_size`.
+    pub fn num_tensors(&self, world_size: i32) -> usize {
+        match self {
+            CollectiveOp::AllReduce { .. }
+            | CollectiveOp::Broadcast { .. }
+            | CollectiveOp::Reduce { .. }
+            | CollectiveOp::Send { .. }
+            | CollectiveOp::Recv { .. } => 1,
+            CollectiveOp::AllGather => world_size as usize + 1,
+            CollectiveOp::AllGatherIntoTensor
+            | CollectiveOp::ReduceScatter { .. }
+            | CollectiveOp::AllToAll => 2,
+            CollectiveOp::Barrier => 0,
+        }
+    }
+
+    /// Check the tensor count and any rank argument against `world_size`.
+    fn validate(&self, world_size: i32, num_tensors: usize) -> Result<(), NcclError> {
+        let expected = self.num_tensors(world_size);
+        if num_tensors != expected {
+            return Err(NcclError::InvalidTensorCount(*self, expected, num_tensors));
+        }
+        match *self {
+            CollectiveOp::Broadcast { root: rank }
+            | CollectiveOp::Reduce { root: rank, .. }
+            | CollectiveOp::Send { dst: rank }
+            | CollectiveOp::Recv { src: rank }
+                if !(0..world_size).contains(&rank) =>
+            {
+                Err(NcclError::InvalidRank(rank, world_size))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+fn check_tensor(tensor: &Tensor, device: CudaDevice, is_p2p: bool) -> Result<(), NcclError> {
     if !tensor.defined() {
         return Err(NcclError::UndefinedTensor);
     }
     if !tensor.is_cuda() {
         return Err(NcclError::InvalidDevice(tensor.device().device_type()));
     }
+    // NCCL does not check this itself; a tensor on another GPU hangs or
+    // corrupts memory instead of failing.
+    if tensor.device().index() != device.index() {
+        return Err(NcclError::InvalidDeviceIndex {
+            tensor: tensor.device().index(),
+            communicator: device.index(),
+        });
+    }
     if tensor.is_sparse() {
         return Err(NcclError::InvalidSparseTensor);
     }
@@ -329,6 +597,25 @@ pub struct Communicator {
     global_world_size: i32,
     global_rank: i32,
     device: CudaDevice,
+    // Buffers registered with `ncclCommRegister`, keyed by (data_ptr, nbytes).
+    registrations: HashMap<(usize, usize), Registration>,
+}
+
+/// A cached `ncclCommRegister` handle and the number of outstanding
+/// [`RegisteredBuffer`]s for it.
+#[derive(Debug)]
+struct Registration {
+    handle: *mut std::ffi::c_void,
+    refs: usize,
+}
+
+/// Memory registered with a [`Communicator`] by [`Communicator::register`].
+/// Give it back with [`Communicator::deregister`] once the memory is no longer
+/// used in collectives.
+#[derive(Debug, PartialEq, Eq)]
+pub struct RegisteredBuffer {
+    key: (usize, usize),
+    handle: *mut std::ffi::c_void,
 }
 
 /// SAFETY: `ncclComm_t` is okay to access from multiple threads, but each
@@ -361,6 +648,19 @@ impl Communicator {
         rank: i32,
     ) -> Result<Self, NcclError> {
         set_device(device)?;
+        if std::env::var("MONARCH_DEBUG_NCCL").is_ok() {
+            match nccl_version() {
+                Ok((major, minor, patch)) => tracing::info!(
+                    "creating NCCL communicator (rank {} of {}) with NCCL {}.{}.{}",
+                    rank,
+                    world_size,
+                    major,
+                    minor,
+                    patch
+                ),
+                Err(err) => tracing::warn!("failed to query NCCL version: {}", err),
+            }
+        }
         let mut inner = MaybeUninit::uninit();
         // SAFETY: intended use of C function
         let inner = unsafe {
@@ -379,6 +679,69 @@ impl Communicator {
             global_rank: rank,
             global_world_size: world_size,
             device,
+            registrations: HashMap::new(),
+        })
+    }
+
+    /// Like [`Communicator::new`], but gives up if the communicator is not
+    /// ready within `timeout`, e.g. because a peer never joined.
+    ///
+    /// The communicator is initialized in non-blocking mode, so operations on
+    /// it may return [`NcclStatus::InProgress`]. On timeout or error the
+    /// partially initialized communicator is aborted.
+    pub fn new_with_timeout(
+        device: CudaDevice,
+        world_size: i32,
+        unique_id: UniqueId,
+        rank: i32,
+        timeout: Duration,
+    ) -> Result<Self, NcclError> {
+        set_device(device)?;
+        let mut config = RawNcclConfig::new(&NcclConfig {
+            blocking: false,
+            ..Default::default()
+        })?;
+        let mut inner = MaybeUninit::uninit();
+        // SAFETY: intended use of C function
+        let inner = unsafe {
+            nccl_check(ncclCommInitRankConfig(
+                inner.as_mut_ptr(),
+                world_size,
+                unique_id.inner,
+                rank,
+                config.as_mut_ptr(),
+            ))?;
+            inner.assume_init()
+        };
+
+        let start = std::time::Instant::now();
+        loop {
+            let mut state = ncclResult_t(0);
+            // SAFETY: intended use of C function
+            let status = nccl_check(unsafe { ncclCommGetAsyncError(inner, &mut state) })
+                .and_then(|_| nccl_check(state));
+            let err = match status {
+                Ok(NcclStatus::Success) => break,
+                Ok(NcclStatus::InProgress) if start.elapsed() < timeout => {
+                    std::thread::sleep(Duration::from_millis(1));
+                    continue;
+                }
+                Ok(NcclStatus::InProgress) => NcclError::InitTimeout(timeout),
+                Err(err) => err.into(),
+            };
+            // SAFETY: intended use of C function; `inner` is not used afterwards.
+            unsafe { ncclCommAbort(inner) };
+            return Err(err);
+        }
+
+        Ok(Self {
+            inner,
+            world_size,
+            rank,
+            global_rank: rank,
+            global_world_size: world_size,
+            device,
+            registrations: HashMap::new(),
         })
     }
 
@@ -412,34 +775,22 @@ impl Communicator {
             Err(_) => NCCL_SPLIT_NOCOLOR,
         };
 
-        let config = config.map(ncclConfig_t::from);
+        let mut config = config.as_ref().map(RawNcclConfig::new).transpose()?;
         let mut new = MaybeUninit::uninit();
 
-        // SAFETY: intended use of C function
+        let config_ptr = config
+            .as_mut()
+            .map_or(std::ptr::null_mut(), RawNcclConfig::as_mut_ptr);
+
+        // SAFETY: intended use of C function; `config` outlives the call.
         let new = unsafe {
-            // This rather awkward duplication is intentional; we are passing in
-            // `config` as a pointer, which is only guaranteed to be valid for
-            // the duration of `Some(mut config)` match arm.
-            match config {
-                Some(mut config) => {
-                    nccl_check(ncclCommSplit(
-                        self.inner,
-                        color,
-                        self.rank,
-                        new.as_mut_ptr(),
-                        &mut config,
-                    ))?;
-                }
-                None => {
-                    nccl_check(ncclCommSplit(
-                        self.inner,
-                        color,
-                        self.rank,
-                        new.as_mut_ptr(),
-                        std::ptr::null_mut(),
-                    ))?;
-                }
-            }
+            nccl_check(ncclCommSplit(
+                self.inner,
+                color,
+                self.rank,
+                new.as_mut_ptr(),
+                config_ptr,
+            ))?;
             new.assume_init()
         };
 
@@ -453,10 +804,65 @@ impl Communicator {
                 global_rank: self.global_rank,
                 global_world_size: self.global_world_size,
                 device: self.device,
+                registrations: HashMap::new(),
             })),
         }
     }
 
+    /// Register `tensor`'s memory with NCCL, which lets collectives on it avoid
+    /// internal copies. The memory must stay allocated until it is deregistered.
+    ///
+    /// Registrations are cached by `(data_ptr, nbytes)`: registering the same
+    /// memory again, e.g. on every training iteration, reuses the existing
+    /// handle and only counts another user of it.
+    pub fn register(&mut self, tensor: &TensorCell) -> Result<RegisteredBuffer, NcclError> {
+        let tensor = tensor.borrow();
+        check_tensor(&tensor, self.device, false)?;
+        // SAFETY: the pointer is only passed to NCCL, never dereferenced here.
+        let data_ptr = unsafe { tensor.data_ptr() };
+        let key = (data_ptr as usize, tensor.nbytes());
+        if let Some(registration) = self.registrations.get_mut(&key) {
+            registration.refs += 1;
+            return Ok(RegisteredBuffer {
+                key,
+                handle: registration.handle,
+            });
+        }
+
+        let mut handle = std::ptr::null_mut();
+        // SAFETY: intended use of C function; the range covers the tensor's data.
+        nccl_check(unsafe {
+            ncclCommRegister(self.inner, data_ptr as *mut _, key.1, &mut handle)
+        })?;
+        self.registrations
+            .insert(key, Registration { handle, refs: 1 });
+        Ok(RegisteredBuffer { key, handle })
+    }
+
+    /// Release a registration made by [`Communicator::register`]. The memory is
+    /// deregistered from NCCL when its last registration is released.
+    pub fn deregister(&mut self, buffer: RegisteredBuffer) -> Result<(), NcclError> {
+        let Some(registration) = self.registrations.get_mut(&buffer.key) else {
+            return Err(NcclError::UnknownRegistration);
+        };
+        if registration.handle != buffer.handle {
+            return Err(NcclError::UnknownRegistration);
+        }
+        registration.refs -= 1;
+        if registration.refs == 0 {
+            self.registrations.remove(&buffer.key);
+            // SAFETY: intended use of C function; `handle` came from `ncclCommRegister`
+            // on this communicator and has no other users.
+            nccl_check(unsafe { ncclCommDeregister(self.inner, buffer.handle) })?;
+        }
+        Ok(())
+    }
+
+    /// Number of distinct memory ranges currently registered with NCCL.
+    pub fn num_registered_buffers(&self) -> usize {
+        self.registrations.len()
+    }
+
     /// Reduce the tensor data across all ranks, with each rank receiving the
     /// final result in-place.
     ///
@@ -467,13 +873,18 @@ impl Communicator {
         reduce_op: ReduceOp,
         stream: &Stream,
     ) -> Result<NcclStatus, NcclError> {
-        let tensor = tensor.borrow_mut();
+        let tensor_cell = tensor;
+        let tensor = tensor_cell.borrow_mut();
         let data_type: DataType = tensor.scalar_type().try_into()?;
 
-        check_tensor(&tensor, false)?;
+        check_tensor(&tensor, self.device, false)?;
         if is_float8_type(tensor.scalar_type()) {
             return Err(NcclError::Float8Reduction);
         }
+        let Some(nccl_op) = nccl_reduce_op(reduce_op, tensor.scalar_type())? else {
+            drop(tensor);
+            return self.all_reduce_bitwise(tensor_cell, reduce_op, stream);
+        };
         // SAFETY: intended use of C function
         unsafe {
             Ok(nccl_check(ncclAllReduce(
@@ -481,13 +892,81 @@ impl Communicator {
                 tensor.mut_data_ptr(),
                 tensor.numel() as usize,
                 data_type.into(),
-                reduce_op.into(),
+                nccl_op,
                 self.inner,
                 stream.stream(),
             ))?)
         }
     }
 
+    /// Like [`Communicator::all_reduce`], but also returns the GPU time the
+    /// collective took on `stream`.
+    ///
+    /// Timing events are recorded around the collective and the end event is
+    /// waited on, so this blocks the calling thread until the collective
+    /// completes. Use the untimed variant when the measurement isn't needed.
+    pub fn all_reduce_timed(
+        &mut self,
+        tensor: &TensorCell,
+        reduce_op: ReduceOp,
+        stream: &Stream,
+    ) -> Result<(NcclStatus, Duration), NcclError> {
+        let mut start = Event::new_with_timing();
+        let mut end = Event::new_with_timing();
+        start.record(Some(stream));
+        let status = self.all_reduce(tensor, reduce_op, stream)?;
+        end.record(Some(stream));
+        end.synchronize();
+        Ok((status, start.elapsed_time(&end)))
+    }
+
+    /// Bitwise all-reduce for integer tensors that NCCL cannot reduce natively.
+    /// Gathers every rank's input, folds it on the host and copies the result
+    /// back on `stream`. This synchronizes `stream`, so it is only suitable for
+    /// small tensors such as bitmasks.
+    fn all_reduce_bitwise(
+        &mut self,
+        tensor_cell: &TensorCell,
+        reduce_op: ReduceOp,
+        stream: &Stream,
+    ) -> Result<NcclStatus, NcclError> {
+        let gathered_cell = {
+            let tensor = tensor_cell.borrow();
+            if tensor.numel() == 0 {
+                return Ok(NcclStatus::Success);
+            }
+            TensorCell::new(factory_empty(
+                &[self.world_size as i64 * tensor.numel()],
+                tensor.scalar_type(),
+                Layout::Strided,
+                tensor.device(),
+            ))
+        };
+        self.all_gather_into_tensor(&gathered_cell, tensor_cell, stream)?;
+        stream.synchronize();
+
+        let mut tensor = tensor_cell.borrow_mut();
+        let gathered = gathered_cell.borrow().cpu();
+        let result = tensor.cpu();
+        let nbytes = tensor.nbytes();
+        // SAFETY: both are dense host tensors; `gathered` holds `world_size`
+        // chunks of `nbytes` each and `result` holds `nbytes`.
+        unsafe {
+            let gathered = std::slice::from_raw_parts(
+                gathered.data_ptr() as *const u8,
+                nbytes * self.world_size as usize,
+            );
+            let result = std::slice::from_raw_parts_mut(result.mut_data_ptr() as *mut u8, nbytes);
+            fold_bitwise(reduce_op, gathered, result);
+        }
+
+        let current_stream = Stream::get_current_stream();
+        Stream::set_current_stream(stream);
+        tensor.copy_(&result);
+        Stream::set_current_stream(&current_stream);
+        Ok(NcclStatus::Success)
+    }
+
     /// Broadcast the tensor data on the `root` rank to all the others.
     ///
     /// See `torch.distributed.broadcast` for more detailed documentation.
@@ -498,7 +977,7 @@ impl Communicator {
         stream: &Stream,
     ) -> Result<NcclStatus, NcclError> {
         let tensor = tensor.borrow_mut();
-        check_tensor(&tensor, false)?;
+        check_tensor(&tensor, self.device, false)?;
         let data_type: DataType = tensor.scalar_type().try_into()?;
         // SAFETY: intended use of C function
         unsafe {
@@ -526,11 +1005,14 @@ impl Communicator {
         stream: &Stream,
     ) -> Result<NcclStatus, NcclError> {
         let tensor = tensor.borrow_mut();
-        check_tensor(&tensor, false)?;
+        check_tensor(&tensor, self.device, false)?;
         if is_float8_type(tensor.scalar_type()) {
             return Err(NcclError::Float8Reduction);
         }
         let data_type: DataType = tensor.scalar_type().try_into()?;
+        let nccl_op = nccl_reduce_op(reduce_op, tensor.scalar_type())?.ok_or(
+            NcclError::UnsupportedReduceOp(reduce_op, tensor.scalar_type()),
+        )?;
         // SAFETY: intended use of C function
         unsafe {
             Ok(nccl_check(ncclReduce(
@@ -538,7 +1020,7 @@ impl Communicator {
                 tensor.mut_data_ptr(),
                 tensor.numel() as usize,
                 data_type.into(),
-                reduce_op.into(),
+                nccl_op,
                 root,
                 self.inner,
                 stream.stream(),
@@ -560,7 +1042,7 @@ impl Communicator {
             .map(|t| t.borrow_mut())
             .collect::<Vec<_>>();
         let input = input_cell.borrow();
-        check_tensor(&input, false)?;
+        check_tensor(&input, self.device, false)?;
         let output_type = output[0].scalar_type();
         let output_numel: i64 = output.iter().map(|t| t.numel()).sum();
         for t in &output {
@@ -631,8 +1113,8 @@ impl Communicator {
         };
         // SAFETY: we either borrowed above or borrowed an alias
         let input = unsafe { input_cell.get_unchecked() };
-        check_tensor(&output, false)?;
-        check_tensor(input, false)?;
+        check_tensor(&output, self.device, false)?;
+        check_tensor(input, self.device, false)?;
         if input.scalar_type() != output.scalar_type() {
             return Err(NcclError::TypeMismatch);
         }
@@ -675,8 +1157,8 @@ impl Communicator {
         // SAFETY: we either borrowed above or borrowed an alias
         let input = unsafe { input_cell.get_unchecked() }; // SAFETY: intended use of C function
 
-        check_tensor(&output, false)?;
-        check_tensor(input, false)?;
+        check_tensor(&output, self.device, false)?;
+        check_tensor(input, self.device, false)?;
         if input.scalar_type() != output.scalar_type() {
             return Err(NcclError::TypeMismatch);
         }
@@ -688,6 +1170,9 @@ impl Communicator {
         }
 
         let data_type: DataType = input.scalar_type().try_into()?;
+        let nccl_op = nccl_reduce_op(reduce_op, input.scalar_type())?.ok_or(
+            NcclError::UnsupportedReduceOp(reduce_op, input.scalar_type()),
+        )?;
         // SAFETY: intended use of C function
         unsafe {
             Ok(nccl_check(ncclReduceScatter(
@@ -695,13 +1180,89 @@ impl Communicator {
                 output.mut_data_ptr(),
                 output.numel() as usize,
                 data_type.into(),
-                reduce_op.into(),
+                nccl_op,
                 self.inner,
                 stream.stream(),
             ))?)
         }
     }
 
+    /// Reduce a list of tensors across all ranks, scattering the result so
+    /// that rank `i` receives the reduction of every rank's `input_cells[i]`.
+    ///
+    /// See `torch.distributed.reduce_scatter` for more detailed documentation.
+    pub fn reduce_scatter(
+        &mut self,
+        output_cell: &TensorCell,
+        input_cells: &[TensorCell],
+        reduce_op: ReduceOp,
+        stream: &Stream,
+    ) -> Result<NcclStatus, NcclError> {
+        if input_cells.len() != self.world_size as usize {
+            return Err(NcclError::InvalidTensorCount(
+                CollectiveOp::ReduceScatter { op: reduce_op },
+                self.world_size as usize,
+                input_cells.len(),
+            ));
+        }
+        let output = output_cell.borrow_mut();
+        let _input_borrows = input_cells
+            .iter()
+            .filter(|t| !t.aliases(output_cell))
+            .map(|t| t.borrow())
+            .collect::<Vec<_>>();
+        // SAFETY: we either borrowed above or borrowed an alias
+        let inputs = input_cells
+            .iter()
+            .map(|t| unsafe { t.get_unchecked() })
+            .collect::<Vec<_>>();
+
+        check_tensor(&output, self.device, false)?;
+        for input in &inputs {
+            check_tensor(input, self.device, false)?;
+            if input.scalar_type() != output.scalar_type() {
+                return Err(NcclError::TypeMismatch);
+            }
+            if input.numel() != output.numel() {
+                return Err(NcclError::InputSizeMismatch);
+            }
+        }
+        if is_float8_type(output.scalar_type()) {
+            return Err(NcclError::Float8Reduction);
+        }
+
+        let data_type: DataType = output.scalar_type().try_into()?;
+        let nccl_op = nccl_reduce_op(reduce_op, output.scalar_type())?.ok_or(
+            NcclError::UnsupportedReduceOp(reduce_op, output.scalar_type()),
+        )?;
+        // Each rank roots one reduce of its own slot; grouping them lets NCCL
+        // schedule the whole list as a single collective.
+        // SAFETY: intended use of C function
+        unsafe {
+            nccl_check(ncclGroupStart())?;
+            for (i, input) in inputs.iter().enumerate() {
+                let root = i as i32;
+                let recv_ptr = if root == self.rank {
+                    output.mut_data_ptr()
+                } else {
+                    std::ptr::null_mut()
+                };
+                nccl_check(ncclReduce(
+                    input.data_ptr(),
+                    recv_ptr,
+                    output.numel() as usize,
+                    data_type.into(),
+                    nccl_op,
+                    root,
+                    self.inner,
+                    stream.stream(),
+                ))?;
+            }
+            nccl_check(ncclGroupEnd())?;
+        }
+        Ok(NcclStatus::Success)
+    }
+
     /// Send a tensor to the rank `dst`.
     pub fn send(
         &mut self,
@@ -712,7 +1273,7 @@ impl Communicator {
         let tensor = tensor_cell.borrow();
         let data_type: DataType = tensor.scalar_type().try_into()?;
 
-        check_tensor(&tensor, true)?;
+        check_tensor(&tensor, self.device, true)?;
 
         // SAFETY: intended use of C function
         unsafe {
@@ -737,7 +1298,7 @@ impl Communicator {
         let tensor = tensor_cell.borrow_mut();
         let data_type: DataType = tensor.scalar_type().try_into()?;
 
-        check_tensor(&tensor, true)?;
+        check_tensor(&tensor, self.device, true)?;
 
         // SAFETY: intended use of C function
         unsafe {
@@ -772,15 +1333,15 @@ impl Communicator {
         // SAFETY: we either borrowed above or borrowed an alias
         let input = unsafe { input_cell.get_unchecked() };
 
-        check_tensor(&output, false)?;
-        check_tensor(input, false)?;
+        check_tensor(&output, self.device, false)?;
+        check_tensor(input, self.device, false)?;
         if input.scalar_type() != output.scalar_type() {
             return Err(NcclError::TypeMismatch);
         }
 
         let data_type: DataType = input.scalar_type().try_into()?;
         let count = input.numel() as usize / self.world_size as usize;
-        let rank_stride = input.nbytes() as isize / self.world_size as isize;
+        let rank_stride = (count * data_type.size()) as isize;
         // SAFETY: intended use of C functions
         unsafe {
             let send_buff = input.data_ptr();
@@ -811,6 +1372,38 @@ impl Communicator {
         Ok(NcclStatus::Success)
     }
 
+    /// Run `op` on `tensors`, dispatching to the matching collective method.
+    ///
+    /// See [`CollectiveOp`] for the tensors each op expects. The tensor count
+    /// and any rank argument are validated before anything is enqueued.
+    pub fn execute(
+        &mut self,
+        op: CollectiveOp,
+        tensors: &[TensorCell],
+        stream: &Stream,
+    ) -> Result<NcclStatus, NcclError> {
+        op.validate(self.world_size, tensors.len())?;
+        match op {
+            CollectiveOp::AllReduce { op } => self.all_reduce(&tensors[0], op, stream),
+            CollectiveOp::Broadcast { root } => self.broadcast(&tensors[0], root, stream),
+            CollectiveOp::Reduce { op, root } => self.reduce(&tensors[0], op, root, stream),
+            CollectiveOp::AllGather => {
+                let (input, outputs) = tensors.split_last().unwrap();
+                self.all_gather(outputs, input, stream)
+            }
+            CollectiveOp::AllGatherIntoTensor => {
+                self.all_gather_into_tensor(&tensors[0], &tensors[1], stream)
+            }
+            CollectiveOp::ReduceScatter { op } => {
+                self.reduce_scatter_tensor(&tensors[0], &tensors[1], op, stream)
+            }
+            CollectiveOp::AllToAll => self.all_to_all_single(&tensors[0], &tensors[1], stream),
+            CollectiveOp::Send { dst } => self.send(&tensors[0], dst, stream),
+            CollectiveOp::Recv { src } => self.recv(&tensors[0], src, stream),
+            CollectiveOp::Barrier => self.barrier(stream),
+        }
+    }
+
     /// Synchronize all ranks.
     ///
     /// See `torch.distributed.barrier` for more detailed documentation.
@@ -827,7 +1420,7 @@ impl Communicator {
                 tensor.mut_data_ptr(),
                 tensor.numel() as usize,
                 data_type.into(),
-                ReduceOp::Sum.into(),
+                ncclRedOp_t(ReduceOp::Sum as _),
                 self.inner,
                 stream.stream(),
             ))?)
@@ -838,6 +1431,7 @@ impl Communicator {
 #[cfg(test)]
 mod tests {
     use torch_sys::CudaDevice;
+    use torch_sys::Device;
     use torch_sys::DeviceIndex;
     use torch_sys::factory_float_tensor;
     use torch_sys::testing::allclose;
@@ -872,6 +1466,354 @@ mod tests {
         }
     }
 
+    #[test]
+    fn register_reuses_cached_handle() {
+        let device = CudaDevice::new(DeviceIndex(0));
+        set_device(device).unwrap();
+        let mut comm = Communicator::new(device, 1, UniqueId::new().unwrap(), 0).unwrap();
+        let cell = TensorCell::new(cuda_full(&[1024], 1.0));
+
+        let first = comm.register(&cell).unwrap();
+        let second = comm.register(&cell).unwrap();
+        assert_eq!(first, second);
+        assert_eq!(comm.num_registered_buffers(), 1);
+
+        // Only the last release deregisters the memory.
+        comm.deregister(first).unwrap();
+        assert_eq!(comm.num_registered_buffers(), 1);
+        comm.deregister(second).unwrap();
+        assert_eq!(comm.num_registered_buffers(), 0);
+    }
+
+    #[test]
+    fn tensor_on_wrong_device() {
+        let device = CudaDevice::new(DeviceIndex(0));
+        let mut comm = Communicator::new(device, 1, UniqueId::new().unwrap(), 0).unwrap();
+        let stream = Stream::new();
+
+        set_device(CudaDevice::new(DeviceIndex(1))).unwrap();
+        let cell = TensorCell::new(cuda_full(&[2, 2], 1.0));
+        let err = comm.all_reduce(&cell, ReduceOp::Sum, &stream).unwrap_err();
+        assert!(matches!(
+            err,
+            NcclError::InvalidDeviceIndex {
+                tensor: DeviceIndex(1),
+                communicator: DeviceIndex(0),
+            }
+        ));
+        assert!(err.to_string().contains("device DeviceIndex(1)"));
+    }
+
+    #[test]
+    fn new_with_timeout_incomplete_world() {
+        // Only rank 0 of a world of 2 ever joins.
+        let unique_id = UniqueId::new().unwrap();
+        let device = CudaDevice::new(DeviceIndex(0));
+        let result =
+            Communicator::new_with_timeout(device, 2, unique_id, 0, Duration::from_secs(2));
+        assert!(matches!(result, Err(NcclError::InitTimeout(_))));
+    }
+
+    #[test]
+    fn nccl_version_is_nonzero() {
+        let (major, minor, patch) = nccl_version().unwrap();
+        assert!(
+            major >= 2,
+            "unexpected NCCL version {major}.{minor}.{patch}"
+        );
+        assert!(minor >= 0 && patch >= 0);
+    }
+
+    #[test]
+    fn decode_nccl_version_codes() {
+        assert_eq!(decode_nccl_version(2708), (2, 7, 8));
+        assert_eq!(decode_nccl_version(21903), (2, 19, 3));
+        assert_eq!(decode_nccl_version(22703), (2, 27, 3));
+    }
+
+    #[test]
+    fn data_type_size_matches_scalar_type_size() {
+        for scalar_type in [
+            ScalarType::Char,
+            ScalarType::Byte,
+            ScalarType::Half,
+            ScalarType::Float,
+            ScalarType::Double,
+            ScalarType::Int,
+            ScalarType::Long,
+            ScalarType::BFloat16,
+            ScalarType::Float8_e4m3fn,
+            ScalarType::Float8_e5m2,
+        ] {
+            let data_type: DataType = scalar_type.try_into().unwrap();
+            assert_eq!(
+                data_type.size(),
+                torch_sys::scalar_type_size(scalar_type),
+                "{:?}",
+                scalar_type
+            );
+        }
+    }
+
+    #[test]
+    fn raw_nccl_config_from_config() {
+        let config = NcclConfig {
+            blocking: false,
+            cga_cluster_size: 2,
+            min_ctas: 4,
+            max_ctas: 16,
+            net_name: Some("IB".to_string()),
+            split_share: true,
+        };
+        let raw = RawNcclConfig::new(&config).unwrap();
+        assert_eq!(
+            format!("{:?}", raw),
+            "RawNcclConfig { blocking: 0, cga_cluster_size: 2, min_ctas: 4, max_ctas: 16, \
+             net_name: Some(\"IB\"), split_share: 1 }"
+        );
+
+        let raw = RawNcclConfig::new(&NcclConfig::default()).unwrap();
+        assert!(format!("{:?}", raw).contains("net_name: None"));
+
+        let err = RawNcclConfig::new(&NcclConfig {
+            net_name: Some("bad\0name".to_string()),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(matches!(err, NcclError::InvalidNetName(_)));
+    }
+
+    #[test]
+    fn unique_id_bytes_round_trip() {
+        let unique_id = UniqueId::new().unwrap();
+        let bytes = unique_id.as_bytes().to_vec();
+        assert_eq!(bytes.len(), UNIQUE_ID_BYTES);
+        let restored = UniqueId::from_bytes(&bytes).unwrap();
+        assert_eq!(restored.as_bytes(), &bytes[..]);
+        assert_eq!(format!("{:?}", restored), format!("{:?}", unique_id));
+
+        let pattern: Vec<u8> = (0..UNIQUE_ID_BYTES).map(|b| b as u8 ^ 0xa5).collect();
+        assert_eq!(
+            UniqueId::from_bytes(&pattern).unwrap().as_bytes(),
+            &pattern[..]
+        );
+
+        assert!(matches!(
+            UniqueId::from_bytes(&bytes[..64]),
+            Err(NcclError::InvalidUniqueIdLength(64))
+        ));
+        assert!(matches!(
+            UniqueId::from_bytes(&[0; UNIQUE_ID_BYTES + 1]),
+            Err(NcclError::InvalidUniqueIdLength(129))
+        ));
+    }
+
+    #[test]
+    fn all_reduce_timed() {
+        let unique_id = UniqueId::new().unwrap();
+        let mut handles = Vec::new();
+        for i in 0..2 {
+            let unique_id = unique_id.clone();
+            handles.push(std::thread::spawn(move || {
+                let device = CudaDevice::new(DeviceIndex(i));
+                set_device(device).unwrap();
+                let stream = Stream::new();
+                let cell = TensorCell::new(cuda_full(&[1024, 1024], 1.0));
+
+                let mut comm = Communicator::new(device, 2, unique_id, i.into()).unwrap();
+                let (_, elapsed) = comm
+                    .all_reduce_timed(&cell, ReduceOp::Sum, &stream)
+                    .unwrap();
+                assert!(allclose(&cell.borrow(), &cuda_full(&[1024, 1024], 2.0)).unwrap());
+                // The collective did real work, but a 4 MiB all-reduce between
+                // two local GPUs should never take anywhere near this long.
+                assert!(elapsed > Duration::ZERO);
+                assert!(elapsed < Duration::from_secs(30), "elapsed: {:?}", elapsed);
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    fn int32_tensor(values: &[i32], device: Device) -> Tensor {
+        let cpu = factory_empty(
+            &[values.len() as i64],
+            ScalarType::Int,
+            Layout::Strided,
+            Device::new(DeviceType::CPU),
+        );
+        // SAFETY: `cpu` is a dense host tensor with `values.len()` elements.
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                values.as_ptr(),
+                cpu.mut_data_ptr() as *mut i32,
+                values.len(),
+            );
+        }
+        let mut tensor = factory_empty(
+            &[values.len() as i64],
+            ScalarType::Int,
+            Layout::Strided,
+            device,
+        );
+        tensor.copy_(&cpu);
+        tensor
+    }
+
+    fn int32_values(tensor: &Tensor) -> Vec<i32> {
+        let cpu = tensor.cpu();
+        // SAFETY: `cpu` is a dense host int32 tensor.
+        unsafe {
+            std::slice::from_raw_parts(cpu.data_ptr() as *const i32, cpu.numel() as usize).to_vec()
+        }
+    }
+
+    #[test]
+    fn all_reduce_bitwise_or() {
+        let unique_id = UniqueId::new().unwrap();
+        let mut handles = Vec::new();
+        for i in 0..2 {
+            let unique_id = unique_id.clone();
+            handles.push(std::thread::spawn(move || {
+                let device = CudaDevice::new(DeviceIndex(i));
+                set_device(device).unwrap();
+                let stream = Stream::new();
+                let masks = [[0b0001, 0b0100, 0, -1], [0b0010, 0b0100, 0, 0]];
+                let cell = TensorCell::new(int32_tensor(&masks[i as usize], device.into()));
+
+                let mut comm = Communicator::new(device, 2, unique_id, i.into()).unwrap();
+                comm.all_reduce(&cell, ReduceOp::BitOr, &stream).unwrap();
+                stream.synchronize();
+                assert_eq!(int32_values(&cell.borrow()), vec![0b0011, 0b0100, 0, -1]);
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn bitwise_reduce_op_mapping() {
+        assert!(matches!(
+            nccl_reduce_op(ReduceOp::BitOr, ScalarType::Float),
+            Err(NcclError::InvalidBitwiseReduction(
+                ReduceOp::BitOr,
+                ScalarType::Float
+            ))
+        ));
+        assert!(matches!(
+            nccl_reduce_op(ReduceOp::BitXor, ScalarType::BFloat16),
+            Err(NcclError::InvalidBitwiseReduction(..))
+        ));
+        assert_eq!(
+            nccl_reduce_op(ReduceOp::BitOr, ScalarType::Bool).unwrap(),
+            Some(ncclRedOp_t(ReduceOp::Max as _))
+        );
+        assert_eq!(
+            nccl_reduce_op(ReduceOp::BitAnd, ScalarType::Bool).unwrap(),
+            Some(ncclRedOp_t(ReduceOp::Min as _))
+        );
+        assert_eq!(
+            nccl_reduce_op(ReduceOp::BitOr, ScalarType::Int).unwrap(),
+            None
+        );
+        assert_eq!(
+            nccl_reduce_op(ReduceOp::Sum, ScalarType::Float).unwrap(),
+            Some(ncclRedOp_t(ReduceOp::Sum as _))
+        );
+    }
+
+    #[test]
+    fn fold_bitwise_bytes() {
+        let gathered = [0b1100u8, 0xff, 0b1010, 0x0f, 0b0110, 0xf0];
+        let mut out = [0u8; 2];
+        fold_bitwise(ReduceOp::BitOr, &gathered, &mut out);
+        assert_eq!(out, [0b1110, 0xff]);
+        fold_bitwise(ReduceOp::BitAnd, &gathered, &mut out);
+        assert_eq!(out, [0b0000, 0x00]);
+        fold_bitwise(ReduceOp::BitXor, &gathered, &mut out);
+        assert_eq!(out, [0b0000, 0x00]);
+    }
+
+    #[test]
+    fn execute_dispatches_collectives() {
+        let unique_id = UniqueId::new().unwrap();
+        let mut handles = Vec::new();
+        for i in 0..2 {
+            let unique_id = unique_id.clone();
+            handles.push(std::thread::spawn(move || {
+                let device = CudaDevice::new(DeviceIndex(i));
+                set_device(device).unwrap();
+                let stream = Stream::new();
+                let mut comm = Communicator::new(device, 2, unique_id, i.into()).unwrap();
+
+                let cell = TensorCell::new(cuda_full(&[2, 2], 1.0));
+                comm.execute(
+                    CollectiveOp::AllReduce { op: ReduceOp::Sum },
+                    std::slice::from_ref(&cell),
+                    &stream,
+                )
+                .unwrap();
+                stream.synchronize();
+                assert!(allclose(&cell.borrow(), &cuda_full(&[2, 2], 2.0)).unwrap());
+
+                let cell = TensorCell::new(cuda_full(&[2, 2], i as f32));
+                comm.execute(
+                    CollectiveOp::Broadcast { root: 1 },
+                    std::slice::from_ref(&cell),
+                    &stream,
+                )
+                .unwrap();
+                stream.synchronize();
+                assert!(allclose(&cell.borrow(), &cuda_full(&[2, 2], 1.0)).unwrap());
+
+                let tensors = [
+                    TensorCell::new(cuda_full(&[2, 2], 0.0)),
+                    TensorCell::new(cuda_full(&[2], i as f32)),
+                ];
+                comm.execute(CollectiveOp::AllGatherIntoTensor, &tensors, &stream)
+                    .unwrap();
+                stream.synchronize();
+                let expected = stack(&[cuda_full(&[2], 0.0), cuda_full(&[2], 1.0)]);
+                assert!(allclose(&tensors[0].borrow(), &expected).unwrap());
+
+                comm.execute(CollectiveOp::Barrier, &[], &stream).unwrap();
+                stream.synchronize();
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn collective_op_validation() {
+        assert!(
+            CollectiveOp::AllReduce { op: ReduceOp::Sum }
+                .validate(2, 1)
+                .is_ok()
+        );
+        assert!(CollectiveOp::AllGather.validate(2, 3).is_ok());
+        assert!(CollectiveOp::Barrier.validate(2, 0).is_ok());
+        assert!(matches!(
+            CollectiveOp::AllGather.validate(2, 2),
+            Err(NcclError::InvalidTensorCount(CollectiveOp::AllGather, 3, 2))
+        ));
+        assert!(matches!(
+            CollectiveOp::ReduceScatter { op: ReduceOp::Sum }.validate(4, 1),
+            Err(NcclError::InvalidTensorCount(_, 2, 1))
+        ));
+        assert!(matches!(
+            CollectiveOp::Broadcast { root: 2 }.validate(2, 1),
+            Err(NcclError::InvalidRank(2, 2))
+        ));
+        assert!(matches!(
+            CollectiveOp::Recv { src: -1 }.validate(2, 1),
+            Err(NcclError::InvalidRank(-1, 2))
+        ));
+    }
+
     #[test]
     fn broadcast() {
         let unique_id = UniqueId::new().unwrap();
@@ -1064,6 +2006,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reduce_scatter() {
+        let unique_id = UniqueId::new().unwrap();
+        let mut handles = Vec::new();
+        for i in 0..2 {
+            let unique_id = unique_id.clone();
+            handles.push(std::thread::spawn(move || {
+                let device = CudaDevice::new(DeviceIndex(i));
+                set_device(device).unwrap();
+                let stream = Stream::new();
+                let mut comm = Communicator::new(device, 2, unique_id, i.into()).unwrap();
+
+                let inputs = vec![
+                    TensorCell::new(factory_float_tensor(&[0.0, 1.0], device.into())),
+                    TensorCell::new(factory_float_tensor(&[2.0, 3.0], device.into())),
+                ];
+                let output = TensorCell::new(cuda_full(&[2], 1.0));
+                comm.reduce_scatter(&output, &inputs, ReduceOp::Sum, &stream)
+                    .unwrap();
+
+                let input =
+                    TensorCell::new(factory_float_tensor(&[0.0, 1.0, 2.0, 3.0], device.into()));
+                let expected = TensorCell::new(cuda_full(&[2], 1.0));
+                comm.reduce_scatter_tensor(&expected, &input, ReduceOp::Sum, &stream)
+                    .unwrap();
+                stream.synchronize();
+
+                assert!(allclose(&output.borrow(), &expected.borrow()).unwrap());
+
+                let err = comm
+                    .reduce_scatter(&output, &inputs[..1], ReduceOp::Sum, &stream)
+                    .unwrap_err();
+                assert!(matches!(err, NcclError::InvalidTensorCount(_, 2, 1)));
+            }));
+        }
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
     #[test]
     fn split_from() {
         let unique_id = UniqueId::new().unwrap();
@@ -13,10 +13,7 @@
 //! This script is not very general atm. Functionality that we would probably want:
 //! * Support for platforms other than linux.
 
-#![feature(exit_status_error)]
-
 use std::path::PathBuf;
-use std::process::Stdio;
 
 use build_utils::*;
 use cxx_build::CFG;
@@ -26,41 +23,27 @@ fn main() {
     let mut libtorch_include_dirs: Vec<PathBuf> = vec![];
     let mut libtorch_lib_dir: Option<PathBuf> = None;
     let mut cxx11_abi = None;
-    let python_interpreter = std::env::var("PYO3_PYTHON")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("python"));
+    let python_interpreter = build_utils::find_python_interpreter();
 
     let use_pytorch_apis = build_utils::get_env_var_with_rerun("TORCH_SYS_USE_PYTORCH_APIS")
         .unwrap_or_else(|_| "1".to_owned());
     if use_pytorch_apis == "1" {
         // We use the user's python installation of PyTorch to get the proper
         // headers/libraries for libtorch
-        let output = std::process::Command::new(&python_interpreter)
-            .arg("-c")
-            .arg(build_utils::PYTHON_PRINT_PYTORCH_DETAILS)
-            .stdout(Stdio::piped())
-            .spawn()
-            .unwrap_or_else(|_| panic!("error spawning {python_interpreter:?}"))
-            .wait_with_output()
-            .unwrap_or_else(|_| panic!("error waiting for {python_interpreter:?}"));
-        output
-            .status
-            .exit_ok()
-            .unwrap_or_else(|_| panic!("error running {python_interpreter:?}"));
-
-        for line in String::from_utf8_lossy(&output.stdout).lines() {
-            match line.strip_prefix("LIBTORCH_CXX11: ") {
-                Some("False") => cxx11_abi = Some("0".to_owned()),
-                Some("True") => cxx11_abi = Some("1".to_owned()),
-                _ => {}
-            };
-            if let Some(path) = line.strip_prefix("LIBTORCH_INCLUDE: ") {
-                libtorch_include_dirs.push(PathBuf::from(path))
-            }
-            if let Some(path) = line.strip_prefix("LIBTORCH_LIB: ") {
-                libtorch_lib_dir = Some(PathBuf::from(path))
-            }
-        }
+        let mut details = build_utils::run_python_kv(
+            &python_interpreter,
+            build_utils::PYTHON_PRINT_PYTORCH_DETAILS,
+        )
+        .unwrap_or_else(|_| panic!("error running {python_interpreter:?}"));
+        let mut take = |key: &str| details.remove(key).unwrap_or_default();
+
+        cxx11_abi = match take("LIBTORCH_CXX11").last().map(String::as_str) {
+            Some("False") => Some("0".to_owned()),
+            Some("True") => Some("1".to_owned()),
+            _ => None,
+        };
+        libtorch_include_dirs.extend(take("LIBTORCH_INCLUDE").into_iter().map(PathBuf::from));
+        libtorch_lib_dir = take("LIBTORCH_LIB").pop().map(PathBuf::from);
     } else {
         cxx11_abi = Some(build_utils::get_env_var_with_rerun("_GLIBCXX_USE_CXX11_ABI").unwrap());
         libtorch_include_dirs.extend(
@@ -79,21 +62,17 @@ fn main() {
     let mut python_include: Option<PathBuf> = None;
     let mut python_include_dir: Option<PathBuf> = None;
     // Include Python headers, and headers / libs from the active env.
-    let output = std::process::Command::new(&python_interpreter)
-        .arg("-c")
-        .arg(build_utils::PYTHON_PRINT_INCLUDE_PATH)
-        .output()
-        .unwrap_or_else(|_| panic!("error running {python_interpreter:?}"));
-    for line in String::from_utf8_lossy(&output.stdout).lines() {
-        if let Some(path) = line.strip_prefix("PYTHON_INCLUDE: ") {
-            python_include = Some(PathBuf::from(path));
-        }
-        if let Some(path) = line.strip_prefix("PYTHON_INCLUDE_DIR: ") {
-            python_include_dir = Some(PathBuf::from(path));
-        }
-        if let Some(path) = line.strip_prefix("PYTHON_LIB_DIR: ") {
-            println!("cargo::rustc-link-search=native={}", path);
-        }
+    let mut paths =
+        build_utils::run_python_kv(&python_interpreter, build_utils::PYTHON_PRINT_INCLUDE_PATH)
+            .unwrap_or_else(|_| panic!("error running {python_interpreter:?}"));
+    if let Some(path) = paths.remove("PYTHON_INCLUDE").and_then(|mut v| v.pop()) {
+        python_include = Some(PathBuf::from(path));
+    }
+    if let Some(path) = paths.remove("PYTHON_INCLUDE_DIR").and_then(|mut v| v.pop()) {
+        python_include_dir = Some(PathBuf::from(path));
+    }
+    for path in paths.remove("PYTHON_LIB_DIR").unwrap_or_default() {
+        println!("cargo::rustc-link-search=native={}", path);
     }
 
     // Use PyO3's Python discovery to find the correct Python library paths
@@ -275,6 +275,9 @@ pub(crate) mod ffi {
         #[namespace = "at"]
         #[rust_name = "is_float8_type"]
         fn isFloat8Type(t: ScalarType) -> bool;
+        #[namespace = "c10"]
+        #[rust_name = "element_size"]
+        fn elementSize(t: ScalarType) -> Result<usize>;
 
         // Convert to Python object.
         fn scalar_type_from_py_object(obj: FFIPyObject) -> Result<ScalarType>;
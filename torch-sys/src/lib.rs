@@ -132,6 +132,7 @@ pub use ivalue::IValueKind;
 pub use ivalue::OpaqueIValue;
 pub use rvalue::RValue;
 pub use rvalue::rvalue_to_ivalue;
+pub use scalar_type::scalar_type_size;
 pub use tensor::Tensor;
 pub use tensor::TensorCell;
 
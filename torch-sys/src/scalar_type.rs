@@ -51,6 +51,15 @@ impl<'py> IntoPyObject<'py> for ScalarType {
     }
 }
 
+/// Returns the size in bytes of a single element of `scalar_type`.
+///
+/// Sub-byte and packed types (e.g. `Float4_e2m1fn_x2`) report the size of their
+/// storage unit. Panics for `ScalarType::Undefined`, which has no element size.
+pub fn scalar_type_size(scalar_type: ScalarType) -> usize {
+    ffi::element_size(scalar_type)
+        .unwrap_or_else(|e| panic!("no element size for {:?}: {}", scalar_type, e))
+}
+
 // Remotely implement Serialize/Deserialize for generated types
 // TODO: we should be able to use parse_callbacks + add_derives, (see
 // https://github.com/rust-lang/rust-bindgen/pull/2059) and avoid a remote
@@ -75,6 +84,22 @@ mod tests {
         assert_eq!(converted_type, ScalarType::Float);
     }
 
+    #[test]
+    fn element_sizes() {
+        assert_eq!(scalar_type_size(ScalarType::Bool), 1);
+        assert_eq!(scalar_type_size(ScalarType::Byte), 1);
+        assert_eq!(scalar_type_size(ScalarType::Float8_e4m3fn), 1);
+        assert_eq!(scalar_type_size(ScalarType::Float8_e5m2), 1);
+        assert_eq!(scalar_type_size(ScalarType::Half), 2);
+        assert_eq!(scalar_type_size(ScalarType::BFloat16), 2);
+        assert_eq!(scalar_type_size(ScalarType::Int), 4);
+        assert_eq!(scalar_type_size(ScalarType::Float), 4);
+        assert_eq!(scalar_type_size(ScalarType::Long), 8);
+        assert_eq!(scalar_type_size(ScalarType::Double), 8);
+        assert_eq!(scalar_type_size(ScalarType::ComplexFloat), 8);
+        assert_eq!(scalar_type_size(ScalarType::ComplexDouble), 16);
+    }
+
     #[test]
     fn from_py() {
         pyo3::prepare_freethreaded_python();